@@ -0,0 +1,149 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::animation::{AnimationClip, Interpolation, Skin, Track, Wrap};
+use kiss3d::event::{Action, Key, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::resource::Mesh;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use na::{Point3, Translation3, UnitQuaternion, Vector3};
+use std::cell::RefCell;
+use std::f32::consts::FRAC_PI_4;
+use std::rc::Rc;
+
+const STEP: f32 = 1.0;
+
+// A clip swinging a limb back and forth around its pivot.
+fn swing(name: &str, phase: f32) -> AnimationClip {
+    let angle = |a: f32| UnitQuaternion::from_axis_angle(&Vector3::x_axis(), a * phase);
+    let rotation = Track::from_keyframes(
+        Interpolation::Linear,
+        vec![
+            (0.0, angle(FRAC_PI_4)),
+            (STEP / 2.0, angle(-FRAC_PI_4)),
+            (STEP, angle(FRAC_PI_4)),
+        ],
+    );
+    AnimationClip::new(name)
+        .with_rotation(rotation)
+        .with_wrap(Wrap::Loop)
+}
+
+// A limb hanging from a pivot, so it rotates around its top end.
+fn limb(parent: &mut SceneNode, x: f32, y: f32, length: f32, phase: f32) -> SceneNode {
+    let mut pivot = parent.add_group();
+    pivot.set_local_translation(Translation3::new(x, y, 0.0));
+    let mut limb = pivot.add_cylinder(0.08, length);
+    limb.set_local_translation(Translation3::new(0.0, -length / 2.0, 0.0));
+    pivot.add_animation(swing("walk", phase));
+    pivot
+}
+
+// A column of `segments` stacked boxes sharing their vertices, each level of four vertices
+// following the lower and upper joints depending on its height.
+fn column(segments: usize) -> (Mesh, Vec<[u16; 4]>, Vec<[f32; 4]>) {
+    let mut coords = Vec::new();
+    let mut joints = Vec::new();
+    let mut weights = Vec::new();
+    let mut faces = Vec::new();
+
+    for level in 0..=segments {
+        let y = level as f32 / segments as f32;
+        for &(x, z) in &[(-0.2, -0.2), (0.2, -0.2), (0.2, 0.2), (-0.2, 0.2)] {
+            coords.push(Point3::new(x, y * 2.0, z));
+            joints.push([0, 1, 0, 0]);
+            weights.push([1.0 - y, y, 0.0, 0.0]);
+        }
+    }
+    for level in 0..segments as u16 {
+        for side in 0..4u16 {
+            let a = level * 4 + side;
+            let b = level * 4 + (side + 1) % 4;
+            faces.push(Point3::new(a, b, b + 4));
+            faces.push(Point3::new(a, b + 4, a + 4));
+        }
+    }
+    let top = segments as u16 * 4;
+    faces.push(Point3::new(top, top + 2, top + 1));
+    faces.push(Point3::new(top, top + 3, top + 2));
+
+    (Mesh::new(coords, faces, None, None, true), joints, weights)
+}
+
+fn main() {
+    let mut window = Window::new("Kiss3d: animation");
+    window.set_light(Light::StickToCamera);
+
+    // A walker whose limbs swing together and whose body bobs and blushes.
+    let mut walker = window.add_group();
+    walker.set_local_translation(Translation3::new(-1.0, 0.0, 0.0));
+    let mut body = walker.add_cube(0.4, 0.6, 0.2);
+    let _ = limb(&mut walker, -0.1, -0.3, 0.6, 1.0);
+    let _ = limb(&mut walker, 0.1, -0.3, 0.6, -1.0);
+    let _ = limb(&mut walker, -0.27, 0.25, 0.5, -1.0);
+    let _ = limb(&mut walker, 0.27, 0.25, 0.5, 1.0);
+
+    let bob = Track::from_keyframes(
+        Interpolation::Linear,
+        vec![
+            (0.0, Vector3::new(0.0, 0.0, 0.0)),
+            (STEP / 4.0, Vector3::new(0.0, 0.05, 0.0)),
+            (STEP / 2.0, Vector3::new(0.0, 0.0, 0.0)),
+        ],
+    );
+    body.add_animation(
+        AnimationClip::new("walk")
+            .with_translation(bob)
+            .with_duration(STEP / 2.0)
+            .with_wrap(Wrap::Loop),
+    );
+    let blush = Track::from_keyframes(
+        Interpolation::Linear,
+        vec![(0.0, Point3::new(0.4, 0.6, 1.0)), (STEP * 2.0, Point3::new(1.0, 0.3, 0.3))],
+    );
+    body.add_animation(
+        AnimationClip::new("walk-color")
+            .with_color(blush)
+            .with_wrap(Wrap::PingPong),
+    );
+
+    // A column bending as its upper joint tilts back and forth.
+    let (mesh, joints, weights) = column(6);
+    let mut base = window.add_group();
+    base.set_local_translation(Translation3::new(1.0, -0.8, 0.0));
+    let mut tip = base.add_group();
+    tip.set_local_translation(Translation3::new(0.0, 2.0, 0.0));
+    let tilt = |a: f32| UnitQuaternion::from_axis_angle(&Vector3::z_axis(), a);
+    tip.add_animation(
+        AnimationClip::new("bend")
+            .with_rotation(Track::from_keyframes(
+                Interpolation::Linear,
+                vec![(0.0, tilt(-0.8)), (1.5, tilt(0.8))],
+            ))
+            .with_wrap(Wrap::PingPong),
+    );
+
+    // The joints must not be ancestors of the skinned node.
+    let mut column = window.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::new(1.0, 1.0, 1.0));
+    column.set_local_translation(Translation3::new(1.0, -0.8, 0.0));
+    column.set_color(0.8, 0.7, 0.3);
+    column.set_skin(Some(Skin::new(vec![base.clone(), tip.clone()], joints, weights)));
+
+    let _ = window.scene_mut().play_animation("walk");
+    let _ = window.scene_mut().play_animation("walk-color");
+    let _ = window.scene_mut().play_animation("bend");
+
+    while window.render() {
+        for event in window.events().iter() {
+            if let WindowEvent::Key(Key::Space, Action::Release, _) = event.value {
+                let scene = window.scene_mut();
+                if scene.is_animation_playing("walk") {
+                    scene.stop_animation("walk");
+                } else {
+                    let _ = scene.play_animation("walk");
+                }
+            }
+        }
+    }
+}
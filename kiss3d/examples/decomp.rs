@@ -104,8 +104,9 @@ fn main() {
 
                     let mut part_faces = Vec::new();
 
+                    let all_faces = faces.to_u32().unwrap();
                     for i in partitioning.into_iter() {
-                        part_faces.push(faces.read().unwrap().data().as_ref().unwrap()[i]);
+                        part_faces.push(all_faces[i]);
                     }
 
                     let faces = GPUVec::new(
@@ -0,0 +1,26 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::ArcBall;
+use kiss3d::light::Light;
+use kiss3d::window::Window;
+use na::{Point3, UnitQuaternion, Vector3};
+use std::path::Path;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: gltf");
+
+    // A house with textured walls, a roof and a door with vertex colors, as child nodes.
+    let path = Path::new("media/house/house.gltf");
+    let mut house = window.add_gltf(path, Vector3::new(0.2, 0.2, 0.2));
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+    let eye = Point3::new(0.0, 0.3, 0.8);
+    let mut camera = ArcBall::new(eye, Point3::new(0.0, 0.1, 0.0));
+
+    while window.render_with_camera(&mut camera) {
+        house.prepend_to_local_rotation(&rot);
+    }
+}
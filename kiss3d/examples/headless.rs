@@ -0,0 +1,39 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+#[cfg(feature = "headless")]
+use kiss3d::context::{Command, NullContext};
+#[cfg(feature = "headless")]
+use kiss3d::window::Window;
+
+#[cfg(not(feature = "headless"))]
+fn main() {
+    panic!("The 'headless' feature must be enabled for this example to work.")
+}
+
+// Renders a frame without any GPU, prints what was sent to the context, then saves the frame
+// drawn by the software rasterizer.
+#[cfg(feature = "headless")]
+fn main() {
+    let mut window = Window::new_headless(800, 600);
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(1.0, 0.0, 0.0);
+
+    NullContext::clear_commands();
+    let _ = window.render();
+
+    let commands = NullContext::take_commands();
+    let draws = commands.iter().filter(|c| c.is_draw()).count();
+    println!("{} commands, {} draw calls:", commands.len(), draws);
+
+    for command in &commands {
+        match command {
+            Command::Uniform { name, value, .. } => println!("    uniform {} = {:?}", name, value),
+            command if command.is_draw() => println!("    {:?}", command),
+            _ => {}
+        }
+    }
+
+    window.snap_image().save("headless.png").unwrap();
+    println!("Frame saved to headless.png");
+}
@@ -0,0 +1,39 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::scene::InstanceData;
+use kiss3d::window::Window;
+use na::{Matrix3, Point3, UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: instancing");
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+
+    // A 20x20x20 grid of cubes, all drawn in a single draw call.
+    let n = 20;
+    let mut instances = Vec::new();
+    for i in 0..n {
+        for j in 0..n {
+            for k in 0..n {
+                let (fi, fj, fk) = (i as f32 / n as f32, j as f32 / n as f32, k as f32 / n as f32);
+                let rotation = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), fi * 3.0);
+                instances.push(InstanceData {
+                    position: Point3::new(fi - 0.5, fj - 0.5, fk - 0.5) * 20.0,
+                    deformation: rotation.to_rotation_matrix().into_inner()
+                        * Matrix3::from_diagonal_element(0.5 + fk * 0.5),
+                    color: Point3::new(fi, fj, fk),
+                });
+            }
+        }
+    }
+    c.set_instances(&instances);
+
+    window.set_light(Light::StickToCamera);
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+    }
+}
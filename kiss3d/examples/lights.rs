@@ -0,0 +1,42 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::{Light, PointLight};
+use kiss3d::window::Window;
+use na::{Point3, Translation3, UnitQuaternion, Vector3};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: lights");
+
+    let mut ground = window.add_cube(10.0, 0.2, 10.0);
+    ground.set_local_translation(Translation3::new(0.0, -0.6, 0.0));
+
+    let mut c = window.add_cube(1.0, 1.0, 1.0);
+    c.set_color(0.9, 0.9, 0.9);
+
+    // A low, warm sun casting shadows...
+    window.set_light(Light::Directional(Vector3::new(-1.0, -1.0, -0.5)));
+    window.set_light_color(Point3::new(1.0, 0.9, 0.7), 1.0);
+    window.enable_shadows(2048, 8.0);
+    window.set_shadow_focus(Some(Point3::origin()));
+
+    // ... and two colored point lights.
+    window.add_point_light(PointLight::new(
+        Point3::new(2.0, 0.5, 2.0),
+        Point3::new(1.0, 0.2, 0.2),
+        1.5,
+        5.0,
+    ));
+    window.add_point_light(PointLight::new(
+        Point3::new(-2.0, 0.5, 2.0),
+        Point3::new(0.2, 0.4, 1.0),
+        1.5,
+        5.0,
+    ));
+
+    let rot = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), 0.014);
+
+    while window.render() {
+        c.prepend_to_local_rotation(&rot);
+    }
+}
@@ -0,0 +1,52 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::{ArcBall, Camera};
+use kiss3d::event::{Action, MouseButton, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::ncollide3d::query::Ray;
+use kiss3d::scene::SceneNode;
+use kiss3d::window::Window;
+use na::{Point2, Point3, Translation3, Vector2};
+
+fn main() {
+    let mut window = Window::new("Kiss3d: picking");
+    window.set_light(Light::StickToCamera);
+    let mut camera = ArcBall::new(Point3::new(0.0, 8.0, 20.0), Point3::origin());
+
+    // A grid of cubes, most of them off screen and culled until the camera turns to them.
+    for i in -20..=20 {
+        for j in -20..=20 {
+            let mut cube = window.add_cube(0.8, 0.8, 0.8);
+            cube.set_local_translation(Translation3::new(i as f32 * 2.0, 0.0, j as f32 * 2.0));
+            cube.set_color(0.6, 0.6, 0.6);
+        }
+    }
+
+    let mut cursor = Point2::origin();
+    let mut picked: Option<SceneNode> = None;
+
+    while window.render_with_camera(&mut camera) {
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::CursorPos(x, y, _) => cursor = Point2::new(x as f32, y as f32),
+                WindowEvent::MouseButton(MouseButton::Button1, Action::Release, _) => {
+                    // Window events are in physical pixels, like the size of the framebuffer.
+                    let size = Vector2::new(window.width() as f32, window.height() as f32);
+                    let (origin, dir) = camera.unproject(&cursor, &size);
+                    let hit = window.scene_mut().cast_ray(&Ray::new(origin, dir), 1000.0);
+
+                    if let Some(mut previous) = picked.take() {
+                        previous.set_color(0.6, 0.6, 0.6);
+                    }
+                    if let Some((mut node, toi)) = hit {
+                        println!("Picked a cube {:.2} units away.", toi);
+                        node.set_color(1.0, 0.2, 0.2);
+                        picked = Some(node);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
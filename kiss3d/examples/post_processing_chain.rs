@@ -0,0 +1,74 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::camera::FirstPerson;
+use kiss3d::event::{Action, Key, WindowEvent};
+use kiss3d::light::Light;
+use kiss3d::post_processing::{ColorGrading, Fog, Fxaa, PostProcessingChain, Vignette};
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use na::{Point2, Point3, Translation3, Vector3};
+
+const STAGES: [(Key, &str); 4] = [
+    (Key::Key1, "fog"),
+    (Key::Key2, "grading"),
+    (Key::Key3, "fxaa"),
+    (Key::Key4, "vignette"),
+];
+
+fn main() {
+    let mut window = Window::new("Kiss3d: post_processing_chain");
+    window.set_light(Light::StickToCamera);
+    window.set_background_color(0.7, 0.75, 0.8);
+
+    // Rows of cubes vanishing into the fog.
+    for i in 0..20 {
+        for &x in &[-2.0, 2.0] {
+            let mut cube = window.add_cube(1.0, 1.0, 1.0);
+            cube.set_local_translation(Translation3::new(x, 0.0, -3.0 * i as f32));
+            cube.set_color(0.9, 0.5 - 0.02 * i as f32, 0.2);
+        }
+    }
+
+    // Warm colors, with the tint of a lookup table made from the identity.
+    let mut lut = ColorGrading::identity_lut(16);
+    for pixel in lut.pixels_mut() {
+        pixel.0[2] /= 2;
+    }
+    let mut grading = ColorGrading::new();
+    grading.set_contrast(1.2);
+    grading.set_saturation(1.3);
+    grading.set_lut(Some(&lut));
+    grading.set_lut_intensity(0.5);
+
+    let mut chain = PostProcessingChain::new()
+        .with("fog", Fog::new(Vector3::new(0.7, 0.75, 0.8), 5.0, 50.0))
+        .with("grading", grading)
+        .with("fxaa", Fxaa::new())
+        .with("vignette", Vignette::new(0.6));
+
+    let font = Font::default();
+    let mut camera = FirstPerson::new(Point3::new(0.0, 2.0, 5.0), Point3::origin());
+
+    while window.render_with_camera_and_effect(&mut camera, &mut chain) {
+        for event in window.events().iter() {
+            if let WindowEvent::Key(key, Action::Release, _) = event.value {
+                if let Some((_, name)) = STAGES.iter().find(|(k, _)| *k == key) {
+                    let enabled = chain.is_enabled(name);
+                    let _ = chain.set_enabled(name, !enabled);
+                }
+            }
+        }
+
+        for (i, (_, name)) in STAGES.iter().enumerate() {
+            let state = if chain.is_enabled(name) { "on" } else { "off" };
+            window.draw_text(
+                &format!("{}: {} ({})", i + 1, name, state),
+                &Point2::new(10.0, 10.0 + 50.0 * i as f32),
+                40.0,
+                &font,
+                &Point3::new(1.0, 1.0, 1.0),
+            );
+        }
+    }
+}
@@ -0,0 +1,28 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::light::Light;
+use kiss3d::recording::{Recorder, RecordingFormat};
+use kiss3d::window::Window;
+use na::{UnitQuaternion, Vector3};
+
+// Records one turn of a spinning cube to `recording.gif`, one frame every 0.1 radian.
+fn main() {
+    let mut window = Window::new_with_size("Kiss3d: recording", 400, 400);
+    let mut c = window.add_cube(0.2, 0.2, 0.2);
+    c.set_color(1.0, 0.0, 0.0);
+    window.set_light(Light::StickToCamera);
+
+    let mut recorder = Recorder::new("recording.gif", RecordingFormat::Gif, 0.1).unwrap();
+    let step = 0.014;
+    let mut angle = 0.0;
+
+    while window.render() && angle < std::f32::consts::PI * 2.0 {
+        let _ = recorder.record(&window, angle as f64).unwrap();
+
+        c.prepend_to_local_rotation(&UnitQuaternion::from_axis_angle(&Vector3::y_axis(), step));
+        angle += step;
+    }
+
+    println!("Recorded {} frames to `recording.gif`", recorder.frames());
+}
@@ -0,0 +1,39 @@
+extern crate kiss3d;
+extern crate nalgebra as na;
+
+use kiss3d::text::{Font, TextAlign, TextAnchor, TextLayout};
+use kiss3d::window::Window;
+use na::Point3;
+
+fn main() {
+    let mut window = Window::new("Kiss3d: text_layout");
+    let font = Font::default();
+    let white = Point3::new(1.0, 1.0, 1.0);
+    let yellow = Point3::new(1.0, 1.0, 0.0);
+
+    let mut paragraph = TextLayout::new(&font, 50.0)
+        .with_span("Text layouts ", &white)
+        .with_span("wrap", &yellow)
+        .with_span(" their lines to a maximum width, ", &white)
+        .with_span("align", &yellow)
+        .with_span(" them, and keep the colors of their spans.", &white)
+        .with_align(TextAlign::Center);
+    let corners = [
+        (TextAnchor::TopLeft, "top left"),
+        (TextAnchor::TopRight, "top right"),
+        (TextAnchor::BottomLeft, "bottom left"),
+        (TextAnchor::BottomRight, "bottom right"),
+    ];
+
+    while window.render() {
+        // Wrap the paragraph to half the width of the window.
+        let screen = window.text_screen_size();
+        paragraph.set_max_width(Some(screen.x / 2.0));
+        let _ = window.draw_anchored_text(&paragraph, TextAnchor::Center, 0.0);
+
+        for (anchor, name) in corners.iter() {
+            let corner = TextLayout::new(&font, 40.0).with_span(name, &Point3::new(0.0, 1.0, 1.0));
+            let _ = window.draw_anchored_text(&corner, *anchor, 20.0);
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use crate::animation::Track;
+use na::{Point3, UnitQuaternion, Vector3};
+
+/// How a clip behaves once its end is reached.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    /// The clip stops on its last pose.
+    Once,
+    /// The clip starts over.
+    Loop,
+    /// The clip plays backwards to its start, then forwards again.
+    PingPong,
+}
+
+/// Tracks animating the transformation and color of one scene node.
+///
+/// Each track overrides the corresponding property of the node while the clip plays; properties
+/// without a track are left as they are.
+#[derive(Clone, Debug)]
+pub struct AnimationClip {
+    name: String,
+    translation: Option<Track<Vector3<f32>>>,
+    rotation: Option<Track<UnitQuaternion<f32>>>,
+    scale: Option<Track<Vector3<f32>>>,
+    color: Option<Track<Point3<f32>>>,
+    wrap: Wrap,
+    duration: Option<f32>,
+}
+
+impl AnimationClip {
+    /// Creates a clip without tracks, played once.
+    ///
+    /// Clips are played by name: playing a name on a node plays the clips of that name of the
+    /// node and all its descendants together, so a model can be animated as a whole.
+    pub fn new(name: &str) -> AnimationClip {
+        AnimationClip {
+            name: name.to_string(),
+            translation: None,
+            rotation: None,
+            scale: None,
+            color: None,
+            wrap: Wrap::Once,
+            duration: None,
+        }
+    }
+
+    /// Sets the track of the local translation of the node.
+    pub fn with_translation(mut self, track: Track<Vector3<f32>>) -> AnimationClip {
+        self.translation = Some(track);
+        self
+    }
+
+    /// Sets the track of the local rotation of the node.
+    pub fn with_rotation(mut self, track: Track<UnitQuaternion<f32>>) -> AnimationClip {
+        self.rotation = Some(track);
+        self
+    }
+
+    /// Sets the track of the local scale of the node.
+    pub fn with_scale(mut self, track: Track<Vector3<f32>>) -> AnimationClip {
+        self.scale = Some(track);
+        self
+    }
+
+    /// Sets the track of the color of the objects of the node and its children.
+    pub fn with_color(mut self, track: Track<Point3<f32>>) -> AnimationClip {
+        self.color = Some(track);
+        self
+    }
+
+    /// Sets what happens once the end of the clip is reached.
+    pub fn with_wrap(mut self, wrap: Wrap) -> AnimationClip {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Sets the duration of the clip, which is otherwise that of its longest track.
+    ///
+    /// Clips of the same name on several nodes should have the same duration to stay in step when
+    /// they loop.
+    pub fn with_duration(mut self, duration: f32) -> AnimationClip {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// The name of this clip.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// What happens once the end of the clip is reached.
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
+    }
+
+    /// The duration of this clip, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.duration.unwrap_or_else(|| {
+            let translation = self.translation.as_ref().map_or(0.0, |t| t.duration());
+            let rotation = self.rotation.as_ref().map_or(0.0, |t| t.duration());
+            let scale = self.scale.as_ref().map_or(0.0, |t| t.duration());
+            let color = self.color.as_ref().map_or(0.0, |t| t.duration());
+            translation.max(rotation).max(scale).max(color)
+        })
+    }
+
+    /// The time within the clip after playing it for `elapsed` seconds, and whether it has
+    /// finished.
+    pub fn local_time(&self, elapsed: f32) -> (f32, bool) {
+        let duration = self.duration();
+        if duration <= 0.0 {
+            return (0.0, self.wrap == Wrap::Once);
+        }
+
+        match self.wrap {
+            Wrap::Once => (elapsed.min(duration), elapsed >= duration),
+            Wrap::Loop => (elapsed.rem_euclid(duration), false),
+            Wrap::PingPong => {
+                let t = elapsed.rem_euclid(2.0 * duration);
+                (if t > duration { 2.0 * duration - t } else { t }, false)
+            }
+        }
+    }
+
+    /// The local translation of the node at `time`, if the clip animates it.
+    pub fn translation(&self, time: f32) -> Option<Vector3<f32>> {
+        self.translation.as_ref().and_then(|t| t.sample(time))
+    }
+
+    /// The local rotation of the node at `time`, if the clip animates it.
+    pub fn rotation(&self, time: f32) -> Option<UnitQuaternion<f32>> {
+        self.rotation.as_ref().and_then(|t| t.sample(time))
+    }
+
+    /// The local scale of the node at `time`, if the clip animates it.
+    pub fn scale(&self, time: f32) -> Option<Vector3<f32>> {
+        self.scale.as_ref().and_then(|t| t.sample(time))
+    }
+
+    /// The color of the node at `time`, if the clip animates it.
+    pub fn color(&self, time: f32) -> Option<Point3<f32>> {
+        self.color.as_ref().and_then(|t| t.sample(time))
+    }
+}
+
+/// A clip attached to a node and how far it has been played.
+#[derive(Clone, Debug)]
+pub(crate) struct ClipState {
+    pub clip: AnimationClip,
+    pub elapsed: f32,
+    pub speed: f32,
+    pub playing: bool,
+}
+
+impl ClipState {
+    pub fn new(clip: AnimationClip) -> ClipState {
+        ClipState {
+            clip,
+            elapsed: 0.0,
+            speed: 1.0,
+            playing: false,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Keyframe animation of scene nodes and skinned meshes.
+//!
+//! An `AnimationClip` holds keyframe `Track`s for the transformation and color of a node, and is
+//! attached to it with `SceneNode::add_animation`. Playing a clip by name with
+//! `SceneNode::play_animation` plays the clips of that name of the node and all its descendants,
+//! so a model made of several nodes, or a skeleton, is animated as a whole. The window advances
+//! the animations of its scene every frame.
+//!
+//! A `Skin` attached with `SceneNode::set_skin` deforms the mesh of a node to follow other nodes,
+//! its joints, weighted per vertex.
+
+pub use self::clip::{AnimationClip, Wrap};
+pub(crate) use self::clip::ClipState;
+pub use self::skin::Skin;
+pub use self::track::{Interpolate, Interpolation, Keyframe, Track};
+
+mod clip;
+mod skin;
+mod track;
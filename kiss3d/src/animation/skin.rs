@@ -0,0 +1,165 @@
+use crate::resource::Mesh;
+use crate::scene::SceneNode;
+use na::{Matrix3, Matrix4, Point3, Vector3, U3};
+
+/// Bone weights deforming the mesh of a node with the poses of other nodes, its joints.
+///
+/// Each vertex follows up to four joints, and is moved by the weighted sum of how far they moved
+/// from their bind pose. Joints are ordinary scene nodes, usually animated by clips. The mesh is
+/// deformed on the CPU each time the animations of the scene are updated, so it should be created
+/// with `dynamic_draw`.
+#[derive(Clone)]
+pub struct Skin {
+    joints: Vec<SceneNode>,
+    inverse_bind_matrices: Option<Vec<Matrix4<f32>>>,
+    vertex_joints: Vec<[u16; 4]>,
+    vertex_weights: Vec<[f32; 4]>,
+    bind_coords: Vec<Point3<f32>>,
+    bind_normals: Vec<Vector3<f32>>,
+}
+
+impl Skin {
+    /// Creates a skin for a mesh with the given joints.
+    ///
+    /// `vertex_joints` and `vertex_weights` give, for each vertex of the mesh, the indices in
+    /// `joints` of the joints it follows and how much it follows each. Weights are normalized, and
+    /// unused slots should have a weight of zero.
+    ///
+    /// The joints should not be ancestors of the skinned node: the node would then own them and
+    /// they would never be freed.
+    pub fn new(
+        joints: Vec<SceneNode>,
+        vertex_joints: Vec<[u16; 4]>,
+        vertex_weights: Vec<[f32; 4]>,
+    ) -> Skin {
+        Skin {
+            joints,
+            inverse_bind_matrices: None,
+            vertex_joints,
+            vertex_weights,
+            bind_coords: Vec::new(),
+            bind_normals: Vec::new(),
+        }
+    }
+
+    /// Sets the inverse bind matrix of each joint, the transformation from the mesh space to the
+    /// joint space in the bind pose, as found in glTF files.
+    ///
+    /// Without them, the poses of the joints and of the skinned node when the skin is attached with
+    /// `SceneNode::set_skin` are the bind pose.
+    pub fn with_inverse_bind_matrices(mut self, matrices: Vec<Matrix4<f32>>) -> Skin {
+        self.inverse_bind_matrices = Some(matrices);
+        self
+    }
+
+    /// The joints deforming the mesh.
+    pub fn joints(&self) -> &[SceneNode] {
+        &self.joints
+    }
+
+    /// The vertices and normals of the mesh in the bind pose.
+    pub fn bind_pose(&self) -> (&[Point3<f32>], &[Vector3<f32>]) {
+        (&self.bind_coords, &self.bind_normals)
+    }
+
+    /// Saves the vertices and normals of `mesh` as the bind pose, given the world matrices of the
+    /// skinned node and of the joints, with the scales of the nodes included.
+    ///
+    /// The inverse bind matrices are computed from those poses if they were not set. This is done
+    /// by `SceneNode::set_skin`.
+    ///
+    /// # Failure
+    /// Fails if the joint indices or the weights do not match the vertices of the mesh.
+    pub fn bind(&mut self, mesh: &Mesh, node: &Matrix4<f32>, joints: &[Matrix4<f32>]) {
+        self.bind_coords = mesh.coords().read().unwrap().to_owned().unwrap_or_default();
+        self.bind_normals = mesh.normals().read().unwrap().to_owned().unwrap_or_default();
+
+        let len = self.bind_coords.len();
+        assert!(
+            self.vertex_joints.len() == len && self.vertex_weights.len() == len,
+            "The skin has {} joint indices and {} weights for a mesh of {} vertices.",
+            self.vertex_joints.len(),
+            self.vertex_weights.len(),
+            len
+        );
+        assert!(
+            self.vertex_joints
+                .iter()
+                .flatten()
+                .all(|&j| (j as usize) < self.joints.len()),
+            "A vertex of the skin refers to a joint out of bounds."
+        );
+
+        if self.inverse_bind_matrices.is_none() {
+            let matrices = joints
+                .iter()
+                .map(|joint| {
+                    joint
+                        .try_inverse()
+                        .map_or_else(Matrix4::identity, |inverse| inverse * node)
+                })
+                .collect();
+            self.inverse_bind_matrices = Some(matrices);
+        }
+        let matrices = self.inverse_bind_matrices.as_ref().unwrap();
+        assert!(
+            matrices.len() == self.joints.len(),
+            "The skin has {} inverse bind matrices for {} joints.",
+            matrices.len(),
+            self.joints.len()
+        );
+    }
+
+    /// The deformed vertices and normals of the mesh given the world matrices of the skinned node
+    /// and of the joints, with the scales of the nodes included.
+    pub fn deform(
+        &self,
+        node: &Matrix4<f32>,
+        joints: &[Matrix4<f32>],
+    ) -> (Vec<Point3<f32>>, Vec<Vector3<f32>>) {
+        let inverse_node = node.try_inverse().unwrap_or_else(Matrix4::identity);
+        let inverse_binds = self.inverse_bind_matrices.as_deref().unwrap_or(&[]);
+        let skinning: Vec<Matrix4<f32>> = joints
+            .iter()
+            .zip(inverse_binds)
+            .map(|(joint, inverse_bind)| inverse_node * joint * inverse_bind)
+            .collect();
+
+        let mut coords = Vec::with_capacity(self.bind_coords.len());
+        let mut normals = Vec::with_capacity(self.bind_normals.len());
+
+        for (i, coord) in self.bind_coords.iter().enumerate() {
+            let weights = &self.vertex_weights[i];
+            let total: f32 = weights.iter().sum();
+            let mut matrix = Matrix4::zeros();
+
+            if total > 0.0 {
+                for (joint, weight) in self.vertex_joints[i].iter().zip(weights) {
+                    if let Some(m) = skinning.get(*joint as usize) {
+                        matrix += m * (*weight / total);
+                    }
+                }
+            } else {
+                matrix = Matrix4::identity();
+            }
+
+            coords.push(matrix.transform_point(coord));
+            if let Some(normal) = self.bind_normals.get(i) {
+                let linear: Matrix3<f32> = matrix.fixed_slice::<U3, U3>(0, 0).into_owned();
+                let normal = linear * normal;
+                normals.push(normal.try_normalize(1.0e-6).unwrap_or(normal));
+            }
+        }
+
+        (coords, normals)
+    }
+
+    // Deforms `mesh` in place.
+    pub(crate) fn apply(&self, mesh: &Mesh, node: &Matrix4<f32>, joints: &[Matrix4<f32>]) {
+        let (coords, normals) = self.deform(node, joints);
+        *mesh.coords().write().unwrap().data_mut() = Some(coords);
+        if !normals.is_empty() {
+            *mesh.normals().write().unwrap().data_mut() = Some(normals);
+        }
+    }
+}
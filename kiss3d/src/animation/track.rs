@@ -0,0 +1,135 @@
+use na::{Point3, UnitQuaternion, Vector3};
+
+/// How a track computes its value between two keyframes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interpolation {
+    /// The value of the previous keyframe is kept until the next one.
+    Step,
+    /// The value changes linearly from one keyframe to the next. Rotations are spherically
+    /// interpolated.
+    Linear,
+}
+
+/// A value that can be interpolated by a `Track`.
+pub trait Interpolate: Copy {
+    /// The value a fraction `t`, from 0 to 1, of the way from `self` to `other`.
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+impl Interpolate for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Vector3<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t)
+    }
+}
+
+impl Interpolate for Point3<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Point3::from(self.coords.lerp(&other.coords, t))
+    }
+}
+
+impl Interpolate for UnitQuaternion<f32> {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        // Slerp is undefined between opposite rotations, where any path is as short as another.
+        self.try_slerp(other, t, 1.0e-6)
+            .unwrap_or_else(|| self.nlerp(other, t))
+    }
+}
+
+/// A value at a given time of a track.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    /// The time of the keyframe, in seconds from the start of its clip.
+    pub time: f32,
+    /// The value of the track at that time.
+    pub value: T,
+}
+
+/// A sequence of keyframes describing how a value changes over time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+    interpolation: Interpolation,
+}
+
+impl<T: Interpolate> Track<T> {
+    /// Creates a track without keyframes.
+    pub fn new(interpolation: Interpolation) -> Track<T> {
+        Track {
+            keyframes: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Creates a track from `(time, value)` pairs, in any order.
+    pub fn from_keyframes<I: IntoIterator<Item = (f32, T)>>(
+        interpolation: Interpolation,
+        keyframes: I,
+    ) -> Track<T> {
+        let mut track = Track::new(interpolation);
+        for (time, value) in keyframes {
+            track.add_keyframe(time, value);
+        }
+        track
+    }
+
+    /// Adds a keyframe, replacing any other keyframe at the same time.
+    pub fn add_keyframe(&mut self, time: f32, value: T) {
+        let keyframe = Keyframe { time, value };
+        match self
+            .keyframes
+            .binary_search_by(|k| k.time.partial_cmp(&time).unwrap_or(std::cmp::Ordering::Less))
+        {
+            Ok(i) => self.keyframes[i] = keyframe,
+            Err(i) => self.keyframes.insert(i, keyframe),
+        }
+    }
+
+    /// The keyframes of this track, ordered by time.
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// How this track interpolates between keyframes.
+    pub fn interpolation(&self) -> Interpolation {
+        self.interpolation
+    }
+
+    /// The time of the last keyframe, or 0 if there are none.
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |k| k.time)
+    }
+
+    /// The value of the track at `time`.
+    ///
+    /// Before the first keyframe and after the last one, the value is that of the closest
+    /// keyframe. Returns `None` if the track has no keyframes.
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        // The index of the first keyframe strictly after `time`.
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+
+        if next == 0 {
+            return Some(first.value);
+        }
+        let prev = &self.keyframes[next - 1];
+        let next = match self.keyframes.get(next) {
+            Some(next) => next,
+            None => return Some(prev.value),
+        };
+
+        match self.interpolation {
+            Interpolation::Step => Some(prev.value),
+            Interpolation::Linear => {
+                let t = (time - prev.time) / (next.time - prev.time);
+                Some(prev.value.interpolate(&next.value, t))
+            }
+        }
+    }
+}
@@ -1,35 +1,98 @@
-#version 100
 #ifdef GL_FRAGMENT_PRECISION_HIGH
    precision highp float;
 #else
    precision mediump float;
 #endif
 
-varying vec3 local_light_position;
+#define MAX_POINT_LIGHTS 4
+
 varying vec2 tex_coord_v;
-varying vec3 normalInterp;
-varying vec3 vertPos;
+varying vec3 normal_v;
+varying vec3 world_pos_v;
+varying vec4 shadow_coord_v;
+varying vec3 color_v;
 
 uniform vec3 color;
 uniform sampler2D tex;
-const vec3 specColor = vec3(0.4, 0.4, 0.4);
+// The region of the texture (min, size) the texture coordinates repeat in, if
+// enabled.
+uniform vec4 tex_region;
+uniform float tex_region_enabled;
+uniform vec3 eye_position;
 
-void main() {
-  vec3 normal = normalize(normalInterp);
-  vec3 lightDir = normalize(local_light_position - vertPos);
+// The main light shines from light_position, or along light_direction if it
+// is directional.
+uniform vec3 light_position;
+uniform vec3 light_direction;
+uniform float light_is_directional;
+uniform vec3 light_color;
 
+// Unused point lights are black.
+uniform vec3 point_light_positions[MAX_POINT_LIGHTS];
+uniform vec3 point_light_colors[MAX_POINT_LIGHTS];
+uniform float point_light_radii[MAX_POINT_LIGHTS];
+
+uniform float shadows_enabled;
+uniform sampler2D shadow_map;
+uniform float shadow_texel_size;
+
+const vec3 specColor = vec3(0.4, 0.4, 0.4);
+
+vec3 shade(vec3 normal, vec3 lightDir, vec3 viewDir, vec3 diffuse, vec3 lightColor) {
   float lambertian = max(dot(lightDir, normal), 0.0);
   float specular = 0.0;
 
   if(lambertian > 0.0) {
-    vec3 viewDir = normalize(-vertPos);
     vec3 halfDir = normalize(lightDir + viewDir);
     float specAngle = max(dot(halfDir, normal), 0.0);
     specular = pow(specAngle, 30.0);
   }
 
-  vec4 tex_color = texture2D(tex, tex_coord_v);
-  gl_FragColor = tex_color * vec4(color / 3.0 +
-                                  lambertian * color / 3.0 +
-                                  specular * specColor / 3.0, 1.0);
+  return (lambertian * diffuse / 3.0 + specular * specColor / 3.0) * lightColor;
+}
+
+// The fraction of the main light reaching this fragment, averaged over four
+// texels of the shadow map to soften the edges.
+float lit_fraction() {
+  vec3 coords = shadow_coord_v.xyz / shadow_coord_v.w * 0.5 + 0.5;
+  if(coords.x < 0.0 || coords.x > 1.0 || coords.y < 0.0 || coords.y > 1.0 || coords.z > 1.0) {
+    return 1.0;
+  }
+
+  float bias = 0.002;
+  float lit = 0.0;
+  for(int i = 0; i < 4; i++) {
+    vec2 offset = vec2(float(i / 2) - 0.5, float(i - (i / 2) * 2) - 0.5) * shadow_texel_size;
+    float depth = texture2D(shadow_map, coords.xy + offset).r;
+    lit += coords.z - bias > depth ? 0.0 : 1.0;
+  }
+  return lit / 4.0;
+}
+
+void main() {
+  vec3 normal = normalize(normal_v);
+  vec3 viewDir = normalize(eye_position - world_pos_v);
+
+  // The instance and vertex colors modulate the diffuse terms only, so baked
+  // occlusion darkens corners without dulling highlights.
+  vec3 diffuse = color * color_v;
+
+  vec3 lightDir = light_is_directional > 0.5 ? -light_direction : light_position - world_pos_v;
+  vec3 lit = shade(normal, normalize(lightDir), viewDir, diffuse, light_color);
+  if(shadows_enabled > 0.5) {
+    lit *= lit_fraction();
+  }
+
+  for(int i = 0; i < MAX_POINT_LIGHTS; i++) {
+    vec3 toLight = point_light_positions[i] - world_pos_v;
+    float falloff = max(1.0 - length(toLight) / point_light_radii[i], 0.0);
+    lit += shade(normal, normalize(toLight), viewDir, diffuse, point_light_colors[i] * falloff * falloff);
+  }
+
+  vec2 uv = tex_coord_v;
+  if(tex_region_enabled > 0.5) {
+    uv = tex_region.xy + fract(uv) * tex_region.zw;
+  }
+  vec4 tex_color = texture2D(tex, uv);
+  gl_FragColor = tex_color * vec4(diffuse / 3.0 + lit, 1.0);
 }
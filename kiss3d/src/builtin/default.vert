@@ -1,22 +1,39 @@
-#version 100
 attribute vec3 position;
 attribute vec2 tex_coord;
 attribute vec3 normal;
+#ifdef VERTEX_COLORS
+attribute vec3 vertex_color;
+#endif
+
+// The rows of the affine transformation of the instance, and its color
+attribute vec4 inst_row_0, inst_row_1, inst_row_2;
+attribute vec3 inst_color;
 
 uniform mat3 ntransform, scale;
 uniform mat4 proj, view, transform;
-uniform vec3 light_position;
+uniform mat4 shadow_view_proj;
 
-varying vec3 local_light_position;
+// Lighting is computed in world space
 varying vec2 tex_coord_v;
-varying vec3 normalInterp;
-varying vec3 vertPos;
+varying vec3 normal_v;
+varying vec3 world_pos_v;
+varying vec4 shadow_coord_v;
+varying vec3 color_v;
 
 void main(){
-    gl_Position = proj * view * transform * vec4(scale * position, 1.0);
-    vec4 vertPos4 = view * transform * vec4(scale * position, 1.0);
-    vertPos = vec3(vertPos4) / vertPos4.w;
-    normalInterp = mat3(view) * ntransform * normal;
+    vec4 local_pos = vec4(scale * position, 1.0);
+    vec3 inst_pos = vec3(dot(inst_row_0, local_pos), dot(inst_row_1, local_pos), dot(inst_row_2, local_pos));
+    vec3 inst_normal = vec3(dot(inst_row_0.xyz, normal), dot(inst_row_1.xyz, normal), dot(inst_row_2.xyz, normal));
+
+    vec4 world_pos = transform * vec4(inst_pos, 1.0);
+    gl_Position = proj * view * world_pos;
+    world_pos_v = world_pos.xyz / world_pos.w;
+    normal_v = ntransform * inst_normal;
     tex_coord_v = tex_coord;
-    local_light_position = (view * vec4(light_position, 1.0)).xyz;
+    shadow_coord_v = shadow_view_proj * world_pos;
+#ifdef VERTEX_COLORS
+    color_v = inst_color * vertex_color;
+#else
+    color_v = inst_color;
+#endif
 }
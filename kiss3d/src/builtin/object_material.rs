@@ -1,49 +1,103 @@
 use crate::camera::Camera;
 use crate::context::Context;
-use crate::light::Light;
-use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3};
+use crate::light::{Light, Lights, MAX_POINT_LIGHTS};
+use na::{Isometry3, Matrix3, Matrix4, Point2, Point3, Vector3, Vector4};
 use crate::resource::Material;
 use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
 use crate::scene::ObjectData;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// The default material used to draw objects.
+///
+/// Objects are lit by the main light, which casts shadows if it is directional and shadows are
+/// enabled, and by up to `MAX_POINT_LIGHTS` point lights.
 pub struct ObjectMaterial {
     effect: Effect,
     pos: ShaderAttribute<Point3<f32>>,
     normal: ShaderAttribute<Vector3<f32>>,
     tex_coord: ShaderAttribute<Point2<f32>>,
-    light: ShaderUniform<Point3<f32>>,
+    vertex_color: Option<ShaderAttribute<Point3<f32>>>,
+    inst_rows: Vec<ShaderAttribute<Vector4<f32>>>,
+    inst_color: ShaderAttribute<Point3<f32>>,
     color: ShaderUniform<Point3<f32>>,
+    tex_region: ShaderUniform<Vector4<f32>>,
+    tex_region_enabled: ShaderUniform<f32>,
     transform: ShaderUniform<Matrix4<f32>>,
     scale: ShaderUniform<Matrix3<f32>>,
     ntransform: ShaderUniform<Matrix3<f32>>,
     proj: ShaderUniform<Matrix4<f32>>,
     view: ShaderUniform<Matrix4<f32>>,
+    eye: ShaderUniform<Point3<f32>>,
+    light_position: ShaderUniform<Point3<f32>>,
+    light_direction: ShaderUniform<Vector3<f32>>,
+    light_is_directional: ShaderUniform<f32>,
+    light_color: ShaderUniform<Point3<f32>>,
+    point_light_positions: Vec<ShaderUniform<Point3<f32>>>,
+    point_light_colors: Vec<ShaderUniform<Point3<f32>>>,
+    point_light_radii: Vec<ShaderUniform<f32>>,
+    shadows_enabled: ShaderUniform<f32>,
+    shadow_map: ShaderUniform<i32>,
+    shadow_view_proj: ShaderUniform<Matrix4<f32>>,
+    shadow_texel_size: ShaderUniform<f32>,
 }
 
 impl ObjectMaterial {
     /// Creates a new `ObjectMaterial`.
     pub fn new() -> ObjectMaterial {
+        ObjectMaterial::new_from_str(OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC, false)
+    }
+
+    // The vertex color variant of the shaders has an additional `vertex_color` attribute.
+    pub(crate) fn new_from_str(vertex_src: &str, fragment_src: &str, vertex_colors: bool) -> ObjectMaterial {
         // load the effect
-        let mut effect = Effect::new_from_str(OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC);
+        let mut effect = Effect::new_from_str(vertex_src, fragment_src);
 
         effect.use_program();
 
+        let array = |name: &str, i: usize| format!("{}[{}]", name, i);
+
         // get the variables locations
         ObjectMaterial {
             pos: effect.get_attrib("position").unwrap(),
             normal: effect.get_attrib("normal").unwrap(),
             tex_coord: effect.get_attrib("tex_coord").unwrap(),
-            light: effect.get_uniform("light_position").unwrap(),
+            vertex_color: if vertex_colors {
+                Some(effect.get_attrib("vertex_color").unwrap())
+            } else {
+                None
+            },
+            inst_rows: (0..3)
+                .map(|i| effect.get_attrib(&format!("inst_row_{}", i)).unwrap())
+                .collect(),
+            inst_color: effect.get_attrib("inst_color").unwrap(),
             color: effect.get_uniform("color").unwrap(),
+            tex_region: effect.get_uniform("tex_region").unwrap(),
+            tex_region_enabled: effect.get_uniform("tex_region_enabled").unwrap(),
             transform: effect.get_uniform("transform").unwrap(),
             scale: effect.get_uniform("scale").unwrap(),
             ntransform: effect.get_uniform("ntransform").unwrap(),
             view: effect.get_uniform("view").unwrap(),
             proj: effect.get_uniform("proj").unwrap(),
+            eye: effect.get_uniform("eye_position").unwrap(),
+            light_position: effect.get_uniform("light_position").unwrap(),
+            light_direction: effect.get_uniform("light_direction").unwrap(),
+            light_is_directional: effect.get_uniform("light_is_directional").unwrap(),
+            light_color: effect.get_uniform("light_color").unwrap(),
+            point_light_positions: (0..MAX_POINT_LIGHTS)
+                .map(|i| effect.get_uniform(&array("point_light_positions", i)).unwrap())
+                .collect(),
+            point_light_colors: (0..MAX_POINT_LIGHTS)
+                .map(|i| effect.get_uniform(&array("point_light_colors", i)).unwrap())
+                .collect(),
+            point_light_radii: (0..MAX_POINT_LIGHTS)
+                .map(|i| effect.get_uniform(&array("point_light_radii", i)).unwrap())
+                .collect(),
+            shadows_enabled: effect.get_uniform("shadows_enabled").unwrap(),
+            shadow_map: effect.get_uniform("shadow_map").unwrap(),
+            shadow_view_proj: effect.get_uniform("shadow_view_proj").unwrap(),
+            shadow_texel_size: effect.get_uniform("shadow_texel_size").unwrap(),
             effect: effect,
         }
     }
@@ -53,12 +107,72 @@ impl ObjectMaterial {
         self.pos.enable();
         self.normal.enable();
         self.tex_coord.enable();
+        if let Some(vertex_color) = &mut self.vertex_color {
+            vertex_color.enable();
+        }
     }
 
     fn deactivate(&mut self) {
         self.pos.disable();
         self.normal.disable();
         self.tex_coord.disable();
+        if let Some(vertex_color) = &mut self.vertex_color {
+            vertex_color.disable();
+        }
+    }
+
+    fn upload_lights(&mut self, camera: &dyn Camera, lights: &Lights) {
+        let ctxt = Context::get();
+
+        match lights.main {
+            Light::Absolute(ref p) => {
+                self.light_position.upload(p);
+                self.light_is_directional.upload(&0.0);
+            }
+            Light::StickToCamera => {
+                self.light_position.upload(&camera.eye());
+                self.light_is_directional.upload(&0.0);
+            }
+            Light::Directional(ref dir) => {
+                self.light_direction.upload(&dir.normalize());
+                self.light_is_directional.upload(&1.0);
+            }
+        }
+        self.light_color.upload(&lights.main_color());
+
+        for i in 0..MAX_POINT_LIGHTS {
+            match lights.point_lights.get(i) {
+                Some(light) => {
+                    self.point_light_positions[i].upload(&light.position);
+                    self.point_light_colors[i].upload(&(light.color * light.intensity));
+                    self.point_light_radii[i].upload(&light.radius);
+                }
+                None => {
+                    self.point_light_colors[i].upload(&Point3::origin());
+                    self.point_light_radii[i].upload(&1.0);
+                }
+            }
+        }
+
+        // The shadow map is bound to the second texture unit.
+        self.shadow_map.upload(&1);
+        let shadow_map = lights
+            .shadow_map()
+            .and_then(|map| map.depth_texture().map(|texture| (map, texture)));
+        match shadow_map {
+            Some((map, texture)) => {
+                verify!(ctxt.active_texture(Context::TEXTURE1));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
+                verify!(ctxt.active_texture(Context::TEXTURE0));
+                self.shadow_view_proj.upload(map.view_proj());
+                self.shadow_texel_size.upload(&(1.0 / map.size() as f32));
+                self.shadows_enabled.upload(&1.0);
+            }
+            None => {
+                self.shadow_view_proj.upload(&Matrix4::identity());
+                self.shadows_enabled.upload(&0.0);
+            }
+        }
     }
 }
 
@@ -72,24 +186,32 @@ impl Material for ObjectMaterial {
         light: &Light,
         data: &ObjectData,
         mesh: &mut Mesh,
+    ) {
+        let lights = Lights::new(light.clone());
+        self.render_with_lights(pass, transform, scale, camera, &lights, data, mesh)
+    }
+
+    fn render_with_lights(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
     ) {
         let ctxt = Context::get();
         self.activate();
 
         /*
          *
-         * Setup camera and light.
+         * Setup camera and lights.
          *
          */
         camera.upload(pass, &mut self.proj, &mut self.view);
-
-        let pos = match *light {
-            Light::Absolute(ref p) => p.clone(),
-            Light::StickToCamera => camera.eye(),
-        };
-
-        self.light.upload(&pos);
-
+        self.eye.upload(&camera.eye());
+        self.upload_lights(camera, lights);
         /*
          *
          * Setup object-related stuffs.
@@ -105,9 +227,26 @@ impl Material for ObjectMaterial {
             self.scale.upload(&formated_scale);
 
             mesh.bind(&mut self.pos, &mut self.normal, &mut self.tex_coord);
+            if let Some(vertex_color) = &mut self.vertex_color {
+                mesh.bind_colors(vertex_color);
+            }
+
+            let mut instances = data.instances().borrow_mut();
+            instances.bind(&mut self.inst_rows, Some(&mut self.inst_color));
 
             verify!(ctxt.active_texture(Context::TEXTURE0));
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*data.texture())));
+            match data.texture_region() {
+                Some((min, max)) => {
+                    let size = max - min;
+                    self.tex_region.upload(&Vector4::new(min.x, min.y, size.x, size.y));
+                    self.tex_region_enabled.upload(&1.0);
+                }
+                None => {
+                    self.tex_region.upload(&Vector4::new(0.0, 0.0, 1.0, 1.0));
+                    self.tex_region_enabled.upload(&0.0);
+                }
+            }
 
             if data.surface_rendering_active() {
                 self.color.upload(data.color());
@@ -119,12 +258,12 @@ impl Material for ObjectMaterial {
                 }
 
                 let _ = verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::FILL));
-                verify!(ctxt.draw_elements(
+                instances.draw_elements(
                     Context::TRIANGLES,
                     mesh.num_pts() as i32,
-                    Context::UNSIGNED_SHORT,
-                    0
-                ));
+                    mesh.index_type(),
+                    0,
+                );
             }
 
             if data.lines_width() != 0.0 {
@@ -135,20 +274,20 @@ impl Material for ObjectMaterial {
                 ignore!(ctxt.line_width(data.lines_width()));
 
                 if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::LINE)) {
-                    verify!(ctxt.draw_elements(
+                    instances.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type(),
+                        0,
+                    );
                 } else {
                     mesh.bind_edges();
-                    verify!(ctxt.draw_elements(
+                    instances.draw_elements(
                         Context::LINES,
                         mesh.num_pts() as i32 * 2,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type(),
+                        0,
+                    );
                 }
                 ctxt.line_width(1.0);
             }
@@ -159,22 +298,24 @@ impl Material for ObjectMaterial {
                 verify!(ctxt.disable(Context::CULL_FACE));
                 ctxt.point_size(data.points_size());
                 if verify!(ctxt.polygon_mode(Context::FRONT_AND_BACK, Context::POINT)) {
-                    verify!(ctxt.draw_elements(
+                    instances.draw_elements(
                         Context::TRIANGLES,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type(),
+                        0,
+                    );
                 } else {
-                    verify!(ctxt.draw_elements(
+                    instances.draw_elements(
                         Context::POINTS,
                         mesh.num_pts() as i32,
-                        Context::UNSIGNED_SHORT,
-                        0
-                    ));
+                        mesh.index_type(),
+                        0,
+                    );
                 }
                 ctxt.point_size(1.0);
             }
+
+            instances.unbind(&mut self.inst_rows, Some(&mut self.inst_color));
         }
 
         mesh.unbind();
@@ -183,12 +324,8 @@ impl Material for ObjectMaterial {
 }
 
 /// Vertex shader of the default object material.
-pub static OBJECT_VERTEX_SRC: &'static str = A_VERY_LONG_STRING;
-/// Fragment shader of the default object material.
-pub static OBJECT_FRAGMENT_SRC: &'static str = ANOTHER_VERY_LONG_STRING;
-
-const A_VERY_LONG_STRING: &'static str = include_str!("default.vert");
-
+pub static OBJECT_VERTEX_SRC: &'static str = concat!("#version 100\n", include_str!("default.vert"));
 // phong-like lighting (heavily) inspired
 // http://www.mathematik.uni-marburg.de/~thormae/lectures/graphics1/code/WebGLShaderLightMat/ShaderLightMat.html
-const ANOTHER_VERY_LONG_STRING: &'static str = include_str!("default.frag");
+/// Fragment shader of the default object material.
+pub static OBJECT_FRAGMENT_SRC: &'static str = concat!("#version 100\n", include_str!("default.frag"));
@@ -5,8 +5,8 @@ use crate::resource::PlanarMaterial;
 use crate::resource::{Effect, PlanarMesh, ShaderAttribute, ShaderUniform};
 use crate::scene::PlanarObjectData;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// The default material used to draw objects.
 pub struct PlanarObjectMaterial {
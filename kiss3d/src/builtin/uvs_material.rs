@@ -6,8 +6,8 @@ use crate::resource::Material;
 use crate::resource::{Effect, Mesh, ShaderAttribute, ShaderUniform};
 use crate::scene::ObjectData;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// A material that draws normals of an object.
 pub struct UvsMaterial {
@@ -92,7 +92,7 @@ impl Material for UvsMaterial {
             ctxt.draw_elements(
                 Context::TRIANGLES,
                 mesh.num_pts() as i32,
-                Context::UNSIGNED_INT,
+                mesh.index_type(),
                 0,
             );
         }
@@ -0,0 +1,68 @@
+use crate::builtin::ObjectMaterial;
+use crate::camera::Camera;
+use crate::light::{Light, Lights};
+use na::{Isometry3, Vector3};
+use crate::resource::{Material, Mesh};
+use crate::scene::ObjectData;
+
+/// A material that multiplies the object color by a per-vertex color.
+///
+/// Vertex colors are set with `Mesh::set_colors` and are typically used for baked lighting
+/// such as ambient occlusion. Meshes without vertex colors are drawn as with the default material.
+pub struct VertexColorMaterial {
+    material: ObjectMaterial,
+}
+
+impl VertexColorMaterial {
+    /// Creates a new `VertexColorMaterial`.
+    pub fn new() -> VertexColorMaterial {
+        VertexColorMaterial {
+            material: ObjectMaterial::new_from_str(
+                VERTEX_COLOR_VERTEX_SRC,
+                VERTEX_COLOR_FRAGMENT_SRC,
+                true,
+            ),
+        }
+    }
+}
+
+impl Material for VertexColorMaterial {
+    fn render(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        light: &Light,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        self.material
+            .render(pass, transform, scale, camera, light, data, mesh)
+    }
+
+    fn render_with_lights(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        self.material
+            .render_with_lights(pass, transform, scale, camera, lights, data, mesh)
+    }
+}
+
+/// Vertex shader of the vertex color material.
+pub static VERTEX_COLOR_VERTEX_SRC: &'static str = concat!(
+    "#version 100\n#define VERTEX_COLORS\n",
+    include_str!("default.vert")
+);
+/// Fragment shader of the vertex color material.
+pub static VERTEX_COLOR_FRAGMENT_SRC: &'static str = concat!(
+    "#version 100\n#define VERTEX_COLORS\n",
+    include_str!("default.frag")
+);
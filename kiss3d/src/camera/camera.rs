@@ -1,3 +1,4 @@
+use crate::camera::Frustum;
 use crate::event::WindowEvent;
 use na::{Isometry3, Matrix4, Point2, Point3, Point4, Vector2, Vector3};
 use crate::resource::ShaderUniform;
@@ -27,6 +28,15 @@ pub trait Camera {
     /// The clipping planes, aka. (`znear`, `zfar`).
     fn clip_planes(&self) -> (f32, f32); // FIXME: should this be here?
 
+    /// The volume seen during the pass `pass`, outside of which objects are not rendered.
+    ///
+    /// Returns `None` to render every object. Cameras with several passes seeing different
+    /// volumes should override this.
+    #[inline]
+    fn frustum(&self, _pass: usize) -> Option<Frustum> {
+        Some(Frustum::from_matrix(&self.transformation()))
+    }
+
     /*
      * Update & upload
      */
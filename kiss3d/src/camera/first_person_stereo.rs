@@ -2,14 +2,14 @@ use std::f32;
 
 use na::{self, Isometry3, Matrix4, Perspective3, Point2, Point3, Vector2, Vector3};
 
-use crate::camera::Camera;
+use crate::camera::{Camera, Frustum};
 use crate::context::Context;
 use crate::event::{Action, Key, MouseButton, WindowEvent};
 use crate::resource::ShaderUniform;
 use crate::window::Canvas;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// First-person camera mode.
 ///
@@ -297,6 +297,10 @@ impl Camera for FirstPersonStereo {
         2usize
     }
 
+    fn frustum(&self, pass: usize) -> Option<Frustum> {
+        Some(Frustum::from_matrix(&(self.proj * self.view_eye(pass))))
+    }
+
     fn start_pass(&self, pass: usize, canvas: &Canvas) {
         let ctxt = Context::get();
         let (win_w, win_h) = canvas.size();
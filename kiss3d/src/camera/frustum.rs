@@ -0,0 +1,58 @@
+use na::{Matrix4, Point3, Vector4};
+use ncollide3d::bounding_volume::AABB;
+
+/// The volume seen by a camera, bounded by six planes.
+///
+/// It is used to skip the objects a camera cannot see when rendering.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frustum {
+    // Each plane is `(a, b, c, d)` such that the points `p` inside the frustum satisfy
+    // `a * p.x + b * p.y + c * p.z + d >= 0`.
+    planes: [Vector4<f32>; 6],
+}
+
+impl Frustum {
+    /// The frustum of a transformation from world coordinates to clip coordinates, like
+    /// `Camera::transformation`.
+    pub fn from_matrix(m: &Matrix4<f32>) -> Frustum {
+        let row = |i: usize| m.row(i).transpose();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+
+        Frustum {
+            planes: [w + x, w - x, w + y, w - y, w + z, w - z],
+        }
+    }
+
+    /// The planes of this frustum: left, right, bottom, top, near and far.
+    ///
+    /// Each plane is `(a, b, c, d)` such that the points `p` inside the frustum satisfy
+    /// `a * p.x + b * p.y + c * p.z + d >= 0`. The planes are not normalized.
+    pub fn planes(&self) -> &[Vector4<f32>; 6] {
+        &self.planes
+    }
+
+    /// Whether a point is inside this frustum.
+    pub fn contains_point(&self, p: &Point3<f32>) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.xyz().dot(&p.coords) + plane.w >= 0.0)
+    }
+
+    /// Whether a box may intersect this frustum.
+    ///
+    /// This is conservative: boxes outside the frustum but close to its edges may still be
+    /// reported as intersecting it.
+    pub fn intersects_aabb(&self, aabb: &AABB<f32>) -> bool {
+        let (mins, maxs) = (aabb.mins(), aabb.maxs());
+
+        self.planes.iter().all(|plane| {
+            // The corner of the box furthest along the normal of the plane.
+            let corner = Point3::new(
+                if plane.x >= 0.0 { maxs.x } else { mins.x },
+                if plane.y >= 0.0 { maxs.y } else { mins.y },
+                if plane.z >= 0.0 { maxs.z } else { mins.z },
+            );
+            plane.xyz().dot(&corner.coords) + plane.w >= 0.0
+        })
+    }
+}
@@ -5,6 +5,7 @@ pub use self::camera::Camera;
 pub use self::first_person::FirstPerson;
 pub use self::first_person_stereo::FirstPersonStereo;
 pub use self::fixed_view::FixedView;
+pub use self::frustum::Frustum;
 
 mod arc_ball;
 #[doc(hidden)]
@@ -12,3 +13,4 @@ pub mod camera;
 mod first_person;
 mod first_person_stereo;
 mod fixed_view;
+mod frustum;
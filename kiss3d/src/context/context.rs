@@ -2,9 +2,17 @@
 
 use std::sync::Once;
 
-#[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
+#[cfg(feature = "headless")]
+use crate::context::NullContext as ContextImpl;
+#[cfg(all(
+    not(feature = "headless"),
+    not(any(target_arch = "wasm32", target_arch = "asmjs"))
+))]
 use crate::context::GLContext as ContextImpl;
-#[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+#[cfg(all(
+    not(feature = "headless"),
+    any(target_arch = "wasm32", target_arch = "asmjs")
+))]
 use crate::context::WebGLContext as ContextImpl;
 
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
@@ -19,8 +27,8 @@ use gl::{
 use na::{Matrix2, Matrix3, Matrix4};
 use crate::resource::GLPrimitive;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 pub type GLenum = GLenumTy;
 pub type GLintptr = GLintptrTy;
@@ -462,6 +470,31 @@ impl Context {
         self.ctxt.draw_elements(mode, count, type_, offset)
     }
 
+    pub fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    ) {
+        self.ctxt
+            .draw_elements_instanced(mode, count, type_, offset, instances)
+    }
+
+    pub fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        self.ctxt.vertex_attrib_divisor(index, divisor)
+    }
+
+    /// Whether `draw_elements_instanced` and `vertex_attrib_divisor` are available.
+    pub fn supports_instancing(&self) -> bool {
+        self.ctxt.supports_instancing()
+    }
+
+    pub fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        self.ctxt.vertex_attrib4f(index, x, y, z, w)
+    }
+
     pub fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
         self.ctxt.draw_arrays(mode, first, count)
     }
@@ -734,6 +767,17 @@ pub(crate) trait AbstractContext {
     fn disable(&self, cap: GLenum);
 
     fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr);
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    );
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32);
+    fn supports_instancing(&self) -> bool;
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32);
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32);
 
     fn point_size(&self, size: f32);
@@ -10,8 +10,8 @@ use num::Zero;
 use na::{Matrix2, Matrix3, Matrix4};
 use crate::resource::GLPrimitive;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// An OpenGL context.
 #[derive(Clone)]
@@ -568,6 +568,33 @@ impl AbstractContext for GLContext {
         unsafe { gl::DrawElements(mode, count, type_, mem::transmute(offset)) }
     }
 
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    ) {
+        unsafe {
+            gl::DrawElementsInstanced(mode, count, type_, mem::transmute(offset), instances)
+        }
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        unsafe { gl::VertexAttribDivisor(index, divisor) }
+    }
+
+    // Attribute divisors are only core since OpenGL 3.3, and OpenGL ES 2.0 has neither them nor
+    // instanced draw calls.
+    fn supports_instancing(&self) -> bool {
+        gl::VertexAttribDivisor::is_loaded() && gl::DrawElementsInstanced::is_loaded()
+    }
+
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        unsafe { gl::VertexAttrib4f(index, x, y, z, w) }
+    }
+
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
         unsafe { gl::DrawArrays(mode, first, count) }
     }
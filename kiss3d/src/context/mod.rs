@@ -3,12 +3,20 @@
 pub use self::context::*;
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 pub use self::gl_context::GLContext;
+#[cfg(feature = "headless")]
+pub use self::null_context::{Command, NullContext, UniformValue};
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
 pub use self::webgl_context::WebGLContext;
 
 mod context;
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 mod gl_context;
+#[cfg(feature = "headless")]
+mod null_context;
+#[cfg(feature = "headless")]
+mod rasterizer;
+#[cfg(feature = "headless")]
+mod software_shaders;
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
 mod webgl_bindings;
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
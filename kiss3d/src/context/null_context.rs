@@ -0,0 +1,1610 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::slice;
+
+use crate::context::rasterizer::{Image, Pipeline, Rasterizer, Sampler, TextureObject};
+use crate::context::software_shaders::BuiltinShader;
+use crate::context::{AbstractContext, AbstractContextConst, GLenum, GLintptr};
+
+use na::{Matrix2, Matrix3, Matrix4, Vector4};
+use crate::resource::GLPrimitive;
+
+/// The value given to a shader uniform.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformValue {
+    /// One to four floats.
+    Float(Vec<f32>),
+    /// One to three integers.
+    Int(Vec<i32>),
+    /// A 2x2 matrix.
+    Matrix2(Matrix2<f32>),
+    /// A 3x3 matrix.
+    Matrix3(Matrix3<f32>),
+    /// A 4x4 matrix.
+    Matrix4(Matrix4<f32>),
+}
+
+/// A call made to the headless context.
+///
+/// Objects (buffers, shaders, programs, textures, framebuffers and renderbuffers) are
+/// identified by the ids the context gave them when they were created.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    CreateBuffer(u32),
+    DeleteBuffer(u32),
+    BindBuffer {
+        target: GLenum,
+        buffer: Option<u32>,
+    },
+    /// A whole buffer upload. Uninitialized buffers are recorded as zeros.
+    BufferData {
+        target: GLenum,
+        data: Vec<u8>,
+        usage: GLenum,
+    },
+    BufferSubData {
+        target: GLenum,
+        offset: u32,
+        data: Vec<u8>,
+    },
+    CreateShader {
+        shader: u32,
+        type_: GLenum,
+    },
+    DeleteShader(u32),
+    ShaderSource {
+        shader: u32,
+        source: String,
+    },
+    CompileShader(u32),
+    CreateProgram(u32),
+    DeleteProgram(u32),
+    AttachShader {
+        program: u32,
+        shader: u32,
+    },
+    LinkProgram(u32),
+    UseProgram(Option<u32>),
+    /// A uniform set on `program`, identified by its name in the shader.
+    Uniform {
+        program: u32,
+        name: String,
+        value: UniformValue,
+    },
+    VertexAttribPointer {
+        index: u32,
+        size: i32,
+        type_: GLenum,
+        normalized: bool,
+        stride: i32,
+        offset: GLintptr,
+    },
+    EnableVertexAttribArray(u32),
+    DisableVertexAttribArray(u32),
+    VertexAttribDivisor {
+        index: u32,
+        divisor: u32,
+    },
+    VertexAttrib4f {
+        index: u32,
+        value: [f32; 4],
+    },
+    Viewport {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    Scissor {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+    },
+    CreateFramebuffer(u32),
+    DeleteFramebuffer(u32),
+    BindFramebuffer {
+        target: GLenum,
+        framebuffer: Option<u32>,
+    },
+    FramebufferTexture2D {
+        target: GLenum,
+        attachment: GLenum,
+        textarget: GLenum,
+        texture: Option<u32>,
+        level: i32,
+    },
+    CreateRenderbuffer(u32),
+    DeleteRenderbuffer(u32),
+    BindRenderbuffer(Option<u32>),
+    RenderbufferStorage {
+        internal_format: GLenum,
+        width: i32,
+        height: i32,
+    },
+    FramebufferRenderbuffer {
+        attachment: GLenum,
+        renderbuffer: Option<u32>,
+    },
+    CreateTexture(u32),
+    DeleteTexture(u32),
+    BindTexture {
+        target: GLenum,
+        texture: Option<u32>,
+    },
+    ActiveTexture(GLenum),
+    /// A texture upload. The pixels of `tex_image2di` are recorded as their native-endian bytes.
+    TexImage2D {
+        target: GLenum,
+        level: i32,
+        internal_format: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<Vec<u8>>,
+    },
+    TexSubImage2D {
+        target: GLenum,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<Vec<u8>>,
+    },
+    TexParameteri {
+        target: GLenum,
+        pname: GLenum,
+        param: i32,
+    },
+    Enable(GLenum),
+    Disable(GLenum),
+    DrawElements {
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+    },
+    DrawElementsInstanced {
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    },
+    DrawArrays {
+        mode: GLenum,
+        first: i32,
+        count: i32,
+    },
+    PointSize(f32),
+    LineWidth(f32),
+    Clear(u32),
+    ClearColor(f32, f32, f32, f32),
+    PolygonMode {
+        face: GLenum,
+        mode: GLenum,
+    },
+    FrontFace(GLenum),
+    DepthFunc(GLenum),
+    CullFace(GLenum),
+    ReadPixels {
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+    },
+    PixelStorei {
+        pname: GLenum,
+        param: i32,
+    },
+    BlendFuncSeparate {
+        src_rgb: GLenum,
+        dst_rgb: GLenum,
+        src_alpha: GLenum,
+        dst_alpha: GLenum,
+    },
+}
+
+impl Command {
+    /// Whether this command is a draw call.
+    pub fn is_draw(&self) -> bool {
+        matches!(
+            self,
+            Command::DrawElements { .. }
+                | Command::DrawElementsInstanced { .. }
+                | Command::DrawArrays { .. }
+        )
+    }
+}
+
+
+// A vertex attribute array, as set by `vertex_attrib_pointer`.
+#[derive(Clone, Default)]
+struct AttribArray {
+    enabled: bool,
+    buffer: Option<u32>,
+    size: i32,
+    type_: GLenum,
+    normalized: bool,
+    stride: i32,
+    offset: usize,
+    divisor: u32,
+    // The value of the attribute while the array is disabled, (0, 0, 0, 1) unless set.
+    value: Option<Vector4<f32>>,
+}
+
+// The values of an attribute array, read from its buffer.
+struct Attribute<'a> {
+    array: &'a AttribArray,
+    data: &'a [u8],
+}
+
+impl<'a> Attribute<'a> {
+    fn fetch(&self, vertex: usize, instance: usize) -> Vector4<f32> {
+        let array = self.array;
+        let element = if array.divisor == 0 {
+            vertex
+        } else {
+            instance / array.divisor as usize
+        };
+        let component_size = match array.type_ {
+            NullContext::UNSIGNED_BYTE => 1,
+            NullContext::UNSIGNED_SHORT => 2,
+            _ => 4,
+        };
+        let stride = if array.stride == 0 {
+            array.size as usize * component_size
+        } else {
+            array.stride as usize
+        };
+        let start = array.offset + element * stride;
+
+        let mut value = Vector4::new(0.0, 0.0, 0.0, 1.0);
+        for i in 0..(array.size as usize).min(4) {
+            let at = start + i * component_size;
+            let bytes = match self.data.get(at..at + component_size) {
+                Some(bytes) => bytes,
+                None => break,
+            };
+            value[i] = match array.type_ {
+                NullContext::UNSIGNED_BYTE if array.normalized => bytes[0] as f32 / 255.0,
+                NullContext::UNSIGNED_BYTE => bytes[0] as f32,
+                NullContext::UNSIGNED_SHORT => {
+                    let x = u16::from_ne_bytes([bytes[0], bytes[1]]) as f32;
+                    if array.normalized {
+                        x / 65535.0
+                    } else {
+                        x
+                    }
+                }
+                NullContext::UNSIGNED_INT => {
+                    u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32
+                }
+                NullContext::INT => i32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+                _ => f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            };
+        }
+        value
+    }
+}
+
+#[derive(Default)]
+struct ProgramObject {
+    shaders: Vec<u32>,
+    // The attribute names, the location of an attribute being its index.
+    attributes: Vec<String>,
+    uniforms: HashMap<String, UniformValue>,
+    // The program the software rasterizer runs for draw calls made with this one.
+    builtin: Option<BuiltinShader>,
+}
+
+// An image draw calls can write to.
+#[derive(Copy, Clone)]
+enum Surface {
+    ScreenColor,
+    ScreenDepth,
+    Texture(u32),
+    Renderbuffer(u32),
+}
+
+#[derive(Default)]
+struct FramebufferObject {
+    color: Option<Surface>,
+    depth: Option<Surface>,
+}
+
+struct State {
+    next_id: u32,
+    buffers: HashMap<u32, Vec<u8>>,
+    // The type and the source of each shader.
+    shaders: HashMap<u32, (GLenum, String)>,
+    programs: HashMap<u32, ProgramObject>,
+    framebuffers: HashMap<u32, FramebufferObject>,
+    renderbuffers: HashMap<u32, Option<Image>>,
+    textures: HashMap<u32, TextureObject>,
+    array_buffer: Option<u32>,
+    element_array_buffer: Option<u32>,
+    attrib_arrays: Vec<AttribArray>,
+    program: Option<u32>,
+    framebuffer: Option<u32>,
+    renderbuffer: Option<u32>,
+    active_texture: usize,
+    texture_units: Vec<Option<u32>>,
+    screen_color: Option<Image>,
+    screen_depth: Option<Image>,
+    capabilities: HashSet<GLenum>,
+    viewport: [i32; 4],
+    scissor: [i32; 4],
+    cull_face: GLenum,
+    front_face: GLenum,
+    depth_func: GLenum,
+    blend_func: [GLenum; 4],
+    polygon_mode: GLenum,
+    line_width: f32,
+    point_size: f32,
+    clear_color: [f32; 4],
+    pack_alignment: usize,
+    unpack_alignment: usize,
+    // Whether instanced draw calls and attribute divisors are available.
+    instancing: bool,
+    commands: Vec<Command>,
+}
+
+impl Default for State {
+    // The initial state of an OpenGL context.
+    fn default() -> Self {
+        State {
+            next_id: 0,
+            buffers: HashMap::new(),
+            shaders: HashMap::new(),
+            programs: HashMap::new(),
+            framebuffers: HashMap::new(),
+            renderbuffers: HashMap::new(),
+            textures: HashMap::new(),
+            array_buffer: None,
+            element_array_buffer: None,
+            attrib_arrays: Vec::new(),
+            program: None,
+            framebuffer: None,
+            renderbuffer: None,
+            active_texture: 0,
+            texture_units: Vec::new(),
+            screen_color: None,
+            screen_depth: None,
+            capabilities: HashSet::new(),
+            viewport: [0; 4],
+            scissor: [0; 4],
+            cull_face: NullContext::BACK,
+            front_face: NullContext::CCW,
+            // GL_LESS
+            depth_func: 0x0201,
+            // GL_ONE, GL_ZERO
+            blend_func: [NullContext::ONE, 0, NullContext::ONE, 0],
+            polygon_mode: NullContext::FILL,
+            line_width: 1.0,
+            point_size: 1.0,
+            clear_color: [0.0; 4],
+            pack_alignment: 4,
+            unpack_alignment: 4,
+            instancing: true,
+            commands: Vec::new(),
+        }
+    }
+}
+
+impl State {
+    fn create(&mut self) -> u32 {
+        // Zero is reserved for "no object", as with OpenGL.
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn bound_buffer(&mut self, target: GLenum) -> Option<&mut Vec<u8>> {
+        let buffer = if target == NullContext::ELEMENT_ARRAY_BUFFER {
+            self.element_array_buffer
+        } else {
+            self.array_buffer
+        };
+        buffer.and_then(move |buffer| self.buffers.get_mut(&buffer))
+    }
+
+    fn bound_texture(&mut self) -> Option<&mut TextureObject> {
+        let texture = self.texture_units.get(self.active_texture).cloned().flatten();
+        texture.and_then(move |texture| self.textures.get_mut(&texture))
+    }
+
+    fn attrib_array(&mut self, index: u32) -> &mut AttribArray {
+        let index = index as usize;
+        if self.attrib_arrays.len() <= index {
+            self.attrib_arrays.resize(index + 1, AttribArray::default());
+        }
+        &mut self.attrib_arrays[index]
+    }
+
+    fn attribute(&self, program: &ProgramObject, name: &str) -> Option<Attribute<'_>> {
+        let location = program.attributes.iter().position(|attribute| attribute == name)?;
+        let array = self.attrib_arrays.get(location).filter(|array| array.enabled)?;
+        let data = self.buffers.get(&array.buffer?)?;
+        Some(Attribute { array, data })
+    }
+
+    // The value of an attribute whose array is disabled.
+    fn constant_attribute(&self, program: &ProgramObject, name: &str) -> Vector4<f32> {
+        program
+            .attributes
+            .iter()
+            .position(|attribute| attribute == name)
+            .and_then(|location| self.attrib_arrays.get(location))
+            .and_then(|array| array.value)
+            .unwrap_or_else(|| Vector4::new(0.0, 0.0, 0.0, 1.0))
+    }
+
+    fn sampler(&self, unit: usize) -> Sampler<'_> {
+        let texture = self.texture_units.get(unit).cloned().flatten();
+        Sampler::new(texture.and_then(|texture| self.textures.get(&texture)))
+    }
+
+    fn enabled(&self, cap: GLenum) -> bool {
+        self.capabilities.contains(&cap)
+    }
+
+    fn scissor_rect(&self) -> Option<[i32; 4]> {
+        if self.enabled(NullContext::SCISSOR_TEST) {
+            Some(self.scissor)
+        } else {
+            None
+        }
+    }
+
+    fn pipeline(&self) -> Pipeline {
+        Pipeline {
+            viewport: self.viewport,
+            scissor: self.scissor_rect(),
+            cull_face: Some(self.cull_face).filter(|_| self.enabled(NullContext::CULL_FACE)),
+            front_face: self.front_face,
+            depth_func: Some(self.depth_func).filter(|_| self.enabled(NullContext::DEPTH_TEST)),
+            blend_func: Some(self.blend_func).filter(|_| self.enabled(NullContext::BLEND)),
+            polygon_mode: self.polygon_mode,
+            line_width: self.line_width,
+            point_size: self.point_size,
+        }
+    }
+
+    fn image_mut(&mut self, surface: Surface) -> Option<&mut Option<Image>> {
+        match surface {
+            Surface::ScreenColor => Some(&mut self.screen_color),
+            Surface::ScreenDepth => Some(&mut self.screen_depth),
+            Surface::Texture(texture) => self.textures.get_mut(&texture).map(|t| &mut t.image),
+            Surface::Renderbuffer(buffer) => self.renderbuffers.get_mut(&buffer),
+        }
+    }
+
+    // Calls `f` with the color and depth images of the bound framebuffer, taken out of the
+    // state meanwhile so the rest of the state can be read.
+    fn with_targets<T>(
+        &mut self,
+        f: impl FnOnce(&State, Option<&mut Image>, Option<&mut Image>) -> T,
+    ) -> T {
+        let (color, depth) = match self.framebuffer {
+            None => (Some(Surface::ScreenColor), Some(Surface::ScreenDepth)),
+            Some(framebuffer) => self
+                .framebuffers
+                .get(&framebuffer)
+                .map_or((None, None), |framebuffer| (framebuffer.color, framebuffer.depth)),
+        };
+        let mut take = |surface: Option<Surface>| {
+            surface
+                .and_then(|surface| self.image_mut(surface))
+                .and_then(|image| image.take())
+        };
+        let mut color_image = take(color);
+        let mut depth_image = take(depth);
+
+        let result = f(self, color_image.as_mut(), depth_image.as_mut());
+
+        for (surface, image) in [(color, color_image), (depth, depth_image)] {
+            if let Some(slot) = surface.and_then(|surface| self.image_mut(surface)) {
+                *slot = image;
+            }
+        }
+        result
+    }
+
+    fn clear(&mut self, mask: u32) {
+        let rect = self.scissor_rect();
+        let color = self.clear_color;
+        self.with_targets(|_, color_image, depth_image| {
+            if mask & NullContext::COLOR_BUFFER_BIT != 0 {
+                if let Some(image) = color_image {
+                    image.fill_color(rect, color)
+                }
+            }
+            if mask & NullContext::DEPTH_BUFFER_BIT != 0 {
+                if let Some(image) = depth_image {
+                    image.fill_depth(rect, 1.0)
+                }
+            }
+        })
+    }
+
+    // Runs a draw call made with a built-in program on the software rasterizer.
+    fn draw(&mut self, mode: GLenum, indices: &[usize], instances: usize) {
+        self.with_targets(|state, color, depth| {
+            let program = match state.program.and_then(|program| state.programs.get(&program)) {
+                Some(program) => program,
+                None => return,
+            };
+            let builtin = match program.builtin {
+                Some(builtin) => builtin,
+                None => return,
+            };
+
+            let shader = builtin.build(&program.uniforms, |unit| state.sampler(unit));
+            let attributes: Vec<_> = builtin
+                .attributes()
+                .iter()
+                .map(|name| state.attribute(program, name))
+                .collect();
+            let constants: Vec<_> = builtin
+                .attributes()
+                .iter()
+                .map(|name| state.constant_attribute(program, name))
+                .collect();
+            let pipeline = state.pipeline();
+            let mut rasterizer = Rasterizer::new(&pipeline, &*shader, color, depth);
+
+            // Each vertex is shaded once per instance, however many primitives share it.
+            let len = indices.iter().max().map_or(0, |max| max + 1);
+            let mut shaded = vec![None; len];
+            let mut vertices = Vec::new();
+            let mut primitives = Vec::with_capacity(indices.len());
+            let mut values = vec![Vector4::new(0.0, 0.0, 0.0, 1.0); attributes.len()];
+
+            for instance in 0..instances {
+                shaded.iter_mut().for_each(|i| *i = None);
+                vertices.clear();
+                primitives.clear();
+
+                for &index in indices {
+                    let i = match shaded[index] {
+                        Some(i) => i,
+                        None => {
+                            let inputs = attributes.iter().zip(constants.iter());
+                            for (value, (attribute, constant)) in values.iter_mut().zip(inputs) {
+                                *value = match attribute {
+                                    Some(attribute) => attribute.fetch(index, instance),
+                                    None => *constant,
+                                };
+                            }
+                            vertices.push(shader.vertex(&values));
+                            shaded[index] = Some(vertices.len() - 1);
+                            vertices.len() - 1
+                        }
+                    };
+                    primitives.push(i);
+                }
+
+                rasterizer.draw(mode, &vertices, &primitives);
+            }
+        })
+    }
+
+    fn draw_elements(
+        &mut self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: usize,
+    ) {
+        let data = match self.element_array_buffer.and_then(|b| self.buffers.get(&b)) {
+            Some(data) => data,
+            None => return,
+        };
+        let size = match type_ {
+            NullContext::UNSIGNED_BYTE => 1,
+            NullContext::UNSIGNED_SHORT => 2,
+            _ => 4,
+        };
+        let indices: Vec<usize> = (0..count.max(0) as usize)
+            .filter_map(|i| {
+                let at = offset as usize + i * size;
+                let bytes = data.get(at..at + size)?;
+                Some(match size {
+                    1 => bytes[0] as usize,
+                    2 => u16::from_ne_bytes([bytes[0], bytes[1]]) as usize,
+                    _ => u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize,
+                })
+            })
+            .collect();
+
+        self.draw(mode, &indices, instances)
+    }
+}
+
+thread_local!(static STATE: RefCell<State> = RefCell::new(State::default()));
+
+fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
+    STATE.with(|state| f(&mut state.borrow_mut()))
+}
+
+fn record(command: Command) {
+    with_state(|state| state.commands.push(command))
+}
+
+fn bytes<T: Copy>(data: &[T]) -> Vec<u8> {
+    unsafe { slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }.to_vec()
+}
+
+/// A context that needs neither a GPU nor a display, so the scene graph and the rendering code
+/// can be exercised on any machine, e.g., by unit tests.
+///
+/// Every call made to it is recorded, and the recorded commands can be inspected with
+/// `NullContext::commands`. The draw calls of kiss3d's own programs (the built-in materials,
+/// the line, point and text renderers and the shadow map) are also executed by a software
+/// rasterizer, so what they draw can be read back with `Window::snap_image`. Draw calls made
+/// with any other program are only recorded. The state is kept per thread.
+#[derive(Clone)]
+pub struct NullContext;
+
+impl NullContext {
+    /// Creates a new headless context.
+    pub fn new() -> Self {
+        NullContext
+    }
+
+    /// The commands recorded so far on the current thread.
+    pub fn commands() -> Vec<Command> {
+        with_state(|state| state.commands.clone())
+    }
+
+    /// Removes and returns the commands recorded so far on the current thread.
+    pub fn take_commands() -> Vec<Command> {
+        with_state(|state| mem::take(&mut state.commands))
+    }
+
+    /// Forgets the commands recorded so far on the current thread.
+    pub fn clear_commands() {
+        with_state(|state| state.commands.clear())
+    }
+
+    /// Sets whether the context of the current thread supports instancing, as OpenGL 3.2,
+    /// OpenGL ES 2.0 and WebGL without the ANGLE_instanced_arrays extension do not. Without it,
+    /// instanced draw calls and attribute divisors panic. It is supported by default.
+    pub fn set_instancing_supported(supported: bool) {
+        with_state(|state| state.instancing = supported)
+    }
+
+    /// Reallocates the default framebuffer, i.e., the screen, and resets the viewport and the
+    /// scissor box to cover it, as a new OpenGL context would.
+    pub(crate) fn set_screen_size(width: u32, height: u32) {
+        with_state(|state| {
+            let (width, height) = (width as usize, height as usize);
+            state.screen_color = Some(Image::color(width, height));
+            state.screen_depth = Some(Image::depth(width, height));
+            state.viewport = [0, 0, width as i32, height as i32];
+            state.scissor = state.viewport;
+        })
+    }
+}
+
+// The OpenGL values of the constants, so recorded commands read the same as OpenGL calls.
+impl AbstractContextConst for NullContext {
+    const FLOAT: u32 = 0x1406;
+    const INT: u32 = 0x1404;
+    const UNSIGNED_INT: u32 = 0x1405;
+    const UNSIGNED_SHORT: u32 = 0x1403;
+    const STATIC_DRAW: u32 = 0x88E4;
+    const DYNAMIC_DRAW: u32 = 0x88E8;
+    const STREAM_DRAW: u32 = 0x88E0;
+    const ARRAY_BUFFER: u32 = 0x8892;
+    const ELEMENT_ARRAY_BUFFER: u32 = 0x8893;
+    const VERTEX_SHADER: u32 = 0x8B31;
+    const FRAGMENT_SHADER: u32 = 0x8B30;
+    const COMPILE_STATUS: u32 = 0x8B81;
+    const FRAMEBUFFER: u32 = 0x8D40;
+    const RENDERBUFFER: u32 = 0x8D41;
+    const DEPTH_ATTACHMENT: u32 = 0x8D00;
+    const COLOR_ATTACHMENT0: u32 = 0x8CE0;
+    const TEXTURE_2D: u32 = 0x0DE1;
+    const DEPTH_COMPONENT: u32 = 0x1902;
+    const DEPTH_COMPONENT16: u32 = 0x81A5;
+    const UNSIGNED_BYTE: u32 = 0x1401;
+    const TEXTURE_WRAP_S: u32 = 0x2802;
+    const TEXTURE_WRAP_T: u32 = 0x2803;
+    const TEXTURE_MIN_FILTER: u32 = 0x2801;
+    const TEXTURE_MAG_FILTER: u32 = 0x2800;
+    const LINEAR: u32 = 0x2601;
+    const NEAREST: u32 = 0x2600;
+    const CLAMP_TO_EDGE: u32 = 0x812F;
+    const RGB: u32 = 0x1907;
+    const RGBA: u32 = 0x1908;
+    const TEXTURE0: u32 = 0x84C0;
+    const TEXTURE1: u32 = 0x84C1;
+    const REPEAT: u32 = 0x2901;
+    const MIRRORED_REPEAT: u32 = 0x8370;
+    const LINEAR_MIPMAP_LINEAR: u32 = 0x2703;
+    const TRIANGLES: u32 = 0x0004;
+    const CULL_FACE: u32 = 0x0B44;
+    const FRONT_AND_BACK: u32 = 0x0408;
+    const LINES: u32 = 0x0001;
+    const POINTS: u32 = 0x0000;
+    const TRIANGLE_STRIP: u32 = 0x0005;
+    const COLOR_BUFFER_BIT: u32 = 0x4000;
+    const DEPTH_BUFFER_BIT: u32 = 0x0100;
+    const CCW: u32 = 0x0901;
+    const DEPTH_TEST: u32 = 0x0B71;
+    const SCISSOR_TEST: u32 = 0x0C11;
+    const LEQUAL: u32 = 0x0203;
+    const BACK: u32 = 0x0405;
+    const PACK_ALIGNMENT: u32 = 0x0D05;
+    const PROGRAM_POINT_SIZE: u32 = 0x8642;
+    const LINE: u32 = 0x1B01;
+    const POINT: u32 = 0x1B00;
+    const FILL: u32 = 0x1B02;
+    const BLEND: u32 = 0x0BE2;
+    const SRC_ALPHA: u32 = 0x0302;
+    const ONE_MINUS_SRC_ALPHA: u32 = 0x0303;
+    const ONE: u32 = 1;
+    const UNPACK_ALIGNMENT: u32 = 0x0CF5;
+    const ALPHA: u32 = 0x1906;
+    const RED: u32 = 0x1903;
+}
+
+
+impl AbstractContext for NullContext {
+    // The program and the name of the uniform.
+    type UniformLocation = (u32, String);
+    type Buffer = u32;
+    type Shader = u32;
+    type Program = u32;
+    type Framebuffer = u32;
+    type Renderbuffer = u32;
+    type Texture = u32;
+
+    fn get_error(&self) -> GLenum {
+        0
+    }
+
+    fn uniform_matrix2fv(
+        &self,
+        location: Option<&Self::UniformLocation>,
+        transpose: bool,
+        m: &Matrix2<f32>,
+    ) {
+        let m = if transpose { m.transpose() } else { *m };
+        uniform(location, UniformValue::Matrix2(m))
+    }
+
+    fn uniform_matrix3fv(
+        &self,
+        location: Option<&Self::UniformLocation>,
+        transpose: bool,
+        m: &Matrix3<f32>,
+    ) {
+        let m = if transpose { m.transpose() } else { *m };
+        uniform(location, UniformValue::Matrix3(m))
+    }
+
+    fn uniform_matrix4fv(
+        &self,
+        location: Option<&Self::UniformLocation>,
+        transpose: bool,
+        m: &Matrix4<f32>,
+    ) {
+        let m = if transpose { m.transpose() } else { *m };
+        uniform(location, UniformValue::Matrix4(m))
+    }
+
+    fn uniform4f(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32, z: f32, w: f32) {
+        uniform(location, UniformValue::Float(vec![x, y, z, w]))
+    }
+
+    fn uniform3f(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32, z: f32) {
+        uniform(location, UniformValue::Float(vec![x, y, z]))
+    }
+
+    fn uniform2f(&self, location: Option<&Self::UniformLocation>, x: f32, y: f32) {
+        uniform(location, UniformValue::Float(vec![x, y]))
+    }
+
+    fn uniform1f(&self, location: Option<&Self::UniformLocation>, x: f32) {
+        uniform(location, UniformValue::Float(vec![x]))
+    }
+
+    fn uniform3i(&self, location: Option<&Self::UniformLocation>, x: i32, y: i32, z: i32) {
+        uniform(location, UniformValue::Int(vec![x, y, z]))
+    }
+
+    fn uniform2i(&self, location: Option<&Self::UniformLocation>, x: i32, y: i32) {
+        uniform(location, UniformValue::Int(vec![x, y]))
+    }
+
+    fn uniform1i(&self, location: Option<&Self::UniformLocation>, x: i32) {
+        uniform(location, UniformValue::Int(vec![x]))
+    }
+
+    fn create_buffer(&self) -> Option<Self::Buffer> {
+        with_state(|state| {
+            let buffer = state.create();
+            let _ = state.buffers.insert(buffer, Vec::new());
+            state.commands.push(Command::CreateBuffer(buffer));
+            Some(buffer)
+        })
+    }
+
+    fn delete_buffer(&self, buffer: Option<&Self::Buffer>) {
+        if let Some(buffer) = buffer {
+            with_state(|state| {
+                let _ = state.buffers.remove(buffer);
+                state.commands.push(Command::DeleteBuffer(*buffer));
+            })
+        }
+    }
+
+    fn is_buffer(&self, buffer: Option<&Self::Buffer>) -> bool {
+        buffer.map_or(false, |buffer| with_state(|state| state.buffers.contains_key(buffer)))
+    }
+
+    fn bind_buffer(&self, target: GLenum, buffer: Option<&Self::Buffer>) {
+        with_state(|state| {
+            if target == Self::ELEMENT_ARRAY_BUFFER {
+                state.element_array_buffer = buffer.cloned();
+            } else {
+                state.array_buffer = buffer.cloned();
+            }
+            state.commands.push(Command::BindBuffer {
+                target,
+                buffer: buffer.cloned(),
+            })
+        })
+    }
+
+    fn buffer_data_uninitialized(&self, target: GLenum, len: usize, usage: GLenum) {
+        buffer_data(target, vec![0; len], usage)
+    }
+
+    fn buffer_data<T: GLPrimitive>(&self, target: GLenum, data: &[T], usage: GLenum) {
+        buffer_data(target, bytes(data), usage)
+    }
+
+    fn buffer_sub_data<T: GLPrimitive>(&self, target: GLenum, offset: u32, data: &[T]) {
+        let data = bytes(data);
+        with_state(|state| {
+            if let Some(buffer) = state.bound_buffer(target) {
+                let offset = offset as usize;
+                if buffer.len() < offset + data.len() {
+                    buffer.resize(offset + data.len(), 0);
+                }
+                buffer[offset..offset + data.len()].copy_from_slice(&data);
+            }
+            state.commands.push(Command::BufferSubData {
+                target,
+                offset,
+                data,
+            })
+        })
+    }
+
+    fn create_shader(&self, type_: GLenum) -> Option<Self::Shader> {
+        with_state(|state| {
+            let shader = state.create();
+            let _ = state.shaders.insert(shader, (type_, String::new()));
+            state.commands.push(Command::CreateShader { shader, type_ });
+            Some(shader)
+        })
+    }
+
+    fn create_program(&self) -> Option<Self::Program> {
+        with_state(|state| {
+            let program = state.create();
+            let _ = state.programs.insert(program, ProgramObject::default());
+            state.commands.push(Command::CreateProgram(program));
+            Some(program)
+        })
+    }
+
+    fn delete_program(&self, program: Option<&Self::Program>) {
+        if let Some(program) = program {
+            with_state(|state| {
+                let _ = state.programs.remove(program);
+                state.commands.push(Command::DeleteProgram(*program));
+            })
+        }
+    }
+
+    fn delete_shader(&self, shader: Option<&Self::Shader>) {
+        if let Some(shader) = shader {
+            with_state(|state| {
+                let _ = state.shaders.remove(shader);
+                state.commands.push(Command::DeleteShader(*shader));
+            })
+        }
+    }
+
+    fn is_shader(&self, shader: Option<&Self::Shader>) -> bool {
+        shader.map_or(false, |shader| with_state(|state| state.shaders.contains_key(shader)))
+    }
+
+    fn is_program(&self, program: Option<&Self::Program>) -> bool {
+        program.map_or(false, |program| with_state(|state| state.programs.contains_key(program)))
+    }
+
+    fn shader_source(&self, shader: &Self::Shader, source: &str) {
+        with_state(|state| {
+            if let Some((_, shader_source)) = state.shaders.get_mut(shader) {
+                *shader_source = source.to_string();
+            }
+            state.commands.push(Command::ShaderSource {
+                shader: *shader,
+                source: source.to_string(),
+            })
+        })
+    }
+
+    fn compile_shader(&self, shader: &Self::Shader) {
+        record(Command::CompileShader(*shader))
+    }
+
+    fn link_program(&self, program: &Self::Program) {
+        with_state(|state| {
+            let State {
+                shaders, programs, ..
+            } = state;
+            if let Some(program) = programs.get_mut(program) {
+                let source = |type_| {
+                    program
+                        .shaders
+                        .iter()
+                        .filter_map(|shader| shaders.get(shader))
+                        .find(|shader| shader.0 == type_)
+                        .map_or("", |shader| &shader.1[..])
+                };
+                program.builtin = BuiltinShader::recognize(
+                    source(Self::VERTEX_SHADER),
+                    source(Self::FRAGMENT_SHADER),
+                );
+            }
+            state.commands.push(Command::LinkProgram(*program))
+        })
+    }
+
+    fn use_program(&self, program: Option<&Self::Program>) {
+        with_state(|state| {
+            state.program = program.cloned();
+            state.commands.push(Command::UseProgram(program.cloned()))
+        })
+    }
+
+    fn attach_shader(&self, program: &Self::Program, shader: &Self::Shader) {
+        with_state(|state| {
+            if let Some(program) = state.programs.get_mut(program) {
+                program.shaders.push(*shader);
+            }
+            state.commands.push(Command::AttachShader {
+                program: *program,
+                shader: *shader,
+            })
+        })
+    }
+
+    fn get_shader_parameter_int(&self, _: &Self::Shader, pname: GLenum) -> Option<i32> {
+        // Every shader compiles.
+        if pname == Self::COMPILE_STATUS {
+            Some(1)
+        } else {
+            Some(0)
+        }
+    }
+
+    fn get_shader_info_log(&self, _: &Self::Shader) -> Option<String> {
+        None
+    }
+
+    fn vertex_attrib_pointer(
+        &self,
+        index: u32,
+        size: i32,
+        type_: GLenum,
+        normalized: bool,
+        stride: i32,
+        offset: GLintptr,
+    ) {
+        with_state(|state| {
+            let buffer = state.array_buffer;
+            let array = state.attrib_array(index);
+            array.buffer = buffer;
+            array.size = size;
+            array.type_ = type_;
+            array.normalized = normalized;
+            array.stride = stride;
+            array.offset = offset as usize;
+            state.commands.push(Command::VertexAttribPointer {
+                index,
+                size,
+                type_,
+                normalized,
+                stride,
+                offset,
+            })
+        })
+    }
+
+    fn enable_vertex_attrib_array(&self, index: u32) {
+        with_state(|state| {
+            state.attrib_array(index).enabled = true;
+            state.commands.push(Command::EnableVertexAttribArray(index))
+        })
+    }
+
+    fn disable_vertex_attrib_array(&self, index: u32) {
+        with_state(|state| {
+            state.attrib_array(index).enabled = false;
+            state.commands.push(Command::DisableVertexAttribArray(index))
+        })
+    }
+
+    fn get_attrib_location(&self, program: &Self::Program, name: &str) -> i32 {
+        // Any attribute exists, and keeps the location it was first given.
+        with_state(|state| {
+            let program = state.programs.entry(*program).or_insert_with(ProgramObject::default);
+            let attributes = &mut program.attributes;
+            match attributes.iter().position(|attribute| attribute == name) {
+                Some(location) => location as i32,
+                None => {
+                    attributes.push(name.to_string());
+                    attributes.len() as i32 - 1
+                }
+            }
+        })
+    }
+
+    fn get_uniform_location(
+        &self,
+        program: &Self::Program,
+        name: &str,
+    ) -> Option<Self::UniformLocation> {
+        Some((*program, name.to_string()))
+    }
+
+    fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+        with_state(|state| {
+            state.viewport = [x, y, width, height];
+            state.commands.push(Command::Viewport {
+                x,
+                y,
+                width,
+                height,
+            })
+        })
+    }
+
+    fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+        with_state(|state| {
+            state.scissor = [x, y, width, height];
+            state.commands.push(Command::Scissor {
+                x,
+                y,
+                width,
+                height,
+            })
+        })
+    }
+
+    fn create_framebuffer(&self) -> Option<Self::Framebuffer> {
+        with_state(|state| {
+            let framebuffer = state.create();
+            let _ = state.framebuffers.insert(framebuffer, FramebufferObject::default());
+            state.commands.push(Command::CreateFramebuffer(framebuffer));
+            Some(framebuffer)
+        })
+    }
+
+    fn is_framebuffer(&self, framebuffer: Option<&Self::Framebuffer>) -> bool {
+        framebuffer.map_or(false, |framebuffer| {
+            with_state(|state| state.framebuffers.contains_key(framebuffer))
+        })
+    }
+
+    fn bind_framebuffer(&self, target: GLenum, framebuffer: Option<&Self::Framebuffer>) {
+        with_state(|state| {
+            state.framebuffer = framebuffer.cloned();
+            state.commands.push(Command::BindFramebuffer {
+                target,
+                framebuffer: framebuffer.cloned(),
+            })
+        })
+    }
+
+    fn delete_framebuffer(&self, framebuffer: Option<&Self::Framebuffer>) {
+        if let Some(framebuffer) = framebuffer {
+            with_state(|state| {
+                let _ = state.framebuffers.remove(framebuffer);
+                state.commands.push(Command::DeleteFramebuffer(*framebuffer));
+            })
+        }
+    }
+
+    fn framebuffer_texture2d(
+        &self,
+        target: GLenum,
+        attachment: GLenum,
+        textarget: GLenum,
+        texture: Option<&Self::Texture>,
+        level: i32,
+    ) {
+        attach(attachment, texture.map(|texture| Surface::Texture(*texture)));
+        record(Command::FramebufferTexture2D {
+            target,
+            attachment,
+            textarget,
+            texture: texture.cloned(),
+            level,
+        })
+    }
+
+    fn create_renderbuffer(&self) -> Option<Self::Renderbuffer> {
+        with_state(|state| {
+            let renderbuffer = state.create();
+            let _ = state.renderbuffers.insert(renderbuffer, None);
+            state.commands.push(Command::CreateRenderbuffer(renderbuffer));
+            Some(renderbuffer)
+        })
+    }
+
+    fn is_renderbuffer(&self, buffer: Option<&Self::Renderbuffer>) -> bool {
+        buffer.map_or(false, |buffer| {
+            with_state(|state| state.renderbuffers.contains_key(buffer))
+        })
+    }
+
+    fn delete_renderbuffer(&self, buffer: Option<&Self::Renderbuffer>) {
+        if let Some(buffer) = buffer {
+            with_state(|state| {
+                let _ = state.renderbuffers.remove(buffer);
+                state.commands.push(Command::DeleteRenderbuffer(*buffer));
+            })
+        }
+    }
+
+    fn bind_renderbuffer(&self, buffer: Option<&Self::Renderbuffer>) {
+        with_state(|state| {
+            state.renderbuffer = buffer.cloned();
+            state.commands.push(Command::BindRenderbuffer(buffer.cloned()))
+        })
+    }
+
+    fn renderbuffer_storage(&self, internal_format: GLenum, width: i32, height: i32) {
+        with_state(|state| {
+            let (w, h) = (width.max(0) as usize, height.max(0) as usize);
+            let image = match internal_format {
+                Self::DEPTH_COMPONENT | Self::DEPTH_COMPONENT16 => Image::depth(w, h),
+                _ => Image::color(w, h),
+            };
+            if let Some(renderbuffer) = state
+                .renderbuffer
+                .and_then(|renderbuffer| state.renderbuffers.get_mut(&renderbuffer))
+            {
+                *renderbuffer = Some(image);
+            }
+            state.commands.push(Command::RenderbufferStorage {
+                internal_format,
+                width,
+                height,
+            })
+        })
+    }
+
+    fn framebuffer_renderbuffer(
+        &self,
+        attachment: GLenum,
+        renderbuffer: Option<&Self::Renderbuffer>,
+    ) {
+        attach(attachment, renderbuffer.map(|buffer| Surface::Renderbuffer(*buffer)));
+        record(Command::FramebufferRenderbuffer {
+            attachment,
+            renderbuffer: renderbuffer.cloned(),
+        })
+    }
+
+    fn bind_texture(&self, target: GLenum, texture: Option<&Self::Texture>) {
+        with_state(|state| {
+            let unit = state.active_texture;
+            if state.texture_units.len() <= unit {
+                state.texture_units.resize(unit + 1, None);
+            }
+            state.texture_units[unit] = texture.cloned();
+            state.commands.push(Command::BindTexture {
+                target,
+                texture: texture.cloned(),
+            })
+        })
+    }
+
+    fn tex_image2d(
+        &self,
+        target: GLenum,
+        level: i32,
+        internalformat: i32,
+        width: i32,
+        height: i32,
+        _border: i32,
+        format: GLenum,
+        pixels: Option<&[u8]>,
+    ) {
+        let pixels = pixels.map(|pixels| pixels.to_vec());
+        tex_image2d(target, level, internalformat, width, height, format, pixels)
+    }
+
+    fn tex_image2di(
+        &self,
+        target: GLenum,
+        level: i32,
+        internalformat: i32,
+        width: i32,
+        height: i32,
+        _border: i32,
+        format: GLenum,
+        pixels: Option<&[i32]>,
+    ) {
+        tex_image2d(target, level, internalformat, width, height, format, pixels.map(bytes))
+    }
+
+    fn tex_sub_image2d(
+        &self,
+        target: GLenum,
+        level: i32,
+        xoffset: i32,
+        yoffset: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<&[u8]>,
+    ) {
+        with_state(|state| {
+            let alignment = state.unpack_alignment;
+            if let (Some(pixels), Some(image), 0) = (
+                pixels,
+                state.bound_texture().and_then(|texture| texture.image.as_mut()),
+                level,
+            ) {
+                image.write_pixels([xoffset, yoffset, width, height], format, alignment, pixels);
+            }
+            state.commands.push(Command::TexSubImage2D {
+                target,
+                level,
+                xoffset,
+                yoffset,
+                width,
+                height,
+                format,
+                pixels: pixels.map(|pixels| pixels.to_vec()),
+            })
+        })
+    }
+
+    fn tex_parameteri(&self, target: GLenum, pname: GLenum, param: i32) {
+        with_state(|state| {
+            if let Some(texture) = state.bound_texture() {
+                let param = param as GLenum;
+                match pname {
+                    Self::TEXTURE_WRAP_S => texture.wrap_s = param,
+                    Self::TEXTURE_WRAP_T => texture.wrap_t = param,
+                    Self::TEXTURE_MIN_FILTER => texture.min_filter = param,
+                    Self::TEXTURE_MAG_FILTER => texture.mag_filter = param,
+                    _ => {}
+                }
+            }
+            state.commands.push(Command::TexParameteri {
+                target,
+                pname,
+                param,
+            })
+        })
+    }
+
+    fn is_texture(&self, texture: Option<&Self::Texture>) -> bool {
+        texture.map_or(false, |texture| with_state(|state| state.textures.contains_key(texture)))
+    }
+
+    fn create_texture(&self) -> Option<Self::Texture> {
+        with_state(|state| {
+            let texture = state.create();
+            let _ = state.textures.insert(texture, TextureObject::default());
+            state.commands.push(Command::CreateTexture(texture));
+            Some(texture)
+        })
+    }
+
+    fn delete_texture(&self, texture: Option<&Self::Texture>) {
+        if let Some(texture) = texture {
+            with_state(|state| {
+                let _ = state.textures.remove(texture);
+                state.commands.push(Command::DeleteTexture(*texture));
+            })
+        }
+    }
+
+    fn active_texture(&self, texture: GLenum) {
+        with_state(|state| {
+            state.active_texture = texture.saturating_sub(Self::TEXTURE0) as usize;
+            state.commands.push(Command::ActiveTexture(texture))
+        })
+    }
+
+    fn enable(&self, cap: GLenum) {
+        with_state(|state| {
+            let _ = state.capabilities.insert(cap);
+            state.commands.push(Command::Enable(cap))
+        })
+    }
+
+    fn disable(&self, cap: GLenum) {
+        with_state(|state| {
+            let _ = state.capabilities.remove(&cap);
+            state.commands.push(Command::Disable(cap))
+        })
+    }
+
+    fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr) {
+        with_state(|state| {
+            state.draw_elements(mode, count, type_, offset, 1);
+            state.commands.push(Command::DrawElements {
+                mode,
+                count,
+                type_,
+                offset,
+            })
+        })
+    }
+
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    ) {
+        with_state(|state| {
+            assert!(state.instancing, "Instancing is not supported.");
+            state.draw_elements(mode, count, type_, offset, instances.max(0) as usize);
+            state.commands.push(Command::DrawElementsInstanced {
+                mode,
+                count,
+                type_,
+                offset,
+                instances,
+            })
+        })
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        with_state(|state| {
+            assert!(state.instancing, "Instancing is not supported.");
+            state.attrib_array(index).divisor = divisor;
+            state.commands.push(Command::VertexAttribDivisor { index, divisor })
+        })
+    }
+
+    fn supports_instancing(&self) -> bool {
+        with_state(|state| state.instancing)
+    }
+
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        with_state(|state| {
+            state.attrib_array(index).value = Some(Vector4::new(x, y, z, w));
+            state.commands.push(Command::VertexAttrib4f {
+                index,
+                value: [x, y, z, w],
+            })
+        })
+    }
+
+    fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
+        with_state(|state| {
+            let indices: Vec<usize> = (first.max(0)..first.max(0) + count.max(0))
+                .map(|i| i as usize)
+                .collect();
+            state.draw(mode, &indices, 1);
+            state.commands.push(Command::DrawArrays { mode, first, count })
+        })
+    }
+
+    fn point_size(&self, size: f32) {
+        with_state(|state| {
+            state.point_size = size;
+            state.commands.push(Command::PointSize(size))
+        })
+    }
+
+    fn line_width(&self, width: f32) {
+        with_state(|state| {
+            state.line_width = width;
+            state.commands.push(Command::LineWidth(width))
+        })
+    }
+
+    fn clear(&self, mask: u32) {
+        with_state(|state| {
+            state.clear(mask);
+            state.commands.push(Command::Clear(mask))
+        })
+    }
+
+    fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+        with_state(|state| {
+            state.clear_color = [r, g, b, a];
+            state.commands.push(Command::ClearColor(r, g, b, a))
+        })
+    }
+
+    fn polygon_mode(&self, face: GLenum, mode: GLenum) -> bool {
+        with_state(|state| {
+            state.polygon_mode = mode;
+            state.commands.push(Command::PolygonMode { face, mode })
+        });
+        true
+    }
+
+    fn front_face(&self, mode: GLenum) {
+        with_state(|state| {
+            state.front_face = mode;
+            state.commands.push(Command::FrontFace(mode))
+        })
+    }
+
+    fn depth_func(&self, mode: GLenum) {
+        with_state(|state| {
+            state.depth_func = mode;
+            state.commands.push(Command::DepthFunc(mode))
+        })
+    }
+
+    fn cull_face(&self, mode: GLenum) {
+        with_state(|state| {
+            state.cull_face = mode;
+            state.commands.push(Command::CullFace(mode))
+        })
+    }
+
+    fn read_pixels(
+        &self,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        format: GLenum,
+        pixels: Option<&mut [u8]>,
+    ) {
+        with_state(|state| {
+            if let Some(pixels) = pixels {
+                let alignment = state.pack_alignment;
+                state.with_targets(|_, color, _| match color {
+                    Some(image) => image.read_pixels([x, y, width, height], format, alignment, pixels),
+                    None => pixels.iter_mut().for_each(|pixel| *pixel = 0),
+                });
+            }
+            state.commands.push(Command::ReadPixels {
+                x,
+                y,
+                width,
+                height,
+                format,
+            })
+        })
+    }
+
+    fn pixel_storei(&self, pname: GLenum, param: i32) {
+        with_state(|state| {
+            match pname {
+                Self::PACK_ALIGNMENT => state.pack_alignment = param.max(1) as usize,
+                Self::UNPACK_ALIGNMENT => state.unpack_alignment = param.max(1) as usize,
+                _ => {}
+            }
+            state.commands.push(Command::PixelStorei { pname, param })
+        })
+    }
+
+    fn blend_func_separate(
+        &self,
+        src_rgb: GLenum,
+        dst_rgb: GLenum,
+        src_alpha: GLenum,
+        dst_alpha: GLenum,
+    ) {
+        with_state(|state| {
+            state.blend_func = [src_rgb, dst_rgb, src_alpha, dst_alpha];
+            state.commands.push(Command::BlendFuncSeparate {
+                src_rgb,
+                dst_rgb,
+                src_alpha,
+                dst_alpha,
+            })
+        })
+    }
+}
+
+fn uniform(location: Option<&(u32, String)>, value: UniformValue) {
+    if let Some((program, name)) = location {
+        with_state(|state| {
+            if let Some(program) = state.programs.get_mut(program) {
+                let _ = program.uniforms.insert(name.clone(), value.clone());
+            }
+            state.commands.push(Command::Uniform {
+                program: *program,
+                name: name.clone(),
+                value,
+            })
+        })
+    }
+}
+
+fn buffer_data(target: GLenum, data: Vec<u8>, usage: GLenum) {
+    with_state(|state| {
+        if let Some(buffer) = state.bound_buffer(target) {
+            buffer.clone_from(&data);
+        }
+        state.commands.push(Command::BufferData {
+            target,
+            data,
+            usage,
+        })
+    })
+}
+
+fn tex_image2d(
+    target: GLenum,
+    level: i32,
+    internal_format: i32,
+    width: i32,
+    height: i32,
+    format: GLenum,
+    pixels: Option<Vec<u8>>,
+) {
+    with_state(|state| {
+        // Only the base level is kept since textures are never sampled with mipmaps.
+        if level == 0 {
+            let alignment = state.unpack_alignment;
+            if let Some(texture) = state.bound_texture() {
+                texture.image = Some(Image::from_pixels(
+                    width.max(0) as usize,
+                    height.max(0) as usize,
+                    format,
+                    alignment,
+                    pixels.as_deref(),
+                ));
+            }
+        }
+        state.commands.push(Command::TexImage2D {
+            target,
+            level,
+            internal_format,
+            width,
+            height,
+            format,
+            pixels,
+        })
+    })
+}
+
+// Attaches an image to the bound framebuffer.
+fn attach(attachment: GLenum, surface: Option<Surface>) {
+    with_state(|state| {
+        if let Some(framebuffer) = state
+            .framebuffer
+            .and_then(|framebuffer| state.framebuffers.get_mut(&framebuffer))
+        {
+            match attachment {
+                NullContext::COLOR_ATTACHMENT0 => framebuffer.color = surface,
+                NullContext::DEPTH_ATTACHMENT => framebuffer.depth = surface,
+                _ => {}
+            }
+        }
+    })
+}
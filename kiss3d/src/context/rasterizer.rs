@@ -0,0 +1,760 @@
+use std::mem;
+
+use na::{Vector2, Vector4};
+
+use crate::context::{AbstractContextConst, GLenum, NullContext};
+
+// The OpenGL values of the enums not in `AbstractContextConst`.
+const NEVER: GLenum = 0x0200;
+const LESS: GLenum = 0x0201;
+const EQUAL: GLenum = 0x0202;
+const GREATER: GLenum = 0x0204;
+const NOTEQUAL: GLenum = 0x0205;
+const GEQUAL: GLenum = 0x0206;
+const ZERO: GLenum = 0;
+const SRC_COLOR: GLenum = 0x0300;
+const ONE_MINUS_SRC_COLOR: GLenum = 0x0301;
+const DST_ALPHA: GLenum = 0x0304;
+const ONE_MINUS_DST_ALPHA: GLenum = 0x0305;
+const DST_COLOR: GLenum = 0x0306;
+const ONE_MINUS_DST_COLOR: GLenum = 0x0307;
+const FRONT: GLenum = 0x0404;
+const LINE_LOOP: GLenum = 0x0002;
+const LINE_STRIP: GLenum = 0x0003;
+const TRIANGLE_FAN: GLenum = 0x0006;
+const LUMINANCE: GLenum = 0x1909;
+const LUMINANCE_ALPHA: GLenum = 0x190A;
+pub(crate) const NEAREST_MIPMAP_LINEAR: GLenum = 0x2702;
+
+type Gl = NullContext;
+
+/// The pixels of a texture, a renderbuffer or the screen, from the bottom row up.
+pub(crate) struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub data: ImageData,
+}
+
+pub(crate) enum ImageData {
+    Color(Vec<[u8; 4]>),
+    Depth(Vec<f32>),
+}
+
+impl Image {
+    pub fn color(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            data: ImageData::Color(vec![[0, 0, 0, 255]; width * height]),
+        }
+    }
+
+    pub fn depth(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            data: ImageData::Depth(vec![1.0; width * height]),
+        }
+    }
+
+    /// An image of the given OpenGL format, from the rows of `pixels` if any.
+    pub fn from_pixels(
+        width: usize,
+        height: usize,
+        format: GLenum,
+        alignment: usize,
+        pixels: Option<&[u8]>,
+    ) -> Image {
+        if format == Gl::DEPTH_COMPONENT {
+            return Image::depth(width, height);
+        }
+
+        let mut image = Image::color(width, height);
+        if let Some(pixels) = pixels {
+            image.write_pixels([0, 0, width as i32, height as i32], format, alignment, pixels);
+        }
+        image
+    }
+
+    /// Replaces the rectangle `[x, y, width, height]` of this image by the rows of `pixels`.
+    pub fn write_pixels(
+        &mut self,
+        rect: [i32; 4],
+        format: GLenum,
+        alignment: usize,
+        pixels: &[u8],
+    ) {
+        let [x, y, width, height] = rect.map(|i| i.max(0) as usize);
+        let channels = channels(format);
+        let row_len = align(width * channels, alignment);
+        let image_width = self.width;
+
+        if let ImageData::Color(texels) = &mut self.data {
+            for row in 0..height.min(self.height.saturating_sub(y)) {
+                for col in 0..width.min(image_width.saturating_sub(x)) {
+                    let start = row * row_len + col * channels;
+                    let pixel = match pixels.get(start..start + channels) {
+                        Some(pixel) => pixel,
+                        None => return,
+                    };
+                    texels[(y + row) * image_width + x + col] = match format {
+                        Gl::RGBA => [pixel[0], pixel[1], pixel[2], pixel[3]],
+                        Gl::RGB => [pixel[0], pixel[1], pixel[2], 255],
+                        Gl::ALPHA => [0, 0, 0, pixel[0]],
+                        LUMINANCE => [pixel[0], pixel[0], pixel[0], 255],
+                        LUMINANCE_ALPHA => [pixel[0], pixel[0], pixel[0], pixel[1]],
+                        _ => [pixel[0], 0, 0, 255],
+                    };
+                }
+            }
+        }
+    }
+
+    /// Copies the rectangle `[x, y, width, height]` of this image into the rows of `pixels`, as
+    /// `glReadPixels` does.
+    pub fn read_pixels(&self, rect: [i32; 4], format: GLenum, alignment: usize, pixels: &mut [u8]) {
+        let [x, y, width, height] = rect;
+        let channels = channels(format);
+        let row_len = align(width.max(0) as usize * channels, alignment);
+
+        for row in 0..height.max(0) {
+            for col in 0..width.max(0) {
+                let rgba = quantize(self.texel(x + col, y + row));
+                let start = row as usize * row_len + col as usize * channels;
+                if let Some(pixel) = pixels.get_mut(start..start + channels) {
+                    match format {
+                        Gl::ALPHA => pixel[0] = rgba[3],
+                        _ => pixel.copy_from_slice(&rgba[..channels]),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets every pixel of the rectangle `[x, y, width, height]` to a color.
+    pub fn fill_color(&mut self, rect: Option<[i32; 4]>, color: [f32; 4]) {
+        let color = quantize(Vector4::new(color[0], color[1], color[2], color[3]));
+        let (x0, y0, x1, y1) = self.clamp(rect);
+        let width = self.width;
+        if let ImageData::Color(texels) = &mut self.data {
+            for y in y0..y1 {
+                for texel in &mut texels[y * width + x0..y * width + x1] {
+                    *texel = color;
+                }
+            }
+        }
+    }
+
+    /// Sets every depth of the rectangle `[x, y, width, height]` to a value.
+    pub fn fill_depth(&mut self, rect: Option<[i32; 4]>, depth: f32) {
+        let (x0, y0, x1, y1) = self.clamp(rect);
+        let width = self.width;
+        if let ImageData::Depth(depths) = &mut self.data {
+            for y in y0..y1 {
+                for value in &mut depths[y * width + x0..y * width + x1] {
+                    *value = depth;
+                }
+            }
+        }
+    }
+
+    // The texel at (x, y), or opaque black outside of the image.
+    fn texel(&self, x: i32, y: i32) -> Vector4<f32> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return Vector4::new(0.0, 0.0, 0.0, 1.0);
+        }
+
+        let i = y as usize * self.width + x as usize;
+        match &self.data {
+            ImageData::Color(texels) => {
+                let [r, g, b, a] = texels[i];
+                Vector4::new(r as f32, g as f32, b as f32, a as f32) / 255.0
+            }
+            ImageData::Depth(depths) => Vector4::new(depths[i], depths[i], depths[i], 1.0),
+        }
+    }
+
+    // The pixel bounds (x0, y0, x1, y1) of a rectangle within this image.
+    fn clamp(&self, rect: Option<[i32; 4]>) -> (usize, usize, usize, usize) {
+        let [x, y, width, height] = rect.unwrap_or([0, 0, self.width as i32, self.height as i32]);
+        let clamp_x = |x: i32| x.max(0).min(self.width as i32) as usize;
+        let clamp_y = |y: i32| y.max(0).min(self.height as i32) as usize;
+        (clamp_x(x), clamp_y(y), clamp_x(x + width), clamp_y(y + height))
+    }
+}
+
+fn channels(format: GLenum) -> usize {
+    match format {
+        Gl::RGBA => 4,
+        Gl::RGB => 3,
+        LUMINANCE_ALPHA => 2,
+        _ => 1,
+    }
+}
+
+fn align(len: usize, alignment: usize) -> usize {
+    let alignment = alignment.max(1);
+    len.div_ceil(alignment) * alignment
+}
+
+fn quantize(color: Vector4<f32>) -> [u8; 4] {
+    let c = color.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+    [c.x, c.y, c.z, c.w]
+}
+
+/// A texture object: its image and sampling parameters.
+pub(crate) struct TextureObject {
+    pub image: Option<Image>,
+    pub wrap_s: GLenum,
+    pub wrap_t: GLenum,
+    pub min_filter: GLenum,
+    pub mag_filter: GLenum,
+}
+
+impl Default for TextureObject {
+    fn default() -> Self {
+        TextureObject {
+            image: None,
+            wrap_s: Gl::REPEAT,
+            wrap_t: Gl::REPEAT,
+            min_filter: NEAREST_MIPMAP_LINEAR,
+            mag_filter: Gl::LINEAR,
+        }
+    }
+}
+
+/// Samples a texture the way `texture2D` does.
+///
+/// There are no mipmaps, so textures are always sampled with their magnification filter.
+#[derive(Copy, Clone)]
+pub(crate) struct Sampler<'a> {
+    texture: Option<&'a TextureObject>,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(texture: Option<&'a TextureObject>) -> Self {
+        Sampler { texture }
+    }
+
+    pub fn sample(&self, uv: Vector2<f32>) -> Vector4<f32> {
+        let (texture, image) = match self.texture.and_then(|t| t.image.as_ref().map(|i| (t, i))) {
+            Some(found) => found,
+            None => return Vector4::new(0.0, 0.0, 0.0, 1.0),
+        };
+
+        let (width, height) = (image.width as i32, image.height as i32);
+        let texel = |x: i32, y: i32| {
+            image.texel(
+                wrap(x, width, texture.wrap_s),
+                wrap(y, height, texture.wrap_t),
+            )
+        };
+
+        let x = uv.x * width as f32;
+        let y = uv.y * height as f32;
+        if texture.mag_filter == Gl::NEAREST {
+            texel(x.floor() as i32, y.floor() as i32)
+        } else {
+            let (x, y) = (x - 0.5, y - 0.5);
+            let (x0, y0) = (x.floor(), y.floor());
+            let (fx, fy) = (x - x0, y - y0);
+            let (x0, y0) = (x0 as i32, y0 as i32);
+            let bottom = texel(x0, y0) * (1.0 - fx) + texel(x0 + 1, y0) * fx;
+            let top = texel(x0, y0 + 1) * (1.0 - fx) + texel(x0 + 1, y0 + 1) * fx;
+            bottom * (1.0 - fy) + top * fy
+        }
+    }
+}
+
+fn wrap(i: i32, size: i32, mode: GLenum) -> i32 {
+    match mode {
+        Gl::CLAMP_TO_EDGE => i.max(0).min(size - 1),
+        Gl::MIRRORED_REPEAT => {
+            let i = i.rem_euclid(2 * size);
+            if i >= size {
+                2 * size - 1 - i
+            } else {
+                i
+            }
+        }
+        _ => i.rem_euclid(size),
+    }
+}
+
+/// The output of a vertex shader.
+#[derive(Clone)]
+pub(crate) struct Vertex {
+    /// `gl_Position`.
+    pub position: Vector4<f32>,
+    /// All the varyings, one after the other.
+    pub varyings: Vec<f32>,
+}
+
+impl Vertex {
+    fn lerp(&self, other: &Vertex, t: f32) -> Vertex {
+        Vertex {
+            position: self.position + (other.position - self.position) * t,
+            varyings: self
+                .varyings
+                .iter()
+                .zip(other.varyings.iter())
+                .map(|(a, b)| a + (b - a) * t)
+                .collect(),
+        }
+    }
+}
+
+/// A shader program run on the CPU.
+pub(crate) trait SoftwareShader {
+    /// Computes a vertex from the values of the attributes of the program.
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex;
+    /// Computes the color of a fragment, or `None` if it is discarded.
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>>;
+}
+
+/// The fixed-function state a draw call is made with.
+pub(crate) struct Pipeline {
+    pub viewport: [i32; 4],
+    pub scissor: Option<[i32; 4]>,
+    pub cull_face: Option<GLenum>,
+    pub front_face: GLenum,
+    pub depth_func: Option<GLenum>,
+    pub blend_func: Option<[GLenum; 4]>,
+    pub polygon_mode: GLenum,
+    pub line_width: f32,
+    pub point_size: f32,
+}
+
+// A vertex in window coordinates.
+struct WindowVertex<'a> {
+    x: f32,
+    y: f32,
+    depth: f32,
+    inv_w: f32,
+    varyings: &'a [f32],
+}
+
+/// Draws primitives into a color and a depth image.
+pub(crate) struct Rasterizer<'a> {
+    pipeline: &'a Pipeline,
+    shader: &'a dyn SoftwareShader,
+    color: Option<&'a mut Image>,
+    depth: Option<&'a mut Image>,
+    // The pixels that may be drawn: (x0, y0, x1, y1), exclusive of x1 and y1.
+    bounds: (i32, i32, i32, i32),
+    varyings: Vec<f32>,
+}
+
+impl<'a> Rasterizer<'a> {
+    pub fn new(
+        pipeline: &'a Pipeline,
+        shader: &'a dyn SoftwareShader,
+        color: Option<&'a mut Image>,
+        depth: Option<&'a mut Image>,
+    ) -> Self {
+        let [x, y, width, height] = pipeline.viewport;
+        let mut bounds = (x, y, x + width, y + height);
+        if let Some([x, y, width, height]) = pipeline.scissor {
+            bounds = (
+                bounds.0.max(x),
+                bounds.1.max(y),
+                bounds.2.min(x + width),
+                bounds.3.min(y + height),
+            );
+        }
+        for image in color.iter().map(|i| &**i).chain(depth.iter().map(|i| &**i)) {
+            bounds.2 = bounds.2.min(image.width as i32);
+            bounds.3 = bounds.3.min(image.height as i32);
+        }
+        bounds.0 = bounds.0.max(0);
+        bounds.1 = bounds.1.max(0);
+
+        Rasterizer {
+            pipeline,
+            shader,
+            color,
+            depth,
+            bounds,
+            varyings: Vec::new(),
+        }
+    }
+
+    /// Draws the primitives made of `vertices[indices[i]]` in the OpenGL primitive `mode`.
+    pub fn draw(&mut self, mode: GLenum, vertices: &[Vertex], indices: &[usize]) {
+        let vertex = |i: usize| &vertices[indices[i]];
+
+        match mode {
+            Gl::TRIANGLES => {
+                for i in (0..indices.len() / 3 * 3).step_by(3) {
+                    self.triangle(vertex(i), vertex(i + 1), vertex(i + 2))
+                }
+            }
+            Gl::TRIANGLE_STRIP => {
+                for i in 2..indices.len() {
+                    // Every other triangle is reversed to keep the winding of the strip.
+                    if i % 2 == 0 {
+                        self.triangle(vertex(i - 2), vertex(i - 1), vertex(i))
+                    } else {
+                        self.triangle(vertex(i - 1), vertex(i - 2), vertex(i))
+                    }
+                }
+            }
+            TRIANGLE_FAN => {
+                for i in 2..indices.len() {
+                    self.triangle(vertex(0), vertex(i - 1), vertex(i))
+                }
+            }
+            Gl::LINES => {
+                for i in (0..indices.len() / 2 * 2).step_by(2) {
+                    self.line(vertex(i), vertex(i + 1))
+                }
+            }
+            LINE_STRIP | LINE_LOOP => {
+                for i in 1..indices.len() {
+                    self.line(vertex(i - 1), vertex(i))
+                }
+                if mode == LINE_LOOP && indices.len() > 2 {
+                    self.line(vertex(indices.len() - 1), vertex(0))
+                }
+            }
+            Gl::POINTS => {
+                for i in 0..indices.len() {
+                    self.point(vertex(i))
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn window(&self, vertex: &'a Vertex) -> WindowVertex<'a> {
+        let [x, y, width, height] = self.pipeline.viewport;
+        let p = vertex.position;
+        let inv_w = 1.0 / p.w;
+        WindowVertex {
+            x: x as f32 + (p.x * inv_w + 1.0) * 0.5 * width as f32,
+            y: y as f32 + (p.y * inv_w + 1.0) * 0.5 * height as f32,
+            depth: (p.z * inv_w + 1.0) * 0.5,
+            inv_w,
+            varyings: &vertex.varyings,
+        }
+    }
+
+    //
+    // Triangles
+    //
+    fn triangle(&mut self, a: &Vertex, b: &Vertex, c: &Vertex) {
+        // Only the near plane is clipped against: the rest of the view volume is handled by
+        // the bounds of the rasterization and the range of the depth.
+        let polygon = clip_polygon(&[a.clone(), b.clone(), c.clone()]);
+        if polygon.len() < 3 {
+            return;
+        }
+
+        // The whole polygon faces the same way.
+        let area = {
+            let (a, b, c) = (
+                self.window(&polygon[0]),
+                self.window(&polygon[1]),
+                self.window(&polygon[2]),
+            );
+            edge(&a, &b, c.x, c.y)
+        };
+        if area == 0.0 {
+            return;
+        }
+        let front = (area > 0.0) == (self.pipeline.front_face == Gl::CCW);
+        match self.pipeline.cull_face {
+            Some(Gl::FRONT_AND_BACK) => return,
+            Some(FRONT) if front => return,
+            Some(Gl::BACK) if !front => return,
+            _ => {}
+        }
+
+        match self.pipeline.polygon_mode {
+            Gl::LINE => {
+                for i in 0..polygon.len() {
+                    self.clipped_line(&polygon[i], &polygon[(i + 1) % polygon.len()])
+                }
+            }
+            Gl::POINT => {
+                for vertex in &polygon {
+                    self.point(vertex)
+                }
+            }
+            _ => {
+                for i in 2..polygon.len() {
+                    self.fill_triangle(&polygon[0], &polygon[i - 1], &polygon[i])
+                }
+            }
+        }
+    }
+
+    fn fill_triangle(&mut self, a: &Vertex, b: &Vertex, c: &Vertex) {
+        let (a, mut b, mut c) = (self.window(a), self.window(b), self.window(c));
+        let mut area = edge(&a, &b, c.x, c.y);
+        if area < 0.0 {
+            mem::swap(&mut b, &mut c);
+            area = -area;
+        }
+        if area == 0.0 {
+            return;
+        }
+
+        let (x0, y0, x1, y1) = self.bounds;
+        let min_x = (a.x.min(b.x).min(c.x).floor() as i32).max(x0);
+        let max_x = (a.x.max(b.x).max(c.x).ceil() as i32).min(x1);
+        let min_y = (a.y.min(b.y).min(c.y).floor() as i32).max(y0);
+        let max_y = (a.y.max(b.y).max(c.y).ceil() as i32).min(y1);
+
+        let mut varyings = mem::take(&mut self.varyings);
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let wa = edge(&b, &c, px, py);
+                let wb = edge(&c, &a, px, py);
+                let wc = edge(&a, &b, px, py);
+                if !covers(wa, &b, &c) || !covers(wb, &c, &a) || !covers(wc, &a, &b) {
+                    continue;
+                }
+
+                let (la, lb, lc) = (wa / area, wb / area, wc / area);
+                let depth = la * a.depth + lb * b.depth + lc * c.depth;
+
+                // Perspective-correct interpolation.
+                let (qa, qb, qc) = (la * a.inv_w, lb * b.inv_w, lc * c.inv_w);
+                let q = qa + qb + qc;
+                varyings.clear();
+                varyings.extend(
+                    a.varyings
+                        .iter()
+                        .zip(b.varyings.iter())
+                        .zip(c.varyings.iter())
+                        .map(|((va, vb), vc)| (qa * va + qb * vb + qc * vc) / q),
+                );
+
+                self.fragment(x, y, depth, &varyings);
+            }
+        }
+        self.varyings = varyings;
+    }
+
+    //
+    // Lines
+    //
+    fn line(&mut self, a: &Vertex, b: &Vertex) {
+        let (a, b) = match clip_segment(a, b) {
+            Some(segment) => segment,
+            None => return,
+        };
+        self.clipped_line(&a, &b)
+    }
+
+    fn clipped_line(&mut self, a: &Vertex, b: &Vertex) {
+        let (a, b) = (self.window(a), self.window(b));
+        let (dx, dy) = (b.x - a.x, b.y - a.y);
+        let steps = dx.abs().max(dy.abs()).ceil().max(1.0) as usize;
+        let x_major = dx.abs() >= dy.abs();
+        let width = self.pipeline.line_width.round().max(1.0) as i32;
+
+        let mut varyings = mem::take(&mut self.varyings);
+        for i in 0..steps {
+            let t = (i as f32 + 0.5) / steps as f32;
+            let (x, y) = (a.x + dx * t, a.y + dy * t);
+            let depth = a.depth + (b.depth - a.depth) * t;
+
+            let (qa, qb) = ((1.0 - t) * a.inv_w, t * b.inv_w);
+            varyings.clear();
+            varyings.extend(
+                a.varyings
+                    .iter()
+                    .zip(b.varyings.iter())
+                    .map(|(va, vb)| (qa * va + qb * vb) / (qa + qb)),
+            );
+
+            // Wide lines are widened along their minor axis.
+            for k in 0..width {
+                let offset = k - (width - 1) / 2;
+                let (px, py) = if x_major {
+                    (x.floor() as i32, y.floor() as i32 + offset)
+                } else {
+                    (x.floor() as i32 + offset, y.floor() as i32)
+                };
+                if self.contains(px, py) {
+                    self.fragment(px, py, depth, &varyings);
+                }
+            }
+        }
+        self.varyings = varyings;
+    }
+
+    //
+    // Points
+    //
+    fn point(&mut self, vertex: &Vertex) {
+        let p = vertex.position;
+        if p.w <= 0.0 || p.z < -p.w || p.z > p.w {
+            return;
+        }
+
+        let center = self.window(vertex);
+        let size = self.pipeline.point_size.round().max(1.0) as i32;
+        let x0 = (center.x - size as f32 * 0.5).round() as i32;
+        let y0 = (center.y - size as f32 * 0.5).round() as i32;
+        for y in y0..y0 + size {
+            for x in x0..x0 + size {
+                if self.contains(x, y) {
+                    self.fragment(x, y, center.depth, center.varyings);
+                }
+            }
+        }
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let (x0, y0, x1, y1) = self.bounds;
+        x >= x0 && x < x1 && y >= y0 && y < y1
+    }
+
+    //
+    // Per-fragment operations
+    //
+    fn fragment(&mut self, x: i32, y: i32, depth: f32, varyings: &[f32]) {
+        if !(0.0..=1.0).contains(&depth) {
+            return;
+        }
+
+        let i = y as usize * self.width() + x as usize;
+        let mut write_depth = false;
+        if let Some(func) = self.pipeline.depth_func {
+            if let Some(Image {
+                data: ImageData::Depth(depths),
+                ..
+            }) = self.depth.as_deref_mut()
+            {
+                if !depth_test(func, depth, depths[i]) {
+                    return;
+                }
+                write_depth = true;
+            }
+        }
+
+        let color = match self.shader.fragment(varyings) {
+            Some(color) => color,
+            None => return,
+        };
+
+        if write_depth {
+            if let Some(Image {
+                data: ImageData::Depth(depths),
+                ..
+            }) = self.depth.as_deref_mut()
+            {
+                depths[i] = depth;
+            }
+        }
+
+        if let Some(Image {
+            data: ImageData::Color(texels),
+            ..
+        }) = self.color.as_deref_mut()
+        {
+            let color = match self.pipeline.blend_func {
+                Some(func) => {
+                    let [r, g, b, a] = texels[i];
+                    let dst = Vector4::new(r as f32, g as f32, b as f32, a as f32) / 255.0;
+                    blend(func, color, dst)
+                }
+                None => color,
+            };
+            texels[i] = quantize(color);
+        }
+    }
+
+    fn width(&self) -> usize {
+        self.color
+            .as_ref()
+            .or(self.depth.as_ref())
+            .map_or(0, |image| image.width)
+    }
+}
+
+// Twice the signed area of (a, b, p), positive if p is left of a -> b.
+fn edge(a: &WindowVertex, b: &WindowVertex, px: f32, py: f32) -> f32 {
+    (b.x - a.x) * (py - a.y) - (b.y - a.y) * (px - a.x)
+}
+
+// Whether a pixel center at the distance `w` of the edge a -> b of a counter-clockwise
+// triangle is inside. Pixels exactly on an edge belong to top and left edges only, so
+// triangles sharing an edge never draw a pixel twice.
+fn covers(w: f32, a: &WindowVertex, b: &WindowVertex) -> bool {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    w > 0.0 || (w == 0.0 && (dy < 0.0 || (dy == 0.0 && dx < 0.0)))
+}
+
+// Clips a polygon against the near plane (z >= -w).
+fn clip_polygon(polygon: &[Vertex]) -> Vec<Vertex> {
+    let distance = |v: &Vertex| v.position.z + v.position.w;
+    if polygon.iter().all(|v| distance(v) >= 0.0) {
+        return polygon.to_vec();
+    }
+
+    let mut clipped = Vec::new();
+    for i in 0..polygon.len() {
+        let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+        let (da, db) = (distance(a), distance(b));
+        if da >= 0.0 {
+            clipped.push(a.clone());
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            clipped.push(a.lerp(b, da / (da - db)));
+        }
+    }
+    clipped
+}
+
+fn clip_segment(a: &Vertex, b: &Vertex) -> Option<(Vertex, Vertex)> {
+    let (da, db) = (a.position.z + a.position.w, b.position.z + b.position.w);
+    match (da >= 0.0, db >= 0.0) {
+        (true, true) => Some((a.clone(), b.clone())),
+        (true, false) => Some((a.clone(), a.lerp(b, da / (da - db)))),
+        (false, true) => Some((a.lerp(b, da / (da - db)), b.clone())),
+        (false, false) => None,
+    }
+}
+
+fn depth_test(func: GLenum, depth: f32, stored: f32) -> bool {
+    match func {
+        NEVER => false,
+        LESS => depth < stored,
+        EQUAL => depth == stored,
+        Gl::LEQUAL => depth <= stored,
+        GREATER => depth > stored,
+        NOTEQUAL => depth != stored,
+        GEQUAL => depth >= stored,
+        _ => true,
+    }
+}
+
+fn blend(
+    [src_rgb, dst_rgb, src_alpha, dst_alpha]: [GLenum; 4],
+    src: Vector4<f32>,
+    dst: Vector4<f32>,
+) -> Vector4<f32> {
+    let factor = |factor: GLenum| -> Vector4<f32> {
+        match factor {
+            ZERO => Vector4::zeros(),
+            SRC_COLOR => src,
+            ONE_MINUS_SRC_COLOR => Vector4::repeat(1.0) - src,
+            Gl::SRC_ALPHA => Vector4::repeat(src.w),
+            Gl::ONE_MINUS_SRC_ALPHA => Vector4::repeat(1.0 - src.w),
+            DST_ALPHA => Vector4::repeat(dst.w),
+            ONE_MINUS_DST_ALPHA => Vector4::repeat(1.0 - dst.w),
+            DST_COLOR => dst,
+            ONE_MINUS_DST_COLOR => Vector4::repeat(1.0) - dst,
+            _ => Vector4::repeat(1.0),
+        }
+    };
+
+    let rgb = src.component_mul(&factor(src_rgb)) + dst.component_mul(&factor(dst_rgb));
+    let alpha = src.w * factor(src_alpha).w + dst.w * factor(dst_alpha).w;
+    Vector4::new(rgb.x, rgb.y, rgb.z, alpha)
+}
@@ -0,0 +1,742 @@
+use std::collections::HashMap;
+
+use na::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
+
+use crate::builtin::{
+    NORMAL_FRAGMENT_SRC, NORMAL_VERTEX_SRC, OBJECT_FRAGMENT_SRC, OBJECT_VERTEX_SRC,
+    UVS_FRAGMENT_SRC, UVS_VERTEX_SRC, VERTEX_COLOR_FRAGMENT_SRC, VERTEX_COLOR_VERTEX_SRC,
+};
+use crate::context::rasterizer::{Sampler, SoftwareShader, Vertex};
+use crate::context::UniformValue;
+use crate::post_processing::{
+    COLOR_GRADING_FRAGMENT_SRC, COPY_FRAGMENT_SRC, FOG_FRAGMENT_SRC, FXAA_FRAGMENT_SRC,
+    POST_PROCESSING_VERTEX_SRC, VIGNETTE_FRAGMENT_SRC,
+};
+use crate::renderer::line_renderer::{LINES_FRAGMENT_SRC, LINES_VERTEX_SRC};
+use crate::renderer::point_renderer::{POINTS_FRAGMENT_SRC, POINTS_VERTEX_SRC};
+use crate::renderer::{SHADOW_FRAGMENT_SRC, SHADOW_VERTEX_SRC};
+use crate::text::{TEXT_FRAGMENT_SRC, TEXT_VERTEX_SRC};
+
+// Must match `MAX_POINT_LIGHTS` in `default.frag`.
+const MAX_POINT_LIGHTS: usize = 4;
+
+/// A shader program of kiss3d that the software rasterizer can run.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum BuiltinShader {
+    Object,
+    VertexColor,
+    Normals,
+    Uvs,
+    Lines,
+    Points,
+    Text,
+    Shadow,
+    PostCopy,
+    PostVignette,
+    PostFog,
+    PostColorGrading,
+    PostFxaa,
+}
+
+impl BuiltinShader {
+    /// The built-in program made of these shaders, if any.
+    pub fn recognize(vertex: &str, fragment: &str) -> Option<BuiltinShader> {
+        let shaders = [
+            (BuiltinShader::Object, OBJECT_VERTEX_SRC, OBJECT_FRAGMENT_SRC),
+            (
+                BuiltinShader::VertexColor,
+                VERTEX_COLOR_VERTEX_SRC,
+                VERTEX_COLOR_FRAGMENT_SRC,
+            ),
+            (BuiltinShader::Normals, NORMAL_VERTEX_SRC, NORMAL_FRAGMENT_SRC),
+            (BuiltinShader::Uvs, UVS_VERTEX_SRC, UVS_FRAGMENT_SRC),
+            (BuiltinShader::Lines, LINES_VERTEX_SRC, LINES_FRAGMENT_SRC),
+            (BuiltinShader::Points, POINTS_VERTEX_SRC, POINTS_FRAGMENT_SRC),
+            (BuiltinShader::Text, TEXT_VERTEX_SRC, TEXT_FRAGMENT_SRC),
+            (BuiltinShader::Shadow, SHADOW_VERTEX_SRC, SHADOW_FRAGMENT_SRC),
+            (BuiltinShader::PostCopy, POST_PROCESSING_VERTEX_SRC, COPY_FRAGMENT_SRC),
+            (
+                BuiltinShader::PostVignette,
+                POST_PROCESSING_VERTEX_SRC,
+                VIGNETTE_FRAGMENT_SRC,
+            ),
+            (BuiltinShader::PostFog, POST_PROCESSING_VERTEX_SRC, FOG_FRAGMENT_SRC),
+            (
+                BuiltinShader::PostColorGrading,
+                POST_PROCESSING_VERTEX_SRC,
+                COLOR_GRADING_FRAGMENT_SRC,
+            ),
+            (BuiltinShader::PostFxaa, POST_PROCESSING_VERTEX_SRC, FXAA_FRAGMENT_SRC),
+        ];
+
+        shaders
+            .iter()
+            .find(|(_, vs, fs)| *vs == vertex && *fs == fragment)
+            .map(|(shader, _, _)| *shader)
+    }
+
+    /// The attributes of the program, in the order the vertex shader expects their values.
+    pub fn attributes(self) -> &'static [&'static str] {
+        match self {
+            BuiltinShader::Object => &[
+                "position",
+                "tex_coord",
+                "normal",
+                "inst_row_0",
+                "inst_row_1",
+                "inst_row_2",
+                "inst_color",
+            ],
+            BuiltinShader::VertexColor => &[
+                "position",
+                "tex_coord",
+                "normal",
+                "inst_row_0",
+                "inst_row_1",
+                "inst_row_2",
+                "inst_color",
+                "vertex_color",
+            ],
+            BuiltinShader::Normals => &["position", "normal"],
+            BuiltinShader::Uvs => &["position", "uvs"],
+            BuiltinShader::Lines | BuiltinShader::Points => &["position", "color"],
+            BuiltinShader::Text => &["pos", "uvs"],
+            BuiltinShader::Shadow => &["position", "inst_row_0", "inst_row_1", "inst_row_2"],
+            BuiltinShader::PostCopy
+            | BuiltinShader::PostVignette
+            | BuiltinShader::PostFog
+            | BuiltinShader::PostColorGrading
+            | BuiltinShader::PostFxaa => &["v_coord"],
+        }
+    }
+
+    /// The program with the given uniforms, its samplers reading the texture units given by
+    /// `unit`.
+    pub fn build<'a>(
+        self,
+        uniforms: &HashMap<String, UniformValue>,
+        unit: impl Fn(usize) -> Sampler<'a>,
+    ) -> Box<dyn SoftwareShader + 'a> {
+        let u = Uniforms(uniforms);
+
+        match self {
+            BuiltinShader::Object | BuiltinShader::VertexColor => Box::new(ObjectShader {
+                vertex_colors: self == BuiltinShader::VertexColor,
+                ntransform: u.mat3("ntransform"),
+                scale: u.mat3("scale"),
+                view_proj: u.mat4("proj") * u.mat4("view"),
+                transform: u.mat4("transform"),
+                shadow_view_proj: u.mat4("shadow_view_proj"),
+                color: u.vec3("color"),
+                tex: unit(u.int("tex")),
+                tex_region: u.vec4("tex_region"),
+                tex_region_enabled: u.float("tex_region_enabled") > 0.5,
+                eye_position: u.vec3("eye_position"),
+                light_position: u.vec3("light_position"),
+                light_direction: u.vec3("light_direction"),
+                light_is_directional: u.float("light_is_directional") > 0.5,
+                light_color: u.vec3("light_color"),
+                point_lights: (0..MAX_POINT_LIGHTS)
+                    .map(|i| PointLight {
+                        position: u.vec3(&format!("point_light_positions[{}]", i)),
+                        color: u.vec3(&format!("point_light_colors[{}]", i)),
+                        radius: u.float(&format!("point_light_radii[{}]", i)),
+                    })
+                    .collect(),
+                shadows_enabled: u.float("shadows_enabled") > 0.5,
+                shadow_map: unit(u.int("shadow_map")),
+                shadow_texel_size: u.float("shadow_texel_size"),
+            }),
+            BuiltinShader::Normals | BuiltinShader::Uvs => Box::new(AttributeColorShader {
+                uvs: self == BuiltinShader::Uvs,
+                transform: u.mat4("proj")
+                    * u.mat4("view")
+                    * u.mat4("transform")
+                    * u.mat3("scale").to_homogeneous(),
+            }),
+            BuiltinShader::Lines | BuiltinShader::Points => Box::new(ColoredVertexShader {
+                view_proj: u.mat4("proj") * u.mat4("view"),
+            }),
+            BuiltinShader::Text => Box::new(TextShader {
+                invsz: u.vec2("invsz"),
+                color: u.vec3("color"),
+                tex0: unit(u.int("tex0")),
+            }),
+            BuiltinShader::Shadow => Box::new(ShadowShader {
+                scale: u.mat3("scale"),
+                transform: u.mat4("light_view_proj") * u.mat4("transform"),
+            }),
+            BuiltinShader::PostCopy => Box::new(CopyShader {
+                fbo_texture: unit(u.int("fbo_texture")),
+            }),
+            BuiltinShader::PostVignette => Box::new(VignetteShader {
+                fbo_texture: unit(u.int("fbo_texture")),
+                color: u.vec3("color"),
+                intensity: u.float("intensity"),
+                radius: u.float("radius"),
+                softness: u.float("softness"),
+            }),
+            BuiltinShader::PostFog => Box::new(FogShader {
+                fbo_texture: unit(u.int("fbo_texture")),
+                fbo_depth: unit(u.int("fbo_depth")),
+                color: u.vec3("color"),
+                start: u.float("start"),
+                end: u.float("end"),
+                density: u.float("density"),
+                znear: u.float("znear"),
+                zfar: u.float("zfar"),
+            }),
+            BuiltinShader::PostColorGrading => Box::new(ColorGradingShader {
+                fbo_texture: unit(u.int("fbo_texture")),
+                lut: unit(u.int("lut")),
+                exposure: u.float("exposure"),
+                contrast: u.float("contrast"),
+                saturation: u.float("saturation"),
+                lut_size: u.float("lut_size"),
+                lut_intensity: u.float("lut_intensity"),
+            }),
+            BuiltinShader::PostFxaa => Box::new(FxaaShader {
+                fbo_texture: unit(u.int("fbo_texture")),
+                texel_size: u.vec2("texel_size"),
+                span_max: u.float("span_max"),
+            }),
+        }
+    }
+}
+
+// The uniforms of a program. Uniforms never set are zero, as with OpenGL.
+struct Uniforms<'a>(&'a HashMap<String, UniformValue>);
+
+impl<'a> Uniforms<'a> {
+    fn floats(&self, name: &str) -> [f32; 4] {
+        let mut out = [0.0; 4];
+        match self.0.get(name) {
+            Some(UniformValue::Float(values)) => {
+                for (out, value) in out.iter_mut().zip(values) {
+                    *out = *value
+                }
+            }
+            Some(UniformValue::Int(values)) => {
+                for (out, value) in out.iter_mut().zip(values) {
+                    *out = *value as f32
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+
+    fn float(&self, name: &str) -> f32 {
+        self.floats(name)[0]
+    }
+
+    fn int(&self, name: &str) -> usize {
+        match self.0.get(name) {
+            Some(UniformValue::Int(values)) => values.first().map_or(0, |i| *i as usize),
+            _ => 0,
+        }
+    }
+
+    fn vec2(&self, name: &str) -> Vector2<f32> {
+        let [x, y, _, _] = self.floats(name);
+        Vector2::new(x, y)
+    }
+
+    fn vec3(&self, name: &str) -> Vector3<f32> {
+        let [x, y, z, _] = self.floats(name);
+        Vector3::new(x, y, z)
+    }
+
+    fn vec4(&self, name: &str) -> Vector4<f32> {
+        let [x, y, z, w] = self.floats(name);
+        Vector4::new(x, y, z, w)
+    }
+
+    fn mat3(&self, name: &str) -> Matrix3<f32> {
+        match self.0.get(name) {
+            Some(UniformValue::Matrix3(m)) => *m,
+            _ => Matrix3::zeros(),
+        }
+    }
+
+    fn mat4(&self, name: &str) -> Matrix4<f32> {
+        match self.0.get(name) {
+            Some(UniformValue::Matrix4(m)) => *m,
+            _ => Matrix4::zeros(),
+        }
+    }
+}
+
+// GLSL's `normalize`, without the NaNs of null vectors.
+fn normalize(v: Vector3<f32>) -> Vector3<f32> {
+    v.try_normalize(0.0).unwrap_or_else(Vector3::zeros)
+}
+
+fn xyz(v: &Vector4<f32>) -> Vector3<f32> {
+    v.xyz()
+}
+
+/*
+ *
+ * default.vert and default.frag
+ *
+ */
+struct PointLight {
+    position: Vector3<f32>,
+    color: Vector3<f32>,
+    radius: f32,
+}
+
+struct ObjectShader<'a> {
+    vertex_colors: bool,
+    ntransform: Matrix3<f32>,
+    scale: Matrix3<f32>,
+    view_proj: Matrix4<f32>,
+    transform: Matrix4<f32>,
+    shadow_view_proj: Matrix4<f32>,
+    color: Vector3<f32>,
+    tex: Sampler<'a>,
+    tex_region: Vector4<f32>,
+    tex_region_enabled: bool,
+    eye_position: Vector3<f32>,
+    light_position: Vector3<f32>,
+    light_direction: Vector3<f32>,
+    light_is_directional: bool,
+    light_color: Vector3<f32>,
+    point_lights: Vec<PointLight>,
+    shadows_enabled: bool,
+    shadow_map: Sampler<'a>,
+    shadow_texel_size: f32,
+}
+
+// The varyings of `ObjectShader`.
+const TEX_COORD: usize = 0;
+const NORMAL: usize = 2;
+const WORLD_POS: usize = 5;
+const SHADOW_COORD: usize = 8;
+const COLOR: usize = 12;
+
+const SPEC_COLOR: f32 = 0.4;
+
+fn shade(
+    normal: &Vector3<f32>,
+    light_dir: &Vector3<f32>,
+    view_dir: &Vector3<f32>,
+    diffuse: &Vector3<f32>,
+    light_color: &Vector3<f32>,
+) -> Vector3<f32> {
+    let lambertian = light_dir.dot(normal).max(0.0);
+    let mut specular = 0.0;
+
+    if lambertian > 0.0 {
+        let half_dir = normalize(light_dir + view_dir);
+        let spec_angle = half_dir.dot(normal).max(0.0);
+        specular = spec_angle.powf(30.0);
+    }
+
+    (diffuse * lambertian / 3.0 + Vector3::repeat(specular * SPEC_COLOR / 3.0))
+        .component_mul(light_color)
+}
+
+impl<'a> ObjectShader<'a> {
+    fn lit_fraction(&self, shadow_coord: &Vector4<f32>) -> f32 {
+        let coords = xyz(shadow_coord) / shadow_coord.w * 0.5 + Vector3::repeat(0.5);
+        if coords.x < 0.0 || coords.x > 1.0 || coords.y < 0.0 || coords.y > 1.0 || coords.z > 1.0
+        {
+            return 1.0;
+        }
+
+        let bias = 0.002;
+        let mut lit = 0.0;
+        for i in 0..4 {
+            let offset = Vector2::new((i / 2) as f32 - 0.5, (i % 2) as f32 - 0.5)
+                * self.shadow_texel_size;
+            let depth = self.shadow_map.sample(coords.xy() + offset).x;
+            lit += if coords.z - bias > depth { 0.0 } else { 1.0 };
+        }
+        lit / 4.0
+    }
+}
+
+impl<'a> SoftwareShader for ObjectShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        let (position, tex_coord, normal) = (xyz(&attributes[0]), attributes[1], xyz(&attributes[2]));
+        let inst_rows = [attributes[3], attributes[4], attributes[5]];
+        let mut color = xyz(&attributes[6]);
+        if self.vertex_colors {
+            color.component_mul_assign(&xyz(&attributes[7]));
+        }
+
+        let local_pos = (self.scale * position).push(1.0);
+        let inst_pos = Vector3::from_fn(|i, _| inst_rows[i].dot(&local_pos));
+        let inst_normal = Vector3::from_fn(|i, _| xyz(&inst_rows[i]).dot(&normal));
+
+        let world_pos = self.transform * inst_pos.push(1.0);
+        let normal = self.ntransform * inst_normal;
+        let world = xyz(&world_pos) / world_pos.w;
+        let shadow_coord = self.shadow_view_proj * world_pos;
+
+        let mut varyings = Vec::with_capacity(15);
+        varyings.extend_from_slice(&[tex_coord.x, tex_coord.y]);
+        varyings.extend_from_slice(normal.as_slice());
+        varyings.extend_from_slice(world.as_slice());
+        varyings.extend_from_slice(shadow_coord.as_slice());
+        varyings.extend_from_slice(color.as_slice());
+
+        Vertex {
+            position: self.view_proj * world_pos,
+            varyings,
+        }
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let vec3 = |i: usize| Vector3::new(varyings[i], varyings[i + 1], varyings[i + 2]);
+        let world_pos = vec3(WORLD_POS);
+        let shadow_coord = Vector4::from_column_slice(&varyings[SHADOW_COORD..SHADOW_COORD + 4]);
+
+        let normal = normalize(vec3(NORMAL));
+        let view_dir = normalize(self.eye_position - world_pos);
+        let diffuse = self.color.component_mul(&vec3(COLOR));
+
+        let light_dir = if self.light_is_directional {
+            -self.light_direction
+        } else {
+            self.light_position - world_pos
+        };
+        let mut lit = shade(
+            &normal,
+            &normalize(light_dir),
+            &view_dir,
+            &diffuse,
+            &self.light_color,
+        );
+        if self.shadows_enabled {
+            lit *= self.lit_fraction(&shadow_coord);
+        }
+
+        for light in &self.point_lights {
+            let to_light = light.position - world_pos;
+            let falloff = (1.0 - to_light.norm() / light.radius).max(0.0);
+            lit += shade(
+                &normal,
+                &normalize(to_light),
+                &view_dir,
+                &diffuse,
+                &(light.color * falloff * falloff),
+            );
+        }
+
+        let mut uv = Vector2::new(varyings[TEX_COORD], varyings[TEX_COORD + 1]);
+        if self.tex_region_enabled {
+            let fract = uv.map(|x| x - x.floor());
+            uv = self.tex_region.xy() + fract.component_mul(&Vector2::new(self.tex_region.z, self.tex_region.w));
+        }
+        let tex_color = self.tex.sample(uv);
+        Some(tex_color.component_mul(&(diffuse / 3.0 + lit).push(1.0)))
+    }
+}
+
+/*
+ *
+ * NormalsMaterial and UvsMaterial
+ *
+ */
+struct AttributeColorShader {
+    uvs: bool,
+    transform: Matrix4<f32>,
+}
+
+impl SoftwareShader for AttributeColorShader {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        let color = if self.uvs {
+            vec![attributes[1].x, attributes[1].y, 0.0]
+        } else {
+            xyz(&attributes[1]).as_slice().to_vec()
+        };
+
+        Vertex {
+            position: self.transform * xyz(&attributes[0]).push(1.0),
+            varyings: color,
+        }
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let color = Vector3::new(varyings[0], varyings[1], varyings[2]);
+        if self.uvs {
+            Some(color.push(1.0))
+        } else {
+            Some(((color + Vector3::repeat(1.0)) / 2.0).push(1.0))
+        }
+    }
+}
+
+/*
+ *
+ * LineRenderer and PointRenderer
+ *
+ */
+struct ColoredVertexShader {
+    view_proj: Matrix4<f32>,
+}
+
+impl SoftwareShader for ColoredVertexShader {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        Vertex {
+            position: self.view_proj * xyz(&attributes[0]).push(1.0),
+            varyings: xyz(&attributes[1]).as_slice().to_vec(),
+        }
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        Some(Vector4::new(varyings[0], varyings[1], varyings[2], 1.0))
+    }
+}
+
+/*
+ *
+ * TextRenderer
+ *
+ */
+struct TextShader<'a> {
+    invsz: Vector2<f32>,
+    color: Vector3<f32>,
+    tex0: Sampler<'a>,
+}
+
+impl<'a> SoftwareShader for TextShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        let (pos, uvs) = (attributes[0], attributes[1]);
+        Vertex {
+            position: Vector4::new(
+                pos.x * self.invsz.x - 1.0,
+                pos.y * self.invsz.y + 1.0,
+                -1.0,
+                1.0,
+            ),
+            varyings: vec![uvs.x, uvs.y],
+        }
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let alpha = self.tex0.sample(Vector2::new(varyings[0], varyings[1])).x;
+        Some(self.color.push(alpha))
+    }
+}
+
+/*
+ *
+ * ShadowMap
+ *
+ */
+struct ShadowShader {
+    scale: Matrix3<f32>,
+    transform: Matrix4<f32>,
+}
+
+impl SoftwareShader for ShadowShader {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        let local_pos = (self.scale * xyz(&attributes[0])).push(1.0);
+        let inst_pos = Vector3::from_fn(|i, _| attributes[i + 1].dot(&local_pos));
+
+        Vertex {
+            position: self.transform * inst_pos.push(1.0),
+            varyings: Vec::new(),
+        }
+    }
+
+    fn fragment(&self, _: &[f32]) -> Option<Vector4<f32>> {
+        Some(Vector4::repeat(1.0))
+    }
+}
+
+/*
+ *
+ * Post-processing effects
+ *
+ */
+fn fullscreen_vertex(attributes: &[Vector4<f32>]) -> Vertex {
+    let v_coord = attributes[0];
+    Vertex {
+        position: Vector4::new(v_coord.x, v_coord.y, 0.0, 1.0),
+        varyings: vec![(v_coord.x + 1.0) / 2.0, (v_coord.y + 1.0) / 2.0],
+    }
+}
+
+fn texcoord(varyings: &[f32]) -> Vector2<f32> {
+    Vector2::new(varyings[0], varyings[1])
+}
+
+fn mix(a: Vector3<f32>, b: Vector3<f32>, t: f32) -> Vector3<f32> {
+    a + (b - a) * t
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+struct CopyShader<'a> {
+    fbo_texture: Sampler<'a>,
+}
+
+impl<'a> SoftwareShader for CopyShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        fullscreen_vertex(attributes)
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        Some(self.fbo_texture.sample(texcoord(varyings)))
+    }
+}
+
+struct VignetteShader<'a> {
+    fbo_texture: Sampler<'a>,
+    color: Vector3<f32>,
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+}
+
+impl<'a> SoftwareShader for VignetteShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        fullscreen_vertex(attributes)
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let uv = texcoord(varyings);
+        let scene = self.fbo_texture.sample(uv);
+        let dist = (uv * 2.0 - Vector2::repeat(1.0)).norm();
+        let amount = smoothstep(self.radius, self.radius + self.softness, dist) * self.intensity;
+        Some(mix(xyz(&scene), self.color, amount).push(scene.w))
+    }
+}
+
+struct FogShader<'a> {
+    fbo_texture: Sampler<'a>,
+    fbo_depth: Sampler<'a>,
+    color: Vector3<f32>,
+    start: f32,
+    end: f32,
+    density: f32,
+    znear: f32,
+    zfar: f32,
+}
+
+impl<'a> SoftwareShader for FogShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        fullscreen_vertex(attributes)
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let uv = texcoord(varyings);
+        let scene = self.fbo_texture.sample(uv);
+        let depth = self.fbo_depth.sample(uv).x;
+        if depth >= 1.0 {
+            return Some(scene);
+        }
+
+        let (near, far) = (self.znear, self.zfar);
+        let dist = near * far / (far - depth * (far - near));
+        let amount = ((dist - self.start) / (self.end - self.start)).clamp(0.0, 1.0) * self.density;
+        Some(mix(xyz(&scene), self.color, amount).push(scene.w))
+    }
+}
+
+struct ColorGradingShader<'a> {
+    fbo_texture: Sampler<'a>,
+    lut: Sampler<'a>,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    lut_size: f32,
+    lut_intensity: f32,
+}
+
+impl<'a> ColorGradingShader<'a> {
+    fn lookup(&self, color: Vector3<f32>) -> Vector3<f32> {
+        let (size, last) = (self.lut_size, self.lut_size - 1.0);
+        let blue = color.z * last;
+        let tile0 = blue.floor();
+        let tile1 = (tile0 + 1.0).min(last);
+        let uv = Vector2::new(
+            (color.x * last + 0.5) / (size * size),
+            (color.y * last + 0.5) / size,
+        );
+        let color0 = xyz(&self.lut.sample(uv + Vector2::new(tile0 / size, 0.0)));
+        let color1 = xyz(&self.lut.sample(uv + Vector2::new(tile1 / size, 0.0)));
+        mix(color0, color1, blue - tile0)
+    }
+}
+
+impl<'a> SoftwareShader for ColorGradingShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        fullscreen_vertex(attributes)
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        let scene = self.fbo_texture.sample(texcoord(varyings));
+        let color = xyz(&scene) * self.exposure;
+        let color = (color - Vector3::repeat(0.5)) * self.contrast + Vector3::repeat(0.5);
+        let luma = color.dot(&Vector3::new(0.2126, 0.7152, 0.0722));
+        let mut color =
+            mix(Vector3::repeat(luma), color, self.saturation).map(|c| c.clamp(0.0, 1.0));
+
+        if self.lut_size > 1.0 {
+            color = mix(color, self.lookup(color), self.lut_intensity);
+        }
+
+        Some(color.push(scene.w))
+    }
+}
+
+struct FxaaShader<'a> {
+    fbo_texture: Sampler<'a>,
+    texel_size: Vector2<f32>,
+    span_max: f32,
+}
+
+impl<'a> SoftwareShader for FxaaShader<'a> {
+    fn vertex(&self, attributes: &[Vector4<f32>]) -> Vertex {
+        fullscreen_vertex(attributes)
+    }
+
+    fn fragment(&self, varyings: &[f32]) -> Option<Vector4<f32>> {
+        const REDUCE_MIN: f32 = 1.0 / 128.0;
+        const REDUCE_MUL: f32 = 1.0 / 8.0;
+        let luma = |color: Vector3<f32>| color.dot(&Vector3::new(0.299, 0.587, 0.114));
+
+        let uv = texcoord(varyings);
+        let fetch = |x: f32, y: f32| {
+            let offset = Vector2::new(x, y).component_mul(&self.texel_size);
+            xyz(&self.fbo_texture.sample(uv + offset))
+        };
+        let luma_nw = luma(fetch(-1.0, -1.0));
+        let luma_ne = luma(fetch(1.0, -1.0));
+        let luma_sw = luma(fetch(-1.0, 1.0));
+        let luma_se = luma(fetch(1.0, 1.0));
+        let center = self.fbo_texture.sample(uv);
+        let luma_m = luma(xyz(&center));
+
+        let luma_min = luma_m.min(luma_nw.min(luma_ne).min(luma_sw.min(luma_se)));
+        let luma_max = luma_m.max(luma_nw.max(luma_ne).max(luma_sw.max(luma_se)));
+
+        let dir = Vector2::new(
+            -((luma_nw + luma_ne) - (luma_sw + luma_se)),
+            (luma_nw + luma_sw) - (luma_ne + luma_se),
+        );
+        let reduce = ((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL).max(REDUCE_MIN);
+        let scale = 1.0 / (dir.x.abs().min(dir.y.abs()) + reduce);
+        let dir = (dir * scale).map(|d| d.clamp(-self.span_max, self.span_max));
+        let along = |t: f32| fetch(dir.x * t, dir.y * t);
+
+        let color_a = (along(1.0 / 3.0 - 0.5) + along(2.0 / 3.0 - 0.5)) * 0.5;
+        let color_b = color_a * 0.5 + (along(-0.5) + along(0.5)) * 0.25;
+        let luma_b = luma(color_b);
+
+        if luma_b < luma_min || luma_b > luma_max {
+            Some(color_a.push(center.w))
+        } else {
+            Some(color_b.push(center.w))
+        }
+    }
+}
@@ -17,6 +17,8 @@ use crate::resource::{GLPrimitive, PrimitiveArray};
 #[derive(Clone)]
 pub struct WebGLContext {
     ctxt: Rc<WebGLRenderingContext>,
+    // The ANGLE_instanced_arrays extension, if supported.
+    instanced_arrays: Option<Value>,
 }
 
 impl WebGLContext {
@@ -36,8 +38,27 @@ impl WebGLContext {
         )
         .try_into()
         .unwrap();
+        // WebGL 1 only draws 32-bit indices once OES_element_index_uint is enabled.
+        js!( @(no_return) @{&web_ctxt}.getExtension("OES_element_index_uint"); );
+        // WebGL 1 only supports instancing through the ANGLE_instanced_arrays extension.
+        let instanced_arrays = js!( return @{&web_ctxt}.getExtension("ANGLE_instanced_arrays"); );
+        let instanced_arrays = if instanced_arrays.is_null() {
+            None
+        } else {
+            Some(instanced_arrays)
+        };
         let ctxt = Rc::new(web_ctxt);
-        WebGLContext { ctxt }
+        WebGLContext {
+            ctxt,
+            instanced_arrays,
+        }
+    }
+
+    // The ANGLE_instanced_arrays extension, which drawing instances requires.
+    fn instanced_arrays(&self) -> &Value {
+        self.instanced_arrays
+            .as_ref()
+            .expect("Drawing instances requires the ANGLE_instanced_arrays extension.")
     }
 }
 
@@ -210,6 +231,10 @@ impl AbstractContext for WebGLContext {
                 let abuf = TypedArray::<u16>::from(arr);
                 self.ctxt.buffer_data_1(target, Some(&abuf.buffer()), usage)
             }
+            PrimitiveArray::UInt32(arr) => {
+                let abuf = TypedArray::<u32>::from(arr);
+                self.ctxt.buffer_data_1(target, Some(&abuf.buffer()), usage)
+            }
         }
     }
 
@@ -230,6 +255,11 @@ impl AbstractContext for WebGLContext {
                 self.ctxt
                     .buffer_sub_data(target, offset as GLintptr, &abuf.buffer())
             }
+            PrimitiveArray::UInt32(arr) => {
+                let abuf = TypedArray::<u32>::from(arr);
+                self.ctxt
+                    .buffer_sub_data(target, offset as GLintptr, &abuf.buffer())
+            }
         }
     }
 
@@ -538,6 +568,35 @@ impl AbstractContext for WebGLContext {
         self.ctxt.draw_elements(mode, count, type_, offset)
     }
 
+    fn draw_elements_instanced(
+        &self,
+        mode: GLenum,
+        count: i32,
+        type_: GLenum,
+        offset: GLintptr,
+        instances: i32,
+    ) {
+        let ext = self.instanced_arrays();
+        js!( @(no_return)
+            @{ext}.drawElementsInstancedANGLE(
+                @{mode}, @{count}, @{type_}, @{(offset as f64)}, @{instances}
+            );
+        );
+    }
+
+    fn vertex_attrib_divisor(&self, index: u32, divisor: u32) {
+        let ext = self.instanced_arrays();
+        js!( @(no_return) @{ext}.vertexAttribDivisorANGLE(@{index}, @{divisor}); );
+    }
+
+    fn supports_instancing(&self) -> bool {
+        self.instanced_arrays.is_some()
+    }
+
+    fn vertex_attrib4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+        self.ctxt.vertex_attrib4f(index, x, y, z, w)
+    }
+
     fn draw_arrays(&self, mode: GLenum, first: i32, count: i32) {
         self.ctxt.draw_arrays(mode, first, count)
     }
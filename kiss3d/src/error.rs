@@ -1,39 +1,153 @@
-#![macro_use]
-
-#[allow(unused_macros)]
-macro_rules! verify(
-    ($e: expr) => {
-        {
-            let res = $e;
-            #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
-            { assert_eq!(crate::context::Context::get().get_error(), 0); }
-            res
+//! Errors returned by the loaders.
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// What is wrong with a malformed line of a text file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// A statement has fewer components than it requires.
+    MissingComponents {
+        /// The number of components required.
+        expected: usize,
+        /// The number of components present.
+        found: usize,
+    },
+    /// A component is not a valid number.
+    InvalidNumber(String),
+    /// A face vertex is not of the form `v`, `v/vt`, `v//vn` or `v/vt/vn`.
+    InvalidFaceVertex(String),
+    /// A face refers to a vertex, texture coordinate or normal that is not defined.
+    IndexOutOfBounds {
+        /// The index, as written in the file.
+        index: i64,
+        /// The number of elements defined when the index was read.
+        len: usize,
+    },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::MissingComponents { expected, found } => {
+                write!(f, "{} components were expected, found {}", expected, found)
+            }
+            ParseErrorKind::InvalidNumber(s) => write!(f, "`{}` is not a valid number", s),
+            ParseErrorKind::InvalidFaceVertex(s) => {
+                write!(f, "`{}` is not a valid face vertex", s)
+            }
+            ParseErrorKind::IndexOutOfBounds { index, len } => {
+                write!(f, "index {} is out of bounds, {} elements are defined", index, len)
+            }
+        }
+    }
+}
+
+/// A malformed line of a text file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The file parsed, if it was read from a path.
+    pub path: Option<PathBuf>,
+    /// The line the error is on, starting at 1.
+    pub line: usize,
+    /// The column, in characters and starting at 1, of the component the error is about.
+    pub column: usize,
+    /// What is wrong with the line.
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    /// Creates an error about a line of a file read from memory.
+    pub fn new(line: usize, column: usize, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            path: None,
+            line,
+            column,
+            kind,
         }
     }
-);
 
-#[allow(unused_macros)]
-macro_rules! ignore(
-    ($e: expr) => {
-        {
-            let res = $e;
-            #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
-            { let _ = crate::context::Context::get().get_error(); }
-            res
+    /// Sets the path of the file the error is in, unless it is already set.
+    pub fn with_path<P: Into<PathBuf>>(mut self, path: P) -> ParseError {
+        if self.path.is_none() {
+            self.path = Some(path.into());
         }
+        self
     }
-);
-
-#[allow(unused_macros)]
-macro_rules! checked(
-    ($e: expr) => {
-        {
-            let res = $e;
-            if cfg!(not(any(target_arch = "wasm32", target_arch = "asmjs"))) && crate::context::Context::get().get_error() != 0 {
-                None
-            } else {
-                Some(res)
-            }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}:", path.display())?;
+        }
+        write!(f, "{}:{}: {}", self.line, self.column, self.kind)
+    }
+}
+
+impl Error for ParseError {}
+
+/// An error that prevented a file from being loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is malformed.
+    Parse(ParseError),
+    /// The glTF file is malformed.
+    Gltf(gltf::Error),
+    /// An image could not be decoded.
+    Image(image::ImageError),
+    /// The file refers to data that is missing, inconsistent or not supported.
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => e.fmt(f),
+            LoadError::Parse(e) => e.fmt(f),
+            LoadError::Gltf(e) => e.fmt(f),
+            LoadError::Image(e) => e.fmt(f),
+            LoadError::Invalid(msg) => f.write_str(msg),
         }
     }
-);
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            LoadError::Io(e) => Some(e),
+            LoadError::Parse(e) => Some(e),
+            LoadError::Gltf(e) => Some(e),
+            LoadError::Image(e) => Some(e),
+            LoadError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+impl From<ParseError> for LoadError {
+    fn from(e: ParseError) -> LoadError {
+        LoadError::Parse(e)
+    }
+}
+
+impl From<gltf::Error> for LoadError {
+    fn from(e: gltf::Error) -> LoadError {
+        LoadError::Gltf(e)
+    }
+}
+
+impl From<image::ImageError> for LoadError {
+    fn from(e: image::ImageError) -> LoadError {
+        LoadError::Image(e)
+    }
+}
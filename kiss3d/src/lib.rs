@@ -135,6 +135,9 @@ extern crate bitflags;
 extern crate nalgebra as na;
 extern crate num_traits as num;
 extern crate rusttype;
+extern crate gif;
+extern crate gltf;
+extern crate base64;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
@@ -155,6 +158,7 @@ extern crate instant;
 #[cfg(feature = "conrod")]
 pub use conrod::widget_ids;
 
+pub use image;
 pub use nalgebra;
 pub use ncollide3d;
 
@@ -163,16 +167,19 @@ pub use crate::renderer::line_renderer;
 #[deprecated(note = "Use the `renderer` module instead.")]
 pub use crate::renderer::point_renderer;
 
+pub mod animation;
 pub mod builtin;
 pub mod camera;
 pub mod context;
-mod error;
+pub mod error;
 pub mod event;
+mod gl_error;
 pub mod light;
 pub mod loader;
 pub mod planar_camera;
 pub mod planar_line_renderer;
 pub mod post_processing;
+pub mod recording;
 pub mod renderer;
 pub mod resource;
 pub mod scene;
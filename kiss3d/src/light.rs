@@ -1,6 +1,12 @@
 //! Lights.
 
-use na::Point3;
+use na::{Point3, Vector3};
+use crate::renderer::ShadowMap;
+
+/// The maximum number of point lights taken into account by the built-in materials.
+///
+/// Additional point lights are ignored.
+pub const MAX_POINT_LIGHTS: usize = 4;
 
 /// The light configuration.
 #[derive(Clone)]
@@ -9,4 +15,73 @@ pub enum Light {
     Absolute(Point3<f32>),
     /// A light superimposed with the camera position.
     StickToCamera,
+    /// A light infinitely far away, like the sun, shining along the given direction.
+    ///
+    /// This is the only kind of main light that casts shadows.
+    Directional(Vector3<f32>),
+}
+
+/// A colored light emitted from a point, fading out with distance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PointLight {
+    /// The position of the light.
+    pub position: Point3<f32>,
+    /// The color of the light.
+    pub color: Point3<f32>,
+    /// A factor applied to the color of the light.
+    pub intensity: f32,
+    /// The distance at which the light has completely faded out.
+    pub radius: f32,
+}
+
+impl PointLight {
+    /// Creates a new point light.
+    pub fn new(position: Point3<f32>, color: Point3<f32>, intensity: f32, radius: f32) -> PointLight {
+        PointLight {
+            position,
+            color,
+            intensity,
+            radius,
+        }
+    }
+}
+
+/// All the lights illuminating a scene.
+pub struct Lights {
+    /// The main light.
+    pub main: Light,
+    /// The color of the main light.
+    pub color: Point3<f32>,
+    /// A factor applied to the color of the main light.
+    pub intensity: f32,
+    /// Additional point lights. See `MAX_POINT_LIGHTS`.
+    pub point_lights: Vec<PointLight>,
+    pub(crate) shadow_map: Option<ShadowMap>,
+}
+
+impl Lights {
+    /// A white main light of intensity 1 and no point lights.
+    pub fn new(main: Light) -> Lights {
+        Lights {
+            main,
+            color: Point3::new(1.0, 1.0, 1.0),
+            intensity: 1.0,
+            point_lights: Vec::new(),
+            shadow_map: None,
+        }
+    }
+
+    /// The color of the main light, scaled by its intensity.
+    pub fn main_color(&self) -> Point3<f32> {
+        self.color * self.intensity
+    }
+
+    /// The shadow map of the main light, if shadows are enabled and the main light is
+    /// directional.
+    pub fn shadow_map(&self) -> Option<&ShadowMap> {
+        match self.main {
+            Light::Directional(_) => self.shadow_map.as_ref(),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,378 @@
+//! Simplistic glTF 2.0 loader.
+//!
+//! Both `.gltf` files, with their buffers and images embedded as data URIs or in separate files,
+//! and binary `.glb` files are supported. Only the default scene is loaded, with its node
+//! hierarchy, triangle meshes and the base color of their materials. Cameras, lights, skins,
+//! morph targets and animations are ignored.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use std::rc::Rc;
+
+use ::gltf::buffer;
+use ::gltf::image::Source;
+use ::gltf::mesh::Mode;
+use ::gltf::texture::WrappingMode;
+use ::gltf::{Document, Gltf};
+use image::{self, DynamicImage};
+use na::{Isometry3, Point2, Point3, Quaternion, Translation3, UnitQuaternion, Vector3};
+
+use crate::error::LoadError;
+use crate::resource::{Mesh, TextureWrapping};
+
+/// A glTF scene.
+pub struct GltfScene {
+    /// The root nodes of the scene.
+    pub nodes: Vec<GltfNode>,
+    /// The images of the file, indexed like in the file.
+    pub images: Vec<DynamicImage>,
+}
+
+/// A node of a glTF scene.
+pub struct GltfNode {
+    /// The name of the node.
+    pub name: Option<String>,
+    /// The transformation of the node, relative to its parent.
+    pub transform: Isometry3<f32>,
+    /// The scale of the node, relative to its parent.
+    pub scale: Vector3<f32>,
+    /// The parts of the mesh of this node.
+    pub primitives: Vec<GltfPrimitive>,
+    /// The children of this node.
+    pub children: Vec<GltfNode>,
+}
+
+/// A part of a mesh with a single material.
+///
+/// Nodes using the same glTF mesh share their `Mesh`es.
+pub struct GltfPrimitive {
+    /// The geometry.
+    pub mesh: Rc<RefCell<Mesh>>,
+    /// How the geometry looks.
+    pub material: GltfMaterial,
+}
+
+/// The parts of a glTF material kiss3d can render.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMaterial {
+    /// The name of the material.
+    pub name: Option<String>,
+    /// The base color, as linear RGBA.
+    pub base_color: [f32; 4],
+    /// The texture the base color is multiplied by.
+    pub base_color_texture: Option<GltfTexture>,
+    /// Whether the back faces are visible.
+    pub double_sided: bool,
+    /// Whether the mesh has per-vertex colors the base color is multiplied by.
+    pub vertex_colors: bool,
+}
+
+/// A texture of a glTF material.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GltfTexture {
+    /// The index of the texture image in `GltfScene::images`.
+    pub image: usize,
+    /// The wrapping along the `s` texture coordinate.
+    pub wrap_s: TextureWrapping,
+    /// The wrapping along the `t` texture coordinate.
+    pub wrap_t: TextureWrapping,
+}
+
+/// Parses a `.gltf` or `.glb` file.
+///
+/// The buffers and images it refers to are looked for relative to the directory of the file.
+pub fn parse_file(path: &Path) -> Result<GltfScene, LoadError> {
+    let file = File::open(path)?;
+    parse_reader(file, path.parent().unwrap_or_else(|| Path::new("")))
+}
+
+/// Parses a `.gltf` or `.glb` file from a reader.
+///
+/// The buffers and images it refers to are looked for in `base_dir`.
+pub fn parse_reader<R: Read>(mut reader: R, base_dir: &Path) -> Result<GltfScene, LoadError> {
+    let mut data = Vec::new();
+    let _ = reader.read_to_end(&mut data)?;
+    parse(&data, base_dir)
+}
+
+/// Parses the content of a `.gltf` or `.glb` file.
+///
+/// The buffers and images it refers to are looked for in `base_dir`.
+pub fn parse(data: &[u8], base_dir: &Path) -> Result<GltfScene, LoadError> {
+    let Gltf { document, blob } = Gltf::from_slice(data)?;
+    let buffers = load_buffers(&document, blob, base_dir)?;
+    let images = load_images(&document, &buffers, base_dir)?;
+
+    // Meshes are shared by the nodes using them.
+    let mut meshes = Vec::new();
+    for mesh in document.meshes() {
+        let mut primitives = Vec::new();
+
+        for primitive in mesh.primitives() {
+            match load_primitive(&primitive, &buffers)? {
+                Some((mesh, vertex_colors)) => {
+                    let material = load_material(&primitive.material(), vertex_colors);
+                    primitives.push((Rc::new(RefCell::new(mesh)), material));
+                }
+                None => println!(
+                    "Warning: primitive {} of mesh {} ignored: only triangles are supported.",
+                    primitive.index(),
+                    mesh.index()
+                ),
+            }
+        }
+
+        meshes.push(primitives);
+    }
+
+    let scene = document.default_scene().or_else(|| document.scenes().next());
+    let nodes = match scene {
+        Some(scene) => scene.nodes().map(|n| load_node(&n, &meshes)).collect(),
+        None => Vec::new(),
+    };
+
+    Ok(GltfScene { nodes, images })
+}
+
+// The meshes and materials of the primitives of each glTF mesh.
+type Meshes = [Vec<(Rc<RefCell<Mesh>>, GltfMaterial)>];
+
+fn load_node(node: &::gltf::Node, meshes: &Meshes) -> GltfNode {
+    let (t, r, s) = node.transform().decomposed();
+    let rotation = UnitQuaternion::from_quaternion(Quaternion::new(r[3], r[0], r[1], r[2]));
+    let transform = Isometry3::from_parts(Translation3::new(t[0], t[1], t[2]), rotation);
+
+    let primitives = match node.mesh() {
+        Some(mesh) => meshes[mesh.index()]
+            .iter()
+            .map(|(mesh, material)| GltfPrimitive {
+                mesh: mesh.clone(),
+                material: material.clone(),
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+
+    GltfNode {
+        name: node.name().map(|n| n.to_string()),
+        transform,
+        scale: Vector3::new(s[0], s[1], s[2]),
+        primitives,
+        children: node.children().map(|c| load_node(&c, meshes)).collect(),
+    }
+}
+
+// Returns the mesh and whether it has vertex colors, or `None` if it is not made of triangles.
+fn load_primitive(
+    primitive: &::gltf::Primitive,
+    buffers: &[Vec<u8>],
+) -> Result<Option<(Mesh, bool)>, LoadError> {
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()][..]));
+    let coords: Vec<Point3<f32>> = match reader.read_positions() {
+        Some(coords) => coords.map(Point3::from).collect(),
+        None => return Ok(None),
+    };
+    let normals: Option<Vec<Vector3<f32>>> = reader
+        .read_normals()
+        .map(|ns| ns.map(Vector3::from).collect());
+    let uvs: Option<Vec<Point2<f32>>> = reader
+        .read_tex_coords(0)
+        .map(|uvs| uvs.into_f32().map(Point2::from).collect());
+    let colors: Option<Vec<Point3<f32>>> = reader
+        .read_colors(0)
+        .map(|cs| cs.into_rgb_f32().map(Point3::from).collect());
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..coords.len() as u32).collect(),
+    };
+
+    let invalid = |what| {
+        let msg = format!("primitive {} has {}", primitive.index(), what);
+        Err(LoadError::Invalid(msg))
+    };
+    let lengths = [
+        normals.as_ref().map(|ns| ns.len()),
+        uvs.as_ref().map(|uvs| uvs.len()),
+        colors.as_ref().map(|cs| cs.len()),
+    ];
+    if lengths.iter().flatten().any(|&len| len != coords.len()) {
+        return invalid("attributes of different lengths");
+    }
+    if indices.iter().any(|&i| i as usize >= coords.len()) {
+        return invalid("indices out of bounds");
+    }
+
+    let mut faces = Vec::with_capacity(indices.len() / 3);
+    match primitive.mode() {
+        Mode::Triangles => {
+            for f in indices.chunks_exact(3) {
+                faces.push(Point3::new(f[0], f[1], f[2]));
+            }
+        }
+        Mode::TriangleStrip => {
+            for (i, f) in indices.windows(3).enumerate() {
+                // Every other triangle has its winding reversed.
+                if i % 2 == 0 {
+                    faces.push(Point3::new(f[0], f[1], f[2]));
+                } else {
+                    faces.push(Point3::new(f[1], f[0], f[2]));
+                }
+            }
+        }
+        Mode::TriangleFan => {
+            for f in indices.windows(2).skip(1) {
+                faces.push(Point3::new(indices[0], f[0], f[1]));
+            }
+        }
+        _ => return Ok(None),
+    }
+
+    // Only fall back to 32-bit indices when 16 bits are not enough
+    let mut mesh = if coords.len() > u16::MAX as usize + 1 {
+        Mesh::new_u32(coords, faces, normals, uvs, false)
+    } else {
+        let faces = faces
+            .into_iter()
+            .map(|f| Point3::new(f.x as u16, f.y as u16, f.z as u16))
+            .collect();
+        Mesh::new(coords, faces, normals, uvs, false)
+    };
+
+    let vertex_colors = colors.is_some();
+    if let Some(colors) = colors {
+        mesh.set_colors(colors);
+    }
+
+    Ok(Some((mesh, vertex_colors)))
+}
+
+fn load_material(material: &::gltf::Material, vertex_colors: bool) -> GltfMaterial {
+    let pbr = material.pbr_metallic_roughness();
+    let base_color_texture = pbr.base_color_texture().map(|info| {
+        let texture = info.texture();
+        let sampler = texture.sampler();
+
+        GltfTexture {
+            image: texture.source().index(),
+            wrap_s: wrapping(sampler.wrap_s()),
+            wrap_t: wrapping(sampler.wrap_t()),
+        }
+    });
+
+    GltfMaterial {
+        name: material.name().map(|n| n.to_string()),
+        base_color: pbr.base_color_factor(),
+        base_color_texture,
+        double_sided: material.double_sided(),
+        vertex_colors,
+    }
+}
+
+fn wrapping(mode: WrappingMode) -> TextureWrapping {
+    match mode {
+        WrappingMode::ClampToEdge => TextureWrapping::ClampToEdge,
+        WrappingMode::MirroredRepeat => TextureWrapping::MirroredRepeat,
+        WrappingMode::Repeat => TextureWrapping::Repeat,
+    }
+}
+
+fn load_buffers(
+    document: &Document,
+    mut blob: Option<Vec<u8>>,
+    base_dir: &Path,
+) -> Result<Vec<Vec<u8>>, LoadError> {
+    let mut buffers = Vec::new();
+
+    for buffer in document.buffers() {
+        let data = match buffer.source() {
+            buffer::Source::Bin => blob.take().ok_or_else(|| {
+                LoadError::Invalid("the binary chunk of the glb file is missing".to_string())
+            })?,
+            buffer::Source::Uri(uri) => load_uri(uri, base_dir)?,
+        };
+
+        if data.len() < buffer.length() {
+            return Err(LoadError::Invalid(format!(
+                "buffer {} has {} bytes instead of {}",
+                buffer.index(),
+                data.len(),
+                buffer.length()
+            )));
+        }
+
+        buffers.push(data);
+    }
+
+    Ok(buffers)
+}
+
+fn load_images(
+    document: &Document,
+    buffers: &[Vec<u8>],
+    base_dir: &Path,
+) -> Result<Vec<DynamicImage>, LoadError> {
+    let mut images = Vec::new();
+
+    for image in document.images() {
+        let image = match image.source() {
+            Source::View { view, .. } => {
+                let range = view.offset()..view.offset() + view.length();
+                let data = buffers[view.buffer().index()].get(range).ok_or_else(|| {
+                    LoadError::Invalid(format!("image {} is out of its buffer", image.index()))
+                })?;
+                image::load_from_memory(data)?
+            }
+            Source::Uri { uri, .. } => image::load_from_memory(&load_uri(uri, base_dir)?)?,
+        };
+
+        images.push(image);
+    }
+
+    Ok(images)
+}
+
+// Reads the data of a data URI, or of a file relative to `base_dir`.
+fn load_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>, LoadError> {
+    if uri.starts_with("data:") {
+        let data = uri
+            .find(";base64,")
+            .map(|i| &uri[i + ";base64,".len()..])
+            .ok_or_else(|| LoadError::Invalid(format!("data URI is not base64: {:.40}", uri)))?;
+        base64::decode(data).map_err(|e| LoadError::Invalid(format!("invalid data URI: {}", e)))
+    } else if uri.contains("://") {
+        Err(LoadError::Invalid(format!("unsupported URI: {}", uri)))
+    } else {
+        let mut data = Vec::new();
+        let _ = File::open(base_dir.join(percent_decode(uri)))?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+// Relative URIs escape spaces and other special characters.
+fn percent_decode(uri: &str) -> String {
+    let bytes = uri.as_bytes();
+    let mut res = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) if bytes[i] == b'%' => {
+                res.push(byte);
+                i += 3;
+            }
+            _ => {
+                res.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&res).into_owned()
+}
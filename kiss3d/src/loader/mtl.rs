@@ -1,36 +1,37 @@
 //! Simplistic mtl loader.
 
+use crate::error::{LoadError, ParseError};
 use crate::loader::obj;
 use crate::loader::obj::Words;
 use na::Vector3;
 use std::fs::File;
 use std::io::Read;
-use std::io::Result as IoResult;
 use std::mem;
 use std::path::Path;
-use std::str::FromStr;
 
-fn error(line: usize, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
+/// Parses a mtl file.
+pub fn parse_file(path: &Path) -> Result<Vec<MtlMaterial>, LoadError> {
+    let file = File::open(path)?;
+    parse_reader(file).map_err(|e| match e {
+        LoadError::Parse(e) => LoadError::Parse(e.with_path(path)),
+        e => e,
+    })
 }
 
-/// Parses a mtl file.
-pub fn parse_file(path: &Path) -> IoResult<Vec<MtlMaterial>> {
-    match File::open(path) {
-        Ok(mut file) => {
-            let mut sfile = String::new();
-            file.read_to_string(&mut sfile).map(|_| parse(&sfile[..]))
-        }
-        Err(e) => Err(e),
-    }
+/// Parses a mtl file from a reader.
+pub fn parse_reader<R: Read>(mut reader: R) -> Result<Vec<MtlMaterial>, LoadError> {
+    let mut string = String::new();
+    let _ = reader.read_to_string(&mut string)?;
+    Ok(parse(&string)?)
 }
 
 /// Parses a string representing a mtl file.
-pub fn parse(string: &str) -> Vec<MtlMaterial> {
+pub fn parse(string: &str) -> Result<Vec<MtlMaterial>, ParseError> {
     let mut res = Vec::new();
     let mut curr_material = MtlMaterial::new_default("".to_string());
 
     for (l, line) in string.lines().enumerate() {
+        let l = l + 1;
         let mut words = obj::split_words(line);
         let tag = words.next();
 
@@ -58,15 +59,15 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
                             }
                         }
                         // ambiant color
-                        "Ka" => curr_material.ambiant = parse_color(l, words),
+                        "Ka" => curr_material.ambiant = parse_color(l, line, words)?,
                         // diffuse color
-                        "Kd" => curr_material.diffuse = parse_color(l, words),
+                        "Kd" => curr_material.diffuse = parse_color(l, line, words)?,
                         // specular color
-                        "Ks" => curr_material.specular = parse_color(l, words),
+                        "Ks" => curr_material.specular = parse_color(l, line, words)?,
                         // shininess
-                        "Ns" => curr_material.shininess = parse_scalar(l, words),
+                        "Ns" => curr_material.shininess = parse_scalar(l, line, words)?,
                         // alpha
-                        "d" => curr_material.alpha = parse_scalar(l, words),
+                        "d" => curr_material.alpha = parse_scalar(l, line, words)?,
                         // ambiant map
                         "map_Ka" => curr_material.ambiant_texture = Some(parse_name(l, words)),
                         // diffuse texture map
@@ -90,7 +91,7 @@ pub fn parse(string: &str) -> Vec<MtlMaterial> {
         res.push(curr_material);
     }
 
-    res
+    Ok(res)
 }
 
 fn parse_name<'a>(_: usize, ws: Words<'a>) -> String {
@@ -98,42 +99,16 @@ fn parse_name<'a>(_: usize, ws: Words<'a>) -> String {
     res.join(" ")
 }
 
-fn parse_color(l: usize, mut ws: Words) -> Vector3<f32> {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 2."));
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    let z: Result<f32, _> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    let z =
-        z.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sz, e)[..]));
-
-    Vector3::new(x, y, z)
+fn parse_color(l: usize, line: &str, mut ws: Words) -> Result<Vector3<f32>, ParseError> {
+    let mut rgb = [0.0; 3];
+    obj::parse_floats(l, line, &mut ws, &mut rgb)?;
+    Ok(Vector3::new(rgb[0], rgb[1], rgb[2]))
 }
 
-fn parse_scalar(l: usize, mut ws: Words) -> f32 {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "1 component was expected, found 0."));
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-
-    x
+fn parse_scalar(l: usize, line: &str, mut ws: Words) -> Result<f32, ParseError> {
+    let mut x = [0.0];
+    obj::parse_floats(l, line, &mut ws, &mut x)?;
+    Ok(x[0])
 }
 
 /// Material informations read from a `.mtl` file.
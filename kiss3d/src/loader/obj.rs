@@ -2,15 +2,14 @@
 
 use crate::loader::mtl;
 use crate::loader::mtl::MtlMaterial;
+use crate::error::{LoadError, ParseError, ParseErrorKind};
 use na::{Point2, Point3, Vector3};
-use num::Bounded;
 use crate::resource::GPUVec;
-use crate::resource::{AllocationType, BufferType, Mesh};
+use crate::resource::{AllocationType, BufferType, Mesh, MeshFaces};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Read;
-use std::io::Result as IoResult;
 use std::iter::repeat;
 use std::iter::Filter;
 use std::path::{Path, PathBuf};
@@ -44,41 +43,88 @@ pub fn split_words(s: &str) -> Words {
     s.split(is_whitespace).filter(is_not_empty)
 }
 
-fn error(line: usize, err: &str) -> ! {
-    panic!("At line {}: {}", line, err)
-}
-
 fn warn(line: usize, err: &str) {
     println!("At line {}: {}", line, err)
 }
 
+/// The column, in characters and starting at 1, of `word` which must be a slice of `line`.
+pub(crate) fn column(line: &str, word: &str) -> usize {
+    let offset = (word.as_ptr() as usize).saturating_sub(line.as_ptr() as usize);
+    line.get(..offset).map_or(1, |s| s.chars().count() + 1)
+}
+
+/// The column just after the end of `line`, where missing components would be.
+pub(crate) fn end_column(line: &str) -> usize {
+    line.trim_end().chars().count() + 1
+}
+
+/// Parses the next `out.len()` words of `ws` as `f32`.
+pub(crate) fn parse_floats(
+    l: usize,
+    line: &str,
+    ws: &mut Words,
+    out: &mut [f32],
+) -> Result<(), ParseError> {
+    for i in 0..out.len() {
+        let w = ws.next().ok_or_else(|| {
+            let kind = ParseErrorKind::MissingComponents {
+                expected: out.len(),
+                found: i,
+            };
+            ParseError::new(l, end_column(line), kind)
+        })?;
+
+        out[i] = f32::from_str(w).map_err(|_| {
+            let kind = ParseErrorKind::InvalidNumber(w.to_string());
+            ParseError::new(l, column(line, w), kind)
+        })?;
+    }
+
+    Ok(())
+}
+
 /// Parses an obj file.
+///
+/// The mtl files it refers to are looked for in `mtl_base_dir`.
 pub fn parse_file(
     path: &Path,
     mtl_base_dir: &Path,
     basename: &str,
-) -> IoResult<Vec<(String, Mesh, Option<MtlMaterial>)>> {
-    match File::open(path) {
-        Ok(mut file) => {
-            let mut sfile = String::new();
-            file.read_to_string(&mut sfile)
-                .map(|_| parse(&sfile[..], mtl_base_dir, basename))
-        }
-        Err(e) => Err(e),
-    }
+) -> Result<Vec<(String, Mesh, Option<MtlMaterial>)>, LoadError> {
+    let file = File::open(path)?;
+    parse_reader(file, mtl_base_dir, basename).map_err(|e| match e {
+        LoadError::Parse(e) => LoadError::Parse(e.with_path(path)),
+        e => e,
+    })
+}
+
+/// Parses an obj file from a reader.
+///
+/// The mtl files it refers to are looked for in `mtl_base_dir`.
+pub fn parse_reader<R: Read>(
+    mut reader: R,
+    mtl_base_dir: &Path,
+    basename: &str,
+) -> Result<Vec<(String, Mesh, Option<MtlMaterial>)>, LoadError> {
+    let mut string = String::new();
+    let _ = reader.read_to_string(&mut string)?;
+    Ok(parse(&string, mtl_base_dir, basename)?)
 }
 
 /// Parses a string representing an obj file.
+///
+/// The mtl files it refers to are looked for in `mtl_base_dir`. An error in one of them is
+/// returned with its path set.
 pub fn parse(
     string: &str,
     mtl_base_dir: &Path,
     basename: &str,
-) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
+) -> Result<Vec<(String, Mesh, Option<MtlMaterial>)>, ParseError> {
     let mut coords: Vec<Coord> = Vec::new();
     let mut normals: Vec<Normal> = Vec::new();
     let mut uvs: Vec<UV> = Vec::new();
     let mut groups: HashMap<String, usize> = HashMap::new();
-    let mut groups_ids: Vec<Vec<Point3<u16>>> = Vec::new();
+    let mut groups_ids: Vec<Vec<Point3<u32>>> = Vec::new();
     let mut curr_group: usize = 0;
     let mut ignore_normals = false;
     let mut ignore_uvs = false;
@@ -90,6 +136,7 @@ pub fn parse(
     let _ = groups.insert(basename.to_string(), 0);
 
     for (l, line) in string.lines().enumerate() {
+        let l = l + 1;
         let mut words = split_words(line);
         let tag = words.next();
         match tag {
@@ -97,14 +144,15 @@ pub fn parse(
             Some(w) => {
                 if w.len() != 0 && w.as_bytes()[0] != ('#' as u8) {
                     match w {
-                        "v" => coords.push(Point3::from(parse_v_or_vn(l, words))),
+                        "v" => coords.push(Point3::from(parse_v_or_vn(l, line, words)?)),
                         "vn" => {
                             if !ignore_normals {
-                                normals.push(parse_v_or_vn(l, words))
+                                normals.push(parse_v_or_vn(l, line, words)?)
                             }
                         }
                         "f" => parse_f(
                             l,
+                            line,
                             words,
                             &coords[..],
                             &uvs[..],
@@ -113,10 +161,10 @@ pub fn parse(
                             &mut ignore_normals,
                             &mut groups_ids,
                             curr_group,
-                        ),
+                        )?,
                         "vt" => {
                             if !ignore_uvs {
-                                uvs.push(parse_vt(l, words))
+                                uvs.push(parse_vt(l, line, words)?)
                             }
                         }
                         "g" => {
@@ -125,7 +173,7 @@ pub fn parse(
                                 .as_ref()
                                 .map(|mtl| group2mtl.insert(curr_group, mtl.clone()));
                         }
-                        "mtllib" => parse_mtllib(l, words, mtl_base_dir, &mut mtllib),
+                        "mtllib" => parse_mtllib(l, words, mtl_base_dir, &mut mtllib)?,
                         "usemtl" => {
                             curr_group = parse_usemtl(
                                 l,
@@ -155,14 +203,14 @@ pub fn parse(
         println!("Warning: some normals are missing. Dropping normals infos for every vertex.");
     }
 
-    reformat(
+    Ok(reformat(
         coords,
         if ignore_normals { None } else { Some(normals) },
         if ignore_uvs { None } else { Some(uvs) },
         groups_ids,
         groups,
         group2mtl,
-    )
+    ))
 }
 
 fn parse_usemtl<'a>(
@@ -172,7 +220,7 @@ fn parse_usemtl<'a>(
     mtllib: &HashMap<String, MtlMaterial>,
     group2mtl: &mut HashMap<usize, MtlMaterial>,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
     curr_mtl: &mut Option<MtlMaterial>,
 ) -> usize {
     let mname: Vec<&'a str> = ws.collect();
@@ -223,7 +271,7 @@ fn parse_mtllib<'a>(
     ws: Words<'a>,
     mtl_base_dir: &Path,
     mtllib: &mut HashMap<String, MtlMaterial>,
-) {
+) -> Result<(), ParseError> {
     let filename: Vec<&'a str> = ws.collect();
     let filename = filename.join(" ");
 
@@ -231,145 +279,138 @@ fn parse_mtllib<'a>(
     path.push(mtl_base_dir);
     path.push(filename);
 
-    let ms = mtl::parse_file(&path);
-
-    match ms {
+    // A missing material library is not fatal: the groups using its materials get none.
+    match mtl::parse_file(&path) {
         Ok(ms) => {
             for m in ms.into_iter() {
                 let _ = mtllib.insert(m.name.to_string(), m);
             }
         }
-        Err(err) => warn(l, &format!("{}", err)[..]),
+        Err(LoadError::Parse(err)) => return Err(err),
+        Err(err) => warn(l, &format!("{}: {}", path.display(), err)[..]),
     }
+
+    Ok(())
 }
 
-fn parse_v_or_vn(l: usize, mut ws: Words) -> Vector3<f32> {
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 1."));
-    let sz = ws
-        .next()
-        .unwrap_or_else(|| error(l, "3 components were expected, found 2."));
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    let z: Result<f32, _> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    let z =
-        z.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sz, e)[..]));
-
-    Vector3::new(x, y, z)
+fn parse_v_or_vn(l: usize, line: &str, mut ws: Words) -> Result<Vector3<f32>, ParseError> {
+    let mut xyz = [0.0; 3];
+    parse_floats(l, line, &mut ws, &mut xyz)?;
+    Ok(Vector3::new(xyz[0], xyz[1], xyz[2]))
+}
+
+// Converts a 1-based or negative, relative, index into a 0-based one.
+fn resolve_index(
+    l: usize,
+    line: &str,
+    word: &str,
+    index: i64,
+    len: usize,
+) -> Result<u32, ParseError> {
+    let resolved = if index < 0 {
+        len as i64 + index
+    } else {
+        index - 1
+    };
+
+    if resolved < 0 || resolved >= len as i64 {
+        let kind = ParseErrorKind::IndexOutOfBounds { index, len };
+        Err(ParseError::new(l, column(line, word), kind))
+    } else {
+        Ok(resolved as u32)
+    }
 }
 
 fn parse_f<'a>(
     l: usize,
+    line: &str,
     ws: Words<'a>,
     coords: &[Point3<f32>],
     uvs: &[Point2<f32>],
     normals: &[Vector3<f32>],
     ignore_uvs: &mut bool,
     ignore_normals: &mut bool,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
     curr_group: usize,
-) {
+) -> Result<(), ParseError> {
     // Four formats possible: v   v/t   v//n   v/t/n
     let mut i = 0;
     for word in ws {
-        let mut curr_ids: Vector3<i32> = Bounded::max_value();
+        let mut curr_ids: [Option<i64>; 3] = [None; 3];
 
         for (i, w) in word.split('/').enumerate() {
-            if i == 0 || w.len() != 0 {
-                let idx: Result<i32, _> = FromStr::from_str(w);
-                match idx {
-                    Ok(id) => curr_ids[i] = id - 1,
-                    Err(e) => error(l, &format!("failed to parse `{}' as a i32: {}", w, e)[..]),
-                }
+            if i > 2 {
+                let kind = ParseErrorKind::InvalidFaceVertex(word.to_string());
+                return Err(ParseError::new(l, column(line, word), kind));
             }
-        }
 
-        if i > 2 {
-            // on the fly triangulation as trangle fan
-            let g = &mut groups_ids[curr_group];
-            let p1 = (*g)[g.len() - i];
-            let p2 = (*g)[g.len() - 1];
-            g.push(p1);
-            g.push(p2);
+            if i == 0 || w.len() != 0 {
+                let idx = i64::from_str(w).map_err(|_| {
+                    let kind = ParseErrorKind::InvalidNumber(w.to_string());
+                    ParseError::new(l, column(line, w), kind)
+                })?;
+                curr_ids[i] = Some(idx);
+            }
         }
 
-        if curr_ids.y == i32::max_value() as i32 {
+        if curr_ids[1].is_none() {
             *ignore_uvs = true;
         }
 
-        if curr_ids.z == i32::max_value() as i32 {
+        if curr_ids[2].is_none() {
             *ignore_normals = true;
         }
 
-        // Handle relatives indice
-        let x;
-        let y;
-        let z;
-
-        if curr_ids.x < 0 {
-            x = coords.len() as i32 + curr_ids.x + 1;
-        } else {
-            x = curr_ids.x;
-        }
+        // Indices of the elements that are dropped are not checked, they are not used. The
+        // `u32::MAX` placeholders are never read either.
+        let x = resolve_index(l, line, word, curr_ids[0].unwrap(), coords.len())?;
+        let y = match curr_ids[1] {
+            Some(id) if !*ignore_uvs => resolve_index(l, line, word, id, uvs.len())?,
+            _ => u32::MAX,
+        };
+        let z = match curr_ids[2] {
+            Some(id) if !*ignore_normals => resolve_index(l, line, word, id, normals.len())?,
+            _ => u32::MAX,
+        };
 
-        if curr_ids.y < 0 {
-            y = uvs.len() as i32 + curr_ids.y + 1;
-        } else {
-            y = curr_ids.y;
-        }
-
-        if curr_ids.z < 0 {
-            z = normals.len() as i32 + curr_ids.z + 1;
-        } else {
-            z = curr_ids.z;
+        if i > 2 {
+            // on the fly triangulation as trangle fan
+            let g = &mut groups_ids[curr_group];
+            let p1 = (*g)[g.len() - i];
+            let p2 = (*g)[g.len() - 1];
+            g.push(p1);
+            g.push(p2);
         }
 
-        assert!(x >= 0 && y >= 0 && z >= 0);
-        groups_ids[curr_group].push(Point3::new(x as u16, y as u16, z as u16));
+        groups_ids[curr_group].push(Point3::new(x, y, z));
 
         i = i + 1;
     }
 
+    if i == 0 {
+        let kind = ParseErrorKind::MissingComponents {
+            expected: 3,
+            found: 0,
+        };
+        return Err(ParseError::new(l, end_column(line), kind));
+    }
+
     // there is not enough vertex to form a triangle. Complete it.
-    if i < 2 {
+    if i < 3 {
         for _ in 0usize..3 - i {
             let last = (*groups_ids)[curr_group].last().unwrap().clone();
             groups_ids[curr_group].push(last);
         }
     }
+
+    Ok(())
 }
 
-fn parse_vt(l: usize, mut ws: Words) -> UV {
-    let _0 = "0.0";
-    let sx = ws
-        .next()
-        .unwrap_or_else(|| error(l, "at least 2 components were expected, found 0."));
-    let sy = ws
-        .next()
-        .unwrap_or_else(|| error(l, "at least 2 components were expected, found 1."));
-    // let sz  = ws.next().unwrap_or(_0);
-
-    let x: Result<f32, _> = FromStr::from_str(sx);
-    let y: Result<f32, _> = FromStr::from_str(sy);
-    // let z: Option<f32> = FromStr::from_str(sz);
-
-    let x =
-        x.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sx, e)[..]));
-    let y =
-        y.unwrap_or_else(|e| error(l, &format!("failed to parse `{}' as a f32: {}", sy, e)[..]));
-    // let z = z.unwrap_or_else(|| error(l, "failed to parse `" + sz + "' as a f32."));
-
-    Point2::new(x, y)
+fn parse_vt(l: usize, line: &str, mut ws: Words) -> Result<UV, ParseError> {
+    // The optional third component is ignored.
+    let mut xy = [0.0; 2];
+    parse_floats(l, line, &mut ws, &mut xy)?;
+    Ok(Point2::new(xy[0], xy[1]))
 }
 
 fn parse_g<'a>(
@@ -377,7 +418,7 @@ fn parse_g<'a>(
     ws: Words<'a>,
     prefix: &str,
     groups: &mut HashMap<String, usize>,
-    groups_ids: &mut Vec<Vec<Point3<u16>>>,
+    groups_ids: &mut Vec<Vec<Point3<u32>>>,
 ) -> usize {
     let suffix: Vec<&'a str> = ws.collect();
     let suffix = suffix.join(" ");
@@ -402,17 +443,17 @@ fn reformat(
     coords: Vec<Coord>,
     normals: Option<Vec<Normal>>,
     uvs: Option<Vec<UV>>,
-    groups_ids: Vec<Vec<Point3<u16>>>,
+    groups_ids: Vec<Vec<Point3<u32>>>,
     groups: HashMap<String, usize>,
     group2mtl: HashMap<usize, MtlMaterial>,
 ) -> Vec<(String, Mesh, Option<MtlMaterial>)> {
-    let mut vt2id: HashMap<Point3<u16>, u16> = HashMap::new();
-    let mut vertex_ids: Vec<u16> = Vec::new();
+    let mut vt2id: HashMap<Point3<u32>, u32> = HashMap::new();
+    let mut vertex_ids: Vec<u32> = Vec::new();
     let mut resc: Vec<Coord> = Vec::new();
     let mut resn: Option<Vec<Normal>> = normals.as_ref().map(|_| Vec::new());
     let mut resu: Option<Vec<UV>> = uvs.as_ref().map(|_| Vec::new());
-    let mut resfs: Vec<Vec<Point3<u16>>> = Vec::new();
-    let mut allfs: Vec<Point3<u16>> = Vec::new();
+    let mut resfs: Vec<Vec<Point3<u32>>> = Vec::new();
+    let mut allfs: Vec<Point3<u32>> = Vec::new();
     let mut names: Vec<String> = Vec::new();
     let mut mtls: Vec<Option<MtlMaterial>> = Vec::new();
 
@@ -427,7 +468,7 @@ fn reformat(
                     None
                 }
                 None => {
-                    let idx = resc.len() as u16;
+                    let idx = resc.len() as u32;
 
                     resc.push(coords[point.x as usize]);
 
@@ -460,6 +501,9 @@ fn reformat(
         vertex_ids.clear();
     }
 
+    // Only fall back to 32-bit indices when 16 bits are not enough
+    let use_u32 = resc.len() > u16::max_value() as usize + 1;
+
     let resn = resn.unwrap_or_else(|| Mesh::compute_normals_array(&resc[..], &allfs[..]));
     let resn = Arc::new(RwLock::new(GPUVec::new(
         resn,
@@ -485,11 +529,25 @@ fn reformat(
         .zip(mtls.into_iter())
     {
         if fs.len() != 0 {
-            let fs = Arc::new(RwLock::new(GPUVec::new(
-                fs,
-                BufferType::ElementArray,
-                AllocationType::StaticDraw,
-            )));
+            let fs: MeshFaces = if use_u32 {
+                Arc::new(RwLock::new(GPUVec::new(
+                    fs,
+                    BufferType::ElementArray,
+                    AllocationType::StaticDraw,
+                )))
+                .into()
+            } else {
+                let fs = fs
+                    .into_iter()
+                    .map(|f| Point3::new(f.x as u16, f.y as u16, f.z as u16))
+                    .collect();
+                Arc::new(RwLock::new(GPUVec::new(
+                    fs,
+                    BufferType::ElementArray,
+                    AllocationType::StaticDraw,
+                )))
+                .into()
+            };
             let mesh = Mesh::new_with_gpu_vectors(resc.clone(), fs, resn.clone(), resu.clone());
             meshes.push((name, mesh, mtl))
         }
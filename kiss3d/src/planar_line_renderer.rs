@@ -5,8 +5,8 @@ use na::{Matrix3, Point2, Point3};
 use crate::planar_camera::PlanarCamera;
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
 
-#[path = "error.rs"]
-mod error;
+#[path = "gl_error.rs"]
+mod gl_error;
 
 /// Structure which manages the display of short-living lines.
 pub struct PlanarLineRenderer {
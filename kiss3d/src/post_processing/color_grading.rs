@@ -0,0 +1,200 @@
+//! Post-processing effect adjusting the colors of the screen.
+
+use image::{ImageBuffer, Pixel, Rgb, RgbImage};
+use std::rc::Rc;
+
+use crate::context::{Context, Texture};
+use crate::post_processing::fullscreen_pass::{self, FullscreenPass};
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{RenderTarget, ShaderUniform};
+
+#[path = "../gl_error.rs"]
+mod gl_error;
+
+/// Post processing effect which adjusts the exposure, contrast and saturation of the screen, then
+/// optionally remaps its colors with a lookup table.
+///
+/// A lookup table of size `n` is an image of `n` squares of `n * n` pixels laid out from left to
+/// right. Red increases from left to right inside each square, green from top to bottom and blue
+/// from the first square to the last. `ColorGrading::identity_lut` creates the table leaving
+/// colors unchanged, which can be edited in any image editor.
+pub struct ColorGrading {
+    pass: FullscreenPass,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    lut: Option<(Rc<Texture>, usize)>,
+    lut_intensity: f32,
+    gl_exposure: ShaderUniform<f32>,
+    gl_contrast: ShaderUniform<f32>,
+    gl_saturation: ShaderUniform<f32>,
+    gl_lut: ShaderUniform<i32>,
+    gl_lut_size: ShaderUniform<f32>,
+    gl_lut_intensity: ShaderUniform<f32>,
+}
+
+impl ColorGrading {
+    /// Creates a new `ColorGrading` post processing effect leaving colors unchanged.
+    pub fn new() -> ColorGrading {
+        let mut pass = FullscreenPass::new(COLOR_GRADING_FRAGMENT_SRC);
+
+        ColorGrading {
+            exposure: 1.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            lut: None,
+            lut_intensity: 1.0,
+            gl_exposure: pass.uniform("exposure"),
+            gl_contrast: pass.uniform("contrast"),
+            gl_saturation: pass.uniform("saturation"),
+            gl_lut: pass.uniform("lut"),
+            gl_lut_size: pass.uniform("lut_size"),
+            gl_lut_intensity: pass.uniform("lut_intensity"),
+            pass,
+        }
+    }
+
+    /// Sets the factor every color is multiplied by. Defaults to 1.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    /// Sets how much colors are pushed away from mid-grey. Defaults to 1, 0 turns everything grey.
+    pub fn set_contrast(&mut self, contrast: f32) {
+        self.contrast = contrast;
+    }
+
+    /// Sets how much colors are pushed away from their luminance. Defaults to 1, 0 turns
+    /// everything in grey levels.
+    pub fn set_saturation(&mut self, saturation: f32) {
+        self.saturation = saturation;
+    }
+
+    /// Sets the lookup table remapping the colors, or removes it.
+    ///
+    /// # Panics
+    /// If the width of the image is not the square of its height.
+    pub fn set_lut(&mut self, lut: Option<&RgbImage>) {
+        self.lut = lut.map(|image| {
+            let size = image.height() as usize;
+            assert!(
+                size > 1 && image.width() as usize == size * size,
+                "A lookup table of size n must be an image of n * n by n pixels."
+            );
+            (lut_texture(image), size)
+        });
+    }
+
+    /// Sets how much the lookup table changes the colors, between 0 and 1. Defaults to 1.
+    pub fn set_lut_intensity(&mut self, intensity: f32) {
+        self.lut_intensity = intensity;
+    }
+
+    /// The lookup table of size `size` leaving colors unchanged.
+    pub fn identity_lut(size: u32) -> RgbImage {
+        let max = (size.max(2) - 1) as f32;
+        let level = |i: u32| (i as f32 / max * 255.0).round() as u8;
+
+        ImageBuffer::from_fn(size * size, size, |x, y| {
+            Rgb([level(x % size), level(y), level(x / size)])
+        })
+    }
+}
+
+impl PostProcessingEffect for ColorGrading {
+    fn update(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn draw(&mut self, target: &RenderTarget) {
+        self.pass.begin(target);
+        self.gl_exposure.upload(&self.exposure);
+        self.gl_contrast.upload(&self.contrast);
+        self.gl_saturation.upload(&self.saturation);
+
+        let (lut, size) = match &self.lut {
+            Some((texture, size)) => (Some(&**texture), *size as f32),
+            None => (None, 0.0),
+        };
+        fullscreen_pass::bind_texture(1, lut);
+        self.gl_lut.upload(&1);
+        self.gl_lut_size.upload(&size);
+        self.gl_lut_intensity.upload(&self.lut_intensity);
+
+        self.pass.draw();
+        fullscreen_pass::bind_texture(1, None);
+        verify!(Context::get().active_texture(Context::TEXTURE0));
+    }
+}
+
+// Uploads a lookup table, interpolated linearly between its entries.
+fn lut_texture(image: &RgbImage) -> Rc<Texture> {
+    let ctxt = Context::get();
+    let texture = Texture::new();
+    // RGBA rows are always aligned, whatever the size of the table.
+    let pixels: Vec<u8> = image.pixels().flat_map(|p| p.to_rgba().0.to_vec()).collect();
+
+    fullscreen_pass::bind_texture(1, Some(&*texture));
+    verify!(ctxt.tex_image2d(
+        Context::TEXTURE_2D,
+        0,
+        Context::RGBA as i32,
+        image.width() as i32,
+        image.height() as i32,
+        0,
+        Context::RGBA,
+        Some(&pixels[..])
+    ));
+    for &(parameter, value) in &[
+        (Context::TEXTURE_WRAP_S, Context::CLAMP_TO_EDGE),
+        (Context::TEXTURE_WRAP_T, Context::CLAMP_TO_EDGE),
+        (Context::TEXTURE_MIN_FILTER, Context::LINEAR),
+        (Context::TEXTURE_MAG_FILTER, Context::LINEAR),
+    ] {
+        verify!(ctxt.tex_parameteri(Context::TEXTURE_2D, parameter, value as i32));
+    }
+    fullscreen_pass::bind_texture(1, None);
+    verify!(ctxt.active_texture(Context::TEXTURE0));
+
+    texture
+}
+
+pub(crate) static COLOR_GRADING_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform sampler2D lut;
+    uniform float     exposure;
+    uniform float     contrast;
+    uniform float     saturation;
+    uniform float     lut_size;
+    uniform float     lut_intensity;
+    varying vec2      f_texcoord;
+
+    vec3 lookup(vec3 color) {
+      float last  = lut_size - 1.0;
+      float blue  = color.b * last;
+      float tile0 = floor(blue);
+      float tile1 = min(tile0 + 1.0, last);
+      vec2 uv     = vec2((color.r * last + 0.5) / (lut_size * lut_size),
+                         (color.g * last + 0.5) / lut_size);
+      vec3 color0 = texture2D(lut, uv + vec2(tile0 / lut_size, 0.0)).rgb;
+      vec3 color1 = texture2D(lut, uv + vec2(tile1 / lut_size, 0.0)).rgb;
+      return mix(color0, color1, blue - tile0);
+    }
+
+    void main(void) {
+      vec4 scene  = texture2D(fbo_texture, f_texcoord);
+      vec3 color  = scene.rgb * exposure;
+      color       = (color - 0.5) * contrast + 0.5;
+      float luma  = dot(color, vec3(0.2126, 0.7152, 0.0722));
+      color       = clamp(mix(vec3(luma), color, saturation), 0.0, 1.0);
+
+      if (lut_size > 1.0) {
+        color = mix(color, lookup(color), lut_intensity);
+      }
+
+      gl_FragColor = vec4(color, scene.a);
+    }";
@@ -0,0 +1,130 @@
+//! Post-processing effect fading distant objects into a color.
+
+use na::Vector3;
+
+use crate::context::Context;
+use crate::post_processing::fullscreen_pass::{self, FullscreenPass};
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{RenderTarget, ShaderUniform};
+
+#[path = "../gl_error.rs"]
+mod gl_error;
+
+/// Post processing effect which fades objects into a color as they get away from the camera.
+///
+/// The fog is computed from the depth buffer of the scene, so the background is left untouched.
+pub struct Fog {
+    pass: FullscreenPass,
+    color: Vector3<f32>,
+    start: f32,
+    end: f32,
+    density: f32,
+    znear: f32,
+    zfar: f32,
+    gl_fbo_depth: ShaderUniform<i32>,
+    gl_color: ShaderUniform<Vector3<f32>>,
+    gl_start: ShaderUniform<f32>,
+    gl_end: ShaderUniform<f32>,
+    gl_density: ShaderUniform<f32>,
+    gl_znear: ShaderUniform<f32>,
+    gl_zfar: ShaderUniform<f32>,
+}
+
+impl Fog {
+    /// Creates a new `Fog` post processing effect.
+    ///
+    /// # Arguments:
+    /// * `color` - the color of the fog.
+    /// * `start` - the distance to the camera where the fog starts.
+    /// * `end` - the distance to the camera where the fog hides everything.
+    pub fn new(color: Vector3<f32>, start: f32, end: f32) -> Fog {
+        let mut pass = FullscreenPass::new(FOG_FRAGMENT_SRC);
+
+        Fog {
+            color,
+            start,
+            end,
+            density: 1.0,
+            znear: 0.0,
+            zfar: 0.0,
+            gl_fbo_depth: pass.uniform("fbo_depth"),
+            gl_color: pass.uniform("color"),
+            gl_start: pass.uniform("start"),
+            gl_end: pass.uniform("end"),
+            gl_density: pass.uniform("density"),
+            gl_znear: pass.uniform("znear"),
+            gl_zfar: pass.uniform("zfar"),
+            pass,
+        }
+    }
+
+    /// Sets the color of the fog.
+    pub fn set_color(&mut self, color: Vector3<f32>) {
+        self.color = color;
+    }
+
+    /// Sets the distances to the camera where the fog starts and where it hides everything.
+    pub fn set_range(&mut self, start: f32, end: f32) {
+        self.start = start;
+        self.end = end;
+    }
+
+    /// Sets the opacity of the fog beyond the end of its range, between 0 and 1. Defaults to 1.
+    pub fn set_density(&mut self, density: f32) {
+        self.density = density;
+    }
+}
+
+impl PostProcessingEffect for Fog {
+    fn update(&mut self, _: f32, _: f32, _: f32, znear: f32, zfar: f32) {
+        self.znear = znear;
+        self.zfar = zfar;
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        self.pass.begin(target);
+        self.gl_color.upload(&self.color);
+        self.gl_start.upload(&self.start);
+        self.gl_end.upload(&self.end);
+        self.gl_density.upload(&self.density);
+        self.gl_znear.upload(&self.znear);
+        self.gl_zfar.upload(&self.zfar);
+
+        fullscreen_pass::bind_texture(1, target.depth_id().and_then(|id| id.as_ref().left()));
+        self.gl_fbo_depth.upload(&1);
+
+        self.pass.draw();
+        fullscreen_pass::bind_texture(1, None);
+        verify!(Context::get().active_texture(Context::TEXTURE0));
+    }
+}
+
+pub(crate) static FOG_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform sampler2D fbo_depth;
+    uniform vec3      color;
+    uniform float     start;
+    uniform float     end;
+    uniform float     density;
+    uniform float     znear;
+    uniform float     zfar;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec4 scene  = texture2D(fbo_texture, f_texcoord);
+      float depth = texture2D(fbo_depth, f_texcoord).x;
+
+      if (depth >= 1.0) {
+        gl_FragColor = scene;
+      } else {
+        float dist   = znear * zfar / (zfar - depth * (zfar - znear));
+        float amount = clamp((dist - start) / (end - start), 0.0, 1.0) * density;
+        gl_FragColor = vec4(mix(scene.rgb, color, amount), scene.a);
+      }
+    }";
@@ -0,0 +1,96 @@
+//! A shader drawn once over the whole render target, shared by post-processing effects.
+
+use na::Vector2;
+
+use crate::context::{Context, Texture};
+use crate::resource::{
+    AllocationType, BufferType, Effect, GPUVec, GLPrimitive, RenderTarget, ShaderAttribute,
+    ShaderUniform,
+};
+
+#[path = "../gl_error.rs"]
+mod gl_error;
+
+/// A fragment shader run for every pixel of the render target, reading the texture of the
+/// previous render target on the texture unit 0.
+pub(crate) struct FullscreenPass {
+    shader: Effect,
+    fbo_texture: ShaderUniform<i32>,
+    v_coord: ShaderAttribute<Vector2<f32>>,
+    fbo_vertices: GPUVec<Vector2<f32>>,
+}
+
+impl FullscreenPass {
+    /// Creates a pass running `fragment_shader`, which must declare the `fbo_texture` sampler
+    /// and the `f_texcoord` varying of `POST_PROCESSING_VERTEX_SRC`.
+    pub fn new(fragment_shader: &str) -> FullscreenPass {
+        let fbo_vertices: Vec<Vector2<f32>> = vec![
+            Vector2::new(-1.0, -1.0),
+            Vector2::new(1.0, -1.0),
+            Vector2::new(-1.0, 1.0),
+            Vector2::new(1.0, 1.0),
+        ];
+
+        let mut fbo_vertices =
+            GPUVec::new(fbo_vertices, BufferType::Array, AllocationType::StaticDraw);
+        fbo_vertices.load_to_gpu();
+        fbo_vertices.unload_from_ram();
+
+        let mut shader = Effect::new_from_str(POST_PROCESSING_VERTEX_SRC, fragment_shader);
+        shader.use_program();
+
+        FullscreenPass {
+            fbo_texture: shader.get_uniform("fbo_texture").unwrap(),
+            v_coord: shader.get_attrib("v_coord").unwrap(),
+            fbo_vertices,
+            shader,
+        }
+    }
+
+    /// The uniform `name` of the fragment shader.
+    pub fn uniform<T: GLPrimitive>(&mut self, name: &str) -> ShaderUniform<T> {
+        self.shader.get_uniform(name).unwrap()
+    }
+
+    /// Clears the selected render target and makes the shader current, reading the texture of
+    /// `target`.
+    ///
+    /// The uniforms of the shader must be uploaded between this and `draw`.
+    pub fn begin(&mut self, target: &RenderTarget) {
+        let ctxt = Context::get();
+
+        self.shader.use_program();
+        verify!(ctxt.clear_color(0.0, 0.0, 0.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT | Context::DEPTH_BUFFER_BIT));
+        bind_texture(0, target.texture_id());
+        self.fbo_texture.upload(&0);
+    }
+
+    /// Draws the quad covering the selected render target.
+    pub fn draw(&mut self) {
+        let ctxt = Context::get();
+
+        self.v_coord.enable();
+        self.v_coord.bind(&mut self.fbo_vertices);
+        verify!(ctxt.draw_arrays(Context::TRIANGLE_STRIP, 0, 4));
+        self.v_coord.disable();
+        bind_texture(0, None);
+    }
+}
+
+/// Binds `texture` to the texture unit `unit`, leaving `unit` active.
+pub(crate) fn bind_texture(unit: u32, texture: Option<&Texture>) {
+    let ctxt = Context::get();
+    verify!(ctxt.active_texture(Context::TEXTURE0 + unit));
+    verify!(ctxt.bind_texture(Context::TEXTURE_2D, texture));
+}
+
+/// The vertex shader of every `FullscreenPass`.
+pub(crate) static POST_PROCESSING_VERTEX_SRC: &str = "#version 100
+    attribute vec2 v_coord;
+    varying vec2   f_texcoord;
+
+    void main(void) {
+      gl_Position = vec4(v_coord, 0.0, 1.0);
+      f_texcoord  = (v_coord + 1.0) / 2.0;
+    }";
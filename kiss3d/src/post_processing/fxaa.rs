@@ -0,0 +1,102 @@
+//! Post-processing effect smoothing the aliased edges of the scene.
+
+use na::Vector2;
+
+use crate::post_processing::fullscreen_pass::FullscreenPass;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{RenderTarget, ShaderUniform};
+
+/// Post processing effect which smoothes jagged edges with fast approximate anti-aliasing
+/// (FXAA).
+///
+/// It is best placed after the effects changing colors, and before the ones drawing text or
+/// sharp patterns.
+pub struct Fxaa {
+    pass: FullscreenPass,
+    texel_size: Vector2<f32>,
+    span_max: f32,
+    gl_texel_size: ShaderUniform<Vector2<f32>>,
+    gl_span_max: ShaderUniform<f32>,
+}
+
+impl Fxaa {
+    /// Creates a new `Fxaa` post processing effect.
+    pub fn new() -> Fxaa {
+        let mut pass = FullscreenPass::new(FXAA_FRAGMENT_SRC);
+
+        Fxaa {
+            texel_size: Vector2::zeros(),
+            span_max: 8.0,
+            gl_texel_size: pass.uniform("texel_size"),
+            gl_span_max: pass.uniform("span_max"),
+            pass,
+        }
+    }
+
+    /// Sets the maximum length, in pixels, of the edges smoothed. Defaults to 8.
+    pub fn set_span_max(&mut self, span_max: f32) {
+        self.span_max = span_max;
+    }
+}
+
+impl PostProcessingEffect for Fxaa {
+    fn update(&mut self, _: f32, w: f32, h: f32, _: f32, _: f32) {
+        self.texel_size = Vector2::new(1.0 / w, 1.0 / h);
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        self.pass.begin(target);
+        self.gl_texel_size.upload(&self.texel_size);
+        self.gl_span_max.upload(&self.span_max);
+        self.pass.draw();
+    }
+}
+
+pub(crate) static FXAA_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform vec2      texel_size;
+    uniform float     span_max;
+    varying vec2      f_texcoord;
+
+    const float REDUCE_MIN = 1.0 / 128.0;
+    const float REDUCE_MUL = 1.0 / 8.0;
+    const vec3  LUMA       = vec3(0.299, 0.587, 0.114);
+
+    vec3 fetch(vec2 offset) {
+      return texture2D(fbo_texture, f_texcoord + offset * texel_size).rgb;
+    }
+
+    void main(void) {
+      float luma_nw = dot(fetch(vec2(-1.0, -1.0)), LUMA);
+      float luma_ne = dot(fetch(vec2(1.0, -1.0)), LUMA);
+      float luma_sw = dot(fetch(vec2(-1.0, 1.0)), LUMA);
+      float luma_se = dot(fetch(vec2(1.0, 1.0)), LUMA);
+      vec4 center   = texture2D(fbo_texture, f_texcoord);
+      float luma_m  = dot(center.rgb, LUMA);
+
+      float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+      float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+      // The direction along the edge, in pixels.
+      vec2 dir = vec2(-((luma_nw + luma_ne) - (luma_sw + luma_se)),
+                      (luma_nw + luma_sw) - (luma_ne + luma_se));
+      float reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+      float scale  = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+      dir          = clamp(dir * scale, -span_max, span_max);
+
+      vec3 color_a = 0.5 * (fetch(dir * (1.0 / 3.0 - 0.5)) + fetch(dir * (2.0 / 3.0 - 0.5)));
+      vec3 color_b = color_a * 0.5 + 0.25 * (fetch(dir * -0.5) + fetch(dir * 0.5));
+      float luma_b = dot(color_b, LUMA);
+
+      if (luma_b < luma_min || luma_b > luma_max) {
+        gl_FragColor = vec4(color_a, center.a);
+      } else {
+        gl_FragColor = vec4(color_b, center.a);
+      }
+    }";
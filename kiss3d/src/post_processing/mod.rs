@@ -1,15 +1,41 @@
 //! Post-processing effects.
 
+pub use crate::post_processing::color_grading::ColorGrading;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
+pub use crate::post_processing::fog::Fog;
+pub use crate::post_processing::fxaa::Fxaa;
 pub use crate::post_processing::grayscales::Grayscales;
 pub use crate::post_processing::oculus_stereo::OculusStereo;
+pub use crate::post_processing::post_processing_chain::PostProcessingChain;
 pub use crate::post_processing::post_processing_effect::PostProcessingEffect;
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 pub use crate::post_processing::sobel_edge_highlight::SobelEdgeHighlight;
+pub use crate::post_processing::vignette::Vignette;
 pub use crate::post_processing::waves::Waves;
 
+#[cfg(feature = "headless")]
+pub(crate) use crate::post_processing::color_grading::COLOR_GRADING_FRAGMENT_SRC;
+#[cfg(all(feature = "headless", not(any(target_arch = "wasm32", target_arch = "asmjs"))))]
+pub(crate) use crate::post_processing::fog::FOG_FRAGMENT_SRC;
+#[cfg(feature = "headless")]
+pub(crate) use crate::post_processing::fullscreen_pass::POST_PROCESSING_VERTEX_SRC;
+#[cfg(feature = "headless")]
+pub(crate) use crate::post_processing::fxaa::FXAA_FRAGMENT_SRC;
+#[cfg(feature = "headless")]
+pub(crate) use crate::post_processing::post_processing_chain::COPY_FRAGMENT_SRC;
+#[cfg(feature = "headless")]
+pub(crate) use crate::post_processing::vignette::VIGNETTE_FRAGMENT_SRC;
+
+mod color_grading;
+#[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
+mod fog;
+mod fullscreen_pass;
+mod fxaa;
 mod grayscales;
 mod oculus_stereo;
+mod post_processing_chain;
 pub mod post_processing_effect;
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 mod sobel_edge_highlight;
+mod vignette;
 mod waves;
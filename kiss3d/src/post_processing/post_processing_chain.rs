@@ -0,0 +1,239 @@
+//! A sequence of post-processing effects applied one after the other.
+
+use std::any::Any;
+
+use crate::post_processing::fullscreen_pass::FullscreenPass;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{FramebufferManager, RenderTarget};
+
+/// A post-processing effect which is an ordered list of named post-processing effects.
+///
+/// Each enabled effect draws the output of the previous one, the first reading the rendered
+/// scene and the last drawing to the screen. Every effect reads the depth buffer of the scene,
+/// whatever its place in the chain. The intermediate images are drawn to two off-screen buffers
+/// the chain allocates and resizes itself.
+///
+/// Effects are found by name. If several effects have the same name, the first one is used.
+pub struct PostProcessingChain {
+    stages: Vec<Stage>,
+    framebuffer_manager: FramebufferManager,
+    // The off-screen buffers drawn to in turns, and their size.
+    buffers: Option<([RenderTarget; 2], (usize, usize))>,
+    size: (usize, usize),
+    copy: FullscreenPass,
+}
+
+struct Stage {
+    name: String,
+    enabled: bool,
+    effect: Box<dyn AnyEffect>,
+}
+
+// A post-processing effect which can be downcasted to its concrete type.
+trait AnyEffect: PostProcessingEffect {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<E: PostProcessingEffect + 'static> AnyEffect for E {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl PostProcessingChain {
+    /// Creates a chain without any effect, which draws the scene unchanged.
+    pub fn new() -> PostProcessingChain {
+        PostProcessingChain {
+            stages: Vec::new(),
+            framebuffer_manager: FramebufferManager::new(),
+            buffers: None,
+            size: (0, 0),
+            copy: FullscreenPass::new(COPY_FRAGMENT_SRC),
+        }
+    }
+
+    /// Appends an enabled effect to this chain and returns it.
+    pub fn with<E: PostProcessingEffect + 'static>(mut self, name: &str, effect: E) -> Self {
+        self.push(name, effect);
+        self
+    }
+
+    /// Appends an enabled effect to this chain.
+    pub fn push<E: PostProcessingEffect + 'static>(&mut self, name: &str, effect: E) {
+        let index = self.stages.len();
+        self.insert(index, name, effect)
+    }
+
+    /// Inserts an enabled effect at position `index` of this chain.
+    ///
+    /// # Panics
+    /// If `index` is greater than the number of effects of this chain.
+    pub fn insert<E>(&mut self, index: usize, name: &str, effect: E)
+    where
+        E: PostProcessingEffect + 'static,
+    {
+        self.stages.insert(
+            index,
+            Stage {
+                name: name.to_string(),
+                enabled: true,
+                effect: Box::new(effect),
+            },
+        )
+    }
+
+    /// Removes the effect named `name` from this chain.
+    ///
+    /// Returns `false` if there is no such effect.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                let _ = self.stages.remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Enables or disables the effect named `name`. Disabled effects are skipped.
+    ///
+    /// Returns `false` if there is no such effect.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.stage_mut(name) {
+            Some(stage) => {
+                stage.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether the effect named `name` exists and is enabled.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.stages
+            .iter()
+            .find(|s| s.name == name)
+            .is_some_and(|s| s.enabled)
+    }
+
+    /// The effect named `name`, if it has the type `E`.
+    pub fn effect<E: PostProcessingEffect + 'static>(&self, name: &str) -> Option<&E> {
+        let index = self.position(name)?;
+        self.stages[index].effect.as_any().downcast_ref()
+    }
+
+    /// The effect named `name`, if it has the type `E`, to change its parameters.
+    pub fn effect_mut<E: PostProcessingEffect + 'static>(&mut self, name: &str) -> Option<&mut E> {
+        self.stage_mut(name)?.effect.as_any_mut().downcast_mut()
+    }
+
+    /// The names of the effects of this chain, in the order they are applied.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|s| &s.name[..])
+    }
+
+    /// The number of effects of this chain, enabled or not.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Whether this chain has no effect.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.stages.iter().position(|s| s.name == name)
+    }
+
+    fn stage_mut(&mut self, name: &str) -> Option<&mut Stage> {
+        self.stages.iter_mut().find(|s| s.name == name)
+    }
+
+    // Allocates the two off-screen buffers, or resizes them to the size of the screen.
+    fn update_buffers(&mut self) {
+        let size = self.size;
+        match &mut self.buffers {
+            Some((buffers, buffers_size)) => {
+                if *buffers_size != size {
+                    for buffer in buffers.iter_mut() {
+                        buffer.resize(size.0 as f32, size.1 as f32);
+                    }
+                    *buffers_size = size;
+                }
+            }
+            None => {
+                let buffer = || FramebufferManager::new_render_target(size.0, size.1, false);
+                self.buffers = Some(([buffer(), buffer()], size));
+            }
+        }
+    }
+}
+
+impl PostProcessingEffect for PostProcessingChain {
+    fn update(&mut self, dt: f32, w: f32, h: f32, znear: f32, zfar: f32) {
+        self.size = (w as usize, h as usize);
+
+        for stage in self.stages.iter_mut().filter(|s| s.enabled) {
+            stage.effect.update(dt, w, h, znear, zfar);
+        }
+    }
+
+    fn draw(&mut self, target: &RenderTarget) {
+        let enabled: Vec<usize> = (0..self.stages.len())
+            .filter(|i| self.stages[*i].enabled)
+            .collect();
+
+        if enabled.is_empty() {
+            self.copy.begin(target);
+            self.copy.draw();
+            return;
+        }
+
+        if enabled.len() > 1 {
+            self.update_buffers();
+        }
+
+        let screen = FramebufferManager::screen();
+        let buffers = self.buffers.as_ref().map(|b| &b.0);
+
+        for (k, &i) in enabled.iter().enumerate() {
+            let output = if k + 1 == enabled.len() {
+                &screen
+            } else {
+                &buffers.unwrap()[k % 2]
+            };
+
+            // The output of the previous effect, with the depth of the scene.
+            let previous;
+            let input = if k == 0 {
+                target
+            } else {
+                previous = buffers.unwrap()[(k - 1) % 2].with_depth_of(target);
+                &previous
+            };
+
+            self.framebuffer_manager.select(output);
+            self.stages[i].effect.draw(input);
+        }
+    }
+}
+
+pub(crate) static COPY_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      gl_FragColor = texture2D(fbo_texture, f_texcoord);
+    }";
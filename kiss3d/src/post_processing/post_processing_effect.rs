@@ -4,8 +4,8 @@ use crate::resource::RenderTarget;
 
 /// Trait of post processing effect.
 ///
-/// One post-processing effect can be used at a time. It is executed once the scene has been
-/// rendered on a texture.
+/// A post-processing effect is executed once the scene has been rendered on a texture. Several
+/// effects can be applied one after the other with a `PostProcessingChain`.
 pub trait PostProcessingEffect {
     /// Updates the post processing effect.
     fn update(&mut self, dt: f32, w: f32, h: f32, znear: f32, zfar: f32);
@@ -8,8 +8,8 @@ use crate::resource::{
     AllocationType, BufferType, Effect, GPUVec, RenderTarget, ShaderAttribute, ShaderUniform,
 };
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Post processing effect which draws detected edges on top of the original buffer.
 pub struct SobelEdgeHighlight {
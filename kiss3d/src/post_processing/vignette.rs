@@ -0,0 +1,95 @@
+//! Post-processing effect darkening the borders of the screen.
+
+use na::Vector3;
+
+use crate::post_processing::fullscreen_pass::FullscreenPass;
+use crate::post_processing::PostProcessingEffect;
+use crate::resource::{RenderTarget, ShaderUniform};
+
+/// Post processing effect which fades the borders of the screen to a color.
+pub struct Vignette {
+    pass: FullscreenPass,
+    color: Vector3<f32>,
+    intensity: f32,
+    radius: f32,
+    softness: f32,
+    gl_color: ShaderUniform<Vector3<f32>>,
+    gl_intensity: ShaderUniform<f32>,
+    gl_radius: ShaderUniform<f32>,
+    gl_softness: ShaderUniform<f32>,
+}
+
+impl Vignette {
+    /// Creates a new black `Vignette` post processing effect.
+    ///
+    /// # Arguments:
+    /// * `intensity` - how much of the color covers the corners of the screen, between 0 and 1.
+    pub fn new(intensity: f32) -> Vignette {
+        let mut pass = FullscreenPass::new(VIGNETTE_FRAGMENT_SRC);
+
+        Vignette {
+            color: Vector3::zeros(),
+            intensity,
+            radius: 0.75,
+            softness: 0.6,
+            gl_color: pass.uniform("color"),
+            gl_intensity: pass.uniform("intensity"),
+            gl_radius: pass.uniform("radius"),
+            gl_softness: pass.uniform("softness"),
+            pass,
+        }
+    }
+
+    /// Sets the color the borders of the screen fade to.
+    pub fn set_color(&mut self, r: f32, g: f32, b: f32) {
+        self.color = Vector3::new(r, g, b);
+    }
+
+    /// Sets how much of the color covers the corners of the screen, between 0 and 1.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    /// Sets where the vignette starts and over which distance it reaches its full intensity.
+    ///
+    /// Distances are measured from the center of the screen, 1.0 being the middle of its edges.
+    /// Defaults to a radius of 0.75 and a softness of 0.6.
+    pub fn set_radius(&mut self, radius: f32, softness: f32) {
+        self.radius = radius;
+        self.softness = softness;
+    }
+}
+
+impl PostProcessingEffect for Vignette {
+    fn update(&mut self, _: f32, _: f32, _: f32, _: f32, _: f32) {}
+
+    fn draw(&mut self, target: &RenderTarget) {
+        self.pass.begin(target);
+        self.gl_color.upload(&self.color);
+        self.gl_intensity.upload(&self.intensity);
+        self.gl_radius.upload(&self.radius);
+        self.gl_softness.upload(&self.softness);
+        self.pass.draw();
+    }
+}
+
+pub(crate) static VIGNETTE_FRAGMENT_SRC: &str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+    uniform sampler2D fbo_texture;
+    uniform vec3      color;
+    uniform float     intensity;
+    uniform float     radius;
+    uniform float     softness;
+    varying vec2      f_texcoord;
+
+    void main(void) {
+      vec4 scene     = texture2D(fbo_texture, f_texcoord);
+      float dist     = length(f_texcoord * 2.0 - 1.0);
+      float amount   = smoothstep(radius, radius + softness, dist) * intensity;
+      gl_FragColor   = vec4(mix(scene.rgb, color, amount), scene.a);
+    }";
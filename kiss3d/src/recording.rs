@@ -0,0 +1,169 @@
+//! Frame capture to image sequences and animations.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use gif::{Encoder, ExtensionData, Frame, Repeat};
+
+use crate::window::Window;
+
+/// The file format frames are recorded to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// One PNG file per frame, named after the frame number, in a directory.
+    PngSequence,
+    /// A single GIF animation looping forever.
+    Gif,
+}
+
+impl RecordingFormat {
+    /// The format for the given path: a GIF animation if its extension is `gif`, a PNG sequence
+    /// otherwise.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> RecordingFormat {
+        match path.as_ref().extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("gif") => RecordingFormat::Gif,
+            _ => RecordingFormat::PngSequence,
+        }
+    }
+}
+
+/// Records what a window displays at a fixed simulation timestep.
+///
+/// Call `record` once per frame, after rendering, with the current simulation time. A frame is
+/// captured each time the simulation time reaches the next multiple of the timestep, so the
+/// recording plays back at a constant speed however fast the simulation and the rendering run.
+/// This makes timelapses: with a timestep of one in-game hour, each frame of the recording is
+/// one hour later than the previous one.
+///
+/// A GIF animation is complete once the recorder is dropped.
+pub struct Recorder {
+    path: PathBuf,
+    format: RecordingFormat,
+    timestep: f64,
+    next_capture: Option<f64>,
+    // In hundredths of a second, as GIF frame delays are.
+    frame_delay: u16,
+    frames: usize,
+    size: Option<(u32, u32)>,
+    gif: Option<Encoder<BufWriter<File>>>,
+}
+
+impl Recorder {
+    /// Creates a recorder writing to `path`, capturing a frame every `timestep` units of
+    /// simulation time.
+    ///
+    /// For a PNG sequence, `path` is the directory the frames are saved to, created if needed.
+    /// Existing frames there are overwritten.
+    pub fn new<P: AsRef<Path>>(
+        path: P,
+        format: RecordingFormat,
+        timestep: f64,
+    ) -> io::Result<Recorder> {
+        let path = path.as_ref().to_path_buf();
+        if format == RecordingFormat::PngSequence {
+            fs::create_dir_all(&path)?;
+        }
+
+        Ok(Recorder {
+            path,
+            format,
+            timestep,
+            next_capture: None,
+            frame_delay: 4,
+            frames: 0,
+            size: None,
+            gif: None,
+        })
+    }
+
+    /// Sets the number of frames per second a GIF animation plays at. Defaults to 25.
+    pub fn set_playback_rate(&mut self, frames_per_second: f32) {
+        self.frame_delay = (100.0 / frames_per_second).round().max(1.0) as u16;
+    }
+
+    /// The number of frames captured so far.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    /// The path frames are written to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Captures the window if the simulation time `time` has reached the time of the next frame.
+    ///
+    /// The first call always captures. Returns whether a frame was captured.
+    pub fn record(&mut self, window: &Window, time: f64) -> io::Result<bool> {
+        if let Some(next) = self.next_capture {
+            if time < next {
+                return Ok(false);
+            }
+        }
+
+        self.capture(window)?;
+
+        // Frames skipped by a large jump of the simulation time are not made up for.
+        let mut next = self.next_capture.unwrap_or(time);
+        if self.timestep > 0.0 {
+            while next <= time {
+                next += self.timestep;
+            }
+        }
+        self.next_capture = Some(next);
+        Ok(true)
+    }
+
+    /// Captures what the window currently displays, whatever the simulation time.
+    ///
+    /// All the frames of a recording must have the size of the first one.
+    pub fn capture(&mut self, window: &Window) -> io::Result<()> {
+        let image = window.snap_image();
+        let size = image.dimensions();
+        let expected = *self.size.get_or_insert(size);
+        if size != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame of {}x{} pixels recorded after frames of {}x{} pixels",
+                    size.0, size.1, expected.0, expected.1
+                ),
+            ));
+        }
+
+        match self.format {
+            RecordingFormat::PngSequence => {
+                let path = self.path.join(format!("{:06}.png", self.frames));
+                image.save(path)?;
+            }
+            RecordingFormat::Gif => {
+                if size.0 > u16::MAX as u32 || size.1 > u16::MAX as u32 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "GIF frames are at most 65535 pixels wide and high",
+                    ));
+                }
+                let (width, height) = (size.0 as u16, size.1 as u16);
+
+                if self.gif.is_none() {
+                    let file = BufWriter::new(File::create(&self.path)?);
+                    let mut encoder = Encoder::new(file, width, height, &[])?;
+                    encoder.write_extension(ExtensionData::Repetitions(Repeat::Infinite))?;
+                    self.gif = Some(encoder);
+                }
+
+                // Each frame gets its own palette. A speed of 10 is a good compromise between
+                // the time taken to quantize the colors and their quality.
+                let mut frame = Frame::from_rgb_speed(width, height, &image.into_raw(), 10);
+                frame.delay = self.frame_delay;
+                if let Some(encoder) = self.gif.as_mut() {
+                    encoder.write_frame(&frame)?;
+                }
+            }
+        }
+
+        self.frames += 1;
+        Ok(())
+    }
+}
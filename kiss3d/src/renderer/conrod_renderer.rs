@@ -11,8 +11,8 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use crate::text::Font;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum RenderMode {
@@ -5,9 +5,13 @@ pub use self::conrod_renderer::ConrodRenderer;
 pub use self::line_renderer::LineRenderer;
 pub use self::point_renderer::PointRenderer;
 pub use self::renderer::Renderer;
+pub use self::shadow_map::ShadowMap;
+#[cfg(feature = "headless")]
+pub(crate) use self::shadow_map::{SHADOW_FRAGMENT_SRC, SHADOW_VERTEX_SRC};
 
 #[cfg(feature = "conrod")]
 mod conrod_renderer;
 pub mod line_renderer;
 pub mod point_renderer;
 mod renderer;
+mod shadow_map;
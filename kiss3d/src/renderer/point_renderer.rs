@@ -6,8 +6,8 @@ use na::{Matrix4, Point3};
 use crate::renderer::Renderer;
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Structure which manages the display of short-living points.
 pub struct PointRenderer {
@@ -0,0 +1,211 @@
+//! A depth map of the scene as seen from a directional light.
+
+use crate::camera::Camera;
+use crate::context::{Context, Texture};
+use na::{Isometry3, Matrix3, Matrix4, Orthographic3, Point3, UnitQuaternion, Vector3, Vector4};
+use crate::resource::{Effect, FramebufferManager, RenderTarget, ShaderAttribute, ShaderUniform};
+use crate::scene::SceneNodeData;
+
+#[path = "../gl_error.rs"]
+mod gl_error;
+
+/// A shadow map for a directional light.
+///
+/// Each frame, the scene is rendered from the point of view of the light into an off-screen
+/// depth buffer. Materials then compare the depth of each fragment, as seen from the light,
+/// with the depth stored in this map to find out whether it is in shadow.
+///
+/// The map covers a square region of the scene of size `2 * extent` centered on the focus
+/// point, or in front of the camera if no focus point is set.
+pub struct ShadowMap {
+    target: RenderTarget,
+    size: usize,
+    extent: f32,
+    focus: Option<Point3<f32>>,
+    view_proj: Matrix4<f32>,
+
+    effect: Effect,
+    pos: ShaderAttribute<Point3<f32>>,
+    inst_rows: Vec<ShaderAttribute<Vector4<f32>>>,
+    transform: ShaderUniform<Matrix4<f32>>,
+    scale: ShaderUniform<Matrix3<f32>>,
+    light_view_proj: ShaderUniform<Matrix4<f32>>,
+}
+
+impl ShadowMap {
+    /// Creates a new shadow map of `size * size` texels covering `2 * extent` world units.
+    pub fn new(size: usize, extent: f32) -> ShadowMap {
+        let mut effect = Effect::new_from_str(SHADOW_VERTEX_SRC, SHADOW_FRAGMENT_SRC);
+        effect.use_program();
+
+        ShadowMap {
+            target: FramebufferManager::new_render_target(size, size, true),
+            size,
+            extent,
+            focus: None,
+            view_proj: Matrix4::identity(),
+            pos: effect.get_attrib("position").unwrap(),
+            inst_rows: (0..3)
+                .map(|i| effect.get_attrib(&format!("inst_row_{}", i)).unwrap())
+                .collect(),
+            transform: effect.get_uniform("transform").unwrap(),
+            scale: effect.get_uniform("scale").unwrap(),
+            light_view_proj: effect.get_uniform("light_view_proj").unwrap(),
+            effect,
+        }
+    }
+
+    /// Sets the point the shadow map is centered on, e.g. the point the camera looks at.
+    ///
+    /// If `None`, the shadow map is centered in front of the camera.
+    pub fn set_focus(&mut self, focus: Option<Point3<f32>>) {
+        self.focus = focus;
+    }
+
+    /// The number of texels along each side of the shadow map.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The transformation from world coordinates to the normalized device coordinates of the
+    /// light.
+    pub fn view_proj(&self) -> &Matrix4<f32> {
+        &self.view_proj
+    }
+
+    /// The depth texture of the shadow map.
+    ///
+    /// Returns `None` if depth textures are not supported on this platform.
+    pub fn depth_texture(&self) -> Option<&Texture> {
+        #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
+        {
+            self.target.depth_id().and_then(|id| id.as_ref().left())
+        }
+        #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+        {
+            None
+        }
+    }
+
+    fn update_view_proj(&mut self, camera: &dyn Camera, direction: &Vector3<f32>) {
+        let dir = direction.normalize();
+        let center = match self.focus {
+            Some(focus) => focus,
+            None => {
+                let forward = camera.view_transform().rotation.inverse() * -Vector3::z();
+                camera.eye() + forward * self.extent * 0.5
+            }
+        };
+        let up = if dir.y.abs() > 0.99 {
+            Vector3::z()
+        } else {
+            Vector3::y()
+        };
+
+        // Snap the center to whole texels so the shadow edges do not shimmer as the focus
+        // moves.
+        let rotation = UnitQuaternion::look_at_rh(&dir, &up);
+        let texel = 2.0 * self.extent / self.size as f32;
+        let mut local = rotation * center.coords;
+        local.x = (local.x / texel).round() * texel;
+        local.y = (local.y / texel).round() * texel;
+        let center = Point3::from(rotation.inverse() * local);
+
+        let eye = center - dir * self.extent * 2.0;
+        let view = Isometry3::look_at_rh(&eye, &center, &up);
+        let proj = Orthographic3::new(
+            -self.extent,
+            self.extent,
+            -self.extent,
+            self.extent,
+            0.0,
+            self.extent * 4.0,
+        );
+        self.view_proj = proj.as_matrix() * view.to_homogeneous();
+    }
+
+    /// Renders the depth of the scene as seen from a directional light.
+    ///
+    /// This changes the viewport and the scissor box. They have to be restored afterwards.
+    pub fn render(
+        &mut self,
+        framebuffer_manager: &mut FramebufferManager,
+        scene: &mut SceneNodeData,
+        camera: &dyn Camera,
+        direction: &Vector3<f32>,
+    ) {
+        let ctxt = Context::get();
+        self.update_view_proj(camera, direction);
+
+        framebuffer_manager.select(&self.target);
+        verify!(ctxt.viewport(0, 0, self.size as i32, self.size as i32));
+        verify!(ctxt.scissor(0, 0, self.size as i32, self.size as i32));
+        verify!(ctxt.clear_color(1.0, 1.0, 1.0, 1.0));
+        verify!(ctxt.clear(Context::COLOR_BUFFER_BIT));
+        verify!(ctxt.clear(Context::DEPTH_BUFFER_BIT));
+
+        self.effect.use_program();
+        self.pos.enable();
+        self.light_view_proj.upload(&self.view_proj);
+
+        // Both sides of the surfaces cast shadows.
+        verify!(ctxt.disable(Context::CULL_FACE));
+
+        let pos = &mut self.pos;
+        let inst_rows = &mut self.inst_rows;
+        let transform = &mut self.transform;
+        let scale = &mut self.scale;
+        scene.visit_visible_objects(&mut |world_transform, world_scale, object| {
+            if !object.data().surface_rendering_active() {
+                return;
+            }
+
+            let formated_scale = Matrix3::from_diagonal(world_scale);
+            transform.upload(&world_transform.to_homogeneous());
+            scale.upload(&formated_scale);
+
+            let mut mesh = object.mesh().borrow_mut();
+            mesh.bind_coords(pos);
+            mesh.bind_faces();
+
+            let mut instances = object.data().instances().borrow_mut();
+            instances.bind(inst_rows, None);
+            instances.draw_elements(
+                Context::TRIANGLES,
+                mesh.num_pts() as i32,
+                mesh.index_type(),
+                0,
+            );
+            instances.unbind(inst_rows, None);
+            mesh.unbind();
+        });
+
+        verify!(ctxt.enable(Context::CULL_FACE));
+        self.pos.disable();
+    }
+}
+
+/// Vertex shader of the shadow map depth pass.
+pub static SHADOW_VERTEX_SRC: &'static str = "#version 100
+attribute vec3 position;
+attribute vec4 inst_row_0, inst_row_1, inst_row_2;
+uniform mat3 scale;
+uniform mat4 light_view_proj, transform;
+
+void main() {
+    vec4 local_pos = vec4(scale * position, 1.0);
+    vec3 inst_pos = vec3(dot(inst_row_0, local_pos), dot(inst_row_1, local_pos), dot(inst_row_2, local_pos));
+    gl_Position = light_view_proj * transform * vec4(inst_pos, 1.0);
+}";
+
+/// Fragment shader of the shadow map depth pass. Only the depth buffer is used.
+pub static SHADOW_FRAGMENT_SRC: &'static str = "#version 100
+#ifdef GL_FRAGMENT_PRECISION_HIGH
+   precision highp float;
+#else
+   precision mediump float;
+#endif
+
+void main() {
+    gl_FragColor = vec4(1.0, 1.0, 1.0, 1.0);
+}";
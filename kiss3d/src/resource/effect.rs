@@ -6,10 +6,11 @@ use std::path::Path;
 use std::str;
 
 use crate::context::{Context, GLintptr, Program, Shader, UniformLocation};
+use na::Vector4;
 use crate::resource::{GLPrimitive, GPUVec};
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Structure encapsulating a program.
 pub struct Effect {
@@ -132,6 +133,21 @@ impl<T: GLPrimitive> ShaderAttribute<T> {
         verify!(Context::get().enable_vertex_attrib_array(self.id));
     }
 
+    /// Sets the number of instances drawn before this attribute advances to its next value.
+    ///
+    /// The default, zero, advances once per vertex instead. The divisor belongs to the attribute
+    /// location rather than to the shader, so it has to be reset to zero after drawing.
+    pub fn set_divisor(&mut self, divisor: u32) {
+        verify!(Context::get().vertex_attrib_divisor(self.id, divisor));
+    }
+
+    /// Sets the value of this attribute for every vertex while it is disabled.
+    ///
+    /// Components missing from the attribute type are ignored.
+    pub fn set_constant(&mut self, value: &Vector4<f32>) {
+        verify!(Context::get().vertex_attrib4f(self.id, value.x, value.y, value.z, value.w));
+    }
+
     /// Binds this attribute to a gpu vector.
     pub fn bind(&mut self, vector: &mut GPUVec<T>) {
         vector.bind();
@@ -2,9 +2,10 @@
 
 use crate::context::{Context, Framebuffer, Renderbuffer, Texture};
 use either::Either;
+use std::rc::Rc;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// The target to every rendering call.
 pub enum RenderTarget {
@@ -15,9 +16,12 @@ pub enum RenderTarget {
 }
 
 /// OpenGL identifiers to an off-screen buffer.
+///
+/// The buffers may be shared by several render targets, see `RenderTarget::with_depth_of`. They
+/// are deleted with the last render target using them.
 pub struct OffscreenBuffers {
-    texture: Texture,
-    depth: Either<Texture, Renderbuffer>,
+    texture: Rc<Texture>,
+    depth: Rc<Either<Texture, Renderbuffer>>,
 }
 
 impl RenderTarget {
@@ -27,7 +31,7 @@ impl RenderTarget {
     pub fn texture_id(&self) -> Option<&Texture> {
         match *self {
             RenderTarget::Screen => None,
-            RenderTarget::Offscreen(ref o) => Some(&o.texture),
+            RenderTarget::Offscreen(ref o) => Some(&*o.texture),
         }
     }
 
@@ -38,7 +42,24 @@ impl RenderTarget {
     pub fn depth_id(&self) -> Option<&Either<Texture, Renderbuffer>> {
         match *self {
             RenderTarget::Screen => None,
-            RenderTarget::Offscreen(ref o) => Some(&o.depth),
+            RenderTarget::Offscreen(ref o) => Some(&*o.depth),
+        }
+    }
+
+    /// A render target drawing to the color buffer of `self` and to the depth buffer of `other`.
+    ///
+    /// This lets post-processing effects chained after the first one read the depth of the
+    /// scene. Returns `RenderTarget::Screen` if either target is the screen. Resizing the
+    /// returned target resizes both buffers.
+    pub fn with_depth_of(&self, other: &RenderTarget) -> RenderTarget {
+        match (self, other) {
+            (RenderTarget::Offscreen(color), RenderTarget::Offscreen(depth)) => {
+                RenderTarget::Offscreen(OffscreenBuffers {
+                    texture: color.texture.clone(),
+                    depth: depth.depth.clone(),
+                })
+            }
+            _ => RenderTarget::Screen,
         }
     }
 
@@ -52,7 +73,7 @@ impl RenderTarget {
             }
             RenderTarget::Offscreen(ref o) => {
                 // Update the fbo
-                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&o.texture)));
+                verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&*o.texture)));
                 verify!(ctxt.tex_image2d(
                     Context::TEXTURE_2D,
                     0,
@@ -65,7 +86,7 @@ impl RenderTarget {
                 ));
                 verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
-                match &o.depth {
+                match &*o.depth {
                     Either::Left(texture) => {
                         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(texture)));
                         verify!(ctxt.tex_image2d(
@@ -206,8 +227,8 @@ impl FramebufferManager {
             verify!(ctxt.bind_texture(Context::TEXTURE_2D, None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
-                depth: Either::Left(fbo_depth),
+                texture: Rc::new(fbo_texture),
+                depth: Rc::new(Either::Left(fbo_depth)),
             })
         } else {
             // Create a renderbuffer instead of the texture for the depth.
@@ -222,8 +243,8 @@ impl FramebufferManager {
             verify!(ctxt.bind_renderbuffer(None));
 
             RenderTarget::Offscreen(OffscreenBuffers {
-                texture: fbo_texture,
-                depth: Either::Right(renderbuffer),
+                texture: Rc::new(fbo_texture),
+                depth: Rc::new(Either::Right(renderbuffer)),
             })
         }
     }
@@ -249,11 +270,11 @@ impl FramebufferManager {
                     Context::FRAMEBUFFER,
                     Context::COLOR_ATTACHMENT0,
                     Context::TEXTURE_2D,
-                    Some(&o.texture),
+                    Some(&*o.texture),
                     0
                 ));
 
-                match &o.depth {
+                match &*o.depth {
                     Either::Left(texture) => {
                         verify!(ctxt.framebuffer_texture2d(
                             Context::FRAMEBUFFER,
@@ -298,11 +319,15 @@ impl Drop for FramebufferManager {
 impl Drop for OffscreenBuffers {
     fn drop(&mut self) {
         let ctxt = Context::get();
-        if ctxt.is_texture(Some(&self.texture)) {
-            verify!(ctxt.delete_texture(Some(&self.texture)));
+        if Rc::strong_count(&self.texture) == 1 && ctxt.is_texture(Some(&*self.texture)) {
+            verify!(ctxt.delete_texture(Some(&*self.texture)));
+        }
+
+        if Rc::strong_count(&self.depth) > 1 {
+            return;
         }
 
-        match &self.depth {
+        match &*self.depth {
             Either::Left(texture) => {
                 if ctxt.is_texture(Some(texture)) {
                     verify!(ctxt.delete_texture(Some(texture)));
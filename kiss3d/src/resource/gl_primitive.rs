@@ -8,8 +8,8 @@ use na::{
     Vector4,
 };
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// An array of primitive types.
 pub enum PrimitiveArray<'a> {
@@ -19,6 +19,8 @@ pub enum PrimitiveArray<'a> {
     Int32(&'a [i32]),
     /// A array of u16.
     UInt16(&'a [u16]),
+    /// A array of u32.
+    UInt32(&'a [u32]),
 }
 
 /// Trait implemented by structures that can be uploaded to a uniform or contained by a gpu array.
@@ -579,6 +581,60 @@ unsafe impl GLPrimitive for Point3<u16> {
     }
 }
 
+unsafe impl GLPrimitive for Point2<u32> {
+    #[inline]
+    fn gl_type() -> u32 {
+        Context::UNSIGNED_INT
+    }
+
+    #[inline]
+    fn flatten(array: &[Self]) -> PrimitiveArray {
+        unsafe {
+            let len = array.len() * Self::size() as usize;
+            let ptr = array.as_ptr();
+
+            PrimitiveArray::UInt32(slice::from_raw_parts(ptr as *const u32, len))
+        }
+    }
+
+    #[inline]
+    fn size() -> u32 {
+        2
+    }
+
+    #[inline]
+    fn upload(&self, _: &UniformLocation) {
+        unimplemented!()
+    }
+}
+
+unsafe impl GLPrimitive for Point3<u32> {
+    #[inline]
+    fn gl_type() -> u32 {
+        Context::UNSIGNED_INT
+    }
+
+    #[inline]
+    fn flatten(array: &[Self]) -> PrimitiveArray {
+        unsafe {
+            let len = array.len() * Self::size() as usize;
+            let ptr = array.as_ptr();
+
+            PrimitiveArray::UInt32(slice::from_raw_parts(ptr as *const u32, len))
+        }
+    }
+
+    #[inline]
+    fn size() -> u32 {
+        3
+    }
+
+    #[inline]
+    fn upload(&self, _: &UniformLocation) {
+        unimplemented!()
+    }
+}
+
 /*
  *
  * Impl for tuples
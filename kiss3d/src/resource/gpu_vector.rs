@@ -3,13 +3,14 @@
 use crate::context::{Buffer, Context};
 use crate::resource::gl_primitive::GLPrimitive;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 // FIXME: generalize this for any resource: GPUResource
 /// A vector of elements that can be loaded to the GPU, on the RAM, or both.
 pub struct GPUVec<T> {
     trash: bool,
+    changes: u64,
     len: usize,
     buf_type: BufferType,
     alloc_type: AllocationType,
@@ -23,6 +24,7 @@ impl<T: GLPrimitive> GPUVec<T> {
     pub fn new(data: Vec<T>, buf_type: BufferType, alloc_type: AllocationType) -> GPUVec<T> {
         GPUVec {
             trash: true,
+            changes: 0,
             len: data.len(),
             buf_type: buf_type,
             alloc_type: alloc_type,
@@ -50,6 +52,7 @@ impl<T: GLPrimitive> GPUVec<T> {
     #[inline]
     pub fn data_mut(&mut self) -> &mut Option<Vec<T>> {
         self.trash = true;
+        self.changes += 1;
 
         &mut self.data
     }
@@ -60,6 +63,15 @@ impl<T: GLPrimitive> GPUVec<T> {
         &self.data
     }
 
+    /// The number of times this vector was mutably accessed.
+    ///
+    /// Data computed from the vector can be cached along with this number, and recomputed once it
+    /// changes.
+    #[inline]
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
+
     /// Returns `true` if this vector is already uploaded to the GPU.
     #[inline]
     pub fn is_on_gpu(&self) -> bool {
@@ -1,7 +1,7 @@
 //! Trait implemented by materials.
 
 use crate::camera::Camera;
-use crate::light::Light;
+use crate::light::{Light, Lights};
 use na::{Isometry2, Isometry3, Vector2, Vector3};
 use crate::planar_camera::PlanarCamera;
 use crate::resource::{Mesh, PlanarMesh};
@@ -21,6 +21,22 @@ pub trait Material {
         data: &ObjectData,
         mesh: &mut Mesh,
     );
+
+    /// Renders an object using this material, lit by all the lights of the scene.
+    ///
+    /// By default, only the main light is used.
+    fn render_with_lights(
+        &mut self,
+        pass: usize,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+        data: &ObjectData,
+        mesh: &mut Mesh,
+    ) {
+        self.render(pass, transform, scale, camera, &lights.main, data, mesh)
+    }
 }
 
 /// A material for 2D objects.
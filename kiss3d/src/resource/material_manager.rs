@@ -1,6 +1,6 @@
 //! A resource manager to load materials.
 
-use crate::builtin::{NormalsMaterial, ObjectMaterial, UvsMaterial};
+use crate::builtin::{NormalsMaterial, ObjectMaterial, UvsMaterial, VertexColorMaterial};
 use crate::resource::Material;
 use std::cell::RefCell;
 use std::collections::HashMap;
@@ -13,6 +13,8 @@ thread_local!(static KEY_MATERIAL_MANAGER: RefCell<MaterialManager> = RefCell::n
 /// Upon construction, it contains:
 /// * the `object` material, used as the default to render objects.
 /// * the `normals` material, used do display an object normals.
+/// * the `uvs` material, used do display an object texture coordinates.
+/// * the `vertex_color` material, which modulates the object color by its mesh vertex colors.
 ///
 /// It keeps a cache of already-loaded materials. Note that this is only a cache, nothing more.
 /// Thus, its usage is not required to load materials.
@@ -42,6 +44,11 @@ impl MaterialManager {
         ));
         let _ = materials.insert("uvs".to_string(), um.clone());
 
+        let vm = Rc::new(RefCell::new(
+            Box::new(VertexColorMaterial::new()) as Box<dyn Material + 'static>
+        ));
+        let _ = materials.insert("vertex_color".to_string(), vm.clone());
+
         MaterialManager {
             default_material: om,
             materials: materials,
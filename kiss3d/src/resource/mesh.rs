@@ -1,29 +1,168 @@
 //! Data structure of a scene node geometry.
+use std::cell::RefCell;
 use std::iter;
 use std::sync::{Arc, RwLock};
 
 use na::{self, Point2, Point3, Vector3};
+use ncollide3d::bounding_volume::AABB;
 use ncollide3d::procedural::{IndexBuffer, TriMesh};
+use ncollide3d::query::{self, Ray, RayCast};
 use num::Zero;
+use crate::context::Context;
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
-use crate::resource::ShaderAttribute;
+use crate::resource::{GLPrimitive, ShaderAttribute};
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
-/// Aggregation of vertices, indices, normals and texture coordinates.
+/// Integer types that can be used to index the vertices of a mesh.
+pub trait MeshIndex: na::Scalar + Copy {
+    /// Converts this index to an `usize`.
+    fn to_usize(self) -> usize;
+}
+
+impl MeshIndex for u16 {
+    #[inline]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+impl MeshIndex for u32 {
+    #[inline]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
+}
+
+/// The index buffer of a mesh.
+///
+/// 16-bit indices use half the memory but can only address 65,536 vertices; meshes with more
+/// vertices need 32-bit indices. The index type is chosen per mesh.
+///
+/// Note that 32-bit indices require the `OES_element_index_uint` extension on WebGL 1, which
+/// `WebGLContext` enables when it is created.
+#[derive(Clone)]
+pub enum MeshFaces {
+    /// Faces indexed with 16-bit integers.
+    U16(Arc<RwLock<GPUVec<Point3<u16>>>>),
+    /// Faces indexed with 32-bit integers.
+    U32(Arc<RwLock<GPUVec<Point3<u32>>>>),
+}
+
+impl From<Arc<RwLock<GPUVec<Point3<u16>>>>> for MeshFaces {
+    fn from(faces: Arc<RwLock<GPUVec<Point3<u16>>>>) -> MeshFaces {
+        MeshFaces::U16(faces)
+    }
+}
+
+impl From<Arc<RwLock<GPUVec<Point3<u32>>>>> for MeshFaces {
+    fn from(faces: Arc<RwLock<GPUVec<Point3<u32>>>>) -> MeshFaces {
+        MeshFaces::U32(faces)
+    }
+}
+
+impl MeshFaces {
+    /// The OpenGL type of the indices, to be passed to `draw_elements`.
+    #[inline]
+    pub fn index_type(&self) -> u32 {
+        match self {
+            MeshFaces::U16(_) => Context::UNSIGNED_SHORT,
+            MeshFaces::U32(_) => Context::UNSIGNED_INT,
+        }
+    }
+
+    /// Whether the indices are 32-bit integers.
+    #[inline]
+    pub fn is_u32(&self) -> bool {
+        match self {
+            MeshFaces::U16(_) => false,
+            MeshFaces::U32(_) => true,
+        }
+    }
+
+    /// The number of faces.
+    pub fn len(&self) -> usize {
+        match self {
+            MeshFaces::U16(faces) => faces.read().unwrap().len(),
+            MeshFaces::U32(faces) => faces.read().unwrap().len(),
+        }
+    }
+
+    /// Whether the faces are available on the CPU.
+    pub fn is_on_ram(&self) -> bool {
+        match self {
+            MeshFaces::U16(faces) => faces.read().unwrap().is_on_ram(),
+            MeshFaces::U32(faces) => faces.read().unwrap().is_on_ram(),
+        }
+    }
+
+    /// A copy of the faces, with indices widened to 32 bits.
+    ///
+    /// Returns `None` if the faces are not available on the CPU.
+    pub fn to_u32(&self) -> Option<Vec<Point3<u32>>> {
+        match self {
+            MeshFaces::U16(faces) => faces.read().unwrap().data().as_ref().map(|faces| {
+                faces
+                    .iter()
+                    .map(|f| Point3::new(f.x as u32, f.y as u32, f.z as u32))
+                    .collect()
+            }),
+            MeshFaces::U32(faces) => faces.read().unwrap().to_owned(),
+        }
+    }
+
+    fn bind(&self) {
+        match self {
+            MeshFaces::U16(faces) => faces.write().unwrap().bind(),
+            MeshFaces::U32(faces) => faces.write().unwrap().bind(),
+        }
+    }
+
+    fn unbind(&self) {
+        match self {
+            MeshFaces::U16(faces) => faces.write().unwrap().unbind(),
+            MeshFaces::U32(faces) => faces.write().unwrap().unbind(),
+        }
+    }
+}
+
+// Edges used to draw wireframes when the polygon mode cannot be changed. They use the same index
+// type as the faces.
+enum MeshEdges {
+    U16(GPUVec<Point2<u16>>),
+    U32(GPUVec<Point2<u32>>),
+}
+
+fn edges_of<I: MeshIndex>(faces: &[Point3<I>]) -> GPUVec<Point2<I>>
+where
+    Point2<I>: GLPrimitive,
+{
+    let mut edges = Vec::new();
+    for face in faces {
+        edges.push(Point2::new(face.x, face.y));
+        edges.push(Point2::new(face.y, face.z));
+        edges.push(Point2::new(face.z, face.x));
+    }
+    GPUVec::new(edges, BufferType::ElementArray, AllocationType::StaticDraw)
+}
+
+/// Aggregation of vertices, indices, normals, texture coordinates and optional vertex colors.
 ///
 /// It also contains the GPU location of those buffers.
 pub struct Mesh {
     coords: Arc<RwLock<GPUVec<Point3<f32>>>>,
-    faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
+    faces: MeshFaces,
     normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
     uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
-    edges: Option<Arc<RwLock<GPUVec<Point2<u16>>>>>,
+    colors: Option<Arc<RwLock<GPUVec<Point3<f32>>>>>,
+    edges: Option<MeshEdges>,
+    // The bounds of the vertices, and the number of changes of the vertices they were computed at.
+    aabb: RefCell<Option<(u64, Option<AABB<f32>>)>>,
 }
 
 impl Mesh {
-    /// Creates a new mesh.
+    /// Creates a new mesh with 16-bit indices.
     ///
     /// If the normals and uvs are not given, they are automatically computed.
     pub fn new(
@@ -33,6 +172,33 @@ impl Mesh {
         uvs: Option<Vec<Point2<f32>>>,
         dynamic_draw: bool,
     ) -> Mesh {
+        Mesh::new_generic(coords, faces, normals, uvs, dynamic_draw)
+    }
+
+    /// Creates a new mesh with 32-bit indices, for meshes with more than 65,536 vertices.
+    ///
+    /// If the normals and uvs are not given, they are automatically computed.
+    pub fn new_u32(
+        coords: Vec<Point3<f32>>,
+        faces: Vec<Point3<u32>>,
+        normals: Option<Vec<Vector3<f32>>>,
+        uvs: Option<Vec<Point2<f32>>>,
+        dynamic_draw: bool,
+    ) -> Mesh {
+        Mesh::new_generic(coords, faces, normals, uvs, dynamic_draw)
+    }
+
+    fn new_generic<I: MeshIndex>(
+        coords: Vec<Point3<f32>>,
+        faces: Vec<Point3<I>>,
+        normals: Option<Vec<Vector3<f32>>>,
+        uvs: Option<Vec<Point2<f32>>>,
+        dynamic_draw: bool,
+    ) -> Mesh
+    where
+        Point3<I>: GLPrimitive,
+        Arc<RwLock<GPUVec<Point3<I>>>>: Into<MeshFaces>,
+    {
         let normals = match normals {
             Some(ns) => ns,
             None => Mesh::compute_normals_array(&coords[..], &faces[..]),
@@ -70,7 +236,8 @@ impl Mesh {
 
     /// Creates a new mesh from a mesh descr.
     ///
-    /// In the normals and uvs are not given, they are automatically computed.
+    /// In the normals and uvs are not given, they are automatically computed. 32-bit indices are
+    /// used if the mesh has more than 65,536 vertices.
     pub fn from_trimesh(mesh: TriMesh<f32>, dynamic_draw: bool) -> Mesh {
         let mut mesh = mesh;
 
@@ -83,17 +250,19 @@ impl Mesh {
             indices,
         } = mesh;
 
-        Mesh::new(
-            coords,
-            indices
-                .unwrap_unified()
-                .into_iter()
-                .map(|e| na::convert(e))
-                .collect(),
-            normals,
-            uvs,
-            dynamic_draw,
-        )
+        let indices = indices.unwrap_unified();
+
+        if coords.len() > u16::max_value() as usize + 1 {
+            Mesh::new_u32(coords, indices, normals, uvs, dynamic_draw)
+        } else {
+            Mesh::new(
+                coords,
+                indices.into_iter().map(|e| na::convert(e)).collect(),
+                normals,
+                uvs,
+                dynamic_draw,
+            )
+        }
     }
 
     // XXX: The `load_to_ram` require WebGL 2.
@@ -102,7 +271,7 @@ impl Mesh {
     /// Return `None` if the mesh data is not available on the CPU.
     pub fn to_trimesh(&self) -> Option<TriMesh<f32>> {
         if !self.coords.read().unwrap().is_on_ram()
-            || !self.faces.read().unwrap().is_on_ram()
+            || !self.faces.is_on_ram()
             || !self.normals.read().unwrap().is_on_ram()
             || !self.uvs.read().unwrap().is_on_ram()
         {
@@ -110,7 +279,7 @@ impl Mesh {
         }
 
         let coords = self.coords.read().unwrap().to_owned();
-        let faces = self.faces.read().unwrap().to_owned();
+        let faces = self.faces.to_u32();
         let normals = self.normals.read().unwrap().to_owned();
         let uvs = self.uvs.read().unwrap().to_owned();
 
@@ -118,13 +287,7 @@ impl Mesh {
             coords.unwrap(),
             normals,
             uvs,
-            Some(IndexBuffer::Unified(
-                faces
-                    .unwrap()
-                    .into_iter()
-                    .map(|e| Point3::new(e.x as u32, e.y as u32, e.z as u32))
-                    .collect(),
-            )),
+            Some(IndexBuffer::Unified(faces.unwrap())),
         ))
 
         /*
@@ -175,21 +338,34 @@ impl Mesh {
     }
 
     /// Creates a new mesh. Arguments set to `None` are automatically computed.
-    pub fn new_with_gpu_vectors(
+    ///
+    /// The faces may use either 16-bit or 32-bit indices.
+    pub fn new_with_gpu_vectors<F: Into<MeshFaces>>(
         coords: Arc<RwLock<GPUVec<Point3<f32>>>>,
-        faces: Arc<RwLock<GPUVec<Point3<u16>>>>,
+        faces: F,
         normals: Arc<RwLock<GPUVec<Vector3<f32>>>>,
         uvs: Arc<RwLock<GPUVec<Point2<f32>>>>,
     ) -> Mesh {
         Mesh {
             coords: coords,
-            faces: faces,
+            faces: faces.into(),
             normals: normals,
             uvs: uvs,
+            colors: None,
             edges: None,
+            aabb: RefCell::new(None),
         }
     }
 
+    /// Sets the per-vertex colors of this mesh.
+    ///
+    /// Vertex colors are only used by materials that read them, like the `vertex_color`
+    /// material. There must be one color per vertex.
+    pub fn set_colors(&mut self, colors: Vec<Point3<f32>>) {
+        let gpu_colors = GPUVec::new(colors, BufferType::Array, AllocationType::StaticDraw);
+        self.colors = Some(Arc::new(RwLock::new(gpu_colors)));
+    }
+
     /// Binds this mesh vertex coordinates buffer to a vertex attribute.
     pub fn bind_coords(&mut self, coords: &mut ShaderAttribute<Point3<f32>>) {
         coords.bind(&mut *self.coords.write().unwrap());
@@ -205,9 +381,21 @@ impl Mesh {
         uvs.bind(&mut *self.uvs.write().unwrap());
     }
 
+    /// Binds this mesh vertex colors buffer to a vertex attribute.
+    ///
+    /// A mesh without vertex colors is given white ones the first time they are bound.
+    pub fn bind_colors(&mut self, colors: &mut ShaderAttribute<Point3<f32>>) {
+        if self.colors.is_none() {
+            let len = self.coords.read().unwrap().len();
+            self.set_colors(iter::repeat(Point3::new(1.0, 1.0, 1.0)).take(len).collect());
+        }
+
+        colors.bind(&mut *self.colors.as_ref().unwrap().write().unwrap());
+    }
+
     /// Binds this mesh index buffer to a vertex attribute.
     pub fn bind_faces(&mut self) {
-        self.faces.write().unwrap().bind();
+        self.faces.bind();
     }
 
     /// Binds this mesh buffers to vertex attributes.
@@ -226,18 +414,20 @@ impl Mesh {
     /// Binds this mesh buffers to vertex attributes.
     pub fn bind_edges(&mut self) {
         if self.edges.is_none() {
-            let mut edges = Vec::new();
-            for face in self.faces.read().unwrap().data().as_ref().unwrap() {
-                edges.push(Point2::new(face.x, face.y));
-                edges.push(Point2::new(face.y, face.z));
-                edges.push(Point2::new(face.z, face.x));
-            }
-            let gpu_edges =
-                GPUVec::new(edges, BufferType::ElementArray, AllocationType::StaticDraw);
-            self.edges = Some(Arc::new(RwLock::new(gpu_edges)));
+            self.edges = Some(match &self.faces {
+                MeshFaces::U16(faces) => {
+                    MeshEdges::U16(edges_of(faces.read().unwrap().data().as_ref().unwrap()))
+                }
+                MeshFaces::U32(faces) => {
+                    MeshEdges::U32(edges_of(faces.read().unwrap().data().as_ref().unwrap()))
+                }
+            });
         }
 
-        self.edges.as_mut().unwrap().write().unwrap().bind();
+        match self.edges.as_mut().unwrap() {
+            MeshEdges::U16(edges) => edges.bind(),
+            MeshEdges::U32(edges) => edges.bind(),
+        }
     }
 
     /// Unbind this mesh buffers to vertex attributes.
@@ -245,25 +435,45 @@ impl Mesh {
         self.coords.write().unwrap().unbind();
         self.normals.write().unwrap().unbind();
         self.uvs.write().unwrap().unbind();
-        self.faces.write().unwrap().unbind();
+        if let Some(colors) = &self.colors {
+            colors.write().unwrap().unbind();
+        }
+        self.faces.unbind();
     }
 
     /// Number of points needed to draw this mesh.
     pub fn num_pts(&self) -> usize {
-        self.faces.read().unwrap().len() * 3
+        self.faces.len() * 3
+    }
+
+    /// The OpenGL type of this mesh indices, to be passed to `draw_elements`.
+    pub fn index_type(&self) -> u32 {
+        self.faces.index_type()
     }
 
     /// Recompute this mesh normals.
     pub fn recompute_normals(&mut self) {
-        Mesh::compute_normals(
-            &self.coords.read().unwrap().data().as_ref().unwrap()[..],
-            &self.faces.read().unwrap().data().as_ref().unwrap()[..],
-            self.normals.write().unwrap().data_mut().as_mut().unwrap(),
-        );
+        let coords = self.coords.read().unwrap();
+        let coords = &coords.data().as_ref().unwrap()[..];
+        let mut normals = self.normals.write().unwrap();
+        let normals = normals.data_mut().as_mut().unwrap();
+
+        match &self.faces {
+            MeshFaces::U16(faces) => Mesh::compute_normals(
+                coords,
+                &faces.read().unwrap().data().as_ref().unwrap()[..],
+                normals,
+            ),
+            MeshFaces::U32(faces) => Mesh::compute_normals(
+                coords,
+                &faces.read().unwrap().data().as_ref().unwrap()[..],
+                normals,
+            ),
+        }
     }
 
     /// This mesh faces.
-    pub fn faces(&self) -> &Arc<RwLock<GPUVec<Point3<u16>>>> {
+    pub fn faces(&self) -> &MeshFaces {
         &self.faces
     }
 
@@ -282,10 +492,83 @@ impl Mesh {
         &self.uvs
     }
 
+    /// This mesh vertex colors, if it has any.
+    pub fn colors(&self) -> Option<&Arc<RwLock<GPUVec<Point3<f32>>>>> {
+        self.colors.as_ref()
+    }
+
+    /// The axis-aligned bounding box of the vertices of this mesh.
+    ///
+    /// It is cached until the vertices are modified. Returns `None` if the mesh has no vertices or
+    /// they are not available on the CPU.
+    pub fn aabb(&self) -> Option<AABB<f32>> {
+        let coords = self.coords.read().unwrap();
+        let changes = coords.changes();
+        let mut cache = self.aabb.borrow_mut();
+
+        match &*cache {
+            Some((computed, aabb)) if *computed == changes => aabb.clone(),
+            _ => {
+                let aabb = coords.data().as_ref().and_then(|coords| {
+                    let first = coords.first()?;
+                    let (mins, maxs) = coords.iter().fold((*first, *first), |(mins, maxs), p| {
+                        (mins.inf(p), maxs.sup(p))
+                    });
+                    Some(AABB::new(mins, maxs))
+                });
+                *cache = Some((changes, aabb.clone()));
+                aabb
+            }
+        }
+    }
+
+    /// The time of impact of a ray, expressed in the space of the vertices, with the faces of
+    /// this mesh.
+    ///
+    /// Both sides of the faces are hit. Returns `None` if the ray misses the mesh or hits it after
+    /// `max_toi`, or if the vertices or the faces are not available on the CPU.
+    pub fn toi_with_ray(&self, ray: &Ray<f32>, max_toi: f32) -> Option<f32> {
+        let aabb = self.aabb()?;
+        let _ = aabb.toi_with_ray(&na::one(), ray, max_toi, true)?;
+
+        let coords = self.coords.read().unwrap();
+        let coords = coords.data().as_ref()?;
+        match &self.faces {
+            MeshFaces::U16(faces) => {
+                let faces = faces.read().unwrap();
+                Mesh::faces_toi_with_ray(coords, faces.data().as_ref()?, ray, max_toi)
+            }
+            MeshFaces::U32(faces) => {
+                let faces = faces.read().unwrap();
+                Mesh::faces_toi_with_ray(coords, faces.data().as_ref()?, ray, max_toi)
+            }
+        }
+    }
+
+    fn faces_toi_with_ray<I: MeshIndex>(
+        coords: &[Point3<f32>],
+        faces: &[Point3<I>],
+        ray: &Ray<f32>,
+        max_toi: f32,
+    ) -> Option<f32> {
+        let mut best: Option<f32> = None;
+        for face in faces {
+            let vertex = |i: I| coords.get(i.to_usize());
+            if let (Some(a), Some(b), Some(c)) = (vertex(face.x), vertex(face.y), vertex(face.z)) {
+                if let Some((inter, _)) = query::ray_intersection_with_triangle(a, b, c, ray) {
+                    if inter.toi <= best.unwrap_or(max_toi) {
+                        best = Some(inter.toi)
+                    }
+                }
+            }
+        }
+        best
+    }
+
     /// Computes normals from a set of faces.
-    pub fn compute_normals_array(
+    pub fn compute_normals_array<I: MeshIndex>(
         coordinates: &[Point3<f32>],
-        faces: &[Point3<u16>],
+        faces: &[Point3<I>],
     ) -> Vec<Vector3<f32>> {
         let mut res = Vec::new();
 
@@ -295,9 +578,9 @@ impl Mesh {
     }
 
     /// Computes normals from a set of faces.
-    pub fn compute_normals(
+    pub fn compute_normals<I: MeshIndex>(
         coordinates: &[Point3<f32>],
-        faces: &[Point3<u16>],
+        faces: &[Point3<I>],
         normals: &mut Vec<Vector3<f32>>,
     ) {
         let mut divisor: Vec<f32> = iter::repeat(0f32).take(coordinates.len()).collect();
@@ -307,8 +590,9 @@ impl Mesh {
 
         // Accumulate normals ...
         for f in faces.iter() {
-            let edge1 = coordinates[f.y as usize] - coordinates[f.x as usize];
-            let edge2 = coordinates[f.z as usize] - coordinates[f.x as usize];
+            let (x, y, z) = (f.x.to_usize(), f.y.to_usize(), f.z.to_usize());
+            let edge1 = coordinates[y] - coordinates[x];
+            let edge2 = coordinates[z] - coordinates[x];
             let cross = edge1.cross(&edge2);
             let normal;
 
@@ -318,13 +602,13 @@ impl Mesh {
                 normal = cross
             }
 
-            normals[f.x as usize] = normals[f.x as usize] + normal;
-            normals[f.y as usize] = normals[f.y as usize] + normal;
-            normals[f.z as usize] = normals[f.z as usize] + normal;
+            normals[x] = normals[x] + normal;
+            normals[y] = normals[y] + normal;
+            normals[z] = normals[z] + normal;
 
-            divisor[f.x as usize] = divisor[f.x as usize] + 1.0;
-            divisor[f.y as usize] = divisor[f.y as usize] + 1.0;
-            divisor[f.z as usize] = divisor[f.z as usize] + 1.0;
+            divisor[x] = divisor[x] + 1.0;
+            divisor[y] = divisor[y] + 1.0;
+            divisor[z] = divisor[z] + 1.0;
         }
 
         // ... and compute the mean
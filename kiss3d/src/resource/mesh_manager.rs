@@ -1,5 +1,6 @@
 //! A resource manager to load meshes.
 
+use crate::error::LoadError;
 use crate::loader::mtl::MtlMaterial;
 use crate::loader::obj;
 use ncollide3d::procedural;
@@ -7,7 +8,6 @@ use ncollide3d::procedural::TriMesh;
 use crate::resource::Mesh;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io::Result as IoResult;
 use std::path::Path;
 use std::rc::Rc;
 
@@ -79,7 +79,7 @@ impl MeshManager {
         path: &Path,
         mtl_dir: &Path,
         geometry_name: &str,
-    ) -> IoResult<Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>)>> {
+    ) -> Result<Vec<(String, Rc<RefCell<Mesh>>, Option<MtlMaterial>)>, LoadError> {
         obj::parse_file(path, mtl_dir, geometry_name).map(|ms| {
             let mut res = Vec::new();
 
@@ -7,7 +7,7 @@ pub use crate::resource::gl_primitive::{GLPrimitive, PrimitiveArray};
 pub use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
 pub use crate::resource::material::{Material, PlanarMaterial};
 pub use crate::resource::material_manager::MaterialManager;
-pub use crate::resource::mesh::Mesh;
+pub use crate::resource::mesh::{Mesh, MeshFaces, MeshIndex};
 pub use crate::resource::mesh_manager::MeshManager;
 pub use crate::resource::planar_material_manager::PlanarMaterialManager;
 pub use crate::resource::planar_mesh::PlanarMesh;
@@ -6,8 +6,8 @@ use na::{Point2, Point3};
 use crate::resource::gpu_vector::{AllocationType, BufferType, GPUVec};
 use crate::resource::ShaderAttribute;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Aggregation of vertices, indices, normals and texture coordinates.
 ///
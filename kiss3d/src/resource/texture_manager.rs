@@ -9,8 +9,8 @@ use std::rc::Rc;
 
 use crate::context::{Context, Texture};
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Filter parameters for a texture.
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
@@ -74,7 +74,7 @@ impl Texture {
     }
 
     /// Sets the wrapping of this texture along the `s` texture coordinate.
-    pub fn set_wrapping_s(&mut self, wrapping: TextureWrapping) {
+    pub fn set_wrapping_s(&self, wrapping: TextureWrapping) {
         let ctxt = Context::get();
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
         let wrap: u32 = wrapping.into();
@@ -82,7 +82,7 @@ impl Texture {
     }
 
     /// Sets the wrapping of this texture along the `t` texture coordinate.
-    pub fn set_wrapping_t(&mut self, wrapping: TextureWrapping) {
+    pub fn set_wrapping_t(&self, wrapping: TextureWrapping) {
         let ctxt = Context::get();
         verify!(ctxt.bind_texture(Context::TEXTURE_2D, Some(&self)));
         let wrap: u32 = wrapping.into();
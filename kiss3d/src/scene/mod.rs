@@ -1,6 +1,6 @@
 //! Everything related to the scene graph.
 
-pub use self::object::{Object, ObjectData};
+pub use self::object::{InstanceData, InstancesBuffer, Object, ObjectData};
 pub use self::planar_object::{PlanarObject, PlanarObjectData};
 pub use self::planar_scene_node::{PlanarSceneNode, PlanarSceneNodeData};
 pub use self::scene_node::{SceneNode, SceneNodeData};
@@ -1,21 +1,170 @@
 //! Data structure of a scene node.
 
 use crate::camera::Camera;
-use crate::light::Light;
-use na::{Isometry3, Point2, Point3, Vector3};
-use crate::resource::{Material, Mesh, Texture, TextureManager};
+use crate::light::{Light, Lights};
+use na::{Isometry3, Matrix3, Point2, Point3, Vector3, Vector4};
+use crate::context::{Context, GLenum, GLintptr};
+use crate::resource::{
+    AllocationType, BufferType, GPUVec, Material, Mesh, MeshFaces, ShaderAttribute, Texture,
+    TextureManager,
+};
 use std::any::Any;
 use std::cell::RefCell;
 use std::path::Path;
 use std::rc::Rc;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
+
+/// One copy of an instanced object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstanceData {
+    /// The position of the instance, in the local space of the object.
+    pub position: Point3<f32>,
+    /// A linear transformation (rotation, scaling, shearing) applied to the instance before it
+    /// is moved to its position.
+    pub deformation: Matrix3<f32>,
+    /// The color of the instance, multiplied by the color of the object.
+    pub color: Point3<f32>,
+}
+
+impl Default for InstanceData {
+    fn default() -> InstanceData {
+        InstanceData {
+            position: Point3::origin(),
+            deformation: Matrix3::identity(),
+            color: Point3::new(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+/// The GPU buffers describing the instances of an object.
+///
+/// Unless set otherwise, objects have a single instance with no deformation and a white color.
+/// A single instance is drawn without instancing, so it works on OpenGL 3.2, OpenGL ES 2.0 and
+/// WebGL without the ANGLE_instanced_arrays extension, which have no attribute divisors. Drawing
+/// several instances on those panics.
+pub struct InstancesBuffer {
+    /// The rows of the affine transformation of each instance, three per instance.
+    pub transforms: GPUVec<Vector4<f32>>,
+    /// The color of each instance.
+    pub colors: GPUVec<Point3<f32>>,
+}
+
+impl InstancesBuffer {
+    /// Creates buffers holding a single default instance.
+    pub fn new() -> InstancesBuffer {
+        let mut buffer = InstancesBuffer {
+            transforms: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+            colors: GPUVec::new(Vec::new(), BufferType::Array, AllocationType::DynamicDraw),
+        };
+        buffer.set(&[InstanceData::default()]);
+        buffer
+    }
+
+    /// The number of instances.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Whether there are no instances, in which case nothing is drawn.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Replaces all the instances.
+    pub fn set(&mut self, instances: &[InstanceData]) {
+        if let Some(transforms) = self.transforms.data_mut() {
+            transforms.clear();
+            for instance in instances {
+                for i in 0..3 {
+                    let row = instance.deformation.row(i);
+                    transforms.push(Vector4::new(row[0], row[1], row[2], instance.position[i]));
+                }
+            }
+        }
+        if let Some(colors) = self.colors.data_mut() {
+            colors.clear();
+            colors.extend(instances.iter().map(|instance| instance.color));
+        }
+    }
+
+    /// Feeds the instances to the attributes of a shader holding the rows of their affine
+    /// transformation and, if any, their color.
+    ///
+    /// A single instance is set as the constant value of the disabled attributes. Otherwise the
+    /// attributes read the buffers, advancing once per instance, which requires instancing.
+    pub fn bind(
+        &mut self,
+        rows: &mut [ShaderAttribute<Vector4<f32>>],
+        color: Option<&mut ShaderAttribute<Point3<f32>>>,
+    ) {
+        if self.len() > 1 {
+            assert!(
+                Context::get().supports_instancing(),
+                "Drawing {} instances requires instancing: OpenGL 3.3, or the \
+                 ANGLE_instanced_arrays extension on WebGL.",
+                self.len()
+            );
+            for (i, attribute) in rows.iter_mut().enumerate() {
+                attribute.enable();
+                attribute.set_divisor(1);
+                attribute.bind_sub_buffer(&mut self.transforms, 2, i);
+            }
+            if let Some(attribute) = color {
+                attribute.enable();
+                attribute.set_divisor(1);
+                attribute.bind(&mut self.colors);
+            }
+        } else {
+            let transforms = self.transforms.data().as_ref().map_or(&[][..], |t| &t[..]);
+            for (attribute, row) in rows.iter_mut().zip(transforms) {
+                attribute.disable();
+                attribute.set_constant(row);
+            }
+            let first = self.colors.data().as_ref().and_then(|colors| colors.first());
+            if let (Some(attribute), Some(c)) = (color, first) {
+                attribute.disable();
+                attribute.set_constant(&Vector4::new(c.x, c.y, c.z, 1.0));
+            }
+        }
+    }
+
+    /// Resets the attributes fed by `bind`, which other shaders may use without instancing.
+    pub fn unbind(
+        &self,
+        rows: &mut [ShaderAttribute<Vector4<f32>>],
+        color: Option<&mut ShaderAttribute<Point3<f32>>>,
+    ) {
+        if self.len() > 1 {
+            for attribute in rows.iter_mut() {
+                attribute.set_divisor(0);
+                attribute.disable();
+            }
+            if let Some(attribute) = color {
+                attribute.set_divisor(0);
+                attribute.disable();
+            }
+        }
+    }
+
+    /// Draws every instance with the attributes fed by `bind`, see `Context::draw_elements`.
+    pub fn draw_elements(&self, mode: GLenum, count: i32, type_: GLenum, offset: GLintptr) {
+        let ctxt = Context::get();
+        match self.len() {
+            0 => {}
+            1 => verify!(ctxt.draw_elements(mode, count, type_, offset)),
+            n => verify!(ctxt.draw_elements_instanced(mode, count, type_, offset, n as i32)),
+        }
+    }
+}
 
 /// Set of data identifying a scene node.
 pub struct ObjectData {
     material: Rc<RefCell<Box<dyn Material + 'static>>>,
     texture: Rc<Texture>,
+    texture_region: Option<(Point2<f32>, Point2<f32>)>,
+    instances: Rc<RefCell<InstancesBuffer>>,
     color: Point3<f32>,
     lines_color: Option<Point3<f32>>,
     wlines: f32,
@@ -32,6 +181,20 @@ impl ObjectData {
         &self.texture
     }
 
+    /// The region of the texture used by this object, if any.
+    ///
+    /// See `Object::set_texture_region`.
+    #[inline]
+    pub fn texture_region(&self) -> Option<&(Point2<f32>, Point2<f32>)> {
+        self.texture_region.as_ref()
+    }
+
+    /// The instances of this object.
+    #[inline]
+    pub fn instances(&self) -> &Rc<RefCell<InstancesBuffer>> {
+        &self.instances
+    }
+
     /// The color of this object.
     #[inline]
     pub fn color(&self) -> &Point3<f32> {
@@ -102,6 +265,8 @@ impl Object {
             color: Point3::new(r, g, b),
             lines_color: None,
             texture,
+            texture_region: None,
+            instances: Rc::new(RefCell::new(InstancesBuffer::new())),
             wlines: 0.0,
             wpoints: 0.0,
             draw_surface: true,
@@ -133,6 +298,26 @@ impl Object {
         );
     }
 
+    #[doc(hidden)]
+    pub fn render_with_lights(
+        &self,
+        transform: &Isometry3<f32>,
+        scale: &Vector3<f32>,
+        pass: usize,
+        camera: &mut dyn Camera,
+        lights: &Lights,
+    ) {
+        self.data.material.borrow_mut().render_with_lights(
+            pass,
+            transform,
+            scale,
+            camera,
+            lights,
+            &self.data,
+            &mut *self.mesh.borrow_mut(),
+        );
+    }
+
     /// Gets the data of this object.
     #[inline]
     pub fn data(&self) -> &ObjectData {
@@ -282,29 +467,91 @@ impl Object {
     }
 
     /// Mutably access the object's faces.
+    ///
+    /// Does nothing if the mesh uses 32-bit indices, see `try_modify_faces`.
     #[inline(always)]
     pub fn modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) {
+        let _ = self.try_modify_faces(f);
+    }
+
+    /// Mutably access the object's faces.
+    ///
+    /// Returns `false`, without calling `f`, if the mesh uses 32-bit indices. Use
+    /// `modify_faces_u32` for those.
+    #[inline(always)]
+    pub fn try_modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) -> bool {
         let bmesh = self.mesh.borrow_mut();
-        let _ = bmesh
-            .faces()
-            .write()
-            .unwrap()
-            .data_mut()
-            .as_mut()
-            .map(|faces| f(faces));
+        match bmesh.faces() {
+            MeshFaces::U16(faces) => {
+                let _ = faces.write().unwrap().data_mut().as_mut().map(|faces| f(faces));
+                true
+            }
+            MeshFaces::U32(_) => false,
+        }
     }
 
     /// Access the object's faces.
+    ///
+    /// Does nothing if the mesh uses 32-bit indices, see `try_read_faces`.
     #[inline(always)]
     pub fn read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) {
+        let _ = self.try_read_faces(f);
+    }
+
+    /// Access the object's faces.
+    ///
+    /// Returns `false`, without calling `f`, if the mesh uses 32-bit indices. Use
+    /// `read_faces_u32` for those.
+    #[inline(always)]
+    pub fn try_read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) -> bool {
         let bmesh = self.mesh.borrow();
-        let _ = bmesh
-            .faces()
-            .read()
-            .unwrap()
-            .data()
-            .as_ref()
-            .map(|faces| f(&faces[..]));
+        match bmesh.faces() {
+            MeshFaces::U16(faces) => {
+                let _ = faces.read().unwrap().data().as_ref().map(|faces| f(&faces[..]));
+                true
+            }
+            MeshFaces::U32(_) => false,
+        }
+    }
+
+    /// Mutably access the object's faces.
+    ///
+    /// Does nothing if the mesh uses 16-bit indices, see `try_modify_faces_u32`.
+    #[inline(always)]
+    pub fn modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) {
+        let _ = self.try_modify_faces_u32(f);
+    }
+
+    /// Mutably access the object's faces.
+    ///
+    /// Returns `false`, without calling `f`, if the mesh uses 16-bit indices. Use
+    /// `modify_faces` for those.
+    #[inline(always)]
+    pub fn try_modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) -> bool {
+        let bmesh = self.mesh.borrow_mut();
+        match bmesh.faces() {
+            MeshFaces::U32(faces) => {
+                let _ = faces.write().unwrap().data_mut().as_mut().map(|faces| f(faces));
+                true
+            }
+            MeshFaces::U16(_) => false,
+        }
+    }
+
+    /// Access the object's faces, whatever the index type of the mesh.
+    ///
+    /// 16-bit indices are widened to 32 bits.
+    #[inline(always)]
+    pub fn read_faces_u32<F: FnMut(&[Point3<u32>])>(&self, f: &mut F) {
+        let bmesh = self.mesh.borrow();
+        match bmesh.faces() {
+            MeshFaces::U32(faces) => {
+                let _ = faces.read().unwrap().data().as_ref().map(|faces| f(&faces[..]));
+            }
+            faces => {
+                let _ = faces.to_u32().map(|faces| f(&faces[..]));
+            }
+        }
     }
 
     /// Mutably access the object's texture coordinates.
@@ -375,4 +622,24 @@ impl Object {
     pub fn set_texture(&mut self, texture: Rc<Texture>) {
         self.data.texture = texture
     }
+
+    /// Restricts the texture of the object to the rectangle between the texture coordinates
+    /// `min` and `max`.
+    ///
+    /// The texture coordinates of the object then repeat inside this region, so a single
+    /// tile of a texture atlas can be repeated across a surface. Use `None` to go back to
+    /// the whole texture.
+    #[inline]
+    pub fn set_texture_region(&mut self, region: Option<(Point2<f32>, Point2<f32>)>) {
+        self.data.texture_region = region
+    }
+
+    /// Draws a copy of this object for each instance, all in a single draw call.
+    ///
+    /// Each instance is transformed in the local space of the object. With no instances, nothing
+    /// is drawn.
+    #[inline]
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        self.data.instances.borrow_mut().set(instances)
+    }
 }
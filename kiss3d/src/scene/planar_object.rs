@@ -8,8 +8,8 @@ use std::cell::RefCell;
 use std::path::Path;
 use std::rc::Rc;
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// Set of data identifying a scene node.
 pub struct PlanarObjectData {
@@ -1,11 +1,19 @@
-use crate::camera::Camera;
-use crate::light::Light;
+use crate::animation::{AnimationClip, ClipState, Skin};
+use crate::camera::{Camera, Frustum};
+use crate::error::LoadError;
+use crate::light::{Light, Lights};
+use crate::loader::gltf::{self, GltfNode};
+use image::DynamicImage;
 use na;
-use na::{Isometry3, Point2, Point3, Translation3, UnitQuaternion, Vector3};
+use na::{
+    Isometry3, Matrix3, Matrix4, Point2, Point3, Translation3, UnitQuaternion, Vector3, Vector4,
+};
+use ncollide3d::bounding_volume::{BoundingVolume, AABB};
 use ncollide3d::procedural;
 use ncollide3d::procedural::TriMesh;
+use ncollide3d::query::{Ray, RayCast};
 use crate::resource::{Material, MaterialManager, Mesh, MeshManager, Texture, TextureManager};
-use crate::scene::Object;
+use crate::scene::{InstanceData, Object};
 use std::cell::{Ref, RefCell, RefMut};
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -23,10 +31,27 @@ pub struct SceneNodeData {
     up_to_date: bool,
     children: Vec<SceneNode>,
     object: Option<Object>,
+    animations: Vec<ClipState>,
+    skin: Option<Skin>,
+    frustum_culling: bool,
+    object_aabb: Option<ObjectAabb>,
+    object_world_aabb: Option<AABB<f32>>,
+    world_aabb: Option<AABB<f32>>,
+    // Whether the bounds of every object of the subtree are known.
+    world_aabb_complete: bool,
     // FIXME: use Weak pointers instead of the raw pointer.
     parent: Option<*const RefCell<SceneNodeData>>,
 }
 
+// The bounds of the object of a node in the node's frame, and the number of changes of the
+// vertices and instances and the scale they were computed from.
+struct ObjectAabb {
+    coords_changes: u64,
+    instances_changes: u64,
+    scale: Vector3<f32>,
+    aabb: Option<AABB<f32>>,
+}
+
 /// A node of the scene graph.
 ///
 /// This may represent a group of other nodes, and/or contain an object that can be rendered.
@@ -81,20 +106,55 @@ impl SceneNodeData {
         self.parent.is_none()
     }
 
-    /// Render the scene graph rooted by this node.
+    /// Render the scene graph rooted by this node, lit by `light` only.
+    ///
+    /// Objects outside of the frustum of the camera are skipped, unless frustum culling is
+    /// disabled.
     pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, light: &Light) {
+        self.render_with_lights(pass, camera, &Lights::new(light.clone()))
+    }
+
+    /// Render the scene graph rooted by this node, lit by all the given lights.
+    ///
+    /// Objects outside of the frustum of the camera are skipped, unless frustum culling is
+    /// disabled.
+    pub fn render_with_lights(&mut self, pass: usize, camera: &mut dyn Camera, lights: &Lights) {
+        let frustum = camera.frustum(pass);
+        if frustum.is_some() {
+            self.update_aabbs(&na::one(), &Vector3::from_element(1.0));
+        }
+
+        if self.visible {
+            self.do_visit(
+                &na::one(),
+                &Vector3::from_element(1.0),
+                frustum.as_ref(),
+                &mut |transform, scale, object| {
+                    object.render_with_lights(transform, scale, pass, camera, lights)
+                },
+            )
+        }
+    }
+
+    /// Calls `f` with the world transform, world scale and object of every visible object of
+    /// the scene graph rooted by this node.
+    pub fn visit_visible_objects(
+        &mut self,
+        f: &mut dyn FnMut(&Isometry3<f32>, &Vector3<f32>, &Object),
+    ) {
         if self.visible {
-            self.do_render(&na::one(), &Vector3::from_element(1.0), pass, camera, light)
+            self.do_visit(&na::one(), &Vector3::from_element(1.0), None, f)
         }
     }
 
-    fn do_render(
+    // Visits the objects of this subtree, skipping those outside of `frustum`. With a frustum, the
+    // bounds must have just been updated by `update_aabbs`.
+    fn do_visit(
         &mut self,
         transform: &Isometry3<f32>,
         scale: &Vector3<f32>,
-        pass: usize,
-        camera: &mut dyn Camera,
-        light: &Light,
+        frustum: Option<&Frustum>,
+        f: &mut dyn FnMut(&Isometry3<f32>, &Vector3<f32>, &Object),
     ) {
         if !self.up_to_date {
             self.up_to_date = true;
@@ -102,27 +162,35 @@ impl SceneNodeData {
             self.world_scale = scale.component_mul(&self.local_scale);
         }
 
+        let frustum = frustum.filter(|_| self.frustum_culling);
+        if let Some(frustum) = frustum {
+            if self.world_aabb_complete
+                && !self
+                    .world_aabb
+                    .as_ref()
+                    .is_some_and(|aabb| frustum.intersects_aabb(aabb))
+            {
+                return;
+            }
+        }
+
         match self.object {
-            Some(ref o) => o.render(
-                &self.world_transform,
-                &self.world_scale,
-                pass,
-                camera,
-                light,
-            ),
+            Some(ref o) => {
+                let culled = match (frustum, &self.object_world_aabb) {
+                    (Some(frustum), Some(aabb)) => !frustum.intersects_aabb(aabb),
+                    _ => false,
+                };
+                if !culled {
+                    f(&self.world_transform, &self.world_scale, o)
+                }
+            }
             None => {}
         }
 
         for c in self.children.iter_mut() {
             let mut bc = c.data_mut();
             if bc.visible {
-                bc.do_render(
-                    &self.world_transform,
-                    &self.world_scale,
-                    pass,
-                    camera,
-                    light,
-                )
+                bc.do_visit(&self.world_transform, &self.world_scale, frustum, f)
             }
         }
     }
@@ -263,20 +331,75 @@ impl SceneNodeData {
 
     /// Mutably accesses the faces of the objects contained by this node and its children.
     ///
-    /// The provided closure is called once per object.
+    /// The provided closure is called once per object using 16-bit indices. Objects using
+    /// 32-bit indices are skipped, see `try_modify_faces`.
     #[inline(always)]
     pub fn modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) {
         self.apply_to_objects_mut(&mut |o| o.modify_faces(f))
     }
 
+    /// Mutably accesses the faces of the objects contained by this node and its children.
+    ///
+    /// The provided closure is called once per object using 16-bit indices. Returns `false` if
+    /// some objects use 32-bit indices and were skipped, see `modify_faces_u32`.
+    #[inline(always)]
+    pub fn try_modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) -> bool {
+        let mut all = true;
+        self.apply_to_objects_mut(&mut |o| all &= o.try_modify_faces(f));
+        all
+    }
+
     /// Accesses the faces of the objects contained by this node and its children.
     ///
-    /// The provided closure is called once per object.
+    /// The provided closure is called once per object using 16-bit indices. Objects using
+    /// 32-bit indices are skipped, see `try_read_faces`.
     #[inline(always)]
     pub fn read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) {
         self.apply_to_objects(&mut |o| o.read_faces(f))
     }
 
+    /// Accesses the faces of the objects contained by this node and its children.
+    ///
+    /// The provided closure is called once per object using 16-bit indices. Returns `false` if
+    /// some objects use 32-bit indices and were skipped, see `read_faces_u32`.
+    #[inline(always)]
+    pub fn try_read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) -> bool {
+        let mut all = true;
+        self.apply_to_objects(&mut |o| all &= o.try_read_faces(f));
+        all
+    }
+
+    /// Mutably accesses the faces of the objects using 32-bit indices contained by this node and
+    /// its children.
+    ///
+    /// The provided closure is called once per object using 32-bit indices. Objects using
+    /// 16-bit indices are skipped, see `try_modify_faces_u32`.
+    #[inline(always)]
+    pub fn modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) {
+        self.apply_to_objects_mut(&mut |o| o.modify_faces_u32(f))
+    }
+
+    /// Mutably accesses the faces of the objects using 32-bit indices contained by this node and
+    /// its children.
+    ///
+    /// The provided closure is called once per object using 32-bit indices. Returns `false` if
+    /// some objects use 16-bit indices and were skipped, see `modify_faces`.
+    #[inline(always)]
+    pub fn try_modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) -> bool {
+        let mut all = true;
+        self.apply_to_objects_mut(&mut |o| all &= o.try_modify_faces_u32(f));
+        all
+    }
+
+    /// Accesses the faces of the objects contained by this node and its children, with indices
+    /// widened to 32 bits.
+    ///
+    /// The provided closure is called once per object.
+    #[inline(always)]
+    pub fn read_faces_u32<F: FnMut(&[Point3<u32>])>(&self, f: &mut F) {
+        self.apply_to_objects(&mut |o| o.read_faces_u32(f))
+    }
+
     /// Mutably accesses the texture coordinates of the objects contained by this node and its
     /// children.
     ///
@@ -365,6 +488,21 @@ impl SceneNodeData {
         self.apply_to_objects_mut(&mut |o| o.set_texture(texture.clone()))
     }
 
+    /// Restricts the texture of the objects contained by this node and its children to a
+    /// region.
+    ///
+    /// See `Object::set_texture_region`.
+    pub fn set_texture_region(&mut self, region: Option<(Point2<f32>, Point2<f32>)>) {
+        self.apply_to_objects_mut(&mut |o| o.set_texture_region(region))
+    }
+
+    /// Sets the instances of the objects contained by this node and its children.
+    ///
+    /// See `Object::set_instances`.
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        self.apply_to_objects_mut(&mut |o| o.set_instances(instances))
+    }
+
     /// Applies a closure to each object contained by this node and its children.
     #[inline]
     pub fn apply_to_objects_mut<F: FnMut(&mut Object)>(&mut self, f: &mut F) {
@@ -454,10 +592,186 @@ impl SceneNodeData {
             let mself: &mut SceneNodeData = mem::transmute(self);
             mself.update();
         }
-        self.local_transform.inverse()
+        self.world_transform.inverse()
+    }
+
+    /// This node world scale, the product of its local scale and those of its parents.
+    ///
+    /// This will force an update of the world transformation of its parents if they have been
+    /// invalidated.
+    #[inline]
+    #[allow(mutable_transmutes)]
+    pub fn world_scale(&self) -> Vector3<f32> {
+        // NOTE: this is to have some kind of laziness without a `&mut self`.
+        unsafe {
+            let mself: &mut SceneNodeData = mem::transmute(self);
+            mself.update();
+        }
+        self.world_scale
+    }
+
+    /// The axis-aligned bounding box of the object of this node, in the frame of this node.
+    ///
+    /// It includes the scale and the instances of the object, and is cached until they or the
+    /// vertices of its mesh change. Returns `None` if this node has no object, or if the vertices
+    /// or the instances of its mesh are not available on the CPU.
+    pub fn local_aabb(&mut self) -> Option<AABB<f32>> {
+        self.update();
+        self.update_object_aabb();
+        self.object_aabb.as_ref().and_then(|b| b.aabb.clone())
+    }
+
+    /// The axis-aligned bounding box, in world space, of the visible objects of this node and of
+    /// its children.
+    ///
+    /// Objects whose bounds are unknown, see `local_aabb`, are ignored. Returns `None` if none of
+    /// the objects have bounds.
+    pub fn world_aabb(&mut self) -> Option<AABB<f32>> {
+        self.update();
+        self.update_aabbs(&na::one(), &Vector3::from_element(1.0));
+        self.world_aabb.clone()
+    }
+
+    /// Enables or disables frustum culling for this node and its children.
+    ///
+    /// Frustum culling skips the objects outside of the volume seen by the camera when rendering.
+    /// It should be disabled for objects whose material moves their vertices. It is enabled by
+    /// default.
+    #[inline]
+    pub fn enable_frustum_culling(&mut self, enabled: bool) {
+        self.frustum_culling = enabled
+    }
+
+    /// Whether frustum culling is enabled for this node and its children.
+    #[inline]
+    pub fn frustum_culling_enabled(&self) -> bool {
+        self.frustum_culling
+    }
+
+    // Updates the world bounds of this subtree, given the world transformation and scale of the
+    // parent.
+    fn update_aabbs(&mut self, transform: &Isometry3<f32>, scale: &Vector3<f32>) {
+        if !self.up_to_date {
+            self.up_to_date = true;
+            self.world_transform = *transform * self.local_transform;
+            self.world_scale = scale.component_mul(&self.local_scale);
+        }
+
+        self.update_object_aabb();
+        let object = self
+            .object_aabb
+            .as_ref()
+            .and_then(|b| b.aabb.as_ref())
+            .map(|aabb| aabb.transform_by(&self.world_transform));
+        let mut complete = self.object.is_none() || object.is_some();
+        let mut aabb = object.clone();
+        self.object_world_aabb = object;
+
+        for c in self.children.iter_mut() {
+            let mut bc = c.data_mut();
+            if !bc.visible {
+                continue;
+            }
+            bc.update_aabbs(&self.world_transform, &self.world_scale);
+            complete &= bc.world_aabb_complete;
+            aabb = match (aabb, &bc.world_aabb) {
+                (Some(aabb), Some(child)) => Some(aabb.merged(child)),
+                (aabb, child) => aabb.or_else(|| child.clone()),
+            };
+        }
+
+        self.world_aabb = aabb;
+        self.world_aabb_complete = complete;
+    }
+
+    // Recomputes the bounds of the object in the frame of this node if its vertices, instances or
+    // scale changed. The world scale must be up to date.
+    fn update_object_aabb(&mut self) {
+        let object = match self.object {
+            Some(ref o) => o,
+            None => {
+                self.object_aabb = None;
+                return;
+            }
+        };
+        let mesh = object.mesh().borrow();
+        let instances = object.data().instances().borrow();
+        let coords_changes = mesh.coords().read().unwrap().changes();
+        let instances_changes = instances.transforms.changes();
+
+        if let Some(cached) = &self.object_aabb {
+            if cached.coords_changes == coords_changes
+                && cached.instances_changes == instances_changes
+                && cached.scale == self.world_scale
+            {
+                return;
+            }
+        }
+
+        let scale = self.world_scale;
+        let aabb = mesh.aabb().and_then(|aabb| {
+            let center = aabb.center().coords.component_mul(&scale);
+            let half_extents = aabb.half_extents().component_mul(&scale).abs();
+            let transforms = instances.transforms.data().as_ref()?;
+
+            transforms
+                .chunks(3)
+                .map(|rows| {
+                    let (deformation, position) = instance_transform(rows);
+                    AABB::from_half_extents(
+                        Point3::from(deformation * center + position),
+                        deformation.abs() * half_extents,
+                    )
+                })
+                .fold(None, |acc: Option<AABB<f32>>, aabb| match acc {
+                    Some(acc) => Some(acc.merged(&aabb)),
+                    None => Some(aabb),
+                })
+        });
+
+        self.object_aabb = Some(ObjectAabb {
+            coords_changes,
+            instances_changes,
+            scale,
+            aabb,
+        });
+    }
+
+    // The time of impact of a ray, in world space, with the object of this node. The bounds of
+    // the object must be up to date.
+    fn object_toi_with_ray(&self, ray: &Ray<f32>, max_toi: f32) -> Option<f32> {
+        let object = self.object.as_ref()?;
+        if let Some(aabb) = &self.object_world_aabb {
+            let _ = aabb.toi_with_ray(&na::one(), ray, max_toi, true)?;
+        }
+
+        let ray = ray.inverse_transform_by(&self.world_transform);
+        let scale = self.world_scale;
+        if scale.iter().any(|s| *s == 0.0) {
+            return None;
+        }
+        let mesh = object.mesh().borrow();
+        let instances = object.data().instances().borrow();
+        let transforms = instances.transforms.data().as_ref()?;
+
+        let mut toi = None;
+        for rows in transforms.chunks(3) {
+            let (deformation, position) = instance_transform(rows);
+            let inverse = match deformation.try_inverse() {
+                Some(inverse) => inverse,
+                None => continue,
+            };
+            // Scaling a ray along with the mesh preserves its times of impact.
+            let origin = (inverse * (ray.origin.coords - position)).component_div(&scale);
+            let dir = (inverse * ray.dir).component_div(&scale);
+            let local = Ray::new(Point3::from(origin), dir);
+            if let Some(t) = mesh.toi_with_ray(&local, toi.unwrap_or(max_toi)) {
+                toi = Some(t)
+            }
+        }
+        toi
     }
 
-    /// Appends a transformation to this node local transformation.
     #[inline]
     pub fn append_transformation(&mut self, t: &Isometry3<f32>) {
         self.invalidate();
@@ -551,6 +865,128 @@ impl SceneNodeData {
         self.local_transform.rotation = r
     }
 
+    /// Attaches an animation clip to this node, replacing any clip of the same name.
+    ///
+    /// The clip does not play until `play_animation` is called with its name.
+    pub fn add_animation(&mut self, clip: AnimationClip) {
+        let state = ClipState::new(clip);
+        match self
+            .animations
+            .iter_mut()
+            .find(|s| s.clip.name() == state.clip.name())
+        {
+            Some(existing) => *existing = state,
+            None => self.animations.push(state),
+        }
+    }
+
+    /// Removes the clips named `name` from this node and its children.
+    pub fn remove_animation(&mut self, name: &str) {
+        self.animations.retain(|s| s.clip.name() != name);
+        for c in self.children.iter_mut() {
+            c.data_mut().remove_animation(name)
+        }
+    }
+
+    /// The clip named `name` attached to this node, if any.
+    pub fn animation(&self, name: &str) -> Option<&AnimationClip> {
+        self.animations
+            .iter()
+            .find(|s| s.clip.name() == name)
+            .map(|s| &s.clip)
+    }
+
+    /// Plays the clips named `name` of this node and its children from their start.
+    ///
+    /// Returns `false` if none of them has a clip of that name.
+    pub fn play_animation(&mut self, name: &str) -> bool {
+        let mut found = false;
+        for state in self.animations.iter_mut().filter(|s| s.clip.name() == name) {
+            state.elapsed = 0.0;
+            state.playing = true;
+            found = true;
+        }
+        for c in self.children.iter_mut() {
+            found |= c.data_mut().play_animation(name);
+        }
+        found
+    }
+
+    /// Stops the clips named `name` of this node and its children, leaving the nodes in their
+    /// current pose.
+    pub fn stop_animation(&mut self, name: &str) {
+        for state in self.animations.iter_mut().filter(|s| s.clip.name() == name) {
+            state.playing = false;
+        }
+        for c in self.children.iter_mut() {
+            c.data_mut().stop_animation(name)
+        }
+    }
+
+    /// Sets how fast the clips named `name` of this node and its children are played, 1 being
+    /// their normal speed.
+    pub fn set_animation_speed(&mut self, name: &str, speed: f32) {
+        for state in self.animations.iter_mut().filter(|s| s.clip.name() == name) {
+            state.speed = speed;
+        }
+        for c in self.children.iter_mut() {
+            c.data_mut().set_animation_speed(name, speed)
+        }
+    }
+
+    /// Whether a clip named `name` of this node or of its children is playing.
+    pub fn is_animation_playing(&self, name: &str) -> bool {
+        self.animations
+            .iter()
+            .any(|s| s.playing && s.clip.name() == name)
+            || self
+                .children
+                .iter()
+                .any(|c| c.data().is_animation_playing(name))
+    }
+
+    /// The skin deforming the mesh of this node, if any.
+    pub fn skin(&self) -> Option<&Skin> {
+        self.skin.as_ref()
+    }
+
+    // Advances the playing clips of this node and its children by `dt` seconds and poses the
+    // nodes accordingly.
+    fn advance_animations(&mut self, dt: f32) {
+        let mut poses = Vec::new();
+        for state in self.animations.iter_mut().filter(|s| s.playing) {
+            state.elapsed += dt * state.speed;
+            let (time, finished) = state.clip.local_time(state.elapsed);
+            state.playing = !finished;
+            let clip = &state.clip;
+            poses.push((
+                clip.translation(time),
+                clip.rotation(time),
+                clip.scale(time),
+                clip.color(time),
+            ));
+        }
+
+        for (translation, rotation, scale, color) in poses {
+            if let Some(t) = translation {
+                self.set_local_translation(Translation3::from(t));
+            }
+            if let Some(r) = rotation {
+                self.set_local_rotation(r);
+            }
+            if let Some(s) = scale {
+                self.set_local_scale(s.x, s.y, s.z);
+            }
+            if let Some(c) = color {
+                self.set_color(c.x, c.y, c.z);
+            }
+        }
+
+        for c in self.children.iter_mut() {
+            c.data_mut().advance_animations(dt)
+        }
+    }
+
     fn invalidate(&mut self) {
         self.up_to_date = false;
 
@@ -572,8 +1008,8 @@ impl SceneNodeData {
                     let mut dp = (**p).borrow_mut();
 
                     dp.update();
-                    self.world_transform = self.local_transform * dp.world_transform;
-                    self.world_scale = self.local_scale.component_mul(&dp.local_scale);
+                    self.world_transform = dp.world_transform * self.local_transform;
+                    self.world_scale = dp.world_scale.component_mul(&self.local_scale);
                     self.up_to_date = true;
                     return;
                 },
@@ -588,6 +1024,15 @@ impl SceneNodeData {
     }
 }
 
+impl PartialEq for SceneNode {
+    /// Two nodes are equal if they are the same node, not if they hold the same data.
+    fn eq(&self, other: &SceneNode) -> bool {
+        Rc::ptr_eq(&self.data, &other.data)
+    }
+}
+
+impl Eq for SceneNode {}
+
 impl SceneNode {
     /// Creates a new scene node that is not rooted.
     pub fn new(
@@ -604,6 +1049,13 @@ impl SceneNode {
             up_to_date: false,
             children: Vec::new(),
             object: object,
+            animations: Vec::new(),
+            skin: None,
+            frustum_culling: true,
+            object_aabb: None,
+            object_world_aabb: None,
+            world_aabb: None,
+            world_aabb_complete: false,
             parent: None,
         };
 
@@ -804,12 +1256,30 @@ impl SceneNode {
     ///
     /// This will create a new node serving as a root of the scene described by the obj file. This
     /// newly created node is added to this node's children.
+    ///
+    /// # Panics
+    /// If the obj file, or one of the mtl files it refers to, cannot be read or is malformed. See
+    /// `try_add_obj` for a version returning an error instead.
     pub fn add_obj(&mut self, path: &Path, mtl_dir: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.try_add_obj(path, mtl_dir, scale)
+            .unwrap_or_else(|e| panic!("Unable to load the obj file {}: {}", path.display(), e))
+    }
+
+    /// Creates and adds multiple nodes created from an obj file, or returns why it could not be
+    /// loaded.
+    ///
+    /// Nothing is added to this node if an error is returned.
+    pub fn try_add_obj(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> Result<SceneNode, LoadError> {
         let tex = TextureManager::get_global_manager(|tm| tm.get_default());
         let mat = MaterialManager::get_global_manager(|mm| mm.get_default());
 
-        // FIXME: is there some error-handling stuff to do here instead of the `let _`.
-        let result = MeshManager::load_obj(path, mtl_dir, path.to_str().unwrap()).map(|objs| {
+        let name = path.to_string_lossy();
+        MeshManager::load_obj(path, mtl_dir, &name).map(|objs| {
             let mut root;
 
             let self_root = objs.len() == 1;
@@ -860,9 +1330,63 @@ impl SceneNode {
             } else {
                 root
             }
-        });
+        })
+    }
 
-        result.unwrap()
+    /// Creates and adds multiple nodes created from a glTF file, either `.gltf` or `.glb`.
+    ///
+    /// This will create a new node serving as a root of the scene described by the file, with
+    /// the scale `scale`. This newly created node is added to this node's children. Its
+    /// descendants mirror the node hierarchy of the file. As scene nodes do not scale the
+    /// translations of their children, the translations of the nodes of the file are scaled when
+    /// they are created: changing the scale of the returned node later only scales the meshes.
+    ///
+    /// # Panics
+    /// If the file, or one of the buffers or images it refers to, cannot be read or is malformed.
+    /// See `try_add_gltf` for a version returning an error instead.
+    pub fn add_gltf(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.try_add_gltf(path, scale)
+            .unwrap_or_else(|e| panic!("Unable to load the glTF file {}: {}", path.display(), e))
+    }
+
+    /// Creates and adds multiple nodes created from a glTF file, or returns why it could not be
+    /// loaded.
+    ///
+    /// Nothing is added to this node if an error is returned.
+    pub fn try_add_gltf(
+        &mut self,
+        path: &Path,
+        scale: Vector3<f32>,
+    ) -> Result<SceneNode, LoadError> {
+        let scene = gltf::parse_file(path)?;
+        let name = path.to_string_lossy();
+
+        // Images are only uploaded once a material uses them.
+        let mut images: Vec<_> = scene.images.into_iter().map(Some).collect();
+        let mut textures: Vec<Option<Rc<Texture>>> = vec![None; images.len()];
+        let mut texture = |i: usize| {
+            let tex = textures[i].get_or_insert_with(|| {
+                let image = match images[i].take().unwrap() {
+                    image @ DynamicImage::ImageRgb8(_) => image,
+                    image @ DynamicImage::ImageRgba8(_) => image,
+                    image => DynamicImage::ImageRgba8(image.to_rgba()),
+                };
+                let mut image = Some(image);
+                let tex_name = format!("{}#image{}", name, i);
+                TextureManager::get_global_manager(|tm| {
+                    tm.add_image(image.take().unwrap(), &tex_name)
+                })
+            });
+            tex.clone()
+        };
+
+        let mut root = SceneNode::new(scale, na::one(), None);
+        for node in scene.nodes {
+            add_gltf_node(&mut root, scale, node, &mut texture);
+        }
+
+        self.add_child(root.clone());
+        Ok(root)
     }
 
     /// Applies a closure to each object contained by this node and its children.
@@ -891,11 +1415,16 @@ impl SceneNode {
     //
     //
 
-    /// Render the scene graph rooted by this node.
+    /// Render the scene graph rooted by this node, lit by `light` only.
     pub fn render(&mut self, pass: usize, camera: &mut dyn Camera, light: &Light) {
         self.data_mut().render(pass, camera, light)
     }
 
+    /// Render the scene graph rooted by this node, lit by all the given lights.
+    pub fn render_with_lights(&mut self, pass: usize, camera: &mut dyn Camera, lights: &Lights) {
+        self.data_mut().render_with_lights(pass, camera, lights)
+    }
+
     /// Sets the material of the objects contained by this node and its children.
     #[inline]
     pub fn set_material(&mut self, material: Rc<RefCell<Box<dyn Material + 'static>>>) {
@@ -981,20 +1510,69 @@ impl SceneNode {
 
     /// Mutably accesses the faces of the objects contained by this node and its children.
     ///
-    /// The provided closure is called once per object.
+    /// The provided closure is called once per object using 16-bit indices. Objects using
+    /// 32-bit indices are skipped, see `try_modify_faces`.
     #[inline(always)]
     pub fn modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) {
         self.data_mut().modify_faces(f)
     }
 
+    /// Mutably accesses the faces of the objects contained by this node and its children.
+    ///
+    /// The provided closure is called once per object using 16-bit indices. Returns `false` if
+    /// some objects use 32-bit indices and were skipped, see `modify_faces_u32`.
+    #[inline(always)]
+    pub fn try_modify_faces<F: FnMut(&mut Vec<Point3<u16>>)>(&mut self, f: &mut F) -> bool {
+        self.data_mut().try_modify_faces(f)
+    }
+
     /// Accesses the faces of the objects contained by this node and its children.
     ///
-    /// The provided closure is called once per object.
+    /// The provided closure is called once per object using 16-bit indices. Objects using
+    /// 32-bit indices are skipped, see `try_read_faces`.
     #[inline(always)]
     pub fn read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) {
         self.data().read_faces(f)
     }
 
+    /// Accesses the faces of the objects contained by this node and its children.
+    ///
+    /// The provided closure is called once per object using 16-bit indices. Returns `false` if
+    /// some objects use 32-bit indices and were skipped, see `read_faces_u32`.
+    #[inline(always)]
+    pub fn try_read_faces<F: FnMut(&[Point3<u16>])>(&self, f: &mut F) -> bool {
+        self.data().try_read_faces(f)
+    }
+
+    /// Mutably accesses the faces of the objects using 32-bit indices contained by this node and
+    /// its children.
+    ///
+    /// The provided closure is called once per object using 32-bit indices. Objects using
+    /// 16-bit indices are skipped, see `try_modify_faces_u32`.
+    #[inline(always)]
+    pub fn modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) {
+        self.data_mut().modify_faces_u32(f)
+    }
+
+    /// Mutably accesses the faces of the objects using 32-bit indices contained by this node and
+    /// its children.
+    ///
+    /// The provided closure is called once per object using 32-bit indices. Returns `false` if
+    /// some objects use 16-bit indices and were skipped, see `modify_faces`.
+    #[inline(always)]
+    pub fn try_modify_faces_u32<F: FnMut(&mut Vec<Point3<u32>>)>(&mut self, f: &mut F) -> bool {
+        self.data_mut().try_modify_faces_u32(f)
+    }
+
+    /// Accesses the faces of the objects contained by this node and its children, with indices
+    /// widened to 32 bits.
+    ///
+    /// The provided closure is called once per object.
+    #[inline(always)]
+    pub fn read_faces_u32<F: FnMut(&[Point3<u32>])>(&self, f: &mut F) {
+        self.data().read_faces_u32(f)
+    }
+
     /// Mutably accesses the texture coordinates of the objects contained by this node and its
     /// children.
     ///
@@ -1070,6 +1648,197 @@ impl SceneNode {
         self.data_mut().set_texture(texture)
     }
 
+    /// Restricts the texture of the objects contained by this node and its children to a
+    /// region.
+    ///
+    /// See `Object::set_texture_region`.
+    pub fn set_texture_region(&mut self, region: Option<(Point2<f32>, Point2<f32>)>) {
+        self.data_mut().set_texture_region(region)
+    }
+
+    /// Sets the instances of the objects contained by this node and its children.
+    ///
+    /// See `Object::set_instances`.
+    pub fn set_instances(&mut self, instances: &[InstanceData]) {
+        self.data_mut().set_instances(instances)
+    }
+
+    /// Attaches an animation clip to this node, replacing any clip of the same name.
+    ///
+    /// See `SceneNodeData::add_animation`.
+    pub fn add_animation(&mut self, clip: AnimationClip) {
+        self.data_mut().add_animation(clip)
+    }
+
+    /// Removes the clips named `name` from this node and its children.
+    pub fn remove_animation(&mut self, name: &str) {
+        self.data_mut().remove_animation(name)
+    }
+
+    /// Plays the clips named `name` of this node and its children from their start.
+    ///
+    /// Returns `false` if none of them has a clip of that name.
+    pub fn play_animation(&mut self, name: &str) -> bool {
+        self.data_mut().play_animation(name)
+    }
+
+    /// Stops the clips named `name` of this node and its children, leaving the nodes in their
+    /// current pose.
+    pub fn stop_animation(&mut self, name: &str) {
+        self.data_mut().stop_animation(name)
+    }
+
+    /// Sets how fast the clips named `name` of this node and its children are played, 1 being
+    /// their normal speed.
+    pub fn set_animation_speed(&mut self, name: &str, speed: f32) {
+        self.data_mut().set_animation_speed(name, speed)
+    }
+
+    /// Whether a clip named `name` of this node or of its children is playing.
+    pub fn is_animation_playing(&self, name: &str) -> bool {
+        self.data().is_animation_playing(name)
+    }
+
+    /// Deforms the mesh of this node with a skin, or stops deforming it with `None`.
+    ///
+    /// The current vertices of the mesh are its bind pose, and the mesh is deformed each time the
+    /// animations are updated.
+    ///
+    /// # Failure
+    /// Fails if this node has no object, or if the skin does not match its mesh.
+    pub fn set_skin(&mut self, skin: Option<Skin>) {
+        let mut skin = match skin {
+            Some(skin) => skin,
+            None => {
+                let old = self.data_mut().skin.take();
+                // Restore the bind pose.
+                if let (Some(old), Some(object)) = (old, self.data().object()) {
+                    let mesh = object.mesh().borrow();
+                    let (coords, normals) = old.bind_pose();
+                    *mesh.coords().write().unwrap().data_mut() = Some(coords.to_vec());
+                    if !normals.is_empty() {
+                        *mesh.normals().write().unwrap().data_mut() = Some(normals.to_vec());
+                    }
+                }
+                return;
+            }
+        };
+
+        let node = world_matrix(self);
+        let joints: Vec<_> = skin.joints().iter().map(world_matrix).collect();
+        {
+            let data = self.data();
+            let object = data
+                .object()
+                .expect("Only a node containing an Object can be skinned.");
+            skin.bind(&object.mesh().borrow(), &node, &joints);
+        }
+        self.data_mut().skin = Some(skin);
+    }
+
+    /// Advances the playing animations of this node and its children by `dt` seconds, then
+    /// deforms their skinned meshes.
+    ///
+    /// The window does this for its scene every frame, with the time elapsed since the previous
+    /// frame.
+    pub fn update_animations(&mut self, dt: f32) {
+        self.data_mut().advance_animations(dt);
+
+        let mut skinned = Vec::new();
+        self.collect_skinned(&mut skinned);
+        for node in skinned {
+            // The joints must not be borrowed while their world transformation is updated, as it
+            // may borrow the skinned node.
+            let joints: Vec<SceneNode> = match node.data().skin() {
+                Some(skin) => skin.joints().to_vec(),
+                None => continue,
+            };
+            let matrix = world_matrix(&node);
+            let joints: Vec<_> = joints.iter().map(world_matrix).collect();
+
+            let data = node.data();
+            if let (Some(skin), Some(object)) = (data.skin(), data.object()) {
+                skin.apply(&object.mesh().borrow(), &matrix, &joints);
+            }
+        }
+    }
+
+    /// The axis-aligned bounding box of the object of this node, in the frame of this node.
+    ///
+    /// See `SceneNodeData::local_aabb`.
+    pub fn local_aabb(&mut self) -> Option<AABB<f32>> {
+        self.data_mut().local_aabb()
+    }
+
+    /// The axis-aligned bounding box, in world space, of the visible objects of this node and of
+    /// its children.
+    ///
+    /// See `SceneNodeData::world_aabb`.
+    pub fn world_aabb(&mut self) -> Option<AABB<f32>> {
+        self.data_mut().world_aabb()
+    }
+
+    /// Enables or disables frustum culling for this node and its children.
+    ///
+    /// See `SceneNodeData::enable_frustum_culling`.
+    pub fn enable_frustum_culling(&mut self, enabled: bool) {
+        self.data_mut().enable_frustum_culling(enabled)
+    }
+
+    /// Finds the first visible object of this node or of its children hit by a ray in world
+    /// space.
+    ///
+    /// Returns the node of the object and the time of impact, which is the distance to the hit
+    /// if the direction of the ray is normalized. Hits after `max_toi` are ignored. Both sides of
+    /// the faces are hit, and objects whose vertices or faces are not available on the CPU are
+    /// never hit.
+    pub fn cast_ray(&mut self, ray: &Ray<f32>, max_toi: f32) -> Option<(SceneNode, f32)> {
+        {
+            let mut data = self.data_mut();
+            if !data.visible || data.world_aabb().is_none() {
+                return None;
+            }
+        }
+
+        let mut hit = None;
+        self.do_cast_ray(ray, max_toi, &mut hit);
+        hit
+    }
+
+    // The world bounds of this subtree must be up to date.
+    fn do_cast_ray(&self, ray: &Ray<f32>, max_toi: f32, hit: &mut Option<(SceneNode, f32)>) {
+        let data = self.data();
+        if !data.visible {
+            return;
+        }
+
+        let toi = |hit: &Option<(SceneNode, f32)>| hit.as_ref().map_or(max_toi, |h| h.1);
+        if data.world_aabb_complete {
+            match &data.world_aabb {
+                Some(aabb) if aabb.toi_with_ray(&na::one(), ray, toi(hit), true).is_some() => {}
+                _ => return,
+            }
+        }
+
+        if let Some(t) = data.object_toi_with_ray(ray, toi(hit)) {
+            *hit = Some((self.clone(), t));
+        }
+
+        for c in data.children.iter() {
+            c.do_cast_ray(ray, max_toi, hit)
+        }
+    }
+
+    fn collect_skinned(&self, out: &mut Vec<SceneNode>) {
+        let data = self.data();
+        if data.skin.is_some() {
+            out.push(self.clone());
+        }
+        for c in data.children.iter() {
+            c.collect_skinned(out)
+        }
+    }
+
     /// Sets the local scaling factors of the object.
     #[inline]
     pub fn set_local_scale(&mut self, sx: f32, sy: f32, sz: f32) {
@@ -1143,3 +1912,63 @@ impl SceneNode {
         self.data_mut().set_local_rotation(r)
     }
 }
+
+// Scene nodes do not scale the translations of their children, so the scale of the ancestors,
+// `parent_scale`, is applied to the translation of the node here.
+// The deformation and position of an instance, from the rows of its transformation.
+fn instance_transform(rows: &[Vector4<f32>]) -> (Matrix3<f32>, Vector3<f32>) {
+    let deformation = Matrix3::new(
+        rows[0].x, rows[0].y, rows[0].z, rows[1].x, rows[1].y, rows[1].z, rows[2].x, rows[2].y,
+        rows[2].z,
+    );
+    (deformation, Vector3::new(rows[0].w, rows[1].w, rows[2].w))
+}
+
+// The world transformation of a node, including its world scale.
+fn world_matrix(node: &SceneNode) -> Matrix4<f32> {
+    let data = node.data();
+    data.world_transformation().to_homogeneous()
+        * Matrix4::new_nonuniform_scaling(&data.world_scale())
+}
+
+fn add_gltf_node(
+    parent: &mut SceneNode,
+    parent_scale: Vector3<f32>,
+    node: GltfNode,
+    texture: &mut dyn FnMut(usize) -> Rc<Texture>,
+) {
+    let mut transform = node.transform;
+    transform.translation.vector.component_mul_assign(&parent_scale);
+    let scale = parent_scale.component_mul(&node.scale);
+    let mut res = SceneNode::new(node.scale, transform, None);
+
+    for primitive in node.primitives {
+        let material = &primitive.material;
+        let tex = TextureManager::get_global_manager(|tm| tm.get_default());
+        let mat = MaterialManager::get_global_manager(|mm| {
+            if material.vertex_colors {
+                mm.get("vertex_color").unwrap()
+            } else {
+                mm.get_default()
+            }
+        });
+        let [r, g, b, _] = material.base_color;
+        let mut object = Object::new(primitive.mesh, r, g, b, tex, mat);
+        object.enable_backface_culling(!material.double_sided);
+
+        if let Some(t) = &material.base_color_texture {
+            let tex = texture(t.image);
+            tex.set_wrapping_s(t.wrap_s);
+            tex.set_wrapping_t(t.wrap_t);
+            object.set_texture(tex);
+        }
+
+        let _ = res.add_object(Vector3::from_element(1.0), na::one(), object);
+    }
+
+    for child in node.children {
+        add_gltf_node(&mut res, scale, child, texture);
+    }
+
+    parent.add_child(res);
+}
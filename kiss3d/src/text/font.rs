@@ -5,10 +5,13 @@ use std::path::Path;
 use std::rc::Rc;
 use std::sync::Once;
 
+use na::{Point3, Vector2};
 use rusttype;
 
-#[path = "../error.rs"]
-mod error;
+use crate::text::TextLayout;
+
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 /// A ttf font.
 pub struct Font {
@@ -57,4 +60,14 @@ impl Font {
     pub fn uid(font: &Rc<Font>) -> usize {
         (*font).borrow() as *const Font as usize
     }
+
+    /// The width and height of `text` drawn with the specified font instance and `scale`, in the
+    /// units of `Window::draw_text`.
+    ///
+    /// Lines are only broken at line feeds. Use a `TextLayout` to wrap them.
+    pub fn measure(font: &Rc<Font>, text: &str, scale: f32) -> Vector2<f32> {
+        TextLayout::new(font, scale)
+            .with_span(text, &Point3::origin())
+            .size()
+    }
 }
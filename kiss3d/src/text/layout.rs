@@ -0,0 +1,376 @@
+//! Measuring, wrapping and aligning text before drawing it.
+
+use na::{Point2, Point3, Vector2};
+use rusttype::{GlyphId, Scale};
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::text::Font;
+
+/// How the lines of a text are aligned horizontally.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Lines start on the left of the text.
+    Left,
+    /// Lines are centered.
+    Center,
+    /// Lines end on the right of the text.
+    Right,
+}
+
+/// The point of the screen a text is placed relative to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextAnchor {
+    /// The top-left corner of the screen.
+    TopLeft,
+    /// The middle of the top edge of the screen.
+    Top,
+    /// The top-right corner of the screen.
+    TopRight,
+    /// The middle of the left edge of the screen.
+    Left,
+    /// The center of the screen.
+    Center,
+    /// The middle of the right edge of the screen.
+    Right,
+    /// The bottom-left corner of the screen.
+    BottomLeft,
+    /// The middle of the bottom edge of the screen.
+    Bottom,
+    /// The bottom-right corner of the screen.
+    BottomRight,
+}
+
+impl TextAnchor {
+    /// The position of the top-left corner of a text of size `size` anchored on a screen of size
+    /// `screen`, `margin` away from the edges it is anchored to.
+    pub fn position(self, size: &Vector2<f32>, screen: &Vector2<f32>, margin: f32) -> Point2<f32> {
+        let place = |fraction: f32, size: f32, screen: f32| {
+            let margin = if fraction == 0.5 { 0.0 } else { margin };
+            margin + (screen - size - 2.0 * margin) * fraction
+        };
+        let (fx, fy) = match self {
+            TextAnchor::TopLeft => (0.0, 0.0),
+            TextAnchor::Top => (0.5, 0.0),
+            TextAnchor::TopRight => (1.0, 0.0),
+            TextAnchor::Left => (0.0, 0.5),
+            TextAnchor::Center => (0.5, 0.5),
+            TextAnchor::Right => (1.0, 0.5),
+            TextAnchor::BottomLeft => (0.0, 1.0),
+            TextAnchor::Bottom => (0.5, 1.0),
+            TextAnchor::BottomRight => (1.0, 1.0),
+        };
+
+        Point2::new(place(fx, size.x, screen.x), place(fy, size.y, screen.y))
+    }
+}
+
+/// A character of a laid out text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LayoutGlyph {
+    /// The character.
+    pub character: char,
+    /// The index of the span this character comes from.
+    pub span: usize,
+    /// The index of the line this character is on.
+    pub line: usize,
+    /// The position of the character relative to the top-left corner of the text: the left of
+    /// its advance and the top of its line.
+    pub position: Point2<f32>,
+    /// The horizontal distance from this character to the next one.
+    pub advance: f32,
+    /// The color of the span this character comes from.
+    pub color: Point3<f32>,
+}
+
+/// A line of a laid out text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextLine {
+    /// The indices of the glyphs on this line.
+    pub glyphs: Range<usize>,
+    /// The position of the start of this line relative to the top-left corner of the text.
+    pub position: Point2<f32>,
+    /// The width of this line, without its trailing whitespaces.
+    pub width: f32,
+}
+
+// A run of text drawn with the same color.
+struct TextSpan {
+    text: String,
+    color: Point3<f32>,
+}
+
+// A character placed on a line being wrapped.
+#[derive(Copy, Clone)]
+struct Placed {
+    character: char,
+    span: usize,
+    id: GlyphId,
+    x: f32,
+    advance: f32,
+}
+
+/// Text made of differently colored spans, wrapped to a maximum width and aligned.
+///
+/// The layout only needs the font, so the position of every character is known without drawing
+/// anything. Sizes are in the units of `Window::draw_text`. Lines are broken at line feeds, and
+/// at whitespaces before they get wider than the maximum width. Words wider than the maximum
+/// width are broken anywhere.
+pub struct TextLayout {
+    font: Rc<Font>,
+    scale: f32,
+    spans: Vec<TextSpan>,
+    max_width: Option<f32>,
+    align: TextAlign,
+    line_spacing: f32,
+    glyphs: Vec<LayoutGlyph>,
+    lines: Vec<TextLine>,
+    size: Vector2<f32>,
+}
+
+impl TextLayout {
+    /// Creates an empty text of the font `font` with characters of height `scale`.
+    pub fn new(font: &Rc<Font>, scale: f32) -> TextLayout {
+        TextLayout {
+            font: font.clone(),
+            scale,
+            spans: Vec::new(),
+            max_width: None,
+            align: TextAlign::Left,
+            line_spacing: 1.0,
+            glyphs: Vec::new(),
+            lines: Vec::new(),
+            size: Vector2::zeros(),
+        }
+    }
+
+    /// Appends a span of text of the given color, and returns this text.
+    pub fn with_span(mut self, text: &str, color: &Point3<f32>) -> Self {
+        self.push_span(text, color);
+        self
+    }
+
+    /// Sets the width past which lines are wrapped, and returns this text.
+    pub fn with_max_width(mut self, max_width: f32) -> Self {
+        self.set_max_width(Some(max_width));
+        self
+    }
+
+    /// Sets the alignment of the lines, and returns this text.
+    pub fn with_align(mut self, align: TextAlign) -> Self {
+        self.set_align(align);
+        self
+    }
+
+    /// Sets the distance between two lines, as a multiple of the height of a line, and returns
+    /// this text.
+    pub fn with_line_spacing(mut self, line_spacing: f32) -> Self {
+        self.set_line_spacing(line_spacing);
+        self
+    }
+
+    /// Appends a span of text of the given color.
+    pub fn push_span(&mut self, text: &str, color: &Point3<f32>) {
+        self.spans.push(TextSpan {
+            text: text.to_string(),
+            color: *color,
+        });
+        self.update();
+    }
+
+    /// Removes every span of this text.
+    pub fn clear(&mut self) {
+        self.spans.clear();
+        self.update();
+    }
+
+    /// Sets the width past which lines are wrapped, or `None` to only break lines at line feeds.
+    pub fn set_max_width(&mut self, max_width: Option<f32>) {
+        self.max_width = max_width;
+        self.update();
+    }
+
+    /// Sets the alignment of the lines. Defaults to `TextAlign::Left`.
+    pub fn set_align(&mut self, align: TextAlign) {
+        self.align = align;
+        self.update();
+    }
+
+    /// Sets the distance between two lines, as a multiple of the height of a line. Defaults to 1.
+    pub fn set_line_spacing(&mut self, line_spacing: f32) {
+        self.line_spacing = line_spacing;
+        self.update();
+    }
+
+    /// The font of this text.
+    pub fn font(&self) -> &Rc<Font> {
+        &self.font
+    }
+
+    /// The height of the characters of this text.
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The height of one line of this text, without spacing.
+    pub fn line_height(&self) -> f32 {
+        let metrics = self.font.font().v_metrics(Scale::uniform(self.scale));
+        metrics.ascent - metrics.descent
+    }
+
+    /// The width of the widest line and the height of all the lines of this text.
+    ///
+    /// Lines are aligned relative to the widest one.
+    pub fn size(&self) -> Vector2<f32> {
+        self.size
+    }
+
+    /// Every character of this text, except the line feeds and the whitespaces where lines are
+    /// wrapped.
+    pub fn glyphs(&self) -> &[LayoutGlyph] {
+        &self.glyphs
+    }
+
+    /// The lines of this text.
+    pub fn lines(&self) -> &[TextLine] {
+        &self.lines
+    }
+
+    /// The runs of consecutive characters of the same span on the same line, with the position
+    /// of their first character.
+    pub(crate) fn runs(&self) -> impl Iterator<Item = (String, Point2<f32>, Point3<f32>)> + '_ {
+        let glyphs = &self.glyphs;
+        let mut start = 0;
+
+        std::iter::from_fn(move || {
+            let first = glyphs.get(start)?;
+            let len = glyphs[start..]
+                .iter()
+                .take_while(|g| g.line == first.line && g.span == first.span)
+                .count();
+            let text = glyphs[start..start + len].iter().map(|g| g.character).collect();
+            start += len;
+            Some((text, first.position, first.color))
+        })
+    }
+
+    // Lays the spans out again.
+    fn update(&mut self) {
+        let font = self.font.font();
+        let scale = Scale::uniform(self.scale);
+        let max_width = self.max_width.unwrap_or(f32::INFINITY);
+
+        let mut lines: Vec<Vec<Placed>> = Vec::new();
+        let mut line: Vec<Placed> = Vec::new();
+        let mut has_text = false;
+
+        for (span, character) in self
+            .spans
+            .iter()
+            .enumerate()
+            .flat_map(|(i, s)| s.text.chars().map(move |c| (i, c)))
+        {
+            if character == '\n' {
+                lines.push(std::mem::take(&mut line));
+                has_text = true;
+                continue;
+            }
+            has_text = true;
+
+            let glyph = font.glyph(character).scaled(scale);
+            let mut placed = Placed {
+                character,
+                span,
+                id: glyph.id(),
+                x: 0.0,
+                advance: glyph.h_metrics().advance_width,
+            };
+            place(font, scale, line.last(), &mut placed);
+
+            if !character.is_whitespace()
+                && placed.x + placed.advance > max_width
+                && line.iter().any(|p| !p.character.is_whitespace())
+            {
+                // Move the word being written to the next line, or break it if it fills the
+                // whole line.
+                let word_start = line
+                    .iter()
+                    .rposition(|p| p.character.is_whitespace())
+                    .map_or(line.len(), |i| i + 1);
+                let mut word = line.split_off(word_start);
+                if line.iter().all(|p| p.character.is_whitespace()) {
+                    line.append(&mut word);
+                }
+                lines.push(std::mem::take(&mut line));
+
+                for mut p in word {
+                    place(font, scale, line.last(), &mut p);
+                    line.push(p);
+                }
+                place(font, scale, line.last(), &mut placed);
+            }
+
+            line.push(placed);
+        }
+
+        if has_text {
+            lines.push(line);
+        }
+
+        for line in &mut lines {
+            while line.last().is_some_and(|p| p.character.is_whitespace()) {
+                let _ = line.pop();
+            }
+        }
+
+        let width_of = |line: &[Placed]| line.last().map_or(0.0, |p| p.x + p.advance);
+        let width = lines.iter().map(|l| width_of(l)).fold(0.0, f32::max);
+        let line_height = self.line_height();
+        let line_advance = line_height * self.line_spacing;
+        let alignment = match self.align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => 0.5,
+            TextAlign::Right => 1.0,
+        };
+
+        let spans = &self.spans;
+        self.glyphs.clear();
+        self.lines.clear();
+
+        for (i, line) in lines.iter().enumerate() {
+            let line_width = width_of(line);
+            let position = Point2::new((width - line_width) * alignment, i as f32 * line_advance);
+            let start = self.glyphs.len();
+
+            self.glyphs.extend(line.iter().map(|p| LayoutGlyph {
+                character: p.character,
+                span: p.span,
+                line: i,
+                position: Point2::new(position.x + p.x, position.y),
+                advance: p.advance,
+                color: spans[p.span].color,
+            }));
+            self.lines.push(TextLine {
+                glyphs: start..self.glyphs.len(),
+                position,
+                width: line_width,
+            });
+        }
+
+        let height = match self.lines.len() {
+            0 => 0.0,
+            n => (n - 1) as f32 * line_advance + line_height,
+        };
+        self.size = Vector2::new(width, height);
+    }
+}
+
+// Places `glyph` after `previous` on the same line, kerning them the way `rusttype` does.
+fn place(font: &rusttype::Font, scale: Scale, previous: Option<&Placed>, glyph: &mut Placed) {
+    glyph.x = match previous {
+        Some(previous) => {
+            previous.x + previous.advance + font.pair_kerning(scale, previous.id, glyph.id)
+        }
+        None => 0.0,
+    };
+}
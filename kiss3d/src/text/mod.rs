@@ -2,8 +2,12 @@
 
 pub use crate::text::font::Font;
 pub use crate::text::glyph::Glyph;
+pub use crate::text::layout::{LayoutGlyph, TextAlign, TextAnchor, TextLayout, TextLine};
 pub use crate::text::renderer::TextRenderer;
+#[cfg(feature = "headless")]
+pub(crate) use crate::text::renderer::{TEXT_FRAGMENT_SRC, TEXT_VERTEX_SRC};
 
 mod font;
 mod glyph;
+mod layout;
 mod renderer;
@@ -9,10 +9,10 @@ use std::rc::Rc;
 
 use crate::context::{Context, Texture};
 use crate::resource::{AllocationType, BufferType, Effect, GPUVec, ShaderAttribute, ShaderUniform};
-use crate::text::Font;
+use crate::text::{Font, TextLayout};
 
-#[path = "../error.rs"]
-mod error;
+#[path = "../gl_error.rs"]
+mod gl_error;
 
 struct TextRenderContext {
     len: usize,
@@ -138,6 +138,16 @@ impl TextRenderer {
         })
     }
 
+    /// Adds a laid out text to be drawn during the next frame, with its top-left corner at `pos`.
+    ///
+    /// Each run of characters of the same span on the same line is drawn like `draw_text`.
+    pub fn draw_layout(&mut self, layout: &TextLayout, pos: &Point2<f32>) {
+        for (text, position, color) in layout.runs() {
+            let run_pos = Point2::new(pos.x + position.x, pos.y + position.y);
+            self.draw_text(&text, &run_pos, layout.scale(), layout.font(), &color);
+        }
+    }
+
     /// Actually draws the text.
     pub fn render(&mut self, width: f32, height: f32) {
         if self.contexts.len() == 0 {
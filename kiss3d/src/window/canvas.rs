@@ -2,9 +2,17 @@ use std::sync::mpsc::Sender;
 
 use crate::event::{Action, Key, MouseButton, WindowEvent};
 use image::{GenericImage, Pixel};
-#[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
+#[cfg(feature = "headless")]
+use crate::window::HeadlessCanvas as CanvasImpl;
+#[cfg(all(
+    not(feature = "headless"),
+    not(any(target_arch = "wasm32", target_arch = "asmjs"))
+))]
 use crate::window::GLCanvas as CanvasImpl;
-#[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
+#[cfg(all(
+    not(feature = "headless"),
+    any(target_arch = "wasm32", target_arch = "asmjs")
+))]
 use crate::window::WebGLCanvas as CanvasImpl;
 
 /// The possible number of samples for multisample anti-aliasing.
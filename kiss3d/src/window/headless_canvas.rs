@@ -0,0 +1,69 @@
+use std::sync::mpsc::Sender;
+
+use crate::context::NullContext;
+use crate::event::{Action, Key, MouseButton, WindowEvent};
+use image::{GenericImage, Pixel};
+use crate::window::canvas::CanvasSetup;
+use crate::window::AbstractCanvas;
+
+/// A canvas without any window, for use with the headless context.
+///
+/// It keeps the size it was opened with and never receives any event. Its framebuffer is the
+/// one `NullContext` rasterizes into.
+pub struct HeadlessCanvas {
+    size: (u32, u32),
+}
+
+impl AbstractCanvas for HeadlessCanvas {
+    fn open(
+        _: &str,
+        _: bool,
+        width: u32,
+        height: u32,
+        _: Option<CanvasSetup>,
+        _: Sender<WindowEvent>,
+    ) -> Self {
+        NullContext::set_screen_size(width, height);
+        HeadlessCanvas {
+            size: (width, height),
+        }
+    }
+
+    fn render_loop(mut callback: impl FnMut(f64) -> bool + 'static) {
+        while callback(0.0) {}
+    }
+
+    fn poll_events(&mut self) {}
+
+    fn swap_buffers(&mut self) {}
+
+    fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn cursor_pos(&self) -> Option<(f64, f64)> {
+        None
+    }
+
+    fn hidpi_factor(&self) -> f64 {
+        1.0
+    }
+
+    fn set_title(&mut self, _: &str) {}
+
+    fn set_icon(&mut self, _: impl GenericImage<Pixel = impl Pixel<Subpixel = u8>>) {}
+
+    fn set_cursor_grab(&self, _: bool) {}
+
+    fn hide(&mut self) {}
+
+    fn show(&mut self) {}
+
+    fn get_mouse_button(&self, _: MouseButton) -> Action {
+        Action::Release
+    }
+
+    fn get_key(&self, _: Key) -> Action {
+        Action::Release
+    }
+}
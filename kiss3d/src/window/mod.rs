@@ -4,6 +4,8 @@ pub(crate) use self::canvas::AbstractCanvas;
 pub use self::canvas::{Canvas, CanvasSetup, NumSamples};
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 pub use self::gl_canvas::GLCanvas;
+#[cfg(feature = "headless")]
+pub use self::headless_canvas::HeadlessCanvas;
 pub use self::state::State;
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
 pub use self::webgl_canvas::WebGLCanvas;
@@ -12,6 +14,8 @@ pub use self::window::Window;
 mod canvas;
 #[cfg(not(any(target_arch = "wasm32", target_arch = "asmjs")))]
 mod gl_canvas;
+#[cfg(feature = "headless")]
+mod headless_canvas;
 mod state;
 #[cfg(any(target_arch = "wasm32", target_arch = "asmjs"))]
 mod webgl_canvas;
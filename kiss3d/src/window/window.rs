@@ -15,21 +15,22 @@ use na::{Point2, Point3, Vector2, Vector3};
 
 use crate::camera::{ArcBall, Camera};
 use crate::context::Context;
+use crate::error::LoadError;
 use crate::event::{Action, EventManager, Key, WindowEvent};
 use image::imageops;
 use image::{GenericImage, Pixel};
 use image::{ImageBuffer, Rgb};
-use crate::light::Light;
+use crate::light::{Light, Lights, PointLight};
 use ncollide3d::procedural::TriMesh;
 use crate::planar_camera::{FixedView, PlanarCamera};
 use crate::planar_line_renderer::PlanarLineRenderer;
 use crate::post_processing::PostProcessingEffect;
 #[cfg(feature = "conrod")]
 use crate::renderer::ConrodRenderer;
-use crate::renderer::{LineRenderer, PointRenderer, Renderer};
+use crate::renderer::{LineRenderer, PointRenderer, Renderer, ShadowMap};
 use crate::resource::{FramebufferManager, Mesh, PlanarMesh, RenderTarget, Texture, TextureManager};
 use crate::scene::{PlanarSceneNode, SceneNode};
-use crate::text::{Font, TextRenderer};
+use crate::text::{Font, TextAnchor, TextLayout, TextRenderer};
 use crate::window::canvas::CanvasSetup;
 use crate::window::{Canvas, State};
 
@@ -67,7 +68,7 @@ pub struct Window {
     max_dur_per_frame: Option<Duration>,
     scene: SceneNode,
     scene2: PlanarSceneNode,
-    light_mode: Light, // FIXME: move that to the scene graph
+    lights: Lights, // FIXME: move that to the scene graph
     background: Vector3<f32>,
     line_renderer: LineRenderer,
     planar_line_renderer: PlanarLineRenderer,
@@ -77,6 +78,7 @@ pub struct Window {
     post_process_render_target: RenderTarget,
     #[cfg(not(target_arch = "wasm32"))]
     curr_time: Instant,
+    animation_time: Instant,
     planar_camera: Rc<RefCell<FixedView>>,
     camera: Rc<RefCell<ArcBall>>,
     should_close: bool,
@@ -218,6 +220,34 @@ impl Window {
         self.text_renderer.draw_text(text, pos, scale, font, color);
     }
 
+    /// Adds a laid out text to be drawn during the next frame, with its top-left corner at `pos`.
+    #[inline]
+    pub fn draw_text_layout(&mut self, layout: &TextLayout, pos: &Point2<f32>) {
+        self.text_renderer.draw_layout(layout, pos);
+    }
+
+    /// Adds a laid out text to be drawn during the next frame, placed relative to `anchor`
+    /// and `margin` away from the edges of the window.
+    ///
+    /// Returns the position of the top-left corner of the text.
+    pub fn draw_anchored_text(
+        &mut self,
+        layout: &TextLayout,
+        anchor: TextAnchor,
+        margin: f32,
+    ) -> Point2<f32> {
+        let pos = anchor.position(&layout.size(), &self.text_screen_size(), margin);
+        self.draw_text_layout(layout, &pos);
+        pos
+    }
+
+    /// The size of the window in the units of `draw_text`, which are half pixels.
+    #[inline]
+    pub fn text_screen_size(&self) -> Vector2<f32> {
+        let (width, height) = self.canvas.size();
+        Vector2::new(width as f32 * 2.0, height as f32 * 2.0)
+    }
+
     /// Removes an object from the scene.
     #[deprecated(note = "Use `remove_node` instead.")]
     pub fn remove(&mut self, sn: &mut SceneNode) {
@@ -257,6 +287,42 @@ impl Window {
         self.scene.add_obj(path, mtl_dir, scale)
     }
 
+    /// Adds an obj model to the scene, or returns why it could not be loaded.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the obj file.
+    /// * `scale` - scale to apply to the model.
+    pub fn try_add_obj(
+        &mut self,
+        path: &Path,
+        mtl_dir: &Path,
+        scale: Vector3<f32>,
+    ) -> Result<SceneNode, LoadError> {
+        self.scene.try_add_obj(path, mtl_dir, scale)
+    }
+
+    /// Adds a glTF model, from a `.gltf` or `.glb` file, to the scene.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the glTF file.
+    /// * `scale` - scale to apply to the model.
+    pub fn add_gltf(&mut self, path: &Path, scale: Vector3<f32>) -> SceneNode {
+        self.scene.add_gltf(path, scale)
+    }
+
+    /// Adds a glTF model to the scene, or returns why it could not be loaded.
+    ///
+    /// # Arguments
+    /// * `path`  - relative path to the glTF file.
+    /// * `scale` - scale to apply to the model.
+    pub fn try_add_gltf(
+        &mut self,
+        path: &Path,
+        scale: Vector3<f32>,
+    ) -> Result<SceneNode, LoadError> {
+        self.scene.try_add_gltf(path, scale)
+    }
+
     /// Adds an unnamed mesh to the scene.
     pub fn add_mesh(&mut self, mesh: Rc<RefCell<Mesh>>, scale: Vector3<f32>) -> SceneNode {
         self.scene.add_mesh(mesh, scale)
@@ -415,9 +481,50 @@ impl Window {
         self.canvas.hidpi_factor()
     }
 
-    /// Sets the light mode. Only one light is supported.
+    /// Sets the main light.
     pub fn set_light(&mut self, pos: Light) {
-        self.light_mode = pos;
+        self.lights.main = pos;
+    }
+
+    /// Sets the color and intensity of the main light. It is white with an intensity of 1 by
+    /// default.
+    pub fn set_light_color(&mut self, color: Point3<f32>, intensity: f32) {
+        self.lights.color = color;
+        self.lights.intensity = intensity;
+    }
+
+    /// Adds a point light in addition to the main light.
+    ///
+    /// The built-in materials only use the first `light::MAX_POINT_LIGHTS` point lights.
+    pub fn add_point_light(&mut self, light: PointLight) {
+        self.lights.point_lights.push(light);
+    }
+
+    /// The point lights of the scene.
+    pub fn point_lights_mut(&mut self) -> &mut Vec<PointLight> {
+        &mut self.lights.point_lights
+    }
+
+    /// Enables shadows cast by the main light, if it is directional.
+    ///
+    /// The shadow map has `map_size * map_size` texels and covers a square of `2 * extent` world
+    /// units centered on the shadow focus. Shadows are not supported on WebGL.
+    pub fn enable_shadows(&mut self, map_size: usize, extent: f32) {
+        self.lights.shadow_map = Some(ShadowMap::new(map_size, extent));
+    }
+
+    /// Disables shadows.
+    pub fn disable_shadows(&mut self) {
+        self.lights.shadow_map = None;
+    }
+
+    /// Sets the point the shadow map is centered on, e.g. the point the camera looks at.
+    ///
+    /// If `None`, the shadow map is centered in front of the camera.
+    pub fn set_shadow_focus(&mut self, focus: Option<Point3<f32>>) {
+        if let Some(shadow_map) = &mut self.lights.shadow_map {
+            shadow_map.set_focus(focus);
+        }
     }
 
     /// Retrieve a mutable reference to the UI based on Conrod.
@@ -492,6 +599,20 @@ impl Window {
         Window::do_new(title, false, width, height, None)
     }
 
+    /// Opens a window without any display, backed by the headless context.
+    ///
+    /// Every call the rendering makes to the context is recorded, see
+    /// `kiss3d::context::NullContext::commands`. The built-in materials, lines, points, text and
+    /// shadows are rasterized on the CPU, so frames can be read back with `snap_image`.
+    ///
+    /// # Arguments
+    /// * `width` - the window width.
+    /// * `height` - the window height.
+    #[cfg(feature = "headless")]
+    pub fn new_headless(width: u32, height: u32) -> Window {
+        Window::do_new("kiss3d", true, width, height, None)
+    }
+
     pub fn new_with_setup(title: &str, width: u32, height: u32, setup: CanvasSetup) -> Window {
         Window::do_new(title, false, width, height, Some(setup))
     }
@@ -517,7 +638,7 @@ impl Window {
             unhandled_events: Rc::new(RefCell::new(Vec::new())),
             scene: SceneNode::new_empty(),
             scene2: PlanarSceneNode::new_empty(),
-            light_mode: Light::Absolute(Point3::new(0.0, 10.0, 0.0)),
+            lights: Lights::new(Light::Absolute(Point3::new(0.0, 10.0, 0.0))),
             background: Vector3::new(0.0, 0.0, 0.0),
             line_renderer: LineRenderer::new(),
             planar_line_renderer: PlanarLineRenderer::new(),
@@ -533,6 +654,7 @@ impl Window {
             framebuffer_manager: FramebufferManager::new(),
             #[cfg(not(target_arch = "wasm32"))]
             curr_time: Instant::now(),
+            animation_time: Instant::now(),
             planar_camera: Rc::new(RefCell::new(FixedView::new())),
             camera: Rc::new(RefCell::new(ArcBall::new(
                 Point3::new(0.0f32, 0.0, -1.0),
@@ -545,7 +667,7 @@ impl Window {
         }
 
         // usr_window.framebuffer_size_callback(DEFAULT_WIDTH, DEFAULT_HEIGHT);
-        let light = usr_window.light_mode.clone();
+        let light = usr_window.lights.main.clone();
         usr_window.set_light(light);
 
         usr_window
@@ -1023,16 +1145,34 @@ impl Window {
         let w = self.width();
         let h = self.height();
 
+        let now = Instant::now();
+        let dt = now.duration_since(self.animation_time).as_secs_f32();
+        self.animation_time = now;
+        self.scene.update_animations(dt);
+
         planar_camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
         camera.handle_event(&self.canvas, &WindowEvent::FramebufferSize(w, h));
         planar_camera.update(&self.canvas);
         camera.update(&self.canvas);
 
-        match self.light_mode {
+        match self.lights.main {
             Light::StickToCamera => self.set_light(Light::StickToCamera),
             _ => {}
         }
 
+        if let (Light::Directional(dir), Some(shadow_map)) =
+            (&self.lights.main, &mut self.lights.shadow_map)
+        {
+            shadow_map.render(
+                &mut self.framebuffer_manager,
+                &mut self.scene.data_mut(),
+                camera,
+                dir,
+            );
+            verify!(Context::get().viewport(0, 0, w as i32, h as i32));
+            verify!(Context::get().scissor(0, 0, w as i32, h as i32));
+        }
+
         if post_processing.is_some() {
             // if we need post-processing, render to our own frame buffer
             self.framebuffer_manager
@@ -1068,8 +1208,7 @@ impl Window {
             self.framebuffer_manager
                 .select(&FramebufferManager::screen());
             // … and execute the post-process
-            // FIXME: use the real time value instead of 0.016!
-            p.update(0.016, w as f32, h as f32, znear, zfar);
+            p.update(dt, w as f32, h as f32, znear, zfar);
             p.draw(&self.post_process_render_target);
         }
 
@@ -1115,7 +1254,7 @@ impl Window {
 
         self.line_renderer.render(pass, camera);
         self.point_renderer.render(pass, camera);
-        self.scene.data_mut().render(pass, camera, &self.light_mode);
+        self.scene.data_mut().render_with_lights(pass, camera, &self.lights);
     }
 
     fn render_planar_scene(&mut self, camera: &mut dyn PlanarCamera) {
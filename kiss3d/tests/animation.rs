@@ -0,0 +1,190 @@
+use kiss3d::animation::{AnimationClip, Interpolation, Skin, Track, Wrap};
+use kiss3d::resource::Mesh;
+use kiss3d::scene::SceneNode;
+use na::{Matrix4, Point3, Translation3, UnitQuaternion, Vector3};
+use nalgebra as na;
+use std::f32::consts::FRAC_PI_2;
+
+mod common;
+use common::EPS;
+
+fn ramp(interpolation: Interpolation) -> Track<f32> {
+    Track::from_keyframes(interpolation, vec![(2.0, 10.0), (0.0, 0.0), (1.0, 4.0)])
+}
+
+fn translation(node: &SceneNode) -> Vector3<f32> {
+    node.data().local_translation().vector
+}
+
+#[test]
+fn tracks_sort_and_replace_keyframes() {
+    let mut track = ramp(Interpolation::Linear);
+    track.add_keyframe(1.0, 6.0);
+
+    let times: Vec<f32> = track.keyframes().iter().map(|k| k.time).collect();
+    let values: Vec<f32> = track.keyframes().iter().map(|k| k.value).collect();
+    assert_eq!(times, vec![0.0, 1.0, 2.0]);
+    assert_eq!(values, vec![0.0, 6.0, 10.0]);
+    assert_eq!(track.duration(), 2.0);
+    assert_eq!(Track::<f32>::new(Interpolation::Step).sample(1.0), None);
+}
+
+#[test]
+fn tracks_interpolate_between_keyframes() {
+    let linear = ramp(Interpolation::Linear);
+    assert!((linear.sample(0.5).unwrap() - 2.0).abs() < EPS);
+    assert!((linear.sample(1.5).unwrap() - 7.0).abs() < EPS);
+    assert_eq!(linear.sample(1.0), Some(4.0));
+
+    let step = ramp(Interpolation::Step);
+    assert_eq!(step.sample(0.5), Some(0.0));
+    assert_eq!(step.sample(1.0), Some(4.0));
+    assert_eq!(step.sample(1.99), Some(4.0));
+}
+
+#[test]
+fn tracks_clamp_outside_their_keyframes() {
+    let track = ramp(Interpolation::Linear);
+    assert_eq!(track.sample(-1.0), Some(0.0));
+    assert_eq!(track.sample(5.0), Some(10.0));
+}
+
+#[test]
+fn rotations_are_spherically_interpolated() {
+    let track = Track::from_keyframes(
+        Interpolation::Linear,
+        vec![
+            (0.0, UnitQuaternion::identity()),
+            (1.0, UnitQuaternion::from_axis_angle(&Vector3::y_axis(), FRAC_PI_2)),
+        ],
+    );
+    let half = track.sample(0.5).unwrap();
+    assert!((half.angle() - FRAC_PI_2 / 2.0).abs() < EPS);
+}
+
+#[test]
+fn clips_wrap() {
+    let track = ramp(Interpolation::Linear);
+    let scale = Track::from_keyframes(
+        Interpolation::Linear,
+        track.keyframes().iter().map(|k| (k.time, Vector3::repeat(k.value))),
+    );
+    let clip = |wrap| {
+        AnimationClip::new("ramp")
+            .with_scale(scale.clone())
+            .with_wrap(wrap)
+    };
+
+    assert_eq!(clip(Wrap::Once).duration(), 2.0);
+    assert_eq!(clip(Wrap::Once).local_time(1.5), (1.5, false));
+    assert_eq!(clip(Wrap::Once).local_time(3.0), (2.0, true));
+    assert_eq!(clip(Wrap::Loop).local_time(3.0), (1.0, false));
+    assert_eq!(clip(Wrap::PingPong).local_time(3.5), (0.5, false));
+    assert_eq!(clip(Wrap::PingPong).local_time(4.5), (0.5, false));
+    assert_eq!(clip(Wrap::Loop).with_duration(4.0).local_time(3.0), (3.0, false));
+}
+
+#[test]
+fn clips_play_by_name_on_a_hierarchy() {
+    let mut root = SceneNode::new_empty();
+    let mut child = root.add_group();
+    let mut other = root.add_group();
+
+    let slide = |name: &str, x: f32| {
+        AnimationClip::new(name).with_translation(Track::from_keyframes(
+            Interpolation::Linear,
+            vec![(0.0, Vector3::zeros()), (1.0, Vector3::new(x, 0.0, 0.0))],
+        ))
+    };
+    child.add_animation(slide("walk", 2.0));
+    other.add_animation(slide("dig", 4.0));
+
+    assert!(!root.play_animation("run"));
+    assert!(root.play_animation("walk"));
+    assert!(root.is_animation_playing("walk"));
+    assert!(!root.is_animation_playing("dig"));
+
+    root.update_animations(0.25);
+    assert!((translation(&child).x - 0.5).abs() < EPS);
+    assert_eq!(translation(&other), Vector3::zeros());
+
+    root.set_animation_speed("walk", 2.0);
+    root.update_animations(0.25);
+    assert!((translation(&child).x - 1.5).abs() < EPS);
+
+    // A clip played once stops on its last pose.
+    root.update_animations(1.0);
+    assert!((translation(&child).x - 2.0).abs() < EPS);
+    assert!(!root.is_animation_playing("walk"));
+
+    // Stopped clips leave the node where it is.
+    root.set_animation_speed("walk", 1.0);
+    assert!(root.play_animation("walk"));
+    root.update_animations(0.5);
+    root.stop_animation("walk");
+    root.update_animations(0.5);
+    assert!((translation(&child).x - 1.0).abs() < EPS);
+
+    root.remove_animation("walk");
+    assert!(child.data().animation("walk").is_none());
+    assert!(other.data().animation("dig").is_some());
+}
+
+#[test]
+fn nodes_compose_their_parent_transformation() {
+    let mut root = SceneNode::new_empty();
+    root.set_local_scale(2.0, 2.0, 2.0);
+    root.set_local_rotation(UnitQuaternion::from_axis_angle(&Vector3::z_axis(), FRAC_PI_2));
+    let mut child = root.add_group();
+    child.set_local_translation(Translation3::new(1.0, 0.0, 0.0));
+    child.set_local_scale(1.0, 3.0, 1.0);
+
+    let data = child.data();
+    let origin = data.world_transformation() * Point3::origin();
+    assert!((origin - Point3::new(0.0, 1.0, 0.0)).norm() < EPS);
+    assert_eq!(data.world_scale(), Vector3::new(2.0, 6.0, 2.0));
+}
+
+#[test]
+fn skins_deform_meshes_with_their_joints() {
+    // A vertical segment whose bottom follows the first joint, its top the second, and its middle
+    // both.
+    let coords = vec![
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Point3::new(0.0, 2.0, 0.0),
+    ];
+    let faces = vec![Point3::new(0u16, 1, 2)];
+    let mesh = Mesh::new(coords.clone(), faces, None, None, true);
+
+    let base = SceneNode::new_empty();
+    let tip = SceneNode::new_empty();
+    let mut skin = Skin::new(
+        vec![base, tip],
+        vec![[0, 0, 0, 0], [0, 1, 0, 0], [1, 0, 0, 0]],
+        vec![[1.0, 0.0, 0.0, 0.0], [1.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0]],
+    );
+    let pose = |x: f32, y: f32| Translation3::new(x, y, 0.0).to_homogeneous();
+    skin.bind(&mesh, &Matrix4::identity(), &[pose(0.0, 0.0), pose(0.0, 2.0)]);
+    assert_eq!(skin.bind_pose().0, &coords[..]);
+
+    let (unchanged, _) = skin.deform(&Matrix4::identity(), &[pose(0.0, 0.0), pose(0.0, 2.0)]);
+    assert_eq!(unchanged, coords);
+
+    // Moving the base carries everything, moving the tip only carries the top and half the middle.
+    let (deformed, normals) =
+        skin.deform(&Matrix4::identity(), &[pose(1.0, 0.0), pose(3.0, 2.0)]);
+    let expected = [
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(2.0, 1.0, 0.0),
+        Point3::new(3.0, 2.0, 0.0),
+    ];
+    for (d, e) in deformed.iter().zip(expected.iter()) {
+        assert!((d - e).norm() < EPS, "{} != {}", d, e);
+    }
+    assert_eq!(normals.len(), coords.len());
+
+    // Deformed vertices are expressed relative to the skinned node.
+    let (relative, _) = skin.deform(&pose(1.0, 0.0), &[pose(1.0, 0.0), pose(1.0, 2.0)]);
+    assert!(relative.iter().zip(&coords).all(|(r, c)| (r - c).norm() < EPS));
+}
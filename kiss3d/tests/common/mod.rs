@@ -0,0 +1,51 @@
+// Fixtures shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use kiss3d::resource::Mesh;
+use nalgebra::Point3;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "headless")]
+use kiss3d::context::Command;
+#[cfg(feature = "headless")]
+use kiss3d::window::Window;
+
+/// The tolerance of float comparisons.
+pub const EPS: f32 = 1.0e-5;
+
+/// The width and height of headless windows.
+pub const SIZE: u32 = 32;
+
+/// An image read back from a window.
+pub type Image = image::ImageBuffer<image::Rgb<u8>, Vec<u8>>;
+
+/// A path relative to the root of the crate.
+pub fn path(relative: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+/// A unit square in the plane z = 0, centered on the origin and facing +z.
+pub fn square() -> Mesh {
+    let coords = vec![
+        Point3::new(-0.5, -0.5, 0.0),
+        Point3::new(0.5, -0.5, 0.0),
+        Point3::new(0.5, 0.5, 0.0),
+        Point3::new(-0.5, 0.5, 0.0),
+    ];
+    let faces = vec![Point3::new(0u16, 1, 2), Point3::new(0, 2, 3)];
+    Mesh::new(coords, faces, None, None, true)
+}
+
+/// A `SIZE` x `SIZE` headless window with a black background.
+#[cfg(feature = "headless")]
+pub fn window() -> Window {
+    let mut window = Window::new_headless(SIZE, SIZE);
+    window.set_background_color(0.0, 0.0, 0.0);
+    window
+}
+
+/// The draw calls among recorded commands.
+#[cfg(feature = "headless")]
+pub fn draws(commands: &[Command]) -> Vec<&Command> {
+    commands.iter().filter(|c| c.is_draw()).collect()
+}
@@ -0,0 +1,130 @@
+use kiss3d::error::LoadError;
+use kiss3d::loader::gltf::{self, GltfNode, GltfScene};
+use kiss3d::resource::TextureWrapping;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+mod common;
+
+fn house_dir() -> PathBuf {
+    common::path("examples/media/house")
+}
+
+// A triangle whose third index, 5, is out of bounds. With an `index_count` of 2, that index is
+// not read and the mesh has no faces.
+fn triangle(index_count: usize) -> String {
+    format!(
+        r#"{{
+  "asset": {{ "version": "2.0" }},
+  "scenes": [{{ "nodes": [0] }}],
+  "nodes": [{{ "mesh": 0 }}],
+  "meshes": [{{ "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "indices": 1 }}] }}],
+  "buffers": [{{
+    "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAUAAAA=",
+    "byteLength": 44
+  }}],
+  "bufferViews": [
+    {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+    {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+  ],
+  "accessors": [
+    {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+      "min": [0, 0, 0], "max": [1, 1, 0] }},
+    {{ "bufferView": 1, "componentType": 5123, "count": {}, "type": "SCALAR" }}
+  ]
+}}"#,
+        index_count
+    )
+}
+
+fn check_house(scene: &GltfScene) {
+    assert_eq!(scene.images.len(), 1);
+    assert_eq!(scene.images[0].to_rgba().dimensions(), (16, 16));
+    assert_eq!(scene.nodes.len(), 1);
+
+    let walls = &scene.nodes[0];
+    assert_eq!(walls.name.as_deref(), Some("walls"));
+    assert_eq!(walls.primitives.len(), 1);
+    assert_eq!(walls.primitives[0].mesh.borrow().coords().read().unwrap().len(), 16);
+
+    let material = &walls.primitives[0].material;
+    assert_eq!(material.name.as_deref(), Some("planks"));
+    assert_eq!(material.base_color, [1.0; 4]);
+    let texture = material.base_color_texture.unwrap();
+    assert_eq!(texture.image, 0);
+    assert_eq!(texture.wrap_s, TextureWrapping::Repeat);
+    assert!(!material.double_sided && !material.vertex_colors);
+
+    let names: Vec<_> = walls.children.iter().map(|c| c.name.as_deref()).collect();
+    assert_eq!(names, [Some("roof"), Some("door")]);
+
+    // The roof has no indices: its triangles are its consecutive vertices.
+    let roof: &GltfNode = &walls.children[0];
+    assert_eq!(roof.transform.translation.vector.y, 1.0);
+    assert_eq!(roof.primitives[0].mesh.borrow().num_pts(), 12);
+    assert_eq!(roof.primitives[0].material.base_color, [0.6, 0.15, 0.1, 1.0]);
+    assert_eq!(roof.primitives[0].material.base_color_texture, None);
+
+    let door = &walls.children[1];
+    assert_eq!(door.scale.y, 1.2);
+    assert!(door.primitives[0].material.double_sided);
+    assert!(door.primitives[0].material.vertex_colors);
+    assert!(door.children.is_empty());
+}
+
+#[test]
+fn gltf_files_are_loaded_with_their_buffers_and_images() {
+    let scene = gltf::parse_file(&house_dir().join("house.gltf")).unwrap();
+    check_house(&scene);
+}
+
+#[test]
+fn glb_files_are_loaded_with_their_binary_chunk() {
+    let json = fs::read_to_string(house_dir().join("house.gltf")).unwrap();
+    let mut json = json.replace(r#""uri": "house.bin","#, "").into_bytes();
+    let mut bin = fs::read(house_dir().join("house.bin")).unwrap();
+    // Chunks are padded to 4 bytes.
+    json.resize(json.len().div_ceil(4) * 4, b' ');
+    bin.resize(bin.len().div_ceil(4) * 4, 0);
+
+    let mut glb = Vec::new();
+    let total = 12 + 8 + json.len() + 8 + bin.len();
+    for word in &[0x4654_6c67, 2, total] {
+        glb.extend_from_slice(&(*word as u32).to_le_bytes());
+    }
+    for (chunk, kind) in &[(&json, 0x4e4f_534a), (&bin, 0x004e_4942)] {
+        glb.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+        glb.extend_from_slice(&(*kind as u32).to_le_bytes());
+        glb.extend_from_slice(chunk);
+    }
+
+    let scene = gltf::parse(&glb, &house_dir()).unwrap();
+    check_house(&scene);
+}
+
+#[test]
+fn data_uris_are_decoded() {
+    let scene = gltf::parse(triangle(2).as_bytes(), Path::new("")).unwrap();
+    let mesh = scene.nodes[0].primitives[0].mesh.borrow();
+    assert_eq!(mesh.coords().read().unwrap().len(), 3);
+}
+
+#[test]
+fn broken_files_are_errors() {
+    match gltf::parse(triangle(3).as_bytes(), Path::new("")) {
+        Err(LoadError::Invalid(msg)) => assert!(msg.contains("out of bounds"), "{}", msg),
+        _ => panic!("expected an out of bounds index to be an error"),
+    }
+
+    let json = fs::read(house_dir().join("house.gltf")).unwrap();
+    match gltf::parse(&json[..json.len() / 2], &house_dir()) {
+        Err(LoadError::Gltf(_)) => {}
+        _ => panic!("expected truncated JSON to be an error"),
+    }
+
+    match gltf::parse(&json, Path::new("does_not_exist")) {
+        Err(LoadError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+        _ => panic!("expected a missing buffer to be an error"),
+    }
+}
@@ -0,0 +1,190 @@
+use kiss3d::camera::ArcBall;
+use kiss3d::context::{Command, Context, NullContext, UniformValue};
+use kiss3d::scene::InstanceData;
+use nalgebra::{Matrix3, Point3};
+
+mod common;
+use common::draws;
+
+// The number of bytes uploaded to buffers bound to `target`.
+fn uploads(commands: &[Command], target: u32) -> Vec<usize> {
+    commands
+        .iter()
+        .filter_map(|c| match c {
+            Command::BufferData { target: t, data, .. } if *t == target => Some(data.len()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn uniform<'a>(commands: &'a [Command], name: &str) -> Option<&'a UniformValue> {
+    commands.iter().rev().find_map(|c| match c {
+        Command::Uniform { name: n, value, .. } if n == name => Some(value),
+        _ => None,
+    })
+}
+
+// The constant attribute values set, in order.
+fn constants(commands: &[Command]) -> Vec<[f32; 4]> {
+    commands
+        .iter()
+        .filter_map(|c| match c {
+            Command::VertexAttrib4f { value, .. } => Some(*value),
+            _ => None,
+        })
+        .collect()
+}
+
+fn instances(count: usize) -> Vec<InstanceData> {
+    (0..count)
+        .map(|i| InstanceData {
+            position: Point3::new(i as f32 - 1.0, 0.0, 0.0),
+            deformation: Matrix3::from_diagonal_element(0.5),
+            color: Point3::new(1.0, 1.0, 1.0),
+        })
+        .collect()
+}
+
+#[test]
+fn a_cube_uploads_its_buffers_once_and_is_drawn_once() {
+    let mut window = common::window();
+    let mut cube = window.add_cube(1.0, 1.0, 1.0);
+    cube.set_color(1.0, 0.0, 0.0);
+
+    NullContext::clear_commands();
+    let _ = window.render();
+    let commands = NullContext::take_commands();
+
+    // The 24 vertices of the cube (positions, normals and uvs). Its only instance is set as
+    // constant attribute values instead: the three rows of its transform, then its color.
+    assert_eq!(
+        uploads(&commands, Context::ARRAY_BUFFER),
+        [24 * 12, 24 * 12, 24 * 8]
+    );
+    assert_eq!(
+        constants(&commands),
+        [
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [1.0, 1.0, 1.0, 1.0]
+        ]
+    );
+    assert_eq!(uploads(&commands, Context::ELEMENT_ARRAY_BUFFER), [36 * 2]);
+
+    assert_eq!(
+        uniform(&commands, "color"),
+        Some(&UniformValue::Float(vec![1.0, 0.0, 0.0]))
+    );
+    assert!(uniform(&commands, "proj").is_some());
+    assert!(uniform(&commands, "view").is_some());
+    assert!(uniform(&commands, "transform").is_some());
+
+    assert_eq!(
+        draws(&commands),
+        [&Command::DrawElements {
+            mode: Context::TRIANGLES,
+            count: 36,
+            type_: Context::UNSIGNED_SHORT,
+            offset: 0,
+        }]
+    );
+
+    // The buffers stay on the GPU, so the next frame only draws.
+    let _ = window.render();
+    let commands = NullContext::take_commands();
+    assert!(uploads(&commands, Context::ARRAY_BUFFER).is_empty());
+    assert!(uploads(&commands, Context::ELEMENT_ARRAY_BUFFER).is_empty());
+    assert_eq!(draws(&commands).len(), 1);
+}
+
+#[test]
+fn taking_or_clearing_the_commands_empties_the_log() {
+    let mut window = common::window();
+    let _ = window.add_cube(1.0, 1.0, 1.0);
+
+    let _ = window.render();
+    assert!(!NullContext::commands().is_empty());
+    NullContext::clear_commands();
+    assert!(NullContext::commands().is_empty());
+
+    let _ = window.render();
+    let commands = NullContext::commands();
+    assert_eq!(NullContext::take_commands(), commands);
+    assert!(NullContext::commands().is_empty());
+    assert!(NullContext::take_commands().is_empty());
+}
+
+#[test]
+fn several_instances_are_drawn_in_one_instanced_call() {
+    let mut window = common::window();
+    let mut cube = window.add_cube(1.0, 1.0, 1.0);
+    cube.set_instances(&instances(3));
+
+    NullContext::clear_commands();
+    let _ = window.render();
+    let commands = NullContext::take_commands();
+
+    // The transforms and colors of the instances are uploaded rather than set as constants.
+    assert_eq!(
+        uploads(&commands, Context::ARRAY_BUFFER),
+        [24 * 12, 24 * 12, 24 * 8, 3 * 48, 3 * 12]
+    );
+    assert!(constants(&commands).is_empty());
+    assert_eq!(
+        draws(&commands),
+        [&Command::DrawElementsInstanced {
+            mode: Context::TRIANGLES,
+            count: 36,
+            type_: Context::UNSIGNED_SHORT,
+            offset: 0,
+            instances: 3,
+        }]
+    );
+
+    // The divisors are reset for the shaders drawing without instancing.
+    let divisors: Vec<_> = commands
+        .iter()
+        .filter_map(|c| match c {
+            Command::VertexAttribDivisor { divisor, .. } => Some(*divisor),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(divisors, [1, 1, 1, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn single_instances_are_drawn_without_instancing_support() {
+    NullContext::set_instancing_supported(false);
+    let mut window = common::window();
+    let mut camera = ArcBall::new(Point3::new(0.0, 0.0, 3.0), Point3::origin());
+    let mut cube = window.add_cube(1.0, 1.0, 1.0);
+    cube.set_color(1.0, 0.0, 0.0);
+    cube.set_instances(&[InstanceData {
+        position: Point3::new(-0.6, 0.0, 0.0),
+        deformation: Matrix3::from_diagonal_element(0.5),
+        color: Point3::new(1.0, 1.0, 1.0),
+    }]);
+
+    NullContext::clear_commands();
+    let _ = window.render_with_camera(&mut camera);
+    let commands = NullContext::take_commands();
+    assert_eq!(draws(&commands).len(), 1);
+    assert_eq!(constants(&commands)[0], [0.5, 0.0, 0.0, -0.6]);
+
+    // The constant transform moved the cube to the left of the window.
+    let image = window.snap_image();
+    let (left, right) = (image.get_pixel(8, 16).0, image.get_pixel(24, 16).0);
+    assert!(left[0] > 0 && left[1] == 0 && left[2] == 0, "{:?}", left);
+    assert_eq!(right, [0, 0, 0]);
+}
+
+#[test]
+#[should_panic(expected = "Drawing 2 instances requires instancing")]
+fn several_instances_panic_without_instancing_support() {
+    NullContext::set_instancing_supported(false);
+    let mut window = common::window();
+    let mut cube = window.add_cube(1.0, 1.0, 1.0);
+    cube.set_instances(&instances(2));
+    let _ = window.render();
+}
@@ -0,0 +1,164 @@
+use kiss3d::error::{LoadError, ParseError, ParseErrorKind};
+use kiss3d::loader::{mtl, obj};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io;
+use std::path::PathBuf;
+
+mod common;
+
+const CUBE: &str = "# A unit cube with texture coordinates and normals.
+mtllib cube.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 0 0 1
+v 1 0 1
+v 1 1 1
+v 0 1 1
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 -1
+vn 0 0 1
+vn 0 -1 0
+vn 0 1 0
+vn -1 0 0
+vn 1 0 0
+g sides
+usemtl grey
+f 1/1/1 4/4/1 3/3/1 2/2/1
+f 5/1/2 6/2/2 7/3/2 8/4/2
+f 1/1/3 2/2/3 6/3/3 5/4/3
+f 4/1/4 8/2/4 7/3/4 3/4/4
+g ends
+f -8/1/5 -4/2/5 -1/3/5 -5/4/5
+f 2//6 3//6 7//6 6//6
+";
+
+fn corpus() -> PathBuf {
+    common::path("tests/media/malformed")
+}
+
+fn parse_error(file: &str) -> ParseError {
+    let path = corpus().join(file);
+    let result = if file.ends_with(".mtl") {
+        mtl::parse_file(&path).map(|_| ())
+    } else {
+        obj::parse_file(&path, &corpus(), file).map(|_| ())
+    };
+
+    match result {
+        Err(LoadError::Parse(e)) => e,
+        Err(e) => panic!("{}: expected a parse error, got {}", file, e),
+        Ok(()) => panic!("{}: expected a parse error, the file was loaded", file),
+    }
+}
+
+#[test]
+fn malformed_files_are_reported_with_their_position() {
+    let missing = |expected, found| ParseErrorKind::MissingComponents { expected, found };
+    let invalid = |s: &str| ParseErrorKind::InvalidNumber(s.to_string());
+    let out_of_bounds = |index, len| ParseErrorKind::IndexOutOfBounds { index, len };
+
+    let cases = vec![
+        ("vertex_missing_component.obj", 2, 6, missing(3, 2)),
+        ("vertex_invalid_number.obj", 2, 5, invalid("O")),
+        ("normal_missing_component.obj", 1, 7, missing(3, 2)),
+        ("uv_missing_component.obj", 1, 7, missing(2, 1)),
+        ("face_empty.obj", 4, 2, missing(3, 0)),
+        ("face_invalid_index.obj", 4, 5, invalid("two")),
+        ("face_index_overflow.obj", 4, 9, invalid("99999999999999999999")),
+        ("face_index_zero.obj", 4, 3, out_of_bounds(0, 3)),
+        ("face_index_out_of_bounds.obj", 4, 7, out_of_bounds(4, 3)),
+        ("face_relative_index_out_of_bounds.obj", 4, 9, out_of_bounds(-4, 3)),
+        ("face_uv_out_of_bounds.obj", 5, 11, out_of_bounds(2, 1)),
+        ("face_normal_out_of_bounds.obj", 5, 13, out_of_bounds(3, 1)),
+        (
+            "face_too_many_indices.obj",
+            4,
+            3,
+            ParseErrorKind::InvalidFaceVertex("1/1/1/1".to_string()),
+        ),
+        ("material_missing_component.mtl", 4, 7, missing(3, 2)),
+    ];
+
+    for (file, line, column, kind) in cases {
+        let e = parse_error(file);
+        assert_eq!(e.path, Some(corpus().join(file)), "{}", file);
+        assert_eq!((e.line, e.column, e.kind), (line, column, kind), "{}", file);
+    }
+}
+
+#[test]
+fn errors_in_material_libraries_name_the_library() {
+    let e = parse_error("malformed_mtl.obj");
+    assert_eq!(e.path, Some(corpus().join("malformed.mtl")));
+    assert_eq!((e.line, e.column), (3, 6));
+    assert_eq!(e.kind, ParseErrorKind::InvalidNumber("zero".to_string()));
+}
+
+#[test]
+fn unreadable_files_are_io_errors() {
+    let path = corpus().join("not_utf8.obj");
+    match obj::parse_file(&path, &corpus(), "not_utf8") {
+        Err(LoadError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::InvalidData),
+        _ => panic!("expected an I/O error"),
+    }
+
+    let path = corpus().join("does_not_exist.obj");
+    match obj::parse_file(&path, &corpus(), "does_not_exist") {
+        Err(LoadError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::NotFound),
+        _ => panic!("expected an I/O error"),
+    }
+}
+
+#[test]
+fn files_are_read_from_buffers_and_readers() {
+    // The missing material library only causes a warning.
+    let meshes = obj::parse(CUBE, &corpus(), "cube").unwrap();
+    assert_eq!(meshes.len(), 2);
+
+    let meshes = obj::parse_reader(CUBE.as_bytes(), &corpus(), "cube").unwrap();
+    let mut names: Vec<_> = meshes.iter().map(|m| m.0.clone()).collect();
+    names.sort();
+    assert_eq!(names, ["cube/ends", "cube/sides"]);
+
+    let materials = mtl::parse_reader(&b"newmtl red\nKd 1 0 0\n"[..]).unwrap();
+    assert_eq!(materials.len(), 1);
+    assert_eq!(materials[0].name, "red");
+    assert_eq!(materials[0].diffuse.x, 1.0);
+}
+
+// Random edits of a valid file must either load or return an error.
+#[test]
+fn mutated_files_do_not_panic() {
+    const ALPHABET: &[u8] = b"0123456789-+./ \n#fgvtne";
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for _ in 0..2000 {
+        let mut bytes = CUBE.as_bytes().to_vec();
+
+        for _ in 0..rng.gen_range(1, 8) {
+            if bytes.is_empty() {
+                break;
+            }
+
+            let i = rng.gen_range(0, bytes.len());
+            match rng.gen_range(0, 4) {
+                0 => {
+                    let _ = bytes.remove(i);
+                }
+                1 => bytes.insert(i, ALPHABET[rng.gen_range(0, ALPHABET.len())]),
+                2 => bytes[i] = ALPHABET[rng.gen_range(0, ALPHABET.len())],
+                _ => bytes.truncate(i),
+            }
+        }
+
+        let string = String::from_utf8(bytes).unwrap();
+        let _ = obj::parse(&string, &corpus(), "mutated");
+        let _ = mtl::parse(&string);
+    }
+}
@@ -0,0 +1,173 @@
+use kiss3d::post_processing::ColorGrading;
+
+mod common;
+
+#[test]
+fn identity_luts_map_colors_to_themselves() {
+    let lut = ColorGrading::identity_lut(4);
+    assert_eq!(lut.dimensions(), (16, 4));
+    assert_eq!(lut.get_pixel(0, 0).0, [0, 0, 0]);
+    assert_eq!(lut.get_pixel(3, 0).0, [255, 0, 0]);
+    assert_eq!(lut.get_pixel(1, 2).0, [85, 170, 0]);
+    assert_eq!(lut.get_pixel(5, 3).0, [85, 255, 85]);
+    assert_eq!(lut.get_pixel(15, 3).0, [255, 255, 255]);
+}
+
+#[cfg(feature = "headless")]
+mod headless {
+    use kiss3d::camera::ArcBall;
+    use kiss3d::context::NullContext;
+    use kiss3d::post_processing::{
+        ColorGrading, Fog, Fxaa, Grayscales, PostProcessingChain, PostProcessingEffect, Vignette,
+    };
+    use kiss3d::window::Window;
+    use nalgebra::{Point3, Vector3};
+
+    use super::common::{self, Image, SIZE};
+
+    fn render(window: &mut Window, effect: &mut dyn PostProcessingEffect) -> Image {
+        let _ = window.render_with_effect(effect);
+        window.snap_image()
+    }
+
+    fn assert_color(image: &Image, x: u32, y: u32, expected: [u8; 3]) {
+        let actual = image.get_pixel(x, y).0;
+        assert!(
+            actual.iter().zip(&expected).all(|(a, e)| (*a as i32 - *e as i32).abs() <= 2),
+            "pixel ({}, {}) is {:?} instead of {:?}",
+            x,
+            y,
+            actual,
+            expected
+        );
+    }
+
+    // Turns everything grey, then fades the corners to blue.
+    fn grey_then_blue_corners() -> PostProcessingChain {
+        let mut grey = ColorGrading::new();
+        grey.set_saturation(0.0);
+        let mut vignette = Vignette::new(1.0);
+        vignette.set_color(0.0, 0.0, 1.0);
+        vignette.set_radius(0.5, 0.1);
+
+        PostProcessingChain::new()
+            .with("grey", grey)
+            .with("vignette", vignette)
+    }
+
+    #[test]
+    fn empty_chains_draw_the_scene_unchanged() {
+        let mut window = common::window();
+        window.set_background_color(0.2, 0.4, 0.6);
+        let _ = window.add_cube(1.0, 1.0, 1.0);
+        let _ = window.render();
+        let expected = window.snap_image();
+
+        let mut chain = PostProcessingChain::new().with("grey", Grayscales::new());
+        let _ = chain.set_enabled("grey", false);
+        assert!(*render(&mut window, &mut chain) == *expected);
+
+        assert!(chain.remove("grey"));
+        assert!(chain.is_empty());
+        assert!(*render(&mut window, &mut chain) == *expected);
+    }
+
+    #[test]
+    fn chains_apply_their_enabled_effects_in_order() {
+        let mut window = common::window();
+        window.set_background_color(1.0, 0.0, 0.0);
+        let mut chain = grey_then_blue_corners();
+        let (center, corner) = (SIZE / 2, 0);
+
+        NullContext::clear_commands();
+        let image = render(&mut window, &mut chain);
+        assert_eq!(common::draws(&NullContext::take_commands()).len(), 2);
+        assert_color(&image, center, center, [54, 54, 54]);
+        assert_color(&image, corner, corner, [0, 0, 255]);
+
+        assert!(chain.set_enabled("grey", false));
+        let image = render(&mut window, &mut chain);
+        assert_color(&image, center, center, [255, 0, 0]);
+        assert_color(&image, corner, corner, [0, 0, 255]);
+
+        // The vignette now comes first, and its blue is turned grey.
+        assert!(chain.effect::<ColorGrading>("grey").is_some());
+        assert!(chain.remove("grey"));
+        let mut grey = ColorGrading::new();
+        grey.set_saturation(0.0);
+        chain.push("grey", grey);
+        assert_eq!(chain.names().collect::<Vec<_>>(), ["vignette", "grey"]);
+        let image = render(&mut window, &mut chain);
+        assert_color(&image, center, center, [54, 54, 54]);
+        assert_color(&image, corner, corner, [18, 18, 18]);
+
+        assert!(chain.is_enabled("grey"));
+        assert!(!chain.set_enabled("sepia", true));
+        assert!(!chain.is_enabled("sepia"));
+        assert!(chain.effect_mut::<Vignette>("grey").is_none());
+        chain.effect_mut::<Vignette>("vignette").unwrap().set_intensity(0.0);
+        let image = render(&mut window, &mut chain);
+        assert_color(&image, corner, corner, [54, 54, 54]);
+    }
+
+    #[test]
+    fn chained_effects_read_the_depth_of_the_scene() {
+        let mut window = common::window();
+        let _ = window.add_cube(1.0, 1.0, 1.0);
+        let mut camera = ArcBall::new(Point3::new(0.0, 0.0, 5.0), Point3::origin());
+
+        let mut chain = PostProcessingChain::new()
+            .with("grey", ColorGrading::new())
+            .with("fog", Fog::new(Vector3::new(0.0, 1.0, 0.0), 1.0, 2.0));
+        let _ = window.render_with_camera_and_effect(&mut camera, &mut chain);
+        let image = window.snap_image();
+        assert_color(&image, SIZE / 2, SIZE / 2, [0, 255, 0]);
+        assert_color(&image, 0, 0, [0, 0, 0]);
+
+        chain.effect_mut::<Fog>("fog").unwrap().set_range(100.0, 200.0);
+        let _ = window.render_with_camera_and_effect(&mut camera, &mut chain);
+        let image = window.snap_image();
+        assert!(image.get_pixel(SIZE / 2, SIZE / 2).0[0] > 0);
+    }
+
+    #[test]
+    fn fxaa_only_changes_edges() {
+        let mut window = common::window();
+        let _ = window.add_cube(1.0, 1.0, 1.0);
+        let mut camera = ArcBall::new(Point3::new(1.0, 2.0, 3.0), Point3::origin());
+        let _ = window.render_with_camera(&mut camera);
+        let aliased = window.snap_image();
+
+        let _ = window.render_with_camera_and_effect(&mut camera, &mut Fxaa::new());
+        let smoothed = window.snap_image();
+        assert_eq!(smoothed.get_pixel(0, 0), aliased.get_pixel(0, 0));
+        assert_eq!(
+            smoothed.get_pixel(SIZE / 2, SIZE / 2),
+            aliased.get_pixel(SIZE / 2, SIZE / 2)
+        );
+        assert!(*smoothed != *aliased);
+    }
+
+    #[test]
+    fn lookup_tables_remap_colors() {
+        let mut window = common::window();
+        window.set_background_color(0.2, 0.6, 0.8);
+        let mut grading = ColorGrading::new();
+
+        grading.set_lut(Some(&ColorGrading::identity_lut(16)));
+        let image = render(&mut window, &mut grading);
+        assert_color(&image, 3, 5, [51, 153, 204]);
+
+        let mut inverted = ColorGrading::identity_lut(16);
+        for pixel in inverted.pixels_mut() {
+            pixel.0 = [255 - pixel.0[0], 255 - pixel.0[1], 255 - pixel.0[2]];
+        }
+        grading.set_lut(Some(&inverted));
+        let image = render(&mut window, &mut grading);
+        assert_color(&image, 3, 5, [204, 102, 51]);
+
+        grading.set_lut_intensity(0.5);
+        let image = render(&mut window, &mut grading);
+        assert_color(&image, 3, 5, [128, 128, 128]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+use gif::SetParameter;
+use kiss3d::recording::{Recorder, RecordingFormat};
+use kiss3d::window::Window;
+
+mod common;
+use common::SIZE;
+
+// The colors of the successive frames, so each recorded frame can be told apart.
+const COLORS: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+
+// A path in the temporary directory, unique to this test process.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("kiss3d-recording-{}-{}", std::process::id(), name))
+}
+
+fn render(window: &mut Window, color: [u8; 3]) {
+    let [r, g, b] = color;
+    window.set_background_color(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let _ = window.render();
+}
+
+#[test]
+fn the_format_follows_the_extension() {
+    assert_eq!(RecordingFormat::from_path("out.gif"), RecordingFormat::Gif);
+    assert_eq!(RecordingFormat::from_path("out.GIF"), RecordingFormat::Gif);
+    assert_eq!(RecordingFormat::from_path("out.png"), RecordingFormat::PngSequence);
+    assert_eq!(RecordingFormat::from_path("frames"), RecordingFormat::PngSequence);
+}
+
+#[test]
+fn frames_are_captured_at_a_fixed_timestep() {
+    let path = temp_path("timestep");
+    let mut window = common::window();
+    let mut recorder = Recorder::new(&path, RecordingFormat::PngSequence, 1.0).unwrap();
+
+    // The first call always captures, then one frame per unit of time is captured. Frames
+    // skipped by the jump from 2.2 to 5.0 are not made up for.
+    let times = [0.0, 0.4, 0.99, 1.0, 1.5, 2.2, 2.9, 5.0, 5.5, 6.0];
+    let captured: Vec<bool> = times
+        .iter()
+        .map(|&time| {
+            render(&mut window, [0, 0, 0]);
+            recorder.record(&window, time).unwrap()
+        })
+        .collect();
+
+    assert_eq!(captured, [true, false, false, true, false, true, false, true, false, true]);
+    assert_eq!(recorder.frames(), 5);
+    assert_eq!(fs::read_dir(&path).unwrap().count(), 5);
+
+    fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn a_png_sequence_names_the_frames_after_their_number() {
+    let path = temp_path("sequence");
+    let mut window = common::window();
+    let mut recorder = Recorder::new(&path, RecordingFormat::PngSequence, 1.0).unwrap();
+    assert_eq!(recorder.path(), path.as_path());
+
+    for (i, &color) in COLORS.iter().enumerate() {
+        render(&mut window, color);
+        assert!(recorder.record(&window, i as f64).unwrap());
+    }
+    assert_eq!(recorder.frames(), COLORS.len());
+
+    let mut names: Vec<String> = fs::read_dir(&path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["000000.png", "000001.png", "000002.png", "000003.png"]);
+
+    for (name, &color) in names.iter().zip(COLORS.iter()) {
+        let frame = image::open(path.join(name)).unwrap().to_rgb();
+        assert_eq!(frame.dimensions(), (SIZE, SIZE));
+        assert!(frame.pixels().all(|p| p.0 == color), "{} is not {:?}", name, color);
+    }
+
+    fs::remove_dir_all(&path).unwrap();
+}
+
+#[test]
+fn a_gif_animation_holds_every_frame() {
+    let path = temp_path("animation.gif");
+    let mut window = common::window();
+    let mut recorder = Recorder::new(&path, RecordingFormat::Gif, 0.5).unwrap();
+    recorder.set_playback_rate(10.0);
+
+    // Captured at 0, 0.5, 1 and 1.5.
+    for step in 0..8 {
+        render(&mut window, COLORS[step / 2]);
+        let _ = recorder.record(&window, step as f64 * 0.25).unwrap();
+    }
+    assert_eq!(recorder.frames(), COLORS.len());
+
+    // The animation is complete once the recorder is dropped.
+    drop(recorder);
+
+    let mut decoder = gif::Decoder::new(File::open(&path).unwrap());
+    decoder.set(gif::ColorOutput::RGBA);
+    let mut reader = decoder.read_info().unwrap();
+    assert_eq!((reader.width() as u32, reader.height() as u32), (SIZE, SIZE));
+
+    let mut frames = 0;
+    while let Some(frame) = reader.read_next_frame().unwrap() {
+        // 10 frames per second are 10 hundredths of a second per frame.
+        assert_eq!(frame.delay, 10);
+        assert_eq!((frame.width as u32, frame.height as u32), (SIZE, SIZE));
+
+        let color = COLORS[frames];
+        assert!(
+            frame.buffer.chunks(4).all(|p| p[..3] == color),
+            "frame {} is not {:?}",
+            frames,
+            color
+        );
+        frames += 1;
+    }
+    assert_eq!(frames, COLORS.len());
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,158 @@
+use kiss3d::camera::Frustum;
+use kiss3d::ncollide3d::bounding_volume::AABB;
+use kiss3d::ncollide3d::query::Ray;
+use kiss3d::resource::Mesh;
+use na::{Matrix4, Perspective3, Point3, Vector3};
+use nalgebra as na;
+
+mod common;
+use common::{square, EPS};
+
+#[test]
+fn frusta_contain_what_the_camera_sees() {
+    // Looking down -z from the origin, as OpenGL cameras do.
+    let proj = Perspective3::new(1.0, std::f32::consts::FRAC_PI_2, 1.0, 100.0);
+    let frustum = Frustum::from_matrix(&proj.to_homogeneous());
+
+    assert!(frustum.contains_point(&Point3::new(0.0, 0.0, -10.0)));
+    assert!(frustum.contains_point(&Point3::new(9.0, -9.0, -10.0)));
+    assert!(!frustum.contains_point(&Point3::new(11.0, 0.0, -10.0)));
+    assert!(!frustum.contains_point(&Point3::new(0.0, 0.0, 10.0)));
+    assert!(!frustum.contains_point(&Point3::new(0.0, 0.0, -0.5)));
+    assert!(!frustum.contains_point(&Point3::new(0.0, 0.0, -200.0)));
+
+    let aabb = |mins: [f32; 3], maxs: [f32; 3]| AABB::new(Point3::from(mins), Point3::from(maxs));
+    assert!(frustum.intersects_aabb(&aabb([-1.0, -1.0, -11.0], [1.0, 1.0, -9.0])));
+    // Straddling the right plane.
+    assert!(frustum.intersects_aabb(&aabb([9.0, -1.0, -11.0], [12.0, 1.0, -9.0])));
+    assert!(!frustum.intersects_aabb(&aabb([12.0, -1.0, -11.0], [14.0, 1.0, -9.0])));
+    assert!(!frustum.intersects_aabb(&aabb([-1.0, -1.0, 1.0], [1.0, 1.0, 3.0])));
+
+    let identity = Frustum::from_matrix(&Matrix4::identity());
+    assert!(identity.contains_point(&Point3::new(1.0, -1.0, 1.0)));
+    assert!(!identity.contains_point(&Point3::new(1.1, 0.0, 0.0)));
+}
+
+#[test]
+fn meshes_cache_their_bounds_until_modified() {
+    let mesh = square();
+    let aabb = mesh.aabb().unwrap();
+    assert_eq!(*aabb.mins(), Point3::new(-0.5, -0.5, 0.0));
+    assert_eq!(*aabb.maxs(), Point3::new(0.5, 0.5, 0.0));
+
+    if let Some(coords) = mesh.coords().write().unwrap().data_mut() {
+        coords[0].z = -2.0;
+    }
+    assert_eq!(*mesh.aabb().unwrap().mins(), Point3::new(-0.5, -0.5, -2.0));
+
+    let empty = Mesh::new(Vec::new(), Vec::<Point3<u16>>::new(), None, None, false);
+    assert!(empty.aabb().is_none());
+}
+
+#[test]
+fn rays_hit_both_sides_of_mesh_faces() {
+    let mesh = square();
+    let down = Ray::new(Point3::new(0.2, 0.2, 3.0), -Vector3::z());
+    let up = Ray::new(Point3::new(0.2, 0.2, -3.0), Vector3::z() * 2.0);
+    let beside = Ray::new(Point3::new(0.7, 0.2, 3.0), -Vector3::z());
+
+    assert!((mesh.toi_with_ray(&down, 10.0).unwrap() - 3.0).abs() < EPS);
+    assert!((mesh.toi_with_ray(&up, 10.0).unwrap() - 1.5).abs() < EPS);
+    assert!(mesh.toi_with_ray(&down, 2.0).is_none());
+    assert!(mesh.toi_with_ray(&beside, 10.0).is_none());
+}
+
+#[cfg(feature = "headless")]
+mod headless {
+    use super::common::{self, EPS};
+    use kiss3d::camera::ArcBall;
+    use kiss3d::context::NullContext;
+    use kiss3d::ncollide3d::query::Ray;
+    use kiss3d::scene::InstanceData;
+    use kiss3d::window::Window;
+    use nalgebra::{Point3, Translation3, Vector3};
+
+    fn draws(window: &mut Window, camera: &mut ArcBall) -> usize {
+        NullContext::clear_commands();
+        let _ = window.render_with_camera(camera);
+        common::draws(&NullContext::take_commands()).len()
+    }
+
+    #[test]
+    fn objects_outside_of_the_frustum_are_not_drawn() {
+        let mut window = Window::new_headless(64, 64);
+        let mut camera = ArcBall::new(Point3::new(0.0, 0.0, 10.0), Point3::origin());
+        let _ = window.add_cube(1.0, 1.0, 1.0);
+        let baseline = draws(&mut window, &mut camera);
+
+        let mut group = window.add_group();
+        let mut far = group.add_cube(1.0, 1.0, 1.0);
+        far.set_local_translation(Translation3::new(100.0, 0.0, 0.0));
+        assert_eq!(draws(&mut window, &mut camera), baseline);
+
+        // Bounds follow the transformations of the parents.
+        group.set_local_translation(Translation3::new(-100.0, 0.0, 0.0));
+        assert_eq!(draws(&mut window, &mut camera), baseline + 1);
+
+        group.set_local_translation(Translation3::new(0.0, 100.0, 0.0));
+        assert_eq!(draws(&mut window, &mut camera), baseline);
+        group.enable_frustum_culling(false);
+        assert_eq!(draws(&mut window, &mut camera), baseline + 1);
+    }
+
+    #[test]
+    fn nodes_bound_their_objects_and_children() {
+        let mut window = Window::new_headless(64, 64);
+        let mut group = window.add_group();
+        group.set_local_translation(Translation3::new(10.0, 0.0, 0.0));
+        group.set_local_scale(2.0, 2.0, 2.0);
+        let mut cube = group.add_cube(1.0, 1.0, 1.0);
+        cube.set_local_translation(Translation3::new(0.0, 3.0, 0.0));
+
+        let local = cube.local_aabb().unwrap();
+        assert!((local.maxs() - Point3::new(1.0, 1.0, 1.0)).norm() < EPS);
+
+        let world = group.world_aabb().unwrap();
+        assert!((world.mins() - Point3::new(9.0, 2.0, -1.0)).norm() < EPS);
+        assert!((world.maxs() - Point3::new(11.0, 4.0, 1.0)).norm() < EPS);
+
+        cube.set_instances(&[
+            InstanceData::default(),
+            InstanceData {
+                position: Point3::new(0.0, 0.0, 5.0),
+                ..InstanceData::default()
+            },
+        ]);
+        let world = group.world_aabb().unwrap();
+        assert!((world.maxs() - Point3::new(11.0, 4.0, 6.0)).norm() < EPS);
+
+        cube.set_visible(false);
+        assert!(group.world_aabb().is_none());
+        assert!(group.local_aabb().is_none());
+    }
+
+    #[test]
+    fn rays_hit_the_closest_visible_object() {
+        let mut window = Window::new_headless(64, 64);
+        let mut near = window.add_cube(1.0, 1.0, 1.0);
+        near.set_local_translation(Translation3::new(0.0, 0.0, 2.0));
+        let mut group = window.add_group();
+        group.set_local_scale(2.0, 2.0, 2.0);
+        let far = group.add_cube(1.0, 1.0, 1.0);
+
+        let ray = Ray::new(Point3::new(0.0, 0.0, 10.0), -Vector3::z());
+        let scene = window.scene_mut();
+        let (node, toi) = scene.cast_ray(&ray, 100.0).unwrap();
+        assert!(node == near);
+        assert!((toi - 7.5).abs() < EPS);
+
+        near.set_visible(false);
+        let (node, toi) = scene.cast_ray(&ray, 100.0).unwrap();
+        assert!(node == far);
+        assert!((toi - 9.0).abs() < EPS);
+        assert!(scene.cast_ray(&ray, 8.0).is_none());
+
+        let beside = Ray::new(Point3::new(3.0, 0.0, 10.0), -Vector3::z());
+        assert!(scene.cast_ray(&beside, 100.0).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use kiss3d::camera::{ArcBall, Camera};
+use kiss3d::light::Light;
+use kiss3d::resource::Mesh;
+use kiss3d::text::Font;
+use kiss3d::window::Window;
+use nalgebra::{Point2, Point3, Translation3, Vector2, Vector3};
+
+mod common;
+use common::{Image, SIZE};
+
+// A window with a black background, lit from behind its objects so they are drawn with their
+// flat ambient color, and a camera looking at the origin down the z axis.
+fn setup() -> (Window, ArcBall) {
+    let mut window = common::window();
+    window.set_light(Light::Absolute(Point3::new(0.0, 0.0, -100.0)));
+    let camera = ArcBall::new(Point3::new(0.0, 0.0, 3.0), Point3::origin());
+    (window, camera)
+}
+
+fn render(window: &mut Window, camera: &mut ArcBall) -> Image {
+    let _ = window.render_with_camera(camera);
+    window.snap_image()
+}
+
+// The window coordinates, y up, of a world point.
+fn project(camera: &ArcBall, p: &Point3<f32>) -> Vector2<f32> {
+    camera.project(p, &Vector2::new(SIZE as f32, SIZE as f32))
+}
+
+// The pixel of the image at the window coordinates, y up, of its center.
+fn pixel(image: &Image, x: u32, y: u32) -> [u8; 3] {
+    image.get_pixel(x, SIZE - 1 - y).0
+}
+
+fn is_black(color: [u8; 3]) -> bool {
+    color == [0, 0, 0]
+}
+
+// Only the red channel is lit.
+fn is_red(color: [u8; 3]) -> bool {
+    color[0] > 0 && color[1] == 0 && color[2] == 0
+}
+
+#[test]
+fn triangles_cover_the_pixels_whose_centers_they_contain() {
+    let (mut window, mut camera) = setup();
+    let vertices = vec![
+        Point3::new(-0.8, -0.7, 0.0),
+        Point3::new(0.9, -0.5, 0.0),
+        Point3::new(-0.3, 0.8, 0.0),
+    ];
+    let faces = vec![Point3::new(0u16, 1, 2)];
+    let mesh = Mesh::new(vertices.clone(), faces, None, None, false);
+    let mut triangle = window.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::from_element(1.0));
+    triangle.set_color(1.0, 0.0, 0.0);
+
+    let _ = render(&mut window, &mut camera);
+    let image = render(&mut window, &mut camera);
+    let corners: Vec<_> = vertices.iter().map(|v| project(&camera, v)).collect();
+
+    // Twice the signed area of (a, b, p), positive inside the counter-clockwise triangle.
+    let edge = |a: Vector2<f32>, b: Vector2<f32>, p: Vector2<f32>| {
+        (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+    };
+    let (mut inside, mut outside) = (0, 0);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let center = Vector2::new(x as f32 + 0.5, y as f32 + 0.5);
+            let distances: Vec<f32> = (0..3)
+                .map(|i| {
+                    let (a, b) = (corners[i], corners[(i + 1) % 3]);
+                    edge(a, b, center) / (b - a).norm()
+                })
+                .collect();
+
+            // Pixels whose centers are too close to an edge could go either way.
+            if distances.iter().all(|d| *d > 0.01) {
+                assert!(is_red(pixel(&image, x, y)), "({}, {}) is not covered", x, y);
+                inside += 1;
+            } else if distances.iter().any(|d| *d < -0.01) {
+                assert!(is_black(pixel(&image, x, y)), "({}, {}) is covered", x, y);
+                outside += 1;
+            }
+        }
+    }
+    assert!(inside > 100 && outside > 100, "{} {}", inside, outside);
+}
+
+#[test]
+fn nearer_surfaces_hide_farther_ones() {
+    let (mut window, mut camera) = setup();
+    // The nearer cube is added first, so it is only visible if the depth test hides the
+    // farther one drawn over it.
+    let mut near = window.add_cube(0.5, 0.5, 0.5);
+    near.set_color(1.0, 0.0, 0.0);
+    near.set_local_translation(Translation3::new(0.0, 0.0, 0.5));
+    let mut far = window.add_cube(1.5, 1.5, 0.5);
+    far.set_color(0.0, 0.0, 1.0);
+
+    let image = render(&mut window, &mut camera);
+    let center = SIZE / 2;
+    assert!(is_red(pixel(&image, center, center)));
+
+    let edge = project(&camera, &Point3::new(0.6, 0.0, 0.25));
+    let blue = pixel(&image, edge.x as u32, center);
+    assert!(blue[0] == 0 && blue[1] == 0 && blue[2] > 0, "{:?}", blue);
+    assert!(is_black(pixel(&image, 0, 0)));
+
+    // Without the near cube, the far one is visible in the middle.
+    window.remove_node(&mut near);
+    let image = render(&mut window, &mut camera);
+    let color = pixel(&image, center, center);
+    assert!(color[0] == 0 && color[2] > 0, "{:?}", color);
+}
+
+#[test]
+fn the_vertex_color_material_modulates_by_the_vertex_colors() {
+    let (mut window, mut camera) = setup();
+    let mut mesh = common::square();
+    mesh.set_colors(vec![Point3::new(0.0, 1.0, 0.0); 4]);
+    let mut square = window.add_mesh(Rc::new(RefCell::new(mesh)), Vector3::from_element(1.0));
+    square.set_color(1.0, 1.0, 1.0);
+
+    // The default material ignores the vertex colors.
+    let image = render(&mut window, &mut camera);
+    let center = SIZE / 2;
+    let color = pixel(&image, center, center);
+    assert!(color[0] > 0 && color[1] > 0 && color[2] > 0, "{:?}", color);
+
+    square.set_material_with_name("vertex_color");
+    let image = render(&mut window, &mut camera);
+    let color = pixel(&image, center, center);
+    assert!(color[0] == 0 && color[1] > 0 && color[2] == 0, "{:?}", color);
+}
+
+#[test]
+fn lines_light_up_the_pixels_they_go_through() {
+    let (mut window, mut camera) = setup();
+    let (a, b) = (Point3::new(-0.5, 0.0, 0.0), Point3::new(0.5, 0.0, 0.0));
+    window.draw_line(&a, &b, &Point3::new(0.0, 1.0, 0.0));
+    let image = render(&mut window, &mut camera);
+
+    let (start, end) = (project(&camera, &a), project(&camera, &b));
+    let row = start.y as u32;
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let color = pixel(&image, x, y);
+            let along = x as f32 + 0.5 > start.x + 1.0 && (x as f32 + 0.5) < end.x - 1.0;
+            if y == row && along {
+                assert_eq!(color, [0, 255, 0], "({}, {})", x, y);
+            } else if (y as i32 - row as i32).abs() > 1 || x as f32 > end.x + 1.0 {
+                assert!(is_black(color), "({}, {})", x, y);
+            }
+        }
+    }
+
+    // Lines only last one frame.
+    let image = render(&mut window, &mut camera);
+    assert!(image.pixels().all(|p| is_black(p.0)));
+}
+
+#[test]
+fn points_are_squares_of_their_size() {
+    let (mut window, mut camera) = setup();
+    window.set_point_size(5.0);
+    window.draw_point(&Point3::origin(), &Point3::new(0.0, 0.0, 1.0));
+    let image = render(&mut window, &mut camera);
+
+    let center = project(&camera, &Point3::origin());
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let d = (x as f32 + 0.5 - center.x)
+                .abs()
+                .max((y as f32 + 0.5 - center.y).abs());
+            let color = pixel(&image, x, y);
+            if d < 2.0 {
+                assert_eq!(color, [0, 0, 255], "({}, {})", x, y);
+            } else if d > 3.0 {
+                assert!(is_black(color), "({}, {})", x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn text_is_drawn_within_its_measured_box() {
+    let (mut window, mut camera) = setup();
+    let font = Font::default();
+    let scale = 30.0;
+    let position = Point2::new(4.0, 6.0);
+    window.draw_text("Hi", &position, scale, &font, &Point3::new(1.0, 1.0, 0.0));
+    let image = render(&mut window, &mut camera);
+
+    // Text is measured in units of half a pixel, from the top-left corner of the window.
+    let size = Font::measure(&font, "Hi", scale);
+    let (x0, y0) = (position.x / 2.0, position.y / 2.0);
+    let (x1, y1) = (x0 + size.x / 2.0, y0 + size.y / 2.0);
+
+    let mut lit = 0;
+    for (x, y, color) in image.enumerate_pixels() {
+        if is_black(color.0) {
+            continue;
+        }
+        let (x, y) = (x as f32 + 0.5, y as f32 + 0.5);
+        assert!(x > x0 - 1.0 && x < x1 + 1.0 && y > y0 - 1.0 && y < y1 + 1.0);
+        assert_eq!(color.0[2], 0);
+        lit += 1;
+    }
+    assert!(lit > 10, "{}", lit);
+}
@@ -0,0 +1,178 @@
+use kiss3d::text::{Font, TextAlign, TextAnchor, TextLayout};
+use nalgebra::{Point2, Point3, Vector2};
+
+const SCALE: f32 = 40.0;
+
+fn white() -> Point3<f32> {
+    Point3::new(1.0, 1.0, 1.0)
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1.0e-3, "{} != {}", a, b);
+}
+
+fn line_texts(layout: &TextLayout) -> Vec<String> {
+    layout
+        .lines()
+        .iter()
+        .map(|l| {
+            layout.glyphs()[l.glyphs.clone()]
+                .iter()
+                .map(|g| g.character)
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn measures_the_widest_line_and_every_line() {
+    let font = Font::default();
+    let line_height = TextLayout::new(&font, SCALE).line_height();
+    let short = Font::measure(&font, "short", SCALE);
+    let long = Font::measure(&font, "a longer line", SCALE);
+
+    assert!(short.x > 0.0 && long.x > short.x);
+    assert_close(short.y, line_height);
+
+    let both = Font::measure(&font, "short\na longer line\n", SCALE);
+    assert_close(both.x, long.x);
+    assert_close(both.y, 3.0 * line_height);
+
+    assert_eq!(Font::measure(&font, "", SCALE), Vector2::zeros());
+    assert_close(Font::measure(&font, "short", 2.0 * SCALE).x, 2.0 * short.x);
+}
+
+#[test]
+fn glyphs_follow_each_other() {
+    let font = Font::default();
+    let layout = TextLayout::new(&font, SCALE).with_span("Hello", &white());
+    let glyphs = layout.glyphs();
+
+    assert_eq!(glyphs.len(), 5);
+    assert_eq!(glyphs[0].position, Point2::origin());
+    for pair in glyphs.windows(2) {
+        assert!(pair[1].position.x >= pair[0].position.x + pair[0].advance - 1.0);
+        assert_eq!(pair[1].position.y, 0.0);
+    }
+    let last = glyphs.last().unwrap();
+    assert_close(layout.size().x, last.position.x + last.advance);
+}
+
+#[test]
+fn lines_wrap_at_whitespaces_to_fit_the_maximum_width() {
+    let font = Font::default();
+    let text = "the quick brown fox jumps over the lazy dog";
+    let width = Font::measure(&font, "the quick brown", SCALE).x + 1.0;
+    let layout = TextLayout::new(&font, SCALE)
+        .with_span(text, &white())
+        .with_max_width(width);
+
+    assert_eq!(
+        line_texts(&layout),
+        ["the quick brown", "fox jumps over", "the lazy dog"]
+    );
+    assert!(layout.size().x <= width);
+    for (i, line) in layout.lines().iter().enumerate() {
+        assert_eq!(line.position.x, 0.0);
+        assert_close(line.position.y, i as f32 * layout.line_height());
+        assert!(layout.glyphs()[line.glyphs.clone()]
+            .iter()
+            .all(|g| g.line == i));
+    }
+
+    // Words wider than a line are broken, and line feeds always break lines.
+    let width = Font::measure(&font, "abc", SCALE).x + 1.0;
+    let layout = TextLayout::new(&font, SCALE)
+        .with_span("abcdefg hi\n\njk", &white())
+        .with_max_width(width);
+    assert_eq!(line_texts(&layout), ["abc", "def", "g hi", "", "jk"]);
+}
+
+#[test]
+fn lines_are_aligned_within_the_widest_one() {
+    let font = Font::default();
+    let mut layout = TextLayout::new(&font, SCALE)
+        .with_span("wide line\nnarrow", &white())
+        .with_line_spacing(1.5);
+    let (wide, narrow) = (layout.lines()[0].width, layout.lines()[1].width);
+    assert_close(layout.size().x, wide);
+    assert_close(layout.size().y, 2.5 * layout.line_height());
+    assert_eq!(layout.lines()[1].position.x, 0.0);
+
+    layout.set_align(TextAlign::Center);
+    assert_close(layout.lines()[0].position.x, 0.0);
+    assert_close(layout.lines()[1].position.x, (wide - narrow) / 2.0);
+
+    layout.set_align(TextAlign::Right);
+    assert_close(layout.lines()[1].position.x, wide - narrow);
+    let first = layout.lines()[1].glyphs.start;
+    assert_close(layout.glyphs()[first].position.x, wide - narrow);
+}
+
+#[test]
+fn spans_keep_their_colors_across_lines() {
+    let font = Font::default();
+    let red = Point3::new(1.0, 0.0, 0.0);
+    let mut layout = TextLayout::new(&font, SCALE)
+        .with_span("Dig ", &red)
+        .with_span("the\nstone", &white());
+
+    let spans: Vec<_> = layout.glyphs().iter().map(|g| g.span).collect();
+    assert_eq!(spans, [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1]);
+    assert!(layout.glyphs()[..4].iter().all(|g| g.color == red));
+    assert!(layout.glyphs()[4..].iter().all(|g| g.color == white()));
+    assert_eq!(line_texts(&layout), ["Dig the", "stone"]);
+
+    layout.clear();
+    assert!(layout.glyphs().is_empty() && layout.lines().is_empty());
+    assert_eq!(layout.size(), Vector2::zeros());
+}
+
+#[test]
+fn anchors_place_text_in_the_corners_of_the_screen() {
+    let size = Vector2::new(100.0, 20.0);
+    let screen = Vector2::new(800.0, 600.0);
+    let position = |anchor: TextAnchor| anchor.position(&size, &screen, 10.0);
+
+    assert_eq!(position(TextAnchor::TopLeft), Point2::new(10.0, 10.0));
+    assert_eq!(position(TextAnchor::TopRight), Point2::new(690.0, 10.0));
+    assert_eq!(position(TextAnchor::BottomLeft), Point2::new(10.0, 570.0));
+    assert_eq!(position(TextAnchor::BottomRight), Point2::new(690.0, 570.0));
+    assert_eq!(position(TextAnchor::Center), Point2::new(350.0, 290.0));
+    assert_eq!(position(TextAnchor::Top), Point2::new(350.0, 10.0));
+    assert_eq!(position(TextAnchor::Right), Point2::new(690.0, 290.0));
+}
+
+#[cfg(feature = "headless")]
+mod headless {
+    use super::*;
+    use kiss3d::window::Window;
+
+    #[test]
+    fn spans_are_drawn_with_their_own_colors() {
+        let mut window = Window::new_headless(200, 60);
+        window.set_background_color(0.0, 0.0, 0.0);
+        let font = Font::default();
+        let layout = TextLayout::new(&font, 60.0)
+            .with_span("MMM", &Point3::new(1.0, 0.0, 0.0))
+            .with_span("MMM", &Point3::new(0.0, 0.0, 1.0));
+
+        assert_eq!(window.text_screen_size(), Vector2::new(400.0, 120.0));
+        let pos = window.draw_anchored_text(&layout, TextAnchor::Center, 0.0);
+        assert!(pos.x > 0.0 && pos.y > 0.0);
+        let _ = window.render();
+        let image = window.snap_image();
+
+        // The left half of the text is red and the right half blue, in pixels.
+        let split = ((pos.x + layout.glyphs()[3].position.x) / 2.0) as u32;
+        let (mut left, mut right) = ([0u32; 3], [0u32; 3]);
+        for (x, _, pixel) in image.enumerate_pixels() {
+            let sums = if x < split { &mut left } else { &mut right };
+            for (sum, c) in sums.iter_mut().zip(pixel.0.iter()) {
+                *sum += *c as u32;
+            }
+        }
+        assert!(left[0] > 0 && left[1] == 0 && left[2] == 0, "{:?}", left);
+        assert!(right[0] == 0 && right[1] == 0 && right[2] > 0, "{:?}", right);
+    }
+}
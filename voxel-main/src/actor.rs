@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
+use crate::item::Inventory;
 use crate::occupation::{Occupation, Strategy};
 use crate::occupations;
+use crate::perception::{Memory, Perception};
+use crate::verb::Order;
 use rand::Rng;
 
 pub struct Actor {
@@ -11,31 +14,43 @@ pub struct Actor {
     pub occupation: Box<dyn Occupation>,
     pub strategy: Option<Box<dyn Strategy>>,
 
-    pub shortterm_memory: HashMap<String, std::time::Instant>,
+    // Markers that expire at the given game_time
+    pub shortterm_memory: HashMap<String, u64>,
 
-    pub node_sync_id: u64,
-    pub node: Option<kiss3d::scene::SceneNode>,
+    // How this actor feels about other actors, keyed by actor id
+    pub relationships: HashMap<u64, i32>,
 }
 
 impl Actor {
-    pub fn new() -> Actor {
+    pub fn new(id: u64) -> Actor {
         Actor {
-            state: ActorState::new(),
+            state: ActorState::new(id),
             name: String::new(),
             occupation: Box::new(occupations::Avatar::new()),
             strategy: None,
 
             shortterm_memory: HashMap::new(),
 
-            node_sync_id: 0,
-            node: None,
+            relationships: HashMap::new(),
         }
     }
+
+    pub fn relationship(&self, other: &Actor) -> i32 {
+        *self.relationships.get(&other.state.id()).unwrap_or(&0)
+    }
+
+    pub fn change_relationship(&mut self, other: &Actor, delta: i32) {
+        *self.relationships.entry(other.state.id()).or_insert(0) += delta;
+    }
 }
 
 // ActorState is split out from the Actor struct to allow for easier borrowing of
 // parts of the composite struct.
 pub struct ActorState {
+    // Unique for the lifetime of the World. Unlike the index into World::actors,
+    // this does not change as the actor list is reordered.
+    id: u64,
+
     sync_id: u64,
     x: i64,
     y: i64,
@@ -51,11 +66,24 @@ pub struct ActorState {
     // a bool. Rust can help detect incorrect assumptions using that approach, whereas a
     // runtime bool is more open to programmer error.
     ethereal: bool,
+
+    perception: Perception,
+
+    inventory: Inventory,
+
+    // Requests from outside the actor's own strategy (i.e. the player's
+    // input for the Avatar), handled in order by the strategy
+    orders: VecDeque<Order>,
+
+    // Feedback for whoever gives the actor orders (i.e. an order that could
+    // not be carried out), shown to the player for the Avatar
+    reports: VecDeque<String>,
 }
 
 impl ActorState {
-    fn new() -> Self {
+    fn new(id: u64) -> Self {
         Self {
+            id,
             // Start at 1 so any dependent sync_id's that start at 0 are out-of-sync by default
             sync_id: 1,
             x: 0,
@@ -65,9 +93,17 @@ impl ActorState {
             b: 0.0,
             beacon: (0, 0),
             ethereal: false,
+            perception: Perception::new(),
+            inventory: Inventory::new(),
+            orders: VecDeque::new(),
+            reports: VecDeque::new(),
         }
     }
 
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
     pub fn sync_id(&self) -> u64 {
         self.sync_id
     }
@@ -108,11 +144,7 @@ impl ActorState {
         self.beacon
     }
 
-    pub fn beacon_point_with_random(
-        &self,
-        rng: &mut rand::rngs::ThreadRng,
-        delta: i64,
-    ) -> (i64, i64) {
+    pub fn beacon_point_with_random<R: Rng>(&self, rng: &mut R, delta: i64) -> (i64, i64) {
         let bx = self.beacon.0 + rng.gen_range(-delta, delta);
         let by = self.beacon.1 + rng.gen_range(-delta, delta);
         (bx, by)
@@ -122,4 +154,34 @@ impl ActorState {
         self.beacon = p;
         self.sync_id += 1;
     }
+
+    pub fn perception_mut(&mut self) -> &mut Perception {
+        &mut self.perception
+    }
+
+    pub fn memory(&self) -> &Memory {
+        &self.perception.memory
+    }
+
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+    pub fn inventory_mut(&mut self) -> &mut Inventory {
+        &mut self.inventory
+    }
+
+    pub fn push_order(&mut self, order: Order) {
+        self.orders.push_back(order);
+    }
+    pub fn pop_order(&mut self) -> Option<Order> {
+        self.orders.pop_front()
+    }
+
+    pub fn push_report(&mut self, text: String) {
+        self.reports.push_back(text);
+    }
+
+    pub fn pop_report(&mut self) -> Option<String> {
+        self.reports.pop_front()
+    }
 }
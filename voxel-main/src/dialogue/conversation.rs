@@ -0,0 +1,211 @@
+use crate::actor::Actor;
+use crate::dialogue::Dialogues;
+
+/// An in-progress conversation between the player and an Actor.
+///
+/// The text of the current node and its choices are resolved (conditions
+/// checked, placeholders filled in) when the node is entered so drawing the
+/// dialogue panel does not need access to the Actors.
+///
+pub struct Conversation {
+    pub actor_id: u64,
+    pub actor_name: String,
+    pub text: String,
+
+    // The visible choices, paired with their index in the node's choice list
+    pub choices: Vec<(usize, String)>,
+
+    tree: usize,
+    node: String,
+}
+
+// Placeholders available in dialogue text
+fn fill(template: &str, actor: &Actor, player: &Actor) -> String {
+    template
+        .replace("{name}", &actor.name)
+        .replace("{player}", &player.name)
+        .replace("{occupation}", actor.occupation.name())
+}
+
+impl Conversation {
+    pub fn start(dialogues: &Dialogues, actor: &Actor, player: &Actor) -> Option<Self> {
+        let tree = dialogues.select(actor, player)?;
+
+        let mut conversation = Self {
+            actor_id: actor.state.id(),
+            actor_name: actor.name.clone(),
+            text: String::new(),
+            choices: Vec::new(),
+            tree,
+            node: String::new(),
+        };
+
+        let start = dialogues.tree(tree).start.clone();
+        if conversation.enter(dialogues, &start, actor, player) {
+            Some(conversation)
+        } else {
+            None
+        }
+    }
+
+    fn enter(&mut self, dialogues: &Dialogues, name: &str, actor: &Actor, player: &Actor) -> bool {
+        let tree = dialogues.tree(self.tree);
+        let node = match tree.nodes.get(name) {
+            Some(node) => node,
+            None => {
+                println!("Dialogue '{}' has no node '{}'", tree.name, name);
+                return false;
+            }
+        };
+
+        self.node = name.to_string();
+        self.text = fill(&node.text, actor, player);
+        self.choices = node
+            .choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.conditions.iter().all(|c| c.check(actor, player)))
+            .map(|(i, choice)| (i, fill(&choice.text, actor, player)))
+            .collect();
+        true
+    }
+
+    /// Picks one of the visible choices (zero-based), applying its effects.
+    ///
+    /// Returns false once the conversation is over.
+    pub fn choose(
+        &mut self,
+        dialogues: &Dialogues,
+        index: usize,
+        actor: &mut Actor,
+        player: &mut Actor,
+    ) -> bool {
+        // Ignore keys that do not correspond to a choice
+        if index >= self.choices.len() {
+            return true;
+        }
+
+        let tree = dialogues.tree(self.tree);
+        let choice = &tree.nodes[&self.node].choices[self.choices[index].0];
+        for effect in &choice.effects {
+            effect.apply(actor, player);
+        }
+
+        match &choice.next {
+            Some(next) => self.enter(dialogues, next, actor, player),
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemKind;
+
+    fn dialogues() -> Dialogues {
+        let mut dialogues = Dialogues::new();
+        dialogues.add(
+            ron::de::from_str(
+                r#"(
+                    name: "test",
+                    start: "greeting",
+                    nodes: {
+                        "greeting": (
+                            text: "Hello {player}, I am {name} the {occupation}.",
+                            choices: [
+                                (
+                                    text: "Have some seeds, {name}.",
+                                    conditions: [PlayerHasItem(Seeds, 1)],
+                                    effects: [TakeItem(Seeds, 1), ChangeRelationship(1)],
+                                    next: Some("thanks"),
+                                ),
+                                (text: "Where to?", next: Some("nowhere")),
+                                (text: "Goodbye."),
+                            ],
+                        ),
+                        "thanks": (
+                            text: "Thank you.",
+                            choices: [(text: "Back", next: Some("greeting"))],
+                        ),
+                    },
+                )"#,
+            )
+            .unwrap(),
+        );
+        dialogues
+    }
+
+    fn player_and_actor() -> (Actor, Actor) {
+        let mut player = Actor::new(1);
+        player.name = "Pat".to_string();
+        let mut actor = Actor::new(2);
+        actor.name = "Sam".to_string();
+        (player, actor)
+    }
+
+    fn choice_texts(conversation: &Conversation) -> Vec<&str> {
+        conversation
+            .choices
+            .iter()
+            .map(|(_, text)| text.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn the_start_node_is_filled_in_and_only_shows_available_choices() {
+        let dialogues = dialogues();
+        let (mut player, actor) = player_and_actor();
+
+        let conversation = Conversation::start(&dialogues, &actor, &player).unwrap();
+        assert_eq!(conversation.actor_id, 2);
+        assert_eq!(conversation.actor_name, "Sam");
+        assert_eq!(conversation.text, "Hello Pat, I am Sam the Avatar.");
+        assert_eq!(choice_texts(&conversation), ["Where to?", "Goodbye."]);
+        assert_eq!(conversation.choices[0].0, 1);
+
+        player.state.inventory_mut().add(ItemKind::Seeds, 1);
+        let conversation = Conversation::start(&dialogues, &actor, &player).unwrap();
+        assert_eq!(
+            choice_texts(&conversation),
+            ["Have some seeds, Sam.", "Where to?", "Goodbye."]
+        );
+    }
+
+    #[test]
+    fn choosing_applies_effects_and_moves_to_the_next_node() {
+        let dialogues = dialogues();
+        let (mut player, mut actor) = player_and_actor();
+        player.state.inventory_mut().add(ItemKind::Seeds, 1);
+        let mut conversation = Conversation::start(&dialogues, &actor, &player).unwrap();
+
+        // Choices that are not shown do nothing
+        assert!(conversation.choose(&dialogues, 3, &mut actor, &mut player));
+        assert_eq!(actor.relationship(&player), 0);
+
+        assert!(conversation.choose(&dialogues, 0, &mut actor, &mut player));
+        assert_eq!(conversation.text, "Thank you.");
+        assert_eq!(actor.relationship(&player), 1);
+        assert_eq!(actor.state.inventory().count(ItemKind::Seeds), 1);
+
+        // Back at the start, the seeds are gone so their choice is too
+        assert!(conversation.choose(&dialogues, 0, &mut actor, &mut player));
+        assert_eq!(choice_texts(&conversation), ["Where to?", "Goodbye."]);
+
+        // A choice without a next node ends the conversation
+        assert!(!conversation.choose(&dialogues, 1, &mut actor, &mut player));
+    }
+
+    #[test]
+    fn missing_nodes_end_the_conversation() {
+        let dialogues = dialogues();
+        let (mut player, mut actor) = player_and_actor();
+        let mut conversation = Conversation::start(&dialogues, &actor, &player).unwrap();
+        assert!(!conversation.choose(&dialogues, 0, &mut actor, &mut player));
+
+        let mut broken = Dialogues::new();
+        broken.add(ron::de::from_str(r#"(name: "broken", start: "nowhere", nodes: {})"#).unwrap());
+        assert!(Conversation::start(&broken, &actor, &player).is_none());
+        assert!(Conversation::start(&Dialogues::new(), &actor, &player).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//
+// Dialogue trees are data-driven: each file in src/assets/dialogue holds one
+// tree in RON format. When the player bumps into an Actor, the highest
+// priority tree whose conditions hold for that Actor is used to start a
+// Conversation.
+//
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::actor::Actor;
+use crate::item::ItemKind;
+use crate::occupations;
+use crate::world::TileKind;
+
+mod conversation;
+pub use conversation::*;
+
+#[derive(Deserialize)]
+pub enum Condition {
+    // The Actor's occupation name, i.e. Occupation::name()
+    Occupation(String),
+    RelationshipAtLeast(i32),
+    RelationshipBelow(i32),
+    // The Actor has seen the player recently enough to still remember them
+    RemembersPlayer,
+    // The Actor remembers seeing a tile of this kind
+    RemembersTile(TileKind),
+    PlayerHasItem(ItemKind, u32),
+}
+
+impl Condition {
+    pub fn check(&self, actor: &Actor, player: &Actor) -> bool {
+        match self {
+            Condition::Occupation(name) => actor.occupation.name() == name,
+            Condition::RelationshipAtLeast(value) => actor.relationship(player) >= *value,
+            Condition::RelationshipBelow(value) => actor.relationship(player) < *value,
+            Condition::RemembersPlayer => actor.state.memory().actor(player.state.id()).is_some(),
+            Condition::RemembersTile(kind) => actor.state.memory().knows_of(*kind),
+            Condition::PlayerHasItem(kind, count) => {
+                player.state.inventory().count(*kind) >= *count
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub enum Effect {
+    // The Actor gives the player an item
+    GiveItem(ItemKind, u32),
+    // The player gives the Actor an item
+    TakeItem(ItemKind, u32),
+    ChangeRelationship(i32),
+    // The Actor moves their beacon to where the player is standing
+    SetBeaconToPlayer,
+    // The Actor takes on a new occupation, by Occupation::name()
+    RequestJob(String),
+}
+
+impl Effect {
+    pub fn apply(&self, actor: &mut Actor, player: &mut Actor) {
+        match self {
+            Effect::GiveItem(kind, count) => player.state.inventory_mut().add(*kind, *count),
+            Effect::TakeItem(kind, count) => {
+                if player.state.inventory_mut().remove(*kind, *count) {
+                    actor.state.inventory_mut().add(*kind, *count);
+                }
+            }
+            Effect::ChangeRelationship(delta) => actor.change_relationship(player, *delta),
+            Effect::SetBeaconToPlayer => {
+                actor.state.set_beacon_point(player.state.position());
+            }
+            Effect::RequestJob(name) => match occupations::from_name(name) {
+                Some(occupation) => {
+                    occupation.init(&mut actor.state);
+                    actor.occupation = occupation;
+                    actor.strategy = None;
+                }
+                None => println!("Unknown occupation in dialogue: {}", name),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Choice {
+    pub text: String,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    // The node to go to next; the conversation ends if there is none
+    #[serde(default)]
+    pub next: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogueNode {
+    pub text: String,
+    #[serde(default)]
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Deserialize)]
+pub struct DialogueTree {
+    pub name: String,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub conditions: Vec<Condition>,
+    pub start: String,
+    pub nodes: HashMap<String, DialogueNode>,
+}
+
+pub struct Dialogues {
+    trees: Vec<DialogueTree>,
+}
+
+impl Dialogues {
+    pub fn new() -> Self {
+        Self { trees: Vec::new() }
+    }
+
+    /// Loads every .ron file matching the pattern. Files that fail to parse
+    /// are reported and skipped rather than aborting the game.
+    pub fn load(pattern: &str) -> Self {
+        use glob::glob;
+
+        let mut paths = Vec::new();
+        for entry in glob(pattern).expect("Failed to read glob pattern") {
+            match entry {
+                Ok(path) => paths.push(path),
+                Err(e) => println!("{:?}", e),
+            }
+        }
+        paths.sort_by(|a, b| alphanumeric_sort::compare_path(a, b));
+
+        let mut dialogues = Dialogues::new();
+        for path in paths {
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|s| ron::de::from_str(&s).map_err(|e| e.to_string()));
+            match result {
+                Ok(tree) => {
+                    println!("Loaded dialogue {}", path.display());
+                    dialogues.add(tree);
+                }
+                Err(e) => println!("Failed to load dialogue {}: {}", path.display(), e),
+            }
+        }
+        dialogues
+    }
+
+    pub fn add(&mut self, tree: DialogueTree) {
+        self.trees.push(tree);
+        // Stable sort so equal priorities keep file order
+        self.trees
+            .sort_by_key(|tree| std::cmp::Reverse(tree.priority));
+    }
+
+    /// The index of the tree to use when the player talks to the given actor
+    pub fn select(&self, actor: &Actor, player: &Actor) -> Option<usize> {
+        self.trees
+            .iter()
+            .position(|tree| tree.conditions.iter().all(|c| c.check(actor, player)))
+    }
+
+    pub fn tree(&self, index: usize) -> &DialogueTree {
+        &self.trees[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perception::Percept;
+
+    fn tree(source: &str) -> DialogueTree {
+        ron::de::from_str(source).unwrap()
+    }
+
+    fn player_and_actor() -> (Actor, Actor) {
+        (Actor::new(1), Actor::new(2))
+    }
+
+    #[test]
+    fn conditions_check_the_actor_and_the_player() {
+        let (mut player, mut actor) = player_and_actor();
+        actor.occupation = occupations::from_name("Farmer").unwrap();
+        assert!(Condition::Occupation("Farmer".to_string()).check(&actor, &player));
+        assert!(!Condition::Occupation("Eater".to_string()).check(&actor, &player));
+
+        actor.change_relationship(&player, 2);
+        assert!(Condition::RelationshipAtLeast(2).check(&actor, &player));
+        assert!(!Condition::RelationshipAtLeast(3).check(&actor, &player));
+        assert!(Condition::RelationshipBelow(3).check(&actor, &player));
+        assert!(!Condition::RelationshipBelow(2).check(&actor, &player));
+
+        assert!(!Condition::RemembersPlayer.check(&actor, &player));
+        assert!(!Condition::RemembersTile(TileKind::Plants).check(&actor, &player));
+        let memory = &mut actor.state.perception_mut().memory;
+        memory.remember(&Percept::Actor { id: 1 }, 0);
+        memory.remember(
+            &Percept::Tile {
+                position: (0, 0),
+                kind: TileKind::Plants,
+            },
+            0,
+        );
+        assert!(Condition::RemembersPlayer.check(&actor, &player));
+        assert!(Condition::RemembersTile(TileKind::Plants).check(&actor, &player));
+
+        player.state.inventory_mut().add(ItemKind::Seeds, 3);
+        assert!(Condition::PlayerHasItem(ItemKind::Seeds, 3).check(&actor, &player));
+        assert!(!Condition::PlayerHasItem(ItemKind::Seeds, 4).check(&actor, &player));
+        assert!(!Condition::PlayerHasItem(ItemKind::Stone, 1).check(&actor, &player));
+    }
+
+    #[test]
+    fn items_only_change_hands_if_the_player_has_enough() {
+        let (mut player, mut actor) = player_and_actor();
+        player.state.inventory_mut().add(ItemKind::Produce, 1);
+
+        Effect::TakeItem(ItemKind::Produce, 2).apply(&mut actor, &mut player);
+        assert_eq!(player.state.inventory().count(ItemKind::Produce), 1);
+        assert_eq!(actor.state.inventory().count(ItemKind::Produce), 0);
+
+        Effect::TakeItem(ItemKind::Produce, 1).apply(&mut actor, &mut player);
+        assert_eq!(player.state.inventory().count(ItemKind::Produce), 0);
+        assert_eq!(actor.state.inventory().count(ItemKind::Produce), 1);
+
+        Effect::GiveItem(ItemKind::Seeds, 5).apply(&mut actor, &mut player);
+        assert_eq!(player.state.inventory().count(ItemKind::Seeds), 5);
+    }
+
+    #[test]
+    fn requesting_a_job_changes_the_occupation_if_it_exists() {
+        let (mut player, mut actor) = player_and_actor();
+        Effect::RequestJob("Farmer".to_string()).apply(&mut actor, &mut player);
+        assert_eq!(actor.occupation.name(), "Farmer");
+        assert_eq!(actor.state.color(), (0.8, 0.8, 0.0));
+
+        Effect::RequestJob("Astronaut".to_string()).apply(&mut actor, &mut player);
+        assert_eq!(actor.occupation.name(), "Farmer");
+    }
+
+    #[test]
+    fn the_highest_priority_tree_whose_conditions_hold_is_selected() {
+        let (player, mut actor) = player_and_actor();
+        let mut dialogues = Dialogues::new();
+        dialogues.add(tree(r#"(name: "anyone", start: "a", nodes: {})"#));
+        dialogues.add(tree(r#"(name: "also anyone", start: "a", nodes: {})"#));
+        dialogues.add(tree(
+            r#"(name: "farmer", priority: 10, conditions: [Occupation("Farmer")],
+                start: "a", nodes: {})"#,
+        ));
+
+        let selected = |actor: &Actor| {
+            let index = dialogues.select(actor, &player).unwrap();
+            dialogues.tree(index).name.clone()
+        };
+        // Equal priorities keep the order they were added in
+        assert_eq!(selected(&actor), "anyone");
+        actor.occupation = occupations::from_name("Farmer").unwrap();
+        assert_eq!(selected(&actor), "farmer");
+
+        let mut picky = Dialogues::new();
+        picky.add(tree(
+            r#"(name: "friends", conditions: [RelationshipAtLeast(5)], start: "a", nodes: {})"#,
+        ));
+        assert!(picky.select(&actor, &player).is_none());
+    }
+
+    #[test]
+    fn files_that_fail_to_parse_are_skipped() {
+        let dir = std::env::temp_dir().join(format!("voxel-main-dialogue-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("00_good.ron"),
+            r#"(name: "good", start: "a", nodes: {})"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("01_bad.ron"), r#"(name: "bad", start: )"#).unwrap();
+        std::fs::write(
+            dir.join("02_unknown.ron"),
+            r#"(name: "unknown", conditions: [Hungry], start: "a", nodes: {})"#,
+        )
+        .unwrap();
+
+        let dialogues = Dialogues::load(dir.join("*.ron").to_str().unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let names: Vec<&str> = dialogues.trees.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["good"]);
+    }
+}
@@ -0,0 +1,249 @@
+use kiss3d::image::{Rgba, RgbaImage};
+use nalgebra::Point2;
+
+use crate::world::TileKind;
+
+//
+// Tile atlas
+//
+// Packs the tile images into a single texture in memory. Tiles may be of
+// different sizes: they are sorted by height and placed on shelves, left to
+// right. Each tile is surrounded by `padding` pixels wrapped around from its
+// opposite edges, so samples that stray just over the edge of a repeating
+// tile still pick up the right colour rather than the neighbouring tile.
+//
+
+/// The region of the atlas used by one tile, in texture coordinates
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TileUv {
+    pub min: Point2<f32>,
+    pub max: Point2<f32>,
+}
+
+/// Where each tile kind is in the atlas
+#[derive(Clone, Debug, Default)]
+pub struct TileUvs {
+    // Indexed by TileKind
+    uvs: Vec<Option<TileUv>>,
+}
+
+impl TileUvs {
+    pub fn get(&self, kind: TileKind) -> Option<TileUv> {
+        self.uvs.get(kind as usize).cloned().flatten()
+    }
+
+    fn insert(&mut self, kind: TileKind, uv: TileUv) {
+        let index = kind as usize;
+        if self.uvs.len() <= index {
+            self.uvs.resize(index + 1, None);
+        }
+        self.uvs[index] = Some(uv);
+    }
+}
+
+pub struct TileAtlas {
+    pub image: RgbaImage,
+    pub uvs: TileUvs,
+}
+
+pub struct TileAtlasBuilder {
+    padding: u32,
+    tiles: Vec<(TileKind, RgbaImage)>,
+}
+
+impl TileAtlasBuilder {
+    pub fn new() -> Self {
+        Self {
+            padding: 1,
+            tiles: Vec::new(),
+        }
+    }
+
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Adds the image for a tile kind, replacing any previous one
+    pub fn add(&mut self, kind: TileKind, image: RgbaImage) {
+        self.tiles.retain(|(k, _)| *k != kind);
+        self.tiles.push((kind, image));
+    }
+
+    pub fn build(self) -> TileAtlas {
+        let padding = self.padding;
+        let padded =
+            |image: &RgbaImage| (image.width() + 2 * padding, image.height() + 2 * padding);
+
+        // Tallest first so each shelf wastes as little height as possible
+        let mut order: Vec<usize> = (0..self.tiles.len()).collect();
+        order.sort_by_key(|i| std::cmp::Reverse(self.tiles[*i].1.height()));
+
+        // Power of two sizes, at least square enough to hold all the tiles
+        let area: u32 = self
+            .tiles
+            .iter()
+            .map(|(_, image)| padded(image).0 * padded(image).1)
+            .sum();
+        let widest = self
+            .tiles
+            .iter()
+            .map(|(_, image)| padded(image).0)
+            .max()
+            .unwrap_or(1);
+        let width = widest
+            .max((area as f32).sqrt().ceil() as u32)
+            .next_power_of_two();
+
+        //
+        // Place the tiles on shelves
+        //
+        let mut positions = vec![(0, 0); self.tiles.len()];
+        let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+        for &i in &order {
+            let (w, h) = padded(&self.tiles[i].1);
+            if x + w > width {
+                x = 0;
+                y += shelf_height;
+                shelf_height = 0;
+            }
+            positions[i] = (x, y);
+            x += w;
+            shelf_height = shelf_height.max(h);
+        }
+        let height = (y + shelf_height).max(1).next_power_of_two();
+
+        //
+        // Copy the tiles and their padding into the atlas
+        //
+        let mut image = RgbaImage::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+        let mut uvs = TileUvs::default();
+        for ((kind, tile), (x, y)) in self.tiles.iter().zip(positions) {
+            let (w, h) = padded(tile);
+            for py in 0..h {
+                for px in 0..w {
+                    let sx = (px + tile.width() - padding % tile.width()) % tile.width();
+                    let sy = (py + tile.height() - padding % tile.height()) % tile.height();
+                    image.put_pixel(x + px, y + py, *tile.get_pixel(sx, sy));
+                }
+            }
+
+            let min = (x + padding, y + padding);
+            let max = (min.0 + tile.width(), min.1 + tile.height());
+            uvs.insert(
+                *kind,
+                TileUv {
+                    min: Point2::new(min.0 as f32 / width as f32, min.1 as f32 / height as f32),
+                    max: Point2::new(max.0 as f32 / width as f32, max.1 as f32 / height as f32),
+                },
+            );
+        }
+
+        TileAtlas { image, uvs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A tile whose pixels encode their own coordinates
+    fn tile(width: u32, height: u32, id: u8) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| Rgba([x as u8, y as u8, id, 255]))
+    }
+
+    // The pixel rect (min, max) of a tile kind in the atlas
+    fn rect(atlas: &TileAtlas, kind: TileKind) -> ((u32, u32), (u32, u32)) {
+        let uv = atlas.uvs.get(kind).unwrap();
+        let (w, h) = (atlas.image.width() as f32, atlas.image.height() as f32);
+        (
+            ((uv.min.x * w) as u32, (uv.min.y * h) as u32),
+            ((uv.max.x * w) as u32, (uv.max.y * h) as u32),
+        )
+    }
+
+    #[test]
+    fn tiles_of_mixed_sizes_are_packed_on_shelves_tallest_first() {
+        let mut builder = TileAtlasBuilder::new();
+        builder.add(TileKind::Concrete, tile(6, 2, 1));
+        builder.add(TileKind::Grass, tile(8, 8, 2));
+        builder.add(TileKind::Plants, tile(3, 3, 3));
+        builder.add(TileKind::Tilled, tile(4, 4, 4));
+        let atlas = builder.build();
+
+        // 193 padded pixels fit a 16 wide atlas: the 8x8 and 4x4 tiles on
+        // the first shelf, the 3x3 and 6x2 ones on the second
+        assert_eq!(atlas.image.dimensions(), (16, 16));
+        assert_eq!(rect(&atlas, TileKind::Grass), ((1, 1), (9, 9)));
+        assert_eq!(rect(&atlas, TileKind::Tilled), ((11, 1), (15, 5)));
+        assert_eq!(rect(&atlas, TileKind::Plants), ((1, 11), (4, 14)));
+        assert_eq!(rect(&atlas, TileKind::Concrete), ((6, 11), (12, 13)));
+
+        for (kind, id) in [
+            (TileKind::Concrete, 1),
+            (TileKind::Grass, 2),
+            (TileKind::Plants, 3),
+            (TileKind::Tilled, 4),
+        ] {
+            let ((x0, y0), (x1, y1)) = rect(&atlas, kind);
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let expected = Rgba([(x - x0) as u8, (y - y0) as u8, id, 255]);
+                    assert_eq!(*atlas.image.get_pixel(x, y), expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn padding_wraps_around_from_the_opposite_edges() {
+        let mut builder = TileAtlasBuilder::new().with_padding(3);
+        builder.add(TileKind::Grass, tile(2, 4, 1));
+        let atlas = builder.build();
+        assert_eq!(atlas.image.dimensions(), (16, 16));
+        assert_eq!(rect(&atlas, TileKind::Grass), ((3, 3), (5, 7)));
+
+        // Every pixel of the padded tile, padding included, repeats the tile
+        for y in 0..10 {
+            for x in 0..8 {
+                let expected = Rgba([(x + 1) % 2, (y + 1) % 4, 1, 255]);
+                assert_eq!(*atlas.image.get_pixel(x as u32, y as u32), expected);
+            }
+        }
+        // Beyond its padding the atlas is empty
+        assert_eq!(*atlas.image.get_pixel(8, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(*atlas.image.get_pixel(0, 10), Rgba([0, 0, 0, 0]));
+    }
+
+    #[test]
+    fn uvs_are_found_by_tile_kind() {
+        let mut builder = TileAtlasBuilder::new().with_padding(0);
+        builder.add(TileKind::Grass, tile(4, 4, 1));
+        builder.add(TileKind::Plants, tile(2, 2, 2));
+        builder.add(TileKind::Grass, tile(2, 2, 3));
+        let atlas = builder.build();
+
+        // The second Grass image replaced the first
+        assert_eq!(atlas.image.dimensions(), (4, 2));
+        assert_eq!(
+            atlas.uvs.get(TileKind::Grass),
+            Some(TileUv {
+                min: Point2::new(0.5, 0.0),
+                max: Point2::new(1.0, 1.0),
+            })
+        );
+        assert_eq!(
+            atlas.uvs.get(TileKind::Plants),
+            Some(TileUv {
+                min: Point2::new(0.0, 0.0),
+                max: Point2::new(0.5, 1.0),
+            })
+        );
+        assert_eq!(atlas.uvs.get(TileKind::Tilled), None);
+        assert_eq!(atlas.uvs.get(TileKind::Concrete), None);
+
+        let empty = TileAtlasBuilder::new().build();
+        assert_eq!(empty.image.dimensions(), (1, 1));
+        assert_eq!(empty.uvs.get(TileKind::Grass), None);
+    }
+}
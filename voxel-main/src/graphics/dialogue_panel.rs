@@ -0,0 +1,26 @@
+use kiss3d::text::{Font, TextAnchor, TextLayout};
+use kiss3d::window::Window;
+use nalgebra::Point3;
+use std::rc::Rc;
+
+use crate::dialogue::Conversation;
+
+/// Draws the current state of a conversation along the bottom of the window:
+/// the speaker's line followed by the numbered choices.
+pub fn draw_dialogue_panel(window: &mut Window, font: &Rc<Font>, conversation: &Conversation) {
+    let margin = 24.0;
+    let choice_color = Point3::new(0.9, 0.8, 0.3);
+
+    let mut layout = TextLayout::new(font, 40.0)
+        .with_span(
+            &format!("{}: \"{}\"\n\n", conversation.actor_name, conversation.text),
+            &Point3::new(1.0, 1.0, 1.0),
+        )
+        .with_max_width(window.text_screen_size().x - 2.0 * margin);
+    for (i, (_, text)) in conversation.choices.iter().enumerate() {
+        layout.push_span(&format!("{}. {}\n", i + 1, text), &choice_color);
+    }
+    layout.push_span("0. Leave", &choice_color);
+
+    window.draw_anchored_text(&layout, TextAnchor::BottomLeft, margin);
+}
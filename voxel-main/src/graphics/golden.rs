@@ -0,0 +1,60 @@
+use kiss3d::image::RgbImage;
+
+//
+// Golden images
+//
+// A frame rendered with the headless feature is compared with a golden image
+// checked into the repository, so changes to the renderer or the world
+// generation show up as a failed comparison. Frames are not bit for bit
+// reproducible: notifications expire in real time and floating point
+// rounding varies between machines, so small differences are tolerated.
+//
+
+/// Channel differences up to this are rounding, not a change
+pub const GOLDEN_TOLERANCE: u8 = 8;
+
+/// The share of pixels which may differ before frames count as different
+pub const GOLDEN_MAX_DIFFERENT: f64 = 0.02;
+
+/// The share of pixels, from 0 to 1, with a channel differing by more than
+/// GOLDEN_TOLERANCE between two images. Images of different sizes differ
+/// everywhere.
+pub fn image_difference(a: &RgbImage, b: &RgbImage) -> f64 {
+    if a.dimensions() != b.dimensions() {
+        return 1.0;
+    }
+    let different = a
+        .pixels()
+        .zip(b.pixels())
+        .filter(|(p, q)| {
+            p.0.iter()
+                .zip(q.0.iter())
+                .any(|(c, d)| (*c as i16 - *d as i16).abs() > GOLDEN_TOLERANCE as i16)
+        })
+        .count();
+    different as f64 / (a.width() * a.height()).max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kiss3d::image::Rgb;
+
+    #[test]
+    fn only_pixels_beyond_the_tolerance_differ() {
+        let a = RgbImage::from_pixel(10, 10, Rgb([100, 100, 100]));
+        assert_eq!(image_difference(&a, &a), 0.0);
+
+        let mut b = a.clone();
+        b.put_pixel(0, 0, Rgb([100 + GOLDEN_TOLERANCE, 100, 100]));
+        b.put_pixel(1, 0, Rgb([100, 100 - GOLDEN_TOLERANCE, 100]));
+        assert_eq!(image_difference(&a, &b), 0.0);
+
+        b.put_pixel(2, 0, Rgb([100, 100, 101 + GOLDEN_TOLERANCE]));
+        b.put_pixel(3, 0, Rgb([0, 0, 0]));
+        assert_eq!(image_difference(&a, &b), 0.02);
+
+        let small = RgbImage::from_pixel(10, 5, Rgb([100, 100, 100]));
+        assert_eq!(image_difference(&a, &small), 1.0);
+    }
+}
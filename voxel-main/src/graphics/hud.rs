@@ -0,0 +1,97 @@
+use kiss3d::text::{Font, TextAlign, TextAnchor, TextLayout};
+use kiss3d::window::Window;
+use nalgebra::Point3;
+use std::rc::Rc;
+
+use crate::item::{Inventory, ItemKind};
+use crate::verb::Verb;
+use crate::world::Clock;
+
+/// Outlines the top face of a tile (in world map coordinates)
+pub fn draw_tile_highlight(window: &mut Window, tile: (i64, i64), height: i32) {
+    // Voxel tops sit half a unit below the tile height; lift the outline
+    // slightly so it does not z-fight with the terrain.
+    let y = height as f32 - 0.5 + 0.02;
+    let (x, z) = (tile.0 as f32, tile.1 as f32);
+    let corners = [
+        Point3::new(x - 0.5, y, z - 0.5),
+        Point3::new(x + 0.5, y, z - 0.5),
+        Point3::new(x + 0.5, y, z + 0.5),
+        Point3::new(x - 0.5, y, z + 0.5),
+    ];
+    let color = Point3::new(1.0, 1.0, 0.2);
+    for i in 0..corners.len() {
+        window.draw_line(&corners[i], &corners[(i + 1) % corners.len()], &color);
+    }
+}
+
+/// Outlines a box in world space, e.g. around a picked object
+pub fn draw_box_outline(
+    window: &mut Window,
+    min: Point3<f32>,
+    max: Point3<f32>,
+    color: Point3<f32>,
+) {
+    let corner = |i: usize| {
+        Point3::new(
+            if i & 1 == 0 { min.x } else { max.x },
+            if i & 2 == 0 { min.y } else { max.y },
+            if i & 4 == 0 { min.z } else { max.z },
+        )
+    };
+    // Each edge joins two corners that differ in exactly one axis
+    for i in 0..8 {
+        for axis in &[1, 2, 4] {
+            if i & axis == 0 {
+                window.draw_line(&corner(i), &corner(i | axis), &color);
+            }
+        }
+    }
+}
+
+/// A line of text in the top right corner, below the FPS counter,
+/// describing the picked object
+pub fn draw_inspector(window: &mut Window, font: &Rc<Font>, text: &str) {
+    let margin = 24.0;
+    let screen = window.text_screen_size();
+    let layout = TextLayout::new(font, 36.0)
+        .with_span(text, &Point3::new(0.7, 0.9, 1.0))
+        .with_max_width(screen.x / 2.0 - margin)
+        .with_align(TextAlign::Right);
+
+    let mut position = TextAnchor::TopRight.position(&layout.size(), &screen, margin);
+    position.y += 40.0;
+    window.draw_text_layout(&layout, &position);
+}
+
+/// The day, season and time in the bottom right corner
+pub fn draw_clock(window: &mut Window, font: &Rc<Font>, clock: &Clock) {
+    let layout =
+        TextLayout::new(font, 36.0).with_span(&clock.to_string(), &Point3::new(0.8, 0.8, 0.8));
+    window.draw_anchored_text(&layout, TextAnchor::BottomRight, 24.0);
+}
+
+/// The selected verb and the player's inventory in the bottom left corner,
+/// wrapped to the left half of the window to leave room for the clock
+pub fn draw_verb_hud(window: &mut Window, font: &Rc<Font>, verb: Verb, inventory: &Inventory) {
+    let margin = 24.0;
+    let items = [
+        ItemKind::Seeds,
+        ItemKind::Produce,
+        ItemKind::Soil,
+        ItemKind::Stone,
+    ]
+    .iter()
+    .map(|kind| format!("{} {}", kind.name(), inventory.count(*kind)))
+    .collect::<Vec<_>>()
+    .join("  ");
+
+    let layout = TextLayout::new(font, 36.0)
+        .with_span(
+            &format!("[Q/E] {}  [Space] act\n", verb.name()),
+            &Point3::new(1.0, 1.0, 0.2),
+        )
+        .with_span(&items, &Point3::new(0.8, 0.8, 0.8))
+        .with_max_width(window.text_screen_size().x / 2.0 - margin);
+    window.draw_anchored_text(&layout, TextAnchor::BottomLeft, margin);
+}
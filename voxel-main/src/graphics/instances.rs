@@ -0,0 +1,186 @@
+use kiss3d::scene::{InstanceData, SceneNode};
+use kiss3d::window::Window;
+use nalgebra::{Matrix3, Point3, Vector3};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::actor::Actor;
+use crate::graphics::mesh_from_buffers;
+use crate::meshing::{greedy_mesh, BlockAppearances};
+use crate::vox::{Model, Models};
+use crate::world::{WorldEntity, WorldMap};
+
+//
+// Instanced nodes
+//
+// All actors share a single kiss3d node, as do all entities, with one
+// instance each. The whole population is drawn in one call and only
+// re-uploaded when the sync id of one of its members has changed, so the
+// cost of a frame barely grows with the number of actors.
+//
+// Entities with a voxel model share one node per model instead, with the
+// model stretched to the size of each entity.
+//
+
+pub struct InstanceBatch {
+    node: SceneNode,
+    // (id, sync id) of each member as of the last upload
+    synced: Vec<(u64, u64)>,
+}
+
+impl InstanceBatch {
+    pub fn new(mut node: SceneNode) -> Self {
+        node.set_instances(&[]);
+        Self {
+            node,
+            synced: Vec::new(),
+        }
+    }
+
+    /// Replaces the instances when the members or their sync ids differ
+    /// from the last update. `build` is only called in that case.
+    pub fn update<F: FnOnce() -> Vec<InstanceData>>(&mut self, synced: Vec<(u64, u64)>, build: F) {
+        if synced == self.synced {
+            return;
+        }
+        self.node.set_instances(&build());
+        self.synced = synced;
+    }
+}
+
+/// One cylinder per visible actor
+pub fn actor_batch(window: &mut Window) -> InstanceBatch {
+    InstanceBatch::new(window.add_cylinder(0.4, 1.8))
+}
+
+pub fn sync_actors(batch: &mut InstanceBatch, world_map: &WorldMap, actors: &[Actor]) {
+    let visible = || actors.iter().filter(|actor| !actor.state.ethereal());
+    let synced = visible()
+        .map(|actor| (actor.state.id(), actor.state.sync_id()))
+        .collect();
+
+    batch.update(synced, || {
+        visible()
+            .map(|actor| {
+                let (x, y) = actor.state.position();
+                let z = world_map.height(x, y);
+                let (r, g, b) = actor.state.color();
+                InstanceData {
+                    position: Point3::new(x as f32, z as f32 + 0.5, y as f32),
+                    deformation: Matrix3::identity(),
+                    color: Point3::new(r, g, b),
+                }
+            })
+            .collect()
+    });
+}
+
+pub struct EntityBatches {
+    // Entities without a loaded model
+    boxes: InstanceBatch,
+    // The batch for each model and its size in voxels, in render space
+    models: HashMap<String, (InstanceBatch, Vector3<f32>)>,
+}
+
+/// One box per entity, stretched from a unit cube, or one instance of its
+/// model
+pub fn entity_batches(window: &mut Window, models: &Models) -> EntityBatches {
+    let boxes = InstanceBatch::new(window.add_cube(1.0, 1.0, 1.0));
+    let models = models
+        .iter()
+        .map(|(name, model)| {
+            let (x, y, z) = model.voxels.size;
+            let size = Vector3::new(x as f32, z as f32, y as f32);
+            let batch = InstanceBatch::new(model_node(window, model));
+            (name.clone(), (batch, size))
+        })
+        .collect();
+    EntityBatches { boxes, models }
+}
+
+// The mesh of a model, one unit per voxel with the model's lowest corner at
+// the origin
+fn model_node(window: &mut Window, model: &Model) -> SceneNode {
+    let mut group = window.add_group();
+    for (_, mut buffers) in greedy_mesh(model, &BlockAppearances::default()).parts {
+        // The mesher puts the voxel at the origin in [-0.5, 0.5] x
+        // [-1.5, -0.5] x [-0.5, 0.5]
+        for p in &mut buffers.positions {
+            *p += Vector3::new(0.5, 1.5, 0.5);
+        }
+        let mesh = Rc::new(RefCell::new(mesh_from_buffers(buffers)));
+        let mut node = group.add_mesh(mesh, Vector3::new(1.0, 1.0, 1.0));
+        node.set_material_with_name("vertex_color");
+    }
+    group
+}
+
+pub fn sync_entities(batches: &mut EntityBatches, entities: &[WorldEntity]) {
+    let EntityBatches { boxes, models } = batches;
+    let has_model = |entity: &&WorldEntity| {
+        entity
+            .state
+            .model()
+            .is_some_and(|name| models.contains_key(name))
+    };
+    let unmodelled: Vec<&WorldEntity> = entities.iter().filter(|e| !has_model(e)).collect();
+    sync_entity_batch(boxes, &unmodelled, None);
+
+    for (name, (batch, size)) in models.iter_mut() {
+        let members: Vec<&WorldEntity> = entities
+            .iter()
+            .filter(|entity| entity.state.model() == Some(name.as_str()))
+            .collect();
+        sync_entity_batch(batch, &members, Some(*size));
+    }
+}
+
+// `model_size` is the size of the model the entities are drawn with, or
+// None for boxes in the colour of each entity
+fn sync_entity_batch(
+    batch: &mut InstanceBatch,
+    entities: &[&WorldEntity],
+    model_size: Option<Vector3<f32>>,
+) {
+    let synced = entities
+        .iter()
+        .map(|entity| (entity.state.id(), entity.state.sync_id()))
+        .collect();
+
+    batch.update(synced, || {
+        entities
+            .iter()
+            .map(|entity| {
+                let state = &entity.state;
+                let size =
+                    Vector3::new(state.width as f32, state.height as f32, state.length as f32);
+                match model_size {
+                    // Models are coloured by their palette and stretched
+                    // from their lowest corner
+                    Some(model_size) => InstanceData {
+                        position: Point3::new(
+                            state.x as f32 - 0.5,
+                            state.z as f32 - 0.5,
+                            state.y as f32 - 0.5,
+                        ),
+                        deformation: Matrix3::from_diagonal(&size.component_div(&model_size)),
+                        color: Point3::new(1.0, 1.0, 1.0),
+                    },
+                    None => {
+                        let (r, g, b) = state.color();
+                        InstanceData {
+                            position: Point3::new(
+                                state.x as f32 + size.x / 2.0 - 0.5,
+                                state.z as f32 + size.y / 2.0 - 0.5,
+                                state.y as f32 + size.z / 2.0 - 0.5,
+                            ),
+                            deformation: Matrix3::from_diagonal(&size),
+                            color: Point3::new(r, g, b),
+                        }
+                    }
+                }
+            })
+            .collect()
+    });
+}
@@ -1,57 +1,133 @@
-use image;
-use kiss3d::resource::TextureManager;
-
-// TODO: This saves the atlas to file and reloads it for two reasons:
-// - Debugability of being able see the file after it is created
-// - I never wrote the in-memory only version :)
-//
-fn create_tile_atlas(atlas_filename: &str) {
-    // for each in src/assets/tiles with n > 0
-    // load & paste into larger image
-    // save image
-
-    use glob::glob;
-
-    let mut paths = Vec::new();
-    for entry in glob("src/assets/tiles/[0-9][0-9]*.png").expect("Failed to read glob pattern") {
-        match entry {
-            Ok(path) => paths.push(path),
-            Err(e) => println!("{:?}", e),
-        }
-    }
+mod dialogue_panel;
+pub use dialogue_panel::*;
 
-    paths.sort_by(|a, b| alphanumeric_sort::compare_path(a, b));
+mod hud;
+pub use hud::*;
 
-    let mut imgbuf = image::ImageBuffer::new(256, 256);
+mod instances;
+pub use instances::*;
 
-    // Skip the 00_ tile
-    for i in 1..paths.len() {
-        let path = &paths[i];
+mod sky;
+pub use sky::*;
 
-        let img1 = image::open(path).unwrap();
-        let img = img1.as_rgba8().unwrap();
+mod atlas;
+pub use atlas::*;
 
-        // The dimensions method returns the images width and height.
-        let dims = img.dimensions();
-        println!("Loaded tile {}x{} {}", dims.0, dims.1, path.display());
+mod golden;
+pub use golden::*;
 
-        let offset = (i as u32 - 1) * 16;
-        let ox: u32 = offset % 256;
-        let oy: u32 = offset / 256;
-        for (x, y, pixel) in img.enumerate_pixels() {
-            let dst = imgbuf.get_pixel_mut(x + ox, y + oy);
-            *dst = *pixel;
-        }
-    }
-    imgbuf.save(atlas_filename).unwrap();
-}
+use kiss3d::image;
+use kiss3d::resource::{Mesh, TextureManager};
+use nalgebra::Point3;
+use std::collections::HashMap;
+
+use crate::meshing::MeshBuffers;
+use crate::world::TileKind;
+
+/// The name of the tile atlas in the TextureManager
+pub const TILE_ATLAS_TEXTURE: &str = "tiles";
 
-pub fn create_texture_atlas() -> TextureManager {
-    let filename = "texture_atlas.png";
+// The tile images are built into the executable, so the game does not
+// depend on being started from the project directory.
+const TILE_IMAGES: [(TileKind, &[u8]); 6] = [
+    (
+        TileKind::Grass,
+        include_bytes!("../assets/tiles/01_grass.png"),
+    ),
+    (
+        TileKind::Tilled,
+        include_bytes!("../assets/tiles/02_soil.png"),
+    ),
+    (
+        TileKind::_DebugTile,
+        include_bytes!("../assets/tiles/03_debug.png"),
+    ),
+    (
+        TileKind::Plants,
+        include_bytes!("../assets/tiles/04_flowers.png"),
+    ),
+    (
+        TileKind::GrassFlowers,
+        include_bytes!("../assets/tiles/05_grass_flowers.png"),
+    ),
+    (
+        TileKind::Concrete,
+        include_bytes!("../assets/tiles/06_concrete.png"),
+    ),
+];
 
-    create_tile_atlas(filename);
+/// Builds the tile atlas and registers it with the TextureManager. The
+/// returned table gives the region of the atlas for each tile kind.
+pub fn create_texture_atlas() -> (TextureManager, TileUvs) {
+    let mut builder = TileAtlasBuilder::new().with_padding(2);
+    for (kind, bytes) in TILE_IMAGES.iter() {
+        let tile = image::load_from_memory(bytes).unwrap().to_rgba();
+        println!("Loaded tile {}x{} {:?}", tile.width(), tile.height(), kind);
+        builder.add(*kind, tile);
+    }
+    let atlas = builder.build();
 
     let mut tm = TextureManager::new();
-    tm.add(std::path::Path::new(filename), "tiles");
-    tm
+    tm.add_image(
+        image::DynamicImage::ImageRgba8(atlas.image),
+        TILE_ATLAS_TEXTURE,
+    );
+    (tm, atlas.uvs)
+}
+
+/// The average colour of each tile image, for tools which only know about
+/// plain colours such as voxel editors
+pub fn tile_colors() -> HashMap<TileKind, [u8; 3]> {
+    TILE_IMAGES
+        .iter()
+        .map(|(kind, bytes)| {
+            let tile = image::load_from_memory(bytes).unwrap().to_rgba();
+            let mut sum = [0u64; 3];
+            for pixel in tile.pixels() {
+                for (i, total) in sum.iter_mut().enumerate() {
+                    *total += pixel[i] as u64;
+                }
+            }
+            let n = (tile.width() as u64 * tile.height() as u64).max(1);
+            (
+                *kind,
+                [(sum[0] / n) as u8, (sum[1] / n) as u8, (sum[2] / n) as u8],
+            )
+        })
+        .collect()
+}
+
+/// Turns meshing output into a kiss3d mesh, with the vertex colours set for
+/// the "vertex_color" material
+pub fn mesh_from_buffers(buffers: MeshBuffers) -> Mesh {
+    // 16-bit indices are enough for most meshes and use half the memory
+    let mut mesh = if buffers.positions.len() <= u16::MAX as usize + 1 {
+        let faces = buffers
+            .indices
+            .chunks(3)
+            .map(|t| Point3::new(t[0] as u16, t[1] as u16, t[2] as u16))
+            .collect();
+        Mesh::new(
+            buffers.positions,
+            faces,
+            Some(buffers.normals),
+            Some(buffers.uvs),
+            false,
+        )
+    } else {
+        let faces = buffers
+            .indices
+            .chunks(3)
+            .map(|t| Point3::new(t[0], t[1], t[2]))
+            .collect();
+        Mesh::new_u32(
+            buffers.positions,
+            faces,
+            Some(buffers.normals),
+            Some(buffers.uvs),
+            false,
+        )
+    };
+    mesh.set_colors(buffers.colors);
+    mesh
 }
@@ -0,0 +1,150 @@
+use nalgebra::{Point3, Vector3};
+
+use crate::world::Clock;
+
+//
+// Sky
+//
+// The lighting for a time of day. Everything is a function of the sun's
+// elevation, which is positive by day and negative by night (following the
+// moon's arc), so colours blend smoothly through dusk and dawn.
+//
+
+const NIGHT_AMBIENT: (f32, f32, f32) = (0.22, 0.26, 0.42);
+const DUSK_AMBIENT: (f32, f32, f32) = (1.0, 0.75, 0.55);
+const DAY_AMBIENT: (f32, f32, f32) = (1.0, 1.0, 1.0);
+
+const MOON_LIGHT: (f32, f32, f32) = (0.35, 0.4, 0.6);
+const DUSK_LIGHT: (f32, f32, f32) = (1.0, 0.7, 0.45);
+const DAY_LIGHT: (f32, f32, f32) = (1.0, 0.97, 0.9);
+
+const NIGHT_SKY: (f32, f32, f32) = (0.02, 0.03, 0.08);
+const DUSK_SKY: (f32, f32, f32) = (0.85, 0.5, 0.35);
+const DAY_SKY: (f32, f32, f32) = (0.45, 0.68, 0.95);
+
+pub struct Sky {
+    // Unit vector (in render space) pointing towards the sun by day and the
+    // moon by night
+    pub light_direction: Vector3<f32>,
+    pub light_color: Point3<f32>,
+    // Multiplies the colour of the terrain
+    pub ambient: Point3<f32>,
+    pub background: Point3<f32>,
+}
+
+impl Sky {
+    pub fn at(clock: &Clock) -> Self {
+        let (sunrise, sunset) = clock.season().daylight_hours();
+
+        // Both the sun and the moon rise in the east (+x), arc over the
+        // south a little and set in the west
+        let (progress, sign) = match clock.sun_progress() {
+            Some(progress) => (progress, 1.0),
+            None => {
+                let night = 24.0 - (sunset - sunrise);
+                ((clock.hour() - sunset).rem_euclid(24.0) / night, -1.0)
+            }
+        };
+        let angle = progress * std::f32::consts::PI;
+        let light_direction = Vector3::new(angle.cos(), angle.sin(), 0.35).normalize();
+        let elevation = sign * angle.sin();
+
+        let daylight = smoothstep(-0.2, 0.3, elevation);
+        let warmth = 1.0 - smoothstep(0.0, 0.35, elevation.abs());
+
+        let ambient = mix(
+            NIGHT_AMBIENT,
+            mix(DAY_AMBIENT, DUSK_AMBIENT, warmth),
+            daylight,
+        );
+        let light_color = mix(MOON_LIGHT, mix(DAY_LIGHT, DUSK_LIGHT, warmth), daylight);
+        let background = mix(NIGHT_SKY, mix(DAY_SKY, DUSK_SKY, warmth), daylight);
+
+        Self {
+            light_direction,
+            light_color: Point3::new(light_color.0, light_color.1, light_color.2),
+            ambient: Point3::new(ambient.0, ambient.1, ambient.2),
+            background: Point3::new(background.0, background.1, background.2),
+        }
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn mix(a: (f32, f32, f32), b: (f32, f32, f32), t: f32) -> (f32, f32, f32) {
+    (
+        a.0 + (b.0 - a.0) * t,
+        a.1 + (b.1 - a.1) * t,
+        a.2 + (b.2 - a.2) * t,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::TICKS_PER_HOUR;
+
+    // Spring, on the second day
+    fn at_hour(hour: f32) -> Sky {
+        let ticks = (24.0 + hour - 7.0) * TICKS_PER_HOUR as f32;
+        Sky::at(&Clock::new(ticks as u64))
+    }
+
+    fn point(c: (f32, f32, f32)) -> Point3<f32> {
+        Point3::new(c.0, c.1, c.2)
+    }
+
+    fn distance(a: &Point3<f32>, b: &Point3<f32>) -> f32 {
+        (a - b).norm()
+    }
+
+    #[test]
+    fn smoothstep_is_clamped_to_its_edges() {
+        assert_eq!(smoothstep(0.0, 1.0, -1.0), 0.0);
+        assert_eq!(smoothstep(0.0, 1.0, 0.5), 0.5);
+        assert_eq!(smoothstep(0.0, 1.0, 2.0), 1.0);
+        assert_eq!(mix((0.0, 1.0, 2.0), (1.0, 3.0, 2.0), 0.5), (0.5, 2.0, 2.0));
+    }
+
+    #[test]
+    fn noon_and_midnight_use_the_day_and_night_colours() {
+        let noon = at_hour(12.5);
+        assert!(distance(&noon.background, &point(DAY_SKY)) < 1e-5);
+        assert!(distance(&noon.ambient, &point(DAY_AMBIENT)) < 1e-5);
+        assert!(distance(&noon.light_color, &point(DAY_LIGHT)) < 1e-5);
+        assert!(noon.light_direction.y > 0.9);
+
+        let midnight = at_hour(0.5);
+        assert!(distance(&midnight.background, &point(NIGHT_SKY)) < 1e-3);
+        assert!(distance(&midnight.ambient, &point(NIGHT_AMBIENT)) < 1e-3);
+        assert!(distance(&midnight.light_color, &point(MOON_LIGHT)) < 1e-3);
+    }
+
+    #[test]
+    fn sunset_blends_towards_dusk() {
+        // Just after sunset the sky is between night and dusk, redder than
+        // by day
+        let sunset = at_hour(19.0);
+        let night = distance(&sunset.background, &point(NIGHT_SKY));
+        let dusk = distance(&sunset.background, &point(DUSK_SKY));
+        let span = distance(&point(NIGHT_SKY), &point(DUSK_SKY));
+        assert!(night > 0.1 && dusk > 0.1);
+        assert!((night + dusk - span).abs() < 1e-4);
+        assert!(sunset.background.x > sunset.background.z);
+    }
+
+    #[test]
+    fn colours_change_smoothly_through_the_day() {
+        let mut previous = at_hour(0.0);
+        for minute in 1..24 * 60 {
+            let sky = at_hour(minute as f32 / 60.0);
+            assert!(distance(&sky.background, &previous.background) < 0.02);
+            assert!(distance(&sky.ambient, &previous.ambient) < 0.02);
+            assert!(distance(&sky.light_color, &previous.light_color) < 0.02);
+            previous = sky;
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//
+// Input
+//
+// Everything the player does is turned into an Action before it reaches the
+// simulation. Actions come either from the window, via the key bindings in
+// src/assets/input/bindings.ron, or from a previously recorded session.
+//
+use kiss3d::event::{Key, MouseButton, WindowEvent};
+use kiss3d::window::Window;
+use serde::{Deserialize, Serialize};
+
+mod recording;
+pub use recording::*;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Action {
+    Move { x: i32, y: i32 },
+    Choose(usize),
+    EndConversation,
+    SelectVerb(i32),
+    Perform,
+    // Selects whatever is under the mouse cursor
+    Select,
+    // Saves the map around the selected tile as a .vox file
+    ExportRegion,
+}
+
+#[derive(Deserialize)]
+pub struct Bindings {
+    held: Vec<(Key, Action)>,
+    pressed: Vec<(Key, Action)>,
+    mouse: Vec<(MouseButton, Action)>,
+    conversation: Vec<(Key, Action)>,
+}
+
+impl Bindings {
+    pub fn load(path: &str) -> Self {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| ron::de::from_str(&s).map_err(|e| e.to_string()));
+        match result {
+            Ok(bindings) => {
+                println!("Loaded key bindings {}", path);
+                bindings
+            }
+            Err(e) => {
+                println!("Failed to load key bindings {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Pushes the Actions triggered this frame onto the queue.
+    ///
+    /// Discrete actions act on key presses rather than the held state so a
+    /// single press does not trigger them several frames in a row. While
+    /// talking only the conversation bindings apply.
+    pub fn check(&self, window: &Window, in_conversation: bool, action_queue: &mut Vec<Action>) {
+        use kiss3d::event::Action::Press;
+
+        for event in window.events().iter() {
+            match event.value {
+                WindowEvent::Key(key, Press, _) => {
+                    let bindings = if in_conversation {
+                        &self.conversation
+                    } else {
+                        &self.pressed
+                    };
+                    push_bound(bindings, key, action_queue);
+                }
+                WindowEvent::MouseButton(button, Press, _) if !in_conversation => {
+                    push_bound(&self.mouse, button, action_queue);
+                }
+                _ => {}
+            }
+        }
+        if in_conversation {
+            return;
+        }
+
+        for (key, action) in &self.held {
+            if window.get_key(*key) == Press {
+                action_queue.push(action.clone());
+            }
+        }
+    }
+}
+
+fn push_bound<T: PartialEq>(bindings: &[(T, Action)], input: T, action_queue: &mut Vec<Action>) {
+    for (bound, action) in bindings {
+        if *bound == input {
+            action_queue.push(action.clone());
+        }
+    }
+}
+
+// The built-in layout, used when the bindings file cannot be read
+impl Default for Bindings {
+    fn default() -> Self {
+        let choices = [
+            Key::Key1,
+            Key::Key2,
+            Key::Key3,
+            Key::Key4,
+            Key::Key5,
+            Key::Key6,
+            Key::Key7,
+            Key::Key8,
+            Key::Key9,
+        ];
+        let mut conversation: Vec<_> = choices
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, Action::Choose(i)))
+            .collect();
+        conversation.push((Key::Key0, Action::EndConversation));
+
+        Self {
+            held: vec![
+                (Key::Left, Action::Move { x: 1, y: 0 }),
+                (Key::Right, Action::Move { x: -1, y: 0 }),
+                (Key::Up, Action::Move { x: 0, y: 1 }),
+                (Key::Down, Action::Move { x: 0, y: -1 }),
+            ],
+            pressed: vec![
+                (Key::Q, Action::SelectVerb(-1)),
+                (Key::E, Action::SelectVerb(1)),
+                (Key::Space, Action::Perform),
+            ],
+            mouse: vec![
+                (MouseButton::Button1, Action::Select),
+                (MouseButton::Button2, Action::Perform),
+            ],
+            conversation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_are_read_from_ron() {
+        let bindings: Bindings = ron::de::from_str(
+            r#"(
+                held: [(W, Move(x: 0, y: 1))],
+                pressed: [(Space, Perform), (Tab, SelectVerb(1))],
+                mouse: [(Button1, Select)],
+                conversation: [(Key1, Choose(0)), (Escape, EndConversation)],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(bindings.held, [(Key::W, Action::Move { x: 0, y: 1 })]);
+        assert_eq!(
+            bindings.pressed,
+            [
+                (Key::Space, Action::Perform),
+                (Key::Tab, Action::SelectVerb(1))
+            ]
+        );
+        assert_eq!(bindings.mouse, [(MouseButton::Button1, Action::Select)]);
+        assert_eq!(
+            bindings.conversation,
+            [
+                (Key::Key1, Action::Choose(0)),
+                (Key::Escape, Action::EndConversation)
+            ]
+        );
+    }
+
+    #[test]
+    fn the_bindings_file_parses() {
+        let text = std::fs::read_to_string("src/assets/input/bindings.ron").unwrap();
+        assert!(ron::de::from_str::<Bindings>(&text).is_ok());
+    }
+
+    #[test]
+    fn unreadable_bindings_fall_back_to_the_default() {
+        let path =
+            std::env::temp_dir().join(format!("voxel-main-bindings-{}.ron", std::process::id()));
+        std::fs::write(&path, "(held: [(NotAKey, Perform)])").unwrap();
+        let bindings = Bindings::load(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let default = Bindings::default();
+        assert_eq!(bindings.held, default.held);
+        assert_eq!(bindings.pressed, default.pressed);
+        assert_eq!(bindings.mouse, default.mouse);
+        assert_eq!(bindings.conversation, default.conversation);
+        assert_eq!(Bindings::load("no/such/bindings.ron").held, default.held);
+
+        assert_eq!(default.conversation.len(), 10);
+        assert_eq!(default.conversation[0], (Key::Key1, Action::Choose(0)));
+        assert_eq!(
+            default.conversation[9],
+            (Key::Key0, Action::EndConversation)
+        );
+    }
+}
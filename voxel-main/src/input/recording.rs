@@ -0,0 +1,176 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+use crate::input::Action;
+
+//
+// Recordings
+//
+// A recording is the world seed followed by every Action the simulation
+// received, tagged with the frame it was processed on. Given the same seed,
+// replaying those Actions on the same frames reproduces the session.
+//
+// The format is line based so recordings can be read and trimmed by hand:
+//
+//      seed 1234
+//      120 Move(x:1,y:0)
+//      184 Perform
+//
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "seed {}", seed)?;
+        Ok(Self { writer })
+    }
+
+    pub fn record(&mut self, frame: u64, actions: &[Action]) -> std::io::Result<()> {
+        for action in actions {
+            let text =
+                ron::ser::to_string(action).map_err(|e| std::io::Error::other(e.to_string()))?;
+            writeln!(self.writer, "{} {}", frame, text)?;
+        }
+        // Flush every frame so the recording survives a crash, which is
+        // usually the reason for recording in the first place.
+        self.writer.flush()
+    }
+}
+
+pub struct Replay {
+    pub seed: u64,
+    actions: VecDeque<(u64, Action)>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+
+        let seed = match lines.next() {
+            Some((_, line)) => line
+                .trim()
+                .strip_prefix("seed ")
+                .and_then(|s| s.trim().parse().ok())
+                .ok_or_else(|| format!("{}:1: expected 'seed <number>'", path))?,
+            None => return Err(format!("{}: empty recording", path)),
+        };
+
+        let mut actions = VecDeque::new();
+        for (i, line) in lines {
+            let error = |e: String| format!("{}:{}: {}", path, i + 1, e);
+            let line = line.trim();
+            let split = line
+                .find(' ')
+                .ok_or_else(|| error("missing action".into()))?;
+            let frame: u64 = line[..split]
+                .parse()
+                .map_err(|_| error("invalid frame number".into()))?;
+            let action: Action =
+                ron::de::from_str(&line[split + 1..]).map_err(|e| error(e.to_string()))?;
+            actions.push_back((frame, action));
+        }
+
+        Ok(Self { seed, actions })
+    }
+
+    /// Moves the Actions recorded for the given frame onto the queue
+    pub fn actions_for(&mut self, frame: u64, action_queue: &mut Vec<Action>) {
+        while let Some((f, _)) = self.actions.front() {
+            if *f > frame {
+                break;
+            }
+            let (_, action) = self.actions.pop_front().unwrap();
+            action_queue.push(action);
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("voxel-main-{}-{}.txt", name, std::process::id()));
+        path.to_str().unwrap().to_string()
+    }
+
+    fn load(name: &str, text: &str) -> Result<Replay, String> {
+        let path = temp_path(name);
+        std::fs::write(&path, text).unwrap();
+        let replay = Replay::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        replay
+    }
+
+    #[test]
+    fn recorded_actions_are_replayed_on_their_frames() {
+        let path = temp_path("round-trip");
+        let mut recorder = Recorder::create(&path, 1234).unwrap();
+        recorder
+            .record(3, &[Action::Move { x: 1, y: 0 }, Action::Perform])
+            .unwrap();
+        recorder.record(4, &[]).unwrap();
+        recorder.record(9, &[Action::Choose(2)]).unwrap();
+        drop(recorder);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text, "seed 1234\n3 Move(x:1,y:0)\n3 Perform\n9 Choose(2)\n");
+
+        let mut replay = Replay::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.seed, 1234);
+
+        let mut frames = Vec::new();
+        for frame in 0..10 {
+            let mut action_queue = Vec::new();
+            replay.actions_for(frame, &mut action_queue);
+            if !action_queue.is_empty() {
+                frames.push((frame, action_queue));
+            }
+        }
+        assert_eq!(
+            frames,
+            [
+                (3, vec![Action::Move { x: 1, y: 0 }, Action::Perform]),
+                (9, vec![Action::Choose(2)]),
+            ]
+        );
+        assert!(replay.is_finished());
+    }
+
+    #[test]
+    fn hand_edited_recordings_are_accepted() {
+        // Blank lines and extra spaces are fine, and actions for frames that
+        // have passed are replayed on the next frame.
+        let mut replay = load("hand-edited", "\n  seed 5 \n\n7 Perform\n2 Select  \n").unwrap();
+        assert_eq!(replay.seed, 5);
+
+        let mut action_queue = Vec::new();
+        replay.actions_for(7, &mut action_queue);
+        assert_eq!(action_queue, [Action::Perform, Action::Select]);
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let error = |name, text| load(name, text).err().unwrap();
+        assert!(error("empty", "\n").ends_with(": empty recording"));
+        assert!(error("no-seed", "3 Perform\n").ends_with(":1: expected 'seed <number>'"));
+        assert!(error("no-action", "seed 1\n3\n").ends_with(":2: missing action"));
+        assert!(error("bad-frame", "seed 1\nsoon Perform\n").ends_with(":2: invalid frame number"));
+        assert!(error("bad-action", "seed 1\n\n3 Jump\n").contains(":3: "));
+        assert!(Replay::load(&temp_path("missing")).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Deserialize)]
+pub enum ItemKind {
+    Seeds,
+    Produce,
+    Soil,
+    Stone,
+}
+
+impl ItemKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ItemKind::Seeds => "seeds",
+            ItemKind::Produce => "produce",
+            ItemKind::Soil => "soil",
+            ItemKind::Stone => "stone",
+        }
+    }
+}
+
+pub struct Inventory {
+    items: HashMap<ItemKind, u32>,
+}
+
+impl Inventory {
+    pub fn new() -> Self {
+        Self {
+            items: HashMap::new(),
+        }
+    }
+
+    pub fn count(&self, kind: ItemKind) -> u32 {
+        *self.items.get(&kind).unwrap_or(&0)
+    }
+
+    pub fn add(&mut self, kind: ItemKind, count: u32) {
+        *self.items.entry(kind).or_insert(0) += count;
+    }
+
+    /// Removes `count` items of the given kind. Returns false (and removes
+    /// nothing) if there are not enough.
+    pub fn remove(&mut self, kind: ItemKind, count: u32) -> bool {
+        let current = self.count(kind);
+        if current < count {
+            return false;
+        }
+        if current == count {
+            self.items.remove(&kind);
+        } else {
+            self.items.insert(kind, current - count);
+        }
+        true
+    }
+}
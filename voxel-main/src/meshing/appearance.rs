@@ -0,0 +1,158 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::world::TileKind;
+
+//
+// Block appearance
+//
+// How each kind of tile looks, loaded from src/assets/blocks.ron. Faces are
+// textured with the image of a tile kind in the tile atlas, so for example
+// grass can show soil on its sides. A face can also vary from tile to tile,
+// either by hashing the tile's position or by the tile's age. Variants are
+// picked per column so a column always looks the same all the way down.
+//
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BlockFace {
+    Top,
+    Side,
+    Bottom,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum FaceTexture {
+    /// The image of a tile kind
+    Tile(TileKind),
+    /// One of several images, picked by hashing the tile's position
+    Hashed(Vec<TileKind>),
+    /// The image for the last age the tile has reached, from (age, image)
+    /// pairs in increasing order of age
+    Aged(Vec<(u64, TileKind)>),
+}
+
+impl FaceTexture {
+    fn pick(&self, kind: TileKind, position: (i64, i64), age: u64) -> TileKind {
+        match self {
+            FaceTexture::Tile(texture) => *texture,
+            FaceTexture::Hashed(textures) if !textures.is_empty() => {
+                let i = hash(position.0, position.1) % textures.len() as u64;
+                textures[i as usize]
+            }
+            FaceTexture::Aged(textures) => textures
+                .iter()
+                .take_while(|(min_age, _)| age >= *min_age)
+                .last()
+                .map(|(_, texture)| *texture)
+                .unwrap_or(kind),
+            _ => kind,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BlockAppearance {
+    pub top: FaceTexture,
+    // Defaults to the top
+    #[serde(default)]
+    pub side: Option<FaceTexture>,
+    // Defaults to the side
+    #[serde(default)]
+    pub bottom: Option<FaceTexture>,
+    // Multiplies the colour of every face
+    #[serde(default = "default_tint")]
+    pub tint: (f32, f32, f32),
+}
+
+fn default_tint() -> (f32, f32, f32) {
+    (1.0, 1.0, 1.0)
+}
+
+impl BlockAppearance {
+    fn face(&self, face: BlockFace) -> &FaceTexture {
+        let side = self.side.as_ref().unwrap_or(&self.top);
+        match face {
+            BlockFace::Top => &self.top,
+            BlockFace::Side => side,
+            BlockFace::Bottom => self.bottom.as_ref().unwrap_or(side),
+        }
+    }
+}
+
+/// The appearance of each tile kind. Kinds without one use their own image
+/// on every face.
+#[derive(Clone, Debug, Default)]
+pub struct BlockAppearances {
+    blocks: HashMap<TileKind, BlockAppearance>,
+}
+
+impl BlockAppearances {
+    pub fn load(path: &str) -> Self {
+        let result = std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|s| ron::de::from_str(&s).map_err(|e| e.to_string()));
+        match result {
+            Ok(blocks) => {
+                println!("Loaded block appearances {}", path);
+                Self { blocks }
+            }
+            Err(e) => {
+                println!("Failed to load block appearances {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// The tile image for one face of a tile at world position (x, y)
+    pub fn texture(
+        &self,
+        kind: TileKind,
+        face: BlockFace,
+        position: (i64, i64),
+        age: u64,
+    ) -> TileKind {
+        match self.blocks.get(&kind) {
+            Some(block) => block.face(face).pick(kind, position, age),
+            None => kind,
+        }
+    }
+
+    /// The ages at which the looks of each tile kind change, for the kinds
+    /// with aged faces
+    pub fn age_thresholds(&self) -> HashMap<TileKind, Vec<u64>> {
+        let mut thresholds = HashMap::new();
+        for (kind, block) in &self.blocks {
+            let mut ages: Vec<u64> = [BlockFace::Top, BlockFace::Side, BlockFace::Bottom]
+                .iter()
+                .filter_map(|face| match block.face(*face) {
+                    FaceTexture::Aged(textures) => Some(textures.iter().map(|(age, _)| *age)),
+                    _ => None,
+                })
+                .flatten()
+                .collect();
+            ages.sort_unstable();
+            ages.dedup();
+            if !ages.is_empty() {
+                thresholds.insert(*kind, ages);
+            }
+        }
+        thresholds
+    }
+
+    pub fn tint(&self, kind: TileKind) -> (f32, f32, f32) {
+        match self.blocks.get(&kind) {
+            Some(block) => block.tint,
+            None => default_tint(),
+        }
+    }
+}
+
+// A well mixed hash of a tile position, the same on every run so replays
+// look the same
+fn hash(x: i64, y: i64) -> u64 {
+    let mut h = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    h ^= h >> 31;
+    h = h.wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h ^ (h >> 29)
+}
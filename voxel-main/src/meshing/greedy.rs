@@ -0,0 +1,379 @@
+use nalgebra::{Point2, Point3, Vector3};
+
+use crate::meshing::{BlockAppearances, BlockFace, ChunkMesh, MeshBuffers, Voxels};
+use crate::world::TileKind;
+
+//
+// Greedy meshing
+//
+// For each of the six face directions the chunk is cut into slices. Each
+// slice becomes a 2D mask of the exposed faces and their appearance, and
+// runs of equal faces are grown into the largest rectangles possible, first along one
+// axis and then the other. Each rectangle becomes a single quad.
+//
+// See https://0fps.net/2012/06/30/meshing-in-a-minecraft-game/
+//
+// Each face corner also gets an ambient occlusion level from the three
+// voxels touching it in front of the face. Faces are only merged when their
+// corners are equally occluded, otherwise the shading would be stretched
+// across the merged quad.
+//
+// See https://0fps.net/2013/07/03/ambient-occlusion-for-minecraft-like-worlds/
+//
+
+// The voxel at world-local (x, y, z) occupies render space
+// [x-0.5, x+0.5] x [z-1.5, z-0.5] x [y-0.5, y+0.5]: tile tops sit half a
+// unit below the tile height. Lower detail voxels are `step` tiles wide but
+// keep the same origin.
+const VOXEL_MIN: [f32; 3] = [-0.5, -0.5, -1.5];
+
+// Ambient light for each occlusion level, from a corner boxed in on both
+// sides to one that is fully open
+const AO_LIGHT: [f32; 4] = [0.45, 0.65, 0.82, 1.0];
+
+// An exposed face in a slice mask: the tile image it is textured with, its
+// tint and the occlusion level of its corners, in the order (0, 0), (1, 0),
+// (1, 1), (0, 1) along (u, v)
+#[derive(Copy, Clone, PartialEq)]
+struct Face {
+    texture: TileKind,
+    tint: (f32, f32, f32),
+    ao: [u8; 4],
+}
+
+// A rectangle of merged faces, (u0, v0, width, height) in slice coordinates,
+// on the plane `axis = plane` and facing along `dir`. The ambient occlusion
+// levels are those of the faces it was merged from.
+struct Quad {
+    axis: usize,
+    dir: i64,
+    plane: i64,
+    rect: (i64, i64, i64, i64),
+    ao: [u8; 4],
+    tint: Point3<f32>,
+}
+
+// The occlusion level of each corner of a face, from 0 (darkest) to 3
+// (open), given `front`, the empty voxel the face looks into
+fn face_ao<V: Voxels>(voxels: &V, front: [i64; 3], u: usize, v: usize) -> [u8; 4] {
+    let solid = |du: i64, dv: i64| {
+        let mut p = front;
+        p[u] += du;
+        p[v] += dv;
+        voxels.is_solid(p[0], p[1], p[2]) as u8
+    };
+    let corner = |du: i64, dv: i64| {
+        let (side1, side2) = (solid(du, 0), solid(0, dv));
+        if side1 == 1 && side2 == 1 {
+            0
+        } else {
+            3 - side1 - side2 - solid(du, dv)
+        }
+    };
+    [corner(-1, -1), corner(1, -1), corner(1, 1), corner(-1, 1)]
+}
+
+fn to_render(p: [f32; 3]) -> Point3<f32> {
+    Point3::new(
+        p[0] + VOXEL_MIN[0],
+        p[2] + VOXEL_MIN[2],
+        p[1] + VOXEL_MIN[1],
+    )
+}
+
+pub fn greedy_mesh<V: Voxels>(voxels: &V, appearances: &BlockAppearances) -> ChunkMesh {
+    // Index by axis: 0 = world x, 1 = world y, 2 = world z
+    let (lower, upper) = voxels.bounds();
+
+    let mut parts: Vec<(TileKind, MeshBuffers)> = Vec::new();
+
+    for axis in 0..3 {
+        let u = (axis + 1) % 3;
+        let v = (axis + 2) % 3;
+        let (width, height) = (
+            (upper[u] - lower[u]) as usize,
+            (upper[v] - lower[v]) as usize,
+        );
+        if width == 0 || height == 0 {
+            continue;
+        }
+        let mut mask: Vec<Option<Face>> = vec![None; width * height];
+
+        for &dir in &[-1i64, 1] {
+            let block_face = match (axis, dir) {
+                (2, 1) => BlockFace::Top,
+                (2, _) => BlockFace::Bottom,
+                _ => BlockFace::Side,
+            };
+
+            for slice in lower[axis]..upper[axis] {
+                //
+                // Build the mask of exposed faces in this slice
+                //
+                for j in 0..height {
+                    for i in 0..width {
+                        let mut p = [0i64; 3];
+                        p[axis] = slice;
+                        p[u] = lower[u] + i as i64;
+                        p[v] = lower[v] + j as i64;
+                        let solid = voxels.is_solid(p[0], p[1], p[2]);
+                        let (x, y, z) = (p[0], p[1], p[2]);
+
+                        p[axis] += dir;
+                        mask[j * width + i] = if solid && !voxels.is_solid(p[0], p[1], p[2]) {
+                            let (texture, tint) = voxels.face(appearances, x, y, z, block_face);
+                            Some(Face {
+                                texture,
+                                tint,
+                                ao: face_ao(voxels, p, u, v),
+                            })
+                        } else {
+                            None
+                        };
+                    }
+                }
+
+                //
+                // Grow rectangles out of the mask
+                //
+                for j in 0..height {
+                    let mut i = 0;
+                    while i < width {
+                        let face = match mask[j * width + i] {
+                            Some(face) => face,
+                            None => {
+                                i += 1;
+                                continue;
+                            }
+                        };
+
+                        let mut w = 1;
+                        while i + w < width && mask[j * width + i + w] == Some(face) {
+                            w += 1;
+                        }
+                        let mut h = 1;
+                        'grow: while j + h < height {
+                            for k in 0..w {
+                                if mask[(j + h) * width + i + k] != Some(face) {
+                                    break 'grow;
+                                }
+                            }
+                            h += 1;
+                        }
+                        for jj in j..j + h {
+                            for ii in i..i + w {
+                                mask[jj * width + ii] = None;
+                            }
+                        }
+
+                        let buffers = match parts.iter().position(|(t, _)| *t == face.texture) {
+                            Some(index) => &mut parts[index].1,
+                            None => {
+                                parts.push((face.texture, MeshBuffers::default()));
+                                &mut parts.last_mut().unwrap().1
+                            }
+                        };
+                        let quad = Quad {
+                            axis,
+                            dir,
+                            plane: slice + if dir > 0 { 1 } else { 0 },
+                            rect: (lower[u] + i as i64, lower[v] + j as i64, w as i64, h as i64),
+                            ao: face.ao,
+                            tint: Point3::new(face.tint.0, face.tint.1, face.tint.2),
+                        };
+                        add_face(buffers, &quad, voxels.step());
+
+                        i += w;
+                    }
+                }
+            }
+        }
+    }
+
+    ChunkMesh { parts }
+}
+
+// Emits the vertices of a quad of voxels `step` tiles wide
+fn add_face(buffers: &mut MeshBuffers, quad: &Quad, step: i64) {
+    let Quad {
+        axis,
+        dir,
+        plane,
+        rect,
+        ao,
+        tint,
+    } = *quad;
+    let u = (axis + 1) % 3;
+    let v = (axis + 2) % 3;
+    let (u0, v0, w, h) = rect;
+
+    // Only the horizontal axes are downsampled
+    let scale = [step as f32, step as f32, 1.0];
+    let corner = |du: i64, dv: i64| {
+        let mut p = [0.0f32; 3];
+        p[axis] = plane as f32 * scale[axis];
+        p[u] = (u0 + du) as f32 * scale[u];
+        p[v] = (v0 + dv) as f32 * scale[v];
+        p
+    };
+    let mut corners = [corner(0, 0), corner(w, 0), corner(w, h), corner(0, h)];
+    let mut light = [
+        AO_LIGHT[ao[0] as usize],
+        AO_LIGHT[ao[1] as usize],
+        AO_LIGHT[ao[2] as usize],
+        AO_LIGHT[ao[3] as usize],
+    ];
+
+    let mut n = [0.0f32; 3];
+    n[axis] = dir as f32;
+    let normal = Vector3::new(n[0], n[2], n[1]);
+
+    let mut points = [
+        to_render(corners[0]),
+        to_render(corners[1]),
+        to_render(corners[2]),
+        to_render(corners[3]),
+    ];
+    // Swapping Y and Z mirrors the winding, so check it rather than work it
+    // out for each axis
+    if (points[1] - points[0])
+        .cross(&(points[2] - points[0]))
+        .dot(&normal)
+        < 0.0
+    {
+        points.swap(1, 3);
+        corners.swap(1, 3);
+        light.swap(1, 3);
+    }
+
+    // Textures repeat once per voxel. Tops use world x/y; sides run
+    // horizontally along the face with v increasing downwards.
+    let uv = |p: &[f32; 3]| match axis {
+        2 => Point2::new(p[0], p[1]),
+        0 => Point2::new(p[1], -p[2]),
+        _ => Point2::new(p[0], -p[2]),
+    };
+    let uvs = [
+        uv(&corners[0]),
+        uv(&corners[1]),
+        uv(&corners[2]),
+        uv(&corners[3]),
+    ];
+
+    buffers.add_quad(points, normal, uvs, light, tint);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::meshing::ChunkVoxels;
+
+    const SIZE: i64 = 4;
+
+    fn mesh<F: Fn(i64, i64) -> (TileKind, i64)>(column: F) -> ChunkMesh {
+        greedy_mesh(
+            &ChunkVoxels::from_fn(SIZE, 0, column),
+            &BlockAppearances::default(),
+        )
+    }
+
+    // The total area of the faces looking along `normal`, in render space
+    fn area(mesh: &ChunkMesh, normal: Vector3<f32>) -> f32 {
+        let mut total = 0.0;
+        for (_, part) in &mesh.parts {
+            for quad in 0..part.quad_count() {
+                let p = &part.positions[quad * 4..quad * 4 + 4];
+                if part.normals[quad * 4] == normal {
+                    total += (p[1] - p[0]).cross(&(p[3] - p[0])).norm();
+                }
+            }
+        }
+        total
+    }
+
+    fn up() -> Vector3<f32> {
+        Vector3::new(0.0, 1.0, 0.0)
+    }
+
+    // The four side directions in render space
+    fn sides() -> [Vector3<f32>; 4] {
+        [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        ]
+    }
+
+    #[test]
+    fn flat_chunks_have_one_top_quad_per_run() {
+        let flat = mesh(|_, _| (TileKind::Grass, 3));
+        assert_eq!(flat.quad_count(), 1);
+        assert_eq!(flat.parts.len(), 1);
+        assert_eq!(flat.parts[0].0, TileKind::Grass);
+        assert_eq!(area(&flat, up()), (SIZE * SIZE) as f32);
+
+        // The quad covers the chunk at the height of the tile tops
+        let part = &flat.parts[0].1;
+        assert_eq!(part.indices.len(), 6);
+        for p in &part.positions {
+            assert_eq!(p.y, 3.0 - 0.5);
+            assert!(p.x == -0.5 || p.x == SIZE as f32 - 0.5);
+            assert!(p.z == -0.5 || p.z == SIZE as f32 - 0.5);
+        }
+
+        // Each kind of tile is its own run
+        let halves = mesh(|x, _| {
+            let kind = if x < 2 {
+                TileKind::Grass
+            } else {
+                TileKind::Tilled
+            };
+            (kind, 3)
+        });
+        assert_eq!(halves.quad_count(), 2);
+        for (kind, part) in &halves.parts {
+            assert!(*kind == TileKind::Grass || *kind == TileKind::Tilled);
+            assert_eq!(part.quad_count(), 1);
+        }
+        assert_eq!(area(&halves, up()), (SIZE * SIZE) as f32);
+    }
+
+    #[test]
+    fn steps_only_have_sides_where_exposed() {
+        // Three tiles high for x < 2, one tile high beyond
+        let step = mesh(|x, _| (TileKind::Grass, if x < 2 { 3 } else { 1 }));
+
+        assert_eq!(area(&step, up()), (SIZE * SIZE) as f32);
+        let [east, west, north, south] = sides();
+        assert_eq!(area(&step, east), (2 * SIZE) as f32);
+        assert_eq!(area(&step, west), 0.0);
+        assert_eq!(area(&step, north), 0.0);
+        assert_eq!(area(&step, south), 0.0);
+        assert_eq!(area(&step, -up()), 0.0);
+    }
+
+    #[test]
+    fn chunk_borders_only_have_sides_against_lower_neighbours() {
+        let inside = |x: i64, y: i64| (0..SIZE).contains(&x) && (0..SIZE).contains(&y);
+
+        // Higher neighbours hide the sides of the chunk
+        let sunken = mesh(|x, y| (TileKind::Grass, if inside(x, y) { 2 } else { 5 }));
+        assert_eq!(area(&sunken, up()), (SIZE * SIZE) as f32);
+        for side in sides().iter() {
+            assert_eq!(area(&sunken, *side), 0.0);
+        }
+
+        // Lower neighbours expose them, two tiles deep
+        let raised = mesh(|x, y| (TileKind::Grass, if inside(x, y) { 2 } else { 0 }));
+        for side in sides().iter() {
+            assert_eq!(area(&raised, *side), (2 * SIZE) as f32);
+        }
+
+        // Neighbours of the same height leave the chunk flat
+        let level = mesh(|_, _| (TileKind::Grass, 2));
+        for side in sides().iter() {
+            assert_eq!(area(&level, *side), 0.0);
+        }
+    }
+}
@@ -0,0 +1,521 @@
+//
+// Meshing
+//
+// Turns a chunk of the world map into triangle meshes. Nothing here touches
+// the GPU: the output is plain vertex and index buffers which the graphics
+// code uploads, so meshing can be inspected (and tested) on its own.
+//
+// Coordinates in the output are in render space, i.e. kiss3d's Y-up
+// convention with world Y and Z swapped, relative to the chunk origin.
+//
+// Besides the terrain, anything implementing Voxels can be meshed, such as
+// the voxel models entities are drawn with.
+//
+use nalgebra::{Point2, Point3, Vector3};
+
+use crate::world::{TileKind, WorldMap};
+
+mod appearance;
+pub use appearance::*;
+
+mod greedy;
+pub use greedy::*;
+
+mod worker;
+pub use worker::*;
+
+/// A grid of voxels the greedy mesher can turn into quads. Coordinates are
+/// local, along world x, y and z.
+pub trait Voxels {
+    /// The voxels to mesh, from `lower` up to but not including `upper`,
+    /// indexed by axis (0 = x, 1 = y, 2 = z)
+    fn bounds(&self) -> ([i64; 3], [i64; 3]);
+
+    /// Tiles per voxel along world x and y, see ChunkVoxels::step
+    fn step(&self) -> i64 {
+        1
+    }
+
+    /// Voxels outside the bounds may be asked for, to tell whether faces on
+    /// the edge are exposed
+    fn is_solid(&self, x: i64, y: i64, z: i64) -> bool;
+
+    /// How one face of a solid voxel looks: the tile image it is textured
+    /// with, or Empty for plain colour, and the colour it is tinted with
+    fn face(
+        &self,
+        appearances: &BlockAppearances,
+        x: i64,
+        y: i64,
+        z: i64,
+        face: BlockFace,
+    ) -> (TileKind, (f32, f32, f32));
+}
+
+/// The coarsest level of detail chunks are sampled at, in tiles per column.
+/// Steps must be powers of two up to this.
+pub const MAX_STEP: i64 = 4;
+
+/// The columns of one chunk plus a one column border sampled from the
+/// neighbouring chunks, so faces on the chunk edge are only emitted when
+/// they are actually exposed.
+///
+/// Distant chunks are sampled at a lower level of detail: each column then
+/// stands for a `step` x `step` block of tiles, represented by the tile at
+/// its centre.
+///
+/// Neighbouring chunks may be drawn at other steps, so their surface along
+/// the shared edge can be lower than this chunk's border suggests. Border
+/// columns therefore take the lowest tile any step up to MAX_STEP could show
+/// there, and the sides on the chunk edge hang down as skirts covering the
+/// cracks between the two levels of detail.
+pub struct ChunkVoxels {
+    pub origin: (i64, i64, i64),
+    // Number of columns along each side
+    pub size: i64,
+    // Tiles per column along each side
+    pub step: i64,
+
+    // (kind, height, age) for (size + 2)^2 columns, including the border
+    columns: Vec<(TileKind, i64, u64)>,
+}
+
+impl ChunkVoxels {
+    /// Samples a chunk `tiles` wide, one column per `step` tiles
+    pub fn sample(world_map: &WorldMap, origin: (i64, i64, i64), tiles: i64, step: i64) -> Self {
+        let (ox, oy, _) = origin;
+        let size = tiles / step;
+        let center = step / 2;
+
+        let mut columns = Vec::with_capacity(((size + 2) * (size + 2)) as usize);
+        for y in -1..=size {
+            for x in -1..=size {
+                let inside = (0..size).contains(&x) && (0..size).contains(&y);
+                let (tx, ty) = if inside {
+                    (ox + x * step + center, oy + y * step + center)
+                } else {
+                    let xs = Self::border_tiles(ox, tiles, step, x);
+                    let ys = Self::border_tiles(oy, tiles, step, y);
+                    ys.flat_map(|ty| xs.clone().map(move |tx| (tx, ty)))
+                        .min_by_key(|&(tx, ty)| Self::top(world_map, tx, ty))
+                        .unwrap()
+                };
+                let tile = world_map.tile(tx, ty);
+                columns.push((tile.kind, tile.height as i64, tile.age()));
+            }
+        }
+        Self {
+            origin,
+            size,
+            step,
+            columns,
+        }
+    }
+
+    // The tiles along one axis a neighbouring chunk at any step may show for
+    // the border column at local `x`. Along the edge that is the MAX_STEP
+    // block the column lies in; across it, the centres of the neighbour's
+    // first column at each step.
+    fn border_tiles(origin: i64, tiles: i64, step: i64, x: i64) -> std::ops::Range<i64> {
+        if x < 0 {
+            origin - MAX_STEP / 2..origin
+        } else if x * step >= tiles {
+            origin + tiles..origin + tiles + MAX_STEP / 2 + 1
+        } else {
+            let start = (origin + x * step).div_euclid(MAX_STEP) * MAX_STEP;
+            start..start + MAX_STEP
+        }
+    }
+
+    // The height of the top of a tile, or -1 for an empty one
+    fn top(world_map: &WorldMap, x: i64, y: i64) -> i64 {
+        let tile = world_map.tile(x, y);
+        if tile.kind == TileKind::Empty {
+            -1
+        } else {
+            tile.height as i64
+        }
+    }
+
+    /// Builds the voxels from a function giving (kind, height) for each
+    /// column, in coordinates local to the chunk
+    #[cfg(test)]
+    pub fn from_fn<F: Fn(i64, i64) -> (TileKind, i64)>(size: i64, oz: i64, column: F) -> Self {
+        let mut columns = Vec::with_capacity(((size + 2) * (size + 2)) as usize);
+        for y in -1..=size {
+            for x in -1..=size {
+                let (kind, height) = column(x, y);
+                columns.push((kind, height, 0));
+            }
+        }
+        Self {
+            origin: (0, 0, oz),
+            size,
+            step: 1,
+            columns,
+        }
+    }
+
+    // Local x and y may be in -1..=size
+    fn column(&self, x: i64, y: i64) -> (TileKind, i64, u64) {
+        self.columns[((y + 1) * (self.size + 2) + (x + 1)) as usize]
+    }
+
+    /// The world position of the tile a column was sampled from
+    pub fn tile_position(&self, x: i64, y: i64) -> (i64, i64) {
+        let center = self.step / 2;
+        (
+            self.origin.0 + x * self.step + center,
+            self.origin.1 + y * self.step + center,
+        )
+    }
+
+    /// The age of the tile a column was sampled from
+    pub fn age(&self, x: i64, y: i64) -> u64 {
+        self.column(x, y).2
+    }
+
+    /// The kind of the voxel at local coordinates, with z relative to the
+    /// chunk origin. Everything below the bottom of the world is treated as
+    /// solid so the underside of the map never produces faces.
+    pub fn voxel(&self, x: i64, y: i64, z: i64) -> TileKind {
+        let z = self.origin.2 + z;
+        let (kind, height, _) = self.column(x, y);
+        if z < 0 {
+            TileKind::_DebugTile
+        } else if z <= height {
+            kind
+        } else {
+            TileKind::Empty
+        }
+    }
+
+    pub fn is_solid(&self, x: i64, y: i64, z: i64) -> bool {
+        self.voxel(x, y, z) != TileKind::Empty
+    }
+
+    /// The range of local z values which can contain visible faces.
+    ///
+    /// Below the lowest column (including the border) every voxel is buried
+    /// and above the highest column in the chunk there is only air.
+    pub fn occupied_range(&self) -> std::ops::Range<i64> {
+        let mut low = i64::MAX;
+        let mut high = i64::MIN;
+        for y in -1..=self.size {
+            for x in -1..=self.size {
+                let (kind, height, _) = self.column(x, y);
+                let height = if kind == TileKind::Empty { -1 } else { height };
+                low = low.min(height);
+                if x >= 0 && x < self.size && y >= 0 && y < self.size {
+                    high = high.max(height);
+                }
+            }
+        }
+        let low = (low - self.origin.2).max(0);
+        // Heights are never downsampled, so the chunk is size * step tall
+        let high = (high + 1 - self.origin.2).min(self.size * self.step);
+        low..high.max(low)
+    }
+}
+
+impl Voxels for ChunkVoxels {
+    fn bounds(&self) -> ([i64; 3], [i64; 3]) {
+        let z_range = self.occupied_range();
+        ([0, 0, z_range.start], [self.size, self.size, z_range.end])
+    }
+
+    fn step(&self) -> i64 {
+        self.step
+    }
+
+    fn is_solid(&self, x: i64, y: i64, z: i64) -> bool {
+        ChunkVoxels::is_solid(self, x, y, z)
+    }
+
+    fn face(
+        &self,
+        appearances: &BlockAppearances,
+        x: i64,
+        y: i64,
+        z: i64,
+        face: BlockFace,
+    ) -> (TileKind, (f32, f32, f32)) {
+        let kind = self.voxel(x, y, z);
+        let texture = appearances.texture(kind, face, self.tile_position(x, y), self.age(x, y));
+        (texture, appearances.tint(kind))
+    }
+}
+
+#[derive(Default)]
+pub struct MeshBuffers {
+    pub positions: Vec<Point3<f32>>,
+    pub normals: Vec<Vector3<f32>>,
+    pub uvs: Vec<Point2<f32>>,
+    // The tint of each vertex, darkened by ambient occlusion
+    pub colors: Vec<Point3<f32>>,
+    // Triangle list, three indices per triangle
+    pub indices: Vec<u32>,
+}
+
+impl MeshBuffers {
+    #[cfg(test)]
+    pub fn quad_count(&self) -> usize {
+        self.indices.len() / 6
+    }
+
+    /// Appends a quad given its corners in counter-clockwise order as seen
+    /// from the side the normal points to.
+    ///
+    /// `ao` is the ambient light reaching each corner, from 0 (fully
+    /// occluded) to 1. The quad is split along the diagonal whose ends are
+    /// least occluded so that it is interpolated evenly across it.
+    pub fn add_quad(
+        &mut self,
+        corners: [Point3<f32>; 4],
+        normal: Vector3<f32>,
+        uvs: [Point2<f32>; 4],
+        ao: [f32; 4],
+        tint: Point3<f32>,
+    ) {
+        let i = self.positions.len() as u32;
+        for k in 0..4 {
+            self.positions.push(corners[k]);
+            self.normals.push(normal);
+            self.uvs.push(uvs[k]);
+            self.colors.push(tint * ao[k]);
+        }
+        if ao[0] + ao[2] >= ao[1] + ao[3] {
+            self.indices
+                .extend_from_slice(&[i, i + 1, i + 2, i, i + 2, i + 3]);
+        } else {
+            self.indices
+                .extend_from_slice(&[i + 1, i + 2, i + 3, i + 1, i + 3, i]);
+        }
+    }
+}
+
+/// The meshes for one chunk, split by the tile image used on the faces.
+/// Each image gets its own buffers since merged faces repeat their tile
+/// across the quad: texture coordinates are in tiles, and each part is drawn
+/// with the atlas region of its image. Faces without an image are in an
+/// Empty part and only coloured by their vertex colours.
+pub struct ChunkMesh {
+    pub parts: Vec<(TileKind, MeshBuffers)>,
+}
+
+impl ChunkMesh {
+    #[cfg(test)]
+    pub fn quad_count(&self) -> usize {
+        self.parts.iter().map(|(_, part)| part.quad_count()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn occupied_range_spans_the_lowest_border_to_the_highest_column() {
+        let size = 4;
+        let inside = |x: i64, y: i64| (0..size).contains(&x) && (0..size).contains(&y);
+        let voxels = ChunkVoxels::from_fn(size, 0, |x, y| {
+            if inside(x, y) {
+                (TileKind::Grass, 1 + x / 2)
+            } else {
+                (TileKind::Grass, if x < 0 { 0 } else { 9 })
+            }
+        });
+        // The high border beyond x = 3 does not raise the top of the chunk
+        assert_eq!(voxels.occupied_range(), 0..3);
+        assert_eq!(voxels.bounds(), ([0, 0, 0], [size, size, 3]));
+
+        // Relative to the chunk origin, and clamped to the chunk
+        let raised = ChunkVoxels::from_fn(size, 3, |x, _| (TileKind::Grass, 2 + x));
+        assert_eq!(raised.occupied_range(), 0..3);
+        let tall = ChunkVoxels::from_fn(size, 0, |_, _| (TileKind::Grass, 100));
+        // Everything in the chunk is buried
+        assert!(tall.occupied_range().is_empty());
+    }
+
+    #[test]
+    fn empty_chunks_have_nothing_to_mesh() {
+        let voxels = ChunkVoxels::from_fn(4, 0, |_, _| (TileKind::Empty, 5));
+        assert_eq!(voxels.occupied_range(), 0..0);
+        assert!(!voxels.is_solid(0, 0, 0));
+        assert!(voxels.is_solid(0, 0, -1));
+
+        let mesh = greedy_mesh(&voxels, &BlockAppearances::default());
+        assert_eq!(mesh.quad_count(), 0);
+    }
+
+    #[test]
+    fn voxels_are_solid_up_to_the_column_height() {
+        let voxels = ChunkVoxels::from_fn(2, 0, |x, _| (TileKind::Tilled, 1 + x));
+        assert_eq!(voxels.voxel(0, 0, 1), TileKind::Tilled);
+        assert_eq!(voxels.voxel(0, 0, 2), TileKind::Empty);
+        assert_eq!(voxels.voxel(1, 0, 2), TileKind::Tilled);
+        // The border can be read too
+        assert_eq!(voxels.voxel(-1, 0, 0), TileKind::Tilled);
+        assert_eq!(voxels.voxel(-1, 0, 1), TileKind::Empty);
+    }
+
+    #[test]
+    fn borders_are_no_higher_than_neighbours_at_any_step() {
+        let tiles = 8;
+        let mut map = WorldMap::new(&mut StdRng::seed_from_u64(1));
+        for y in -4..tiles + 4 {
+            for x in -4..2 * tiles + 4 {
+                map.set_height(x, y, (x * 7 + y * 3).rem_euclid(5) + 2);
+                map.set_kind(x, y, TileKind::Grass, false);
+            }
+        }
+
+        // The surface height of a chunk at world (x, y), inside the chunk
+        let height_at = |voxels: &ChunkVoxels, x: i64, y: i64| {
+            let (ox, oy, _) = voxels.origin;
+            let column = voxels.column((x - ox) / voxels.step, (y - oy) / voxels.step);
+            column.1
+        };
+        let steps = [1, 2, MAX_STEP];
+        for &west_step in &steps {
+            for &east_step in &steps {
+                let west = ChunkVoxels::sample(&map, (0, 0, 0), tiles, west_step);
+                let east = ChunkVoxels::sample(&map, (tiles, 0, 0), tiles, east_step);
+
+                // Along the shared edge, each side's border hangs down to the
+                // other side's surface, so whichever is higher covers the gap
+                for y in 0..tiles {
+                    let west_border = west.column(west.size, y / west_step).1;
+                    let east_border = east.column(-1, y / east_step).1;
+                    assert!(west_border <= height_at(&east, tiles, y));
+                    assert!(east_border <= height_at(&west, tiles - 1, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn editing_a_tile_invalidates_every_chunk_sampling_it() {
+        let chunk = 32;
+        let mut map = WorldMap::new(&mut StdRng::seed_from_u64(1));
+        for y in -chunk - 4..2 * chunk + 4 {
+            for x in -chunk - 4..2 * chunk + 4 {
+                map.set_height(x, y, 10);
+                map.set_kind(x, y, TileKind::Grass, false);
+            }
+        }
+
+        let origins: Vec<(i64, i64, i64)> = (-1..=1)
+            .flat_map(|cy| (-1..=1).map(move |cx| (cx * chunk, cy * chunk, 0)))
+            .collect();
+        // The sync id of each chunk and its columns at every step
+        let sample = |map: &WorldMap| {
+            origins
+                .iter()
+                .map(|&(ox, oy, oz)| {
+                    let columns = [1, 2, MAX_STEP]
+                        .iter()
+                        .map(|&step| ChunkVoxels::sample(map, (ox, oy, oz), chunk, step).columns)
+                        .collect::<Vec<_>>();
+                    (map.chunk_sync_id(ox, oy, oz), columns)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Tiles on and around the chunk edges, on both sides of the origin
+        let edits = [
+            (0, 0),
+            (2, 5),
+            (31, 5),
+            (33, -2),
+            (34, 34),
+            (-2, 30),
+            (-1, -1),
+            (16, 1),
+        ];
+        for &(x, y) in &edits {
+            let before = sample(&map);
+            map.set_height(x, y, 2);
+            let after = sample(&map);
+
+            let mut seen_by = 0;
+            for ((old_id, old_columns), (new_id, new_columns)) in before.iter().zip(&after) {
+                if old_columns != new_columns {
+                    assert_ne!(old_id, new_id, "({}, {}) left a chunk stale", x, y);
+                    seen_by += 1;
+                }
+            }
+            assert!(
+                seen_by > 1,
+                "({}, {}) is only seen by {} chunks",
+                x,
+                y,
+                seen_by
+            );
+            map.set_height(x, y, 10);
+        }
+    }
+
+    #[test]
+    fn ageing_invalidates_the_chunks_whose_faces_change() {
+        let appearances = BlockAppearances::load("src/assets/blocks.ron");
+        let thresholds = appearances.age_thresholds();
+        assert_eq!(thresholds.get(&TileKind::Plants), Some(&vec![0, 20_000]));
+        assert_eq!(thresholds.get(&TileKind::Grass), None);
+
+        let chunk = 32;
+        let mut map = WorldMap::new(&mut StdRng::seed_from_u64(1));
+        map.set_age_thresholds(thresholds);
+        for y in -chunk..2 * chunk {
+            for x in -chunk..2 * chunk {
+                map.set_kind(x, y, TileKind::Grass, true);
+            }
+        }
+        map.set_kind(5, 5, TileKind::Plants, true);
+        map.set_kind(-3, 40, TileKind::Plants, true);
+
+        // The sync id of each chunk and the image on top of each of its tiles
+        let sample = |map: &WorldMap| {
+            (-1..=1)
+                .flat_map(|cy| (-1..=1).map(move |cx| (cx * chunk, cy * chunk)))
+                .map(|(ox, oy)| {
+                    let mut tops = Vec::new();
+                    for y in oy..oy + chunk {
+                        for x in ox..ox + chunk {
+                            let tile = map.tile(x, y);
+                            tops.push(appearances.texture(
+                                tile.kind,
+                                BlockFace::Top,
+                                (x, y),
+                                tile.age(),
+                            ));
+                        }
+                    }
+                    ((ox, oy), map.chunk_sync_id(ox, oy, 0), tops)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The plants come up on the second update, in two chunks
+        for update in 1..=3 {
+            let before = sample(&map);
+            map.update_tile_ages();
+            let after = sample(&map);
+
+            let mut changed = Vec::new();
+            for ((origin, old_id, old_tops), (_, new_id, new_tops)) in before.iter().zip(&after) {
+                assert_eq!(old_id != new_id, old_tops != new_tops, "{:?}", origin);
+                if old_id != new_id {
+                    changed.push(*origin);
+                }
+            }
+            if update == 2 {
+                assert_eq!(changed, [(0, 0), (-32, 32)]);
+            } else {
+                assert!(changed.is_empty());
+            }
+        }
+    }
+}
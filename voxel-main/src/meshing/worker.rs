@@ -0,0 +1,111 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::meshing::{greedy_mesh, BlockAppearances, ChunkMesh, ChunkVoxels};
+
+//
+// Background meshing
+//
+// Chunks are meshed on worker threads from a ChunkVoxels snapshot taken on
+// the main thread. The finished buffers come back tagged with the chunk's
+// sync id at the time of the snapshot so the caller can drop results that
+// were overtaken by later edits.
+//
+
+pub type ChunkKey = (i64, i64, i64);
+
+struct Job {
+    key: ChunkKey,
+    sync_id: u64,
+    voxels: ChunkVoxels,
+}
+
+pub struct MeshResult {
+    pub key: ChunkKey,
+    pub sync_id: u64,
+    // See ChunkVoxels::step
+    pub step: i64,
+    pub mesh: ChunkMesh,
+}
+
+pub struct MeshWorkers {
+    jobs: Option<Sender<Job>>,
+    results: Receiver<MeshResult>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+impl MeshWorkers {
+    pub fn new(count: usize, appearances: Arc<BlockAppearances>) -> Self {
+        let (job_sender, job_receiver) = channel::<Job>();
+        let (result_sender, results) = channel();
+
+        // Workers take turns pulling from the one job queue
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let threads = (0..count.max(1))
+            .map(|i| {
+                let jobs = job_receiver.clone();
+                let results = result_sender.clone();
+                let appearances = appearances.clone();
+                thread::Builder::new()
+                    .name(format!("mesher-{}", i))
+                    .spawn(move || loop {
+                        let job = match jobs.lock().unwrap().recv() {
+                            Ok(job) => job,
+                            Err(_) => break,
+                        };
+                        let result = MeshResult {
+                            key: job.key,
+                            sync_id: job.sync_id,
+                            step: job.voxels.step,
+                            mesh: greedy_mesh(&job.voxels, &appearances),
+                        };
+                        if results.send(result).is_err() {
+                            break;
+                        }
+                    })
+                    .expect("Failed to start meshing thread")
+            })
+            .collect();
+
+        Self {
+            jobs: Some(job_sender),
+            results,
+            threads,
+        }
+    }
+
+    /// One worker per core, leaving one for the main thread
+    pub fn with_default_threads(appearances: Arc<BlockAppearances>) -> Self {
+        let cores = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2);
+        Self::new(cores.saturating_sub(1), appearances)
+    }
+
+    pub fn submit(&self, key: ChunkKey, sync_id: u64, voxels: ChunkVoxels) {
+        let job = Job {
+            key,
+            sync_id,
+            voxels,
+        };
+        // The workers only exit once the sender is dropped
+        self.jobs.as_ref().unwrap().send(job).unwrap();
+    }
+
+    /// A finished mesh, if any is ready
+    pub fn try_recv(&self) -> Option<MeshResult> {
+        self.results.try_recv().ok()
+    }
+}
+
+impl Drop for MeshWorkers {
+    fn drop(&mut self) {
+        // Closing the job queue stops the workers once they finish their
+        // current job
+        self.jobs = None;
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,7 +1,9 @@
 use crate::actor::{Actor, ActorState};
+use crate::world::Clock;
 use crate::world::WorldEntityList;
+use crate::world::WorldItemList;
 use crate::world::WorldMap;
-use rand;
+use crate::world::WorldRng;
 
 pub trait Occupation {
     fn name(&self) -> &'static str;
@@ -16,9 +18,11 @@ pub trait Occupation {
 
 pub struct Context<'a> {
     pub game_time: u64,
-    pub rng: &'a mut rand::rngs::ThreadRng,
+    pub clock: Clock,
+    pub rng: &'a mut WorldRng,
     pub map: &'a mut WorldMap,
     pub entities: &'a mut WorldEntityList,
+    pub items: &'a mut WorldItemList,
     pub actor_state: &'a mut ActorState,
     pub other_actors: &'a [Actor],
 }
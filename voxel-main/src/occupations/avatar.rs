@@ -1,5 +1,9 @@
 use crate::actor::ActorState;
-use crate::occupation::{Context, Occupation, Strategy};
+use crate::item::ItemKind;
+use crate::occupation::{Context, Occupation, Status2, Strategy};
+use crate::tasks;
+use crate::verb::{Order, Verb};
+use crate::world::TileKind;
 
 pub struct Avatar {}
 
@@ -28,18 +32,103 @@ impl Avatar {
 //
 // Strategy
 //
+// The Avatar is, by definition, controlled by something else. That something
+// pushes Orders onto the actor and the strategy carries each one out with the
+// same Tasks the other occupations use.
+//
+
+enum AvatarTask {
+    ChangeTile(tasks::ChangeTileTask),
+    ChangeHeight(tasks::ChangeHeightTask),
+    PlaceEntity(tasks::PlaceEntityTask),
+    RemoveEntity(tasks::RemoveEntityTask),
+    PickUp(tasks::PickUpTask),
+}
+
+impl AvatarTask {
+    fn from_order(order: Order) -> Self {
+        use AvatarTask::*;
+
+        let target = Some(order.target);
+        match order.verb {
+            Verb::Dig => ChangeHeight(tasks::ChangeHeightTask::new(order.target, -1)),
+            Verb::Raise => ChangeHeight(tasks::ChangeHeightTask::new(order.target, 1)),
+            Verb::Till => ChangeTile(
+                tasks::change_tile(TileKind::Tilled)
+                    .with_src(Some(TileKind::Grass))
+                    .with_target(target)
+                    .build(),
+            ),
+            Verb::Plant => ChangeTile(
+                tasks::change_tile(TileKind::Plants)
+                    .with_src(Some(TileKind::Tilled))
+                    .with_consumes(Some(ItemKind::Seeds))
+                    .with_target(target)
+                    .build(),
+            ),
+            Verb::Pave => ChangeTile(
+                tasks::change_tile(TileKind::Concrete)
+                    .with_target(target)
+                    .build(),
+            ),
+            Verb::PlaceBlock => PlaceEntity(tasks::PlaceEntityTask::new(order.target)),
+            Verb::RemoveEntity => RemoveEntity(tasks::RemoveEntityTask::new(order.target)),
+            Verb::PickUp => PickUp(tasks::PickUpTask::new(order.target)),
+        }
+    }
+
+    fn update(&mut self, ctx: &mut Context) -> Status2 {
+        use AvatarTask::*;
 
-struct AvatarStrategy {}
+        match self {
+            ChangeTile(task) => task.update(ctx),
+            ChangeHeight(task) => task.update(ctx),
+            PlaceEntity(task) => task.update(ctx),
+            RemoveEntity(task) => task.update(ctx),
+            PickUp(task) => task.update(ctx),
+        }
+    }
+}
+
+struct AvatarStrategy {
+    task: Option<(Verb, AvatarTask)>,
+    scaffold_wait: u64,
+}
 
 impl AvatarStrategy {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            task: None,
+            scaffold_wait: 0,
+        }
     }
 }
 
 impl Strategy for AvatarStrategy {
-    fn update(&mut self, _ctx: Context) {
-        // No-op since the Avatar is, by definition, controlled by
-        // something else.
+    fn update(&mut self, mut ctx: Context) {
+        if self.scaffold_wait > 0 {
+            self.scaffold_wait -= 1;
+            return;
+        }
+
+        if self.task.is_none() {
+            self.task = ctx
+                .actor_state
+                .pop_order()
+                .map(|order| (order.verb, AvatarTask::from_order(order)));
+        }
+
+        if let Some((verb, task)) = self.task.as_mut() {
+            match task.update(&mut ctx) {
+                Status2::Wait(frames) => self.scaffold_wait = frames,
+                Status2::Continue => {}
+                Status2::Success => self.task = None,
+                Status2::Failure => {
+                    ctx.actor_state
+                        .push_report(format!("{} failed", verb.name()));
+                    self.task = None;
+                }
+            }
+        }
     }
 }
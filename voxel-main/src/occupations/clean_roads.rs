@@ -60,9 +60,8 @@ impl Strategy for GrowStrategy {
                 }
             }
             Grow => {
-                let mut rng = rand::thread_rng();
-                let x = rng.gen_range(-150, 150);
-                let y = rng.gen_range(-150, 150);
+                let x = ctx.rng.gen_range(-150, 150);
+                let y = ctx.rng.gen_range(-150, 150);
                 let tile = ctx.map.tile(x, y);
                 if tile.kind == TileKind::Concrete && tile.age() > 10_000 {
                     let mut count = 0;
@@ -92,7 +91,7 @@ impl Strategy for GrowStrategy {
                         ctx.map.set_kind(x, y, TileKind::Grass, true);
                     }
                 }
-                self.state = Wait(rng.gen_range(300, 3000), Box::new(Grow));
+                self.state = Wait(ctx.rng.gen_range(300, 3000), Box::new(Grow));
             }
         }
     }
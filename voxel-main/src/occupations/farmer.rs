@@ -66,6 +66,7 @@ enum PlotPlanState {
     Till(TillTask),
     Done(u64, Box<tasks::RandomMove>),
     MoveToBeacon(tasks::MoveToTask),
+    Sleep(tasks::Sleep, Box<PlotPlanState>),
 }
 
 struct PlotPlan {
@@ -80,6 +81,13 @@ impl PlotPlan {
     fn update(&mut self, mut ctx: Context) {
         use PlotPlanState::*;
 
+        // Work stops for the night at the next break
+        if ctx.clock.is_night() {
+            if let Wait(..) | Done(..) = self.state {
+                self.state = Sleep(tasks::Sleep::new(), Box::new(self.state.clone()));
+            }
+        }
+
         match self.state {
             Init => {
                 self.state = Wait(
@@ -295,6 +303,12 @@ impl PlotPlan {
                 }
             }
 
+            Sleep(ref mut task, ref next_state) => {
+                if task.update(&mut ctx) == TaskStatus::Success {
+                    self.state = *next_state.clone();
+                }
+            }
+
             MoveToBeacon(ref mut task) => {
                 self.scaffold_wait = tasks::task2_wrapper(
                     self.scaffold_wait,
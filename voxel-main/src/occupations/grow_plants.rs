@@ -60,16 +60,15 @@ impl Strategy for GrowStrategy {
                 }
             }
             Grow => {
-                let mut rng = rand::thread_rng();
-                let x = rng.gen_range(-150, 150);
-                let y = rng.gen_range(-150, 150);
+                let x = ctx.rng.gen_range(-150, 150);
+                let y = ctx.rng.gen_range(-150, 150);
                 let tile = ctx.map.tile(x, y);
                 if tile.kind == TileKind::Tilled && tile.age() > 200 {
                     ctx.map.set_kind(x, y, TileKind::Plants, true);
                 } else if tile.kind == TileKind::Plants && tile.age() > 200_000 {
                     ctx.map.set_kind(x, y, TileKind::Grass, true);
                 }
-                self.state = Wait(rng.gen_range(300, 3000), Box::new(Grow));
+                self.state = Wait(ctx.rng.gen_range(300, 3000), Box::new(Grow));
             }
         }
     }
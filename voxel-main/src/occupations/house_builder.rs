@@ -1,7 +1,7 @@
 use crate::actor::ActorState;
 use crate::occupation::{Context, Occupation, Status2, Strategy, Task, TaskStatus};
 use crate::tasks;
-use crate::world::TileKind;
+use crate::world::{TileKind, WorldEntity};
 use rand::Rng;
 
 pub struct HouseBuilder {}
@@ -68,6 +68,7 @@ enum PlotPlanState {
     SetFoundationTile(LayFoundationTask),
     Rest(u64, Box<tasks::RandomMove>),
     MoveToBeacon(tasks::MoveToTask),
+    Sleep(tasks::Sleep, Box<PlotPlanState>),
 }
 
 struct PlotPlan {
@@ -83,6 +84,13 @@ impl PlotPlan {
     fn update(&mut self, mut ctx: Context) {
         use PlotPlanState::*;
 
+        // Work stops for the night at the next break
+        if ctx.clock.is_night() {
+            if let Wait(..) | Rest(..) = self.state {
+                self.state = Sleep(tasks::Sleep::new(), Box::new(self.state.clone()));
+            }
+        }
+
         match self.state {
             Init => {
                 self.state = Rest(
@@ -282,15 +290,13 @@ impl PlotPlan {
             BuildHouse => {
                 let (x0, y0, x1, y1) = self.plot;
                 let z = ctx.map.tile(x0, y0).height as i64;
-                ctx.entities.add(
-                    x0 + 1,
-                    y0 + 1,
-                    z,
-                    x1 - x0 - 2,
-                    y1 - y0 - 2,
-                    self.height,
-                    (0.709, 0.576, 0.470),
-                );
+                let house = WorldEntity::at(x0 + 1, y0 + 1, z)
+                    .with_size(x1 - x0 - 2, y1 - y0 - 2, self.height)
+                    .with_color((0.709, 0.576, 0.470));
+                let id = ctx.entities.add(house);
+                if let Some(house) = ctx.entities.get_mut(id) {
+                    house.state.set_model(Some("house"));
+                }
 
                 for y in y0 + 1..y1 - 1 {
                     for x in x0 + 1..x1 - 1 {
@@ -325,6 +331,12 @@ impl PlotPlan {
                     }
                 }
             }
+            Sleep(ref mut task, ref next_state) => {
+                if task.update(&mut ctx) == TaskStatus::Success {
+                    self.state = *next_state.clone();
+                }
+            }
+
             MoveToBeacon(ref mut task) => {
                 self.scaffold_wait = tasks::task2_wrapper(
                     self.scaffold_wait,
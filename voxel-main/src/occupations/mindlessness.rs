@@ -91,8 +91,12 @@ impl RandomMovements {
             }
             TaskStatus::Failure => {
                 self.tasks.pop_front();
-                self.tasks
-                    .push_front(Box::new(tasks::Wait::new(ctx.game_time, 500, 1000)));
+                self.tasks.push_front(Box::new(tasks::Wait::new(
+                    ctx.rng,
+                    ctx.game_time,
+                    500,
+                    1000,
+                )));
             }
         }
 
@@ -21,3 +21,19 @@ pub use clean_roads::CleanRoads;
 
 pub mod eater;
 pub use eater::Eater;
+
+use crate::occupation::Occupation;
+
+// Look up an occupation by its Occupation::name(), i.e. for data files
+pub fn from_name(name: &str) -> Option<Box<dyn Occupation>> {
+    let occupation: Box<dyn Occupation> = match name {
+        "Vagrant" => Box::new(Mindlessness::new()),
+        "Avatar" => Box::new(Avatar::new()),
+        "Farmer" => Box::new(Farmer::new()),
+        "House Builder" => Box::new(HouseBuilder::new()),
+        "Road Builder" => Box::new(RoadBuilder::new()),
+        "Eater" => Box::new(Eater::new()),
+        _ => return None,
+    };
+    Some(occupation)
+}
@@ -54,6 +54,10 @@ enum PlanState {
         expiration: u64,
         next_state: Box<PlanState>,
     },
+    Sleep {
+        task: tasks::Sleep,
+        next_state: Box<PlanState>,
+    },
 }
 
 struct RoadStrategy {
@@ -85,6 +89,16 @@ impl Strategy for RoadStrategy {
     fn update(&mut self, mut ctx: Context) {
         use PlanState::*;
 
+        // Work stops for the night at the next break
+        if ctx.clock.is_night() {
+            if let Wander { .. } | WaitThen { .. } = self.state {
+                self.state = Sleep {
+                    task: tasks::Sleep::new(),
+                    next_state: Box::new(self.state.clone()),
+                };
+            }
+        }
+
         match self.state {
             Init => {
                 let bx = ctx.rng.gen_range(0, 64);
@@ -224,6 +238,15 @@ impl Strategy for RoadStrategy {
                     self.state = *next_state.clone();
                 }
             }
+
+            Sleep {
+                ref mut task,
+                ref next_state,
+            } => {
+                if task.update(&mut ctx) == TaskStatus::Success {
+                    self.state = *next_state.clone();
+                }
+            }
         }
     }
 }
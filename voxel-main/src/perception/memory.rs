@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::perception::Percept;
+use crate::world::TileKind;
+
+#[derive(Copy, Clone)]
+pub struct RememberedTile {
+    pub kind: TileKind,
+    pub seen: u64,
+}
+
+#[derive(Copy, Clone)]
+pub struct RememberedActor {
+    pub seen: u64,
+}
+
+#[derive(Copy, Clone)]
+pub struct RememberedEntity {
+    pub seen: u64,
+}
+
+/// What an Actor believes about the world, built up from percepts.
+///
+/// Memory is intentionally allowed to go stale: a remembered patch of Plants may
+/// have been eaten since it was last seen. Tasks that act on memory should verify
+/// the world when they arrive rather than assume the memory is correct.
+///
+pub struct Memory {
+    // How long (in game time) something is remembered after it was last seen
+    retention: u64,
+
+    tiles: HashMap<(i64, i64), RememberedTile>,
+    actors: HashMap<u64, RememberedActor>,
+    entities: HashMap<u64, RememberedEntity>,
+}
+
+impl Memory {
+    pub fn new(retention: u64) -> Self {
+        Self {
+            retention,
+            tiles: HashMap::new(),
+            actors: HashMap::new(),
+            entities: HashMap::new(),
+        }
+    }
+
+    pub fn remember(&mut self, percept: &Percept, game_time: u64) {
+        match *percept {
+            Percept::Tile { position, kind } => {
+                self.tiles.insert(
+                    position,
+                    RememberedTile {
+                        kind,
+                        seen: game_time,
+                    },
+                );
+            }
+            Percept::Actor { id } => {
+                self.actors.insert(id, RememberedActor { seen: game_time });
+            }
+            Percept::Entity { id } => {
+                self.entities
+                    .insert(id, RememberedEntity { seen: game_time });
+            }
+        }
+    }
+
+    /// Drop anything that has not been seen within the retention window
+    pub fn forget(&mut self, game_time: u64) {
+        let cutoff = game_time.saturating_sub(self.retention);
+        self.tiles.retain(|_, t| t.seen >= cutoff);
+        self.actors.retain(|_, a| a.seen >= cutoff);
+        self.entities.retain(|_, e| e.seen >= cutoff);
+    }
+
+    #[cfg(test)]
+    pub fn tile(&self, x: i64, y: i64) -> Option<&RememberedTile> {
+        self.tiles.get(&(x, y))
+    }
+
+    pub fn actor(&self, id: u64) -> Option<&RememberedActor> {
+        self.actors.get(&id)
+    }
+
+    #[cfg(test)]
+    pub fn entity(&self, id: u64) -> Option<&RememberedEntity> {
+        self.entities.get(&id)
+    }
+
+    pub fn knows_of(&self, kind: TileKind) -> bool {
+        self.tiles.values().any(|t| t.kind == kind)
+    }
+
+    /// Returns the remembered tile of the given kind that is closest to `center`
+    /// and no further than `range` tiles (Chebyshev distance) from it.
+    pub fn nearest_tile(
+        &self,
+        kind: TileKind,
+        center: (i64, i64),
+        range: i64,
+    ) -> Option<(i64, i64)> {
+        let mut best: Option<((i64, i64), i64)> = None;
+        for (&(x, y), tile) in &self.tiles {
+            if tile.kind != kind {
+                continue;
+            }
+            let (dx, dy) = (x - center.0, y - center.1);
+            if dx.abs() > range || dy.abs() > range {
+                continue;
+            }
+
+            let d = dx * dx + dy * dy;
+            // Break ties by position so the result does not depend on HashMap order
+            let closer = match best {
+                Some((q, best_d)) => d < best_d || (d == best_d && (x, y) < q),
+                None => true,
+            };
+            if closer {
+                best = Some(((x, y), d));
+            }
+        }
+        best.map(|(p, _)| p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tile(x: i64, y: i64, kind: TileKind) -> Percept {
+        Percept::Tile {
+            position: (x, y),
+            kind,
+        }
+    }
+
+    #[test]
+    fn nearest_tile_is_the_closest_in_range() {
+        let mut memory = Memory::new(100);
+        for p in &[tile(5, 0, TileKind::Plants), tile(2, 2, TileKind::Plants)] {
+            memory.remember(p, 0);
+        }
+        memory.remember(&tile(1, 0, TileKind::Grass), 0);
+
+        assert_eq!(
+            memory.nearest_tile(TileKind::Plants, (0, 0), 10),
+            Some((2, 2))
+        );
+        assert_eq!(
+            memory.nearest_tile(TileKind::Plants, (6, 0), 10),
+            Some((5, 0))
+        );
+        assert_eq!(
+            memory.nearest_tile(TileKind::Grass, (0, 0), 10),
+            Some((1, 0))
+        );
+        assert_eq!(memory.nearest_tile(TileKind::Tilled, (0, 0), 10), None);
+    }
+
+    #[test]
+    fn nearest_tile_respects_the_range() {
+        let mut memory = Memory::new(100);
+        memory.remember(&tile(4, 1, TileKind::Plants), 0);
+
+        // The range is a square around the center
+        assert_eq!(
+            memory.nearest_tile(TileKind::Plants, (0, 0), 4),
+            Some((4, 1))
+        );
+        assert_eq!(memory.nearest_tile(TileKind::Plants, (0, 0), 3), None);
+        assert_eq!(memory.nearest_tile(TileKind::Plants, (0, -3), 3), None);
+    }
+
+    #[test]
+    fn nearest_tile_breaks_ties_by_position() {
+        let mut memory = Memory::new(100);
+        for &(x, y) in &[(0, 3), (3, 0), (-3, 0), (0, -3)] {
+            memory.remember(&tile(x, y, TileKind::Plants), 0);
+        }
+        assert_eq!(
+            memory.nearest_tile(TileKind::Plants, (0, 0), 5),
+            Some((-3, 0))
+        );
+    }
+
+    #[test]
+    fn stale_memories_are_forgotten() {
+        let mut memory = Memory::new(100);
+        memory.remember(&tile(1, 1, TileKind::Plants), 0);
+        memory.remember(&Percept::Actor { id: 7 }, 50);
+        memory.remember(&Percept::Entity { id: 3 }, 150);
+
+        memory.forget(120);
+        assert!(memory.tile(1, 1).is_none());
+        assert!(!memory.knows_of(TileKind::Plants));
+        assert_eq!(memory.actor(7).map(|a| a.seen), Some(50));
+        assert_eq!(memory.entity(3).map(|e| e.seen), Some(150));
+
+        // Seeing something again refreshes it
+        memory.remember(&Percept::Actor { id: 7 }, 200);
+        memory.forget(200);
+        assert_eq!(memory.actor(7).map(|a| a.seen), Some(200));
+        assert!(memory.entity(3).is_some());
+    }
+}
@@ -0,0 +1,330 @@
+//
+// Perception is what gives an Actor knowledge of the world. Rather than
+// letting Tasks query the WorldMap directly for "where is the nearest X?",
+// Actors periodically look around, limited by a vision radius and by
+// line-of-sight over the terrain, and what they see is recorded in their
+// Memory. Tasks then act on that (possibly stale) memory.
+//
+use crate::actor::{Actor, ActorState};
+use crate::world::{TileKind, WorldEntity, WorldEntityList, WorldMap};
+
+mod line_of_sight;
+pub use line_of_sight::*;
+
+mod memory;
+pub use memory::*;
+
+#[derive(Copy, Clone)]
+pub enum Percept {
+    Tile {
+        position: (i64, i64),
+        kind: TileKind,
+    },
+    Actor {
+        id: u64,
+    },
+    Entity {
+        id: u64,
+    },
+}
+
+pub struct Perception {
+    pub vision_radius: i64,
+
+    // Game time between each look around
+    pub interval: u64,
+    next_update: u64,
+
+    pub memory: Memory,
+}
+
+impl Perception {
+    pub fn new() -> Self {
+        Self {
+            vision_radius: 12,
+            interval: 60,
+            next_update: 0,
+            memory: Memory::new(50_000),
+        }
+    }
+}
+
+// Heights (including anything standing on the terrain) for the square of
+// tiles around a point. Sampled once up-front so the many line-of-sight
+// walks do not each go back to the WorldMap.
+struct OccluderGrid {
+    x0: i64,
+    y0: i64,
+    size: i64,
+    heights: Vec<f32>,
+}
+
+impl OccluderGrid {
+    fn new(map: &WorldMap, entities: &WorldEntityList, center: (i64, i64), radius: i64) -> Self {
+        let (x0, y0) = (center.0 - radius, center.1 - radius);
+        let size = 2 * radius + 1;
+
+        let mut heights = Vec::with_capacity((size * size) as usize);
+        for y in y0..y0 + size {
+            for x in x0..x0 + size {
+                heights.push(map.height(x, y) as f32);
+            }
+        }
+
+        let mut grid = Self {
+            x0,
+            y0,
+            size,
+            heights,
+        };
+
+        for entity in &entities.entities {
+            let s = &entity.state;
+            let top = (s.z + s.height as i64) as f32;
+            for y in s.y..s.y + s.length as i64 {
+                for x in s.x..s.x + s.width as i64 {
+                    if let Some(i) = grid.index(x, y) {
+                        grid.heights[i] = grid.heights[i].max(top);
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    fn index(&self, x: i64, y: i64) -> Option<usize> {
+        let (gx, gy) = (x - self.x0, y - self.y0);
+        if gx < 0 || gy < 0 || gx >= self.size || gy >= self.size {
+            None
+        } else {
+            Some((gy * self.size + gx) as usize)
+        }
+    }
+
+    fn height(&self, map: &WorldMap, x: i64, y: i64) -> f32 {
+        match self.index(x, y) {
+            Some(i) => self.heights[i],
+            None => map.height(x, y) as f32,
+        }
+    }
+}
+
+/// Returns everything visible from `position` within `radius` tiles.
+pub fn perceive(
+    map: &WorldMap,
+    entities: &WorldEntityList,
+    other_actors: &[Actor],
+    position: (i64, i64),
+    radius: i64,
+) -> Vec<Percept> {
+    let grid = OccluderGrid::new(map, entities, position, radius);
+    let height_at = |x, y| grid.height(map, x, y);
+    let in_radius = |x: i64, y: i64| {
+        let (dx, dy) = (x - position.0, y - position.1);
+        dx * dx + dy * dy <= radius * radius
+    };
+
+    let mut percepts = Vec::new();
+
+    //
+    // Tiles
+    //
+    for y in position.1 - radius..=position.1 + radius {
+        for x in position.0 - radius..=position.0 + radius {
+            if !in_radius(x, y) {
+                continue;
+            }
+            if !has_line_of_sight(position, (x, y), 0.0, height_at) {
+                continue;
+            }
+            let tile = map.tile(x, y);
+            percepts.push(Percept::Tile {
+                position: (x, y),
+                kind: tile.kind,
+            });
+        }
+    }
+
+    //
+    // Actors
+    //
+    for other in other_actors {
+        if other.state.ethereal() {
+            continue;
+        }
+        let (x, y) = other.state.position();
+        if !in_radius(x, y) {
+            continue;
+        }
+        if has_line_of_sight(position, (x, y), 1.0, height_at) {
+            percepts.push(Percept::Actor {
+                id: other.state.id(),
+            });
+        }
+    }
+
+    //
+    // Entities
+    //
+    // Look toward the closest point of the entity's footprint and aim
+    // at its roof line.
+    //
+    for entity in &entities.entities {
+        let s = &entity.state;
+        let x = position.0.max(s.x).min(s.x + s.width as i64 - 1);
+        let y = position.1.max(s.y).min(s.y + s.length as i64 - 1);
+        if !in_radius(x, y) {
+            continue;
+        }
+        if has_line_of_sight(position, (x, y), 0.0, height_at) {
+            percepts.push(Percept::Entity { id: s.id() });
+        }
+    }
+
+    percepts
+}
+
+/// Let the actor look around (if it is time to) and record what was seen.
+pub fn update(
+    actor_state: &mut ActorState,
+    map: &WorldMap,
+    entities: &WorldEntityList,
+    other_actors: &[Actor],
+    game_time: u64,
+) {
+    if actor_state.ethereal() {
+        return;
+    }
+
+    let position = actor_state.position();
+    let perception = actor_state.perception_mut();
+    if game_time < perception.next_update {
+        return;
+    }
+    perception.next_update = game_time + perception.interval;
+
+    let percepts = perceive(
+        map,
+        entities,
+        other_actors,
+        position,
+        perception.vision_radius,
+    );
+
+    perception.memory.forget(game_time);
+    for percept in &percepts {
+        perception.memory.remember(percept, game_time);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // Flat grass around the origin with a wall along x = 3
+    fn walled_map() -> WorldMap {
+        let mut map = WorldMap::new(&mut StdRng::seed_from_u64(1));
+        for y in -20..=20 {
+            for x in -20..=20 {
+                map.set_height(x, y, if x == 3 { 10 } else { 0 });
+                map.set_kind(x, y, TileKind::Grass, false);
+            }
+        }
+        map
+    }
+
+    fn actor_at(id: u64, position: (i64, i64)) -> Actor {
+        let mut actor = Actor::new(id);
+        actor.state.set_position(position.0, position.1);
+        actor
+    }
+
+    fn sees_tile(percepts: &[Percept], p: (i64, i64)) -> bool {
+        percepts
+            .iter()
+            .any(|percept| matches!(percept, Percept::Tile { position, .. } if *position == p))
+    }
+
+    #[test]
+    fn tiles_are_seen_within_the_radius_up_to_walls() {
+        let mut map = walled_map();
+        map.set_kind(-2, 1, TileKind::Plants, false);
+        let percepts = perceive(&map, &WorldEntityList::new(), &[], (0, 0), 8);
+
+        assert!(sees_tile(&percepts, (2, 0)));
+        assert!(sees_tile(&percepts, (3, 0)));
+        assert!(!sees_tile(&percepts, (6, 0)));
+        assert!(sees_tile(&percepts, (-8, 0)));
+        assert!(!sees_tile(&percepts, (-9, 0)));
+        assert!(!sees_tile(&percepts, (-6, -6)));
+
+        let plants = percepts.iter().any(|percept| match percept {
+            Percept::Tile { position, kind, .. } => {
+                *position == (-2, 1) && *kind == TileKind::Plants
+            }
+            _ => false,
+        });
+        assert!(plants);
+    }
+
+    #[test]
+    fn entities_are_seen_by_id_and_hide_what_is_behind_them() {
+        let map = walled_map();
+        let mut entities = WorldEntityList::new();
+        let shed = entities.add(WorldEntity::at(-4, 0, 0).with_size(1, 1, 2));
+        let hidden = entities.add(WorldEntity::at(6, 1, 0).with_size(1, 1, 2));
+        let percepts = perceive(&map, &entities, &[], (0, 0), 8);
+
+        let seen: Vec<u64> = percepts
+            .iter()
+            .filter_map(|percept| match percept {
+                Percept::Entity { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(seen, [shed]);
+        assert!(!seen.contains(&hidden));
+        assert!(!sees_tile(&percepts, (-6, 0)));
+        assert!(sees_tile(&percepts, (-6, 3)));
+    }
+
+    #[test]
+    fn visible_actors_are_seen_unless_ethereal() {
+        let map = walled_map();
+        let mut ghost = actor_at(3, (0, 2));
+        ghost.state.set_ethereal(true);
+        let others = [actor_at(1, (-3, 0)), actor_at(2, (6, 0)), ghost];
+        let percepts = perceive(&map, &WorldEntityList::new(), &others, (0, 0), 8);
+
+        let seen: Vec<u64> = percepts
+            .iter()
+            .filter_map(|percept| match percept {
+                Percept::Actor { id, .. } => Some(*id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(seen, [1]);
+    }
+
+    #[test]
+    fn memories_follow_entities_after_others_are_removed() {
+        let map = walled_map();
+        let mut entities = WorldEntityList::new();
+        let first = entities.add(WorldEntity::at(-2, 0, 0));
+        let second = entities.add(WorldEntity::at(0, -2, 0));
+        let mut memory = Memory::new(100);
+
+        for percept in &perceive(&map, &entities, &[], (0, 0), 8) {
+            memory.remember(percept, 0);
+        }
+        assert!(entities.remove(first).is_some());
+        for percept in &perceive(&map, &entities, &[], (0, 0), 8) {
+            memory.remember(percept, 10);
+        }
+
+        assert_eq!(memory.entity(first).map(|e| e.seen), Some(0));
+        assert_eq!(memory.entity(second).map(|e| e.seen), Some(10));
+    }
+}
@@ -0,0 +1,344 @@
+//
+// Picking
+//
+// Finds what lies under a point on the screen by casting a ray from the
+// camera through it and intersecting the terrain, the Actors and the entities.
+// The geometry here mirrors how those are drawn: voxel tops sit half a unit
+// below the tile height and Actors are 1.8 unit tall cylinders.
+//
+use kiss3d::camera::Camera;
+use kiss3d::window::Window;
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+use crate::actor::Actor;
+use crate::world::{World, WorldEntityState};
+
+// Nothing further than this from the camera can be picked
+const MAX_DISTANCE: f32 = 250.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Pick {
+    Tile(i64, i64),
+    Actor(u64),
+    Entity(u64),
+}
+
+pub struct Ray {
+    pub origin: Point3<f32>,
+    pub dir: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn at(&self, t: f32) -> Point3<f32> {
+        self.origin + self.dir * t
+    }
+}
+
+/// The ray under the mouse cursor, if the cursor is over the window
+pub fn cursor_ray(window: &Window, camera: &dyn Camera) -> Option<Ray> {
+    let (x, y) = window.cursor_pos()?;
+    let size = window.size();
+    let size = Vector2::new(size.x as f32, size.y as f32);
+    let (origin, dir) = camera.unproject(&Point2::new(x as f32, y as f32), &size);
+    Some(Ray { origin, dir })
+}
+
+/// The closest thing hit by the ray
+pub fn pick(world: &World, ray: &Ray) -> Option<Pick> {
+    let mut best = cast_terrain(world, ray).map(|(t, x, y)| (t, Pick::Tile(x, y)));
+
+    let mut consider = |hit: Option<f32>, pick: Pick| {
+        if let Some(t) = hit {
+            if best.is_none_or(|(best_t, _)| t < best_t) {
+                best = Some((t, pick));
+            }
+        }
+    };
+    for actor in world.actors.iter().filter(|a| !a.state.ethereal()) {
+        let (min, max) = actor_bounds(world, actor);
+        consider(intersect_box(ray, min, max), Pick::Actor(actor.state.id()));
+    }
+    for entity in &world.entities.entities {
+        let (min, max) = entity_bounds(&entity.state);
+        consider(
+            intersect_box(ray, min, max),
+            Pick::Entity(entity.state.id()),
+        );
+    }
+
+    best.map(|(_, pick)| pick)
+}
+
+/// A one line description of a picked object for the inspector
+pub fn describe(world: &World, pick: Pick) -> Option<String> {
+    match pick {
+        Pick::Tile(x, y) => {
+            let tile = world.world_map.tile(x, y);
+            Some(format!(
+                "{:?} at ({}, {}), height {}, age {}",
+                tile.kind,
+                x,
+                y,
+                world.world_map.height(x, y),
+                tile.age()
+            ))
+        }
+        Pick::Actor(id) => world.actor_index(id).map(|index| {
+            let actor = &world.actors[index];
+            format!("{}, the {}", actor.name, actor.occupation.name())
+        }),
+        Pick::Entity(id) => world
+            .entities
+            .entities
+            .iter()
+            .find(|entity| entity.state.id() == id)
+            .map(|entity| {
+                let state = &entity.state;
+                format!(
+                    "Entity {} at ({}, {}), {}x{}x{}",
+                    id, state.x, state.y, state.width, state.length, state.height
+                )
+            }),
+    }
+}
+
+/// The world space box around a picked object, e.g. for drawing a highlight
+pub fn bounds(world: &World, pick: Pick) -> Option<(Point3<f32>, Point3<f32>)> {
+    match pick {
+        Pick::Tile(x, y) => {
+            let top = world.world_map.height(x, y) as f32 - 0.5;
+            let (x, y) = (x as f32, y as f32);
+            Some((
+                Point3::new(x - 0.5, top - 1.0, y - 0.5),
+                Point3::new(x + 0.5, top, y + 0.5),
+            ))
+        }
+        Pick::Actor(id) => world
+            .actor_index(id)
+            .map(|index| actor_bounds(world, &world.actors[index])),
+        Pick::Entity(id) => world
+            .entities
+            .entities
+            .iter()
+            .find(|entity| entity.state.id() == id)
+            .map(|entity| entity_bounds(&entity.state)),
+    }
+}
+
+fn actor_bounds(world: &World, actor: &Actor) -> (Point3<f32>, Point3<f32>) {
+    let (x, y) = actor.state.position();
+    let z = world.world_map.height(x, y) as f32 + 0.5;
+    let (x, y) = (x as f32, y as f32);
+    (
+        Point3::new(x - 0.4, z - 0.9, y - 0.4),
+        Point3::new(x + 0.4, z + 0.9, y + 0.4),
+    )
+}
+
+fn entity_bounds(state: &WorldEntityState) -> (Point3<f32>, Point3<f32>) {
+    let min = Point3::new(
+        state.x as f32 - 0.5,
+        state.z as f32 - 0.5,
+        state.y as f32 - 0.5,
+    );
+    let size = Vector3::new(state.width as f32, state.height as f32, state.length as f32);
+    (min, min + size)
+}
+
+// Slab test. Returns the distance along the ray to the box, zero if the ray
+// starts inside it.
+fn intersect_box(ray: &Ray, min: Point3<f32>, max: Point3<f32>) -> Option<f32> {
+    let mut t0 = 0.0f32;
+    let mut t1 = MAX_DISTANCE;
+    for i in 0..3 {
+        let inv = 1.0 / ray.dir[i];
+        let mut near = (min[i] - ray.origin[i]) * inv;
+        let mut far = (max[i] - ray.origin[i]) * inv;
+        if near > far {
+            std::mem::swap(&mut near, &mut far);
+        }
+        // NaN (a zero direction component on a slab boundary) leaves the
+        // interval unchanged
+        if near > t0 {
+            t0 = near;
+        }
+        if far < t1 {
+            t1 = far;
+        }
+        if t0 > t1 {
+            return None;
+        }
+    }
+    Some(t0)
+}
+
+// Walks the tile columns the ray passes over (a 2D DDA in the x/z plane) and
+// returns the first column whose top is at or above the ray, along with the
+// distance at which the ray enters it.
+fn cast_terrain(world: &World, ray: &Ray) -> Option<(f32, i64, i64)> {
+    let (ox, oz) = (ray.origin.x, ray.origin.z);
+    let (dx, dz) = (ray.dir.x, ray.dir.z);
+
+    let mut cell = (ox.round() as i64, oz.round() as i64);
+    let step = (dx.signum() as i64, dz.signum() as i64);
+
+    // Distance along the ray to the next cell boundary on each axis, and
+    // between successive boundaries
+    let boundary = |o: f32, d: f32, c: i64| {
+        if d == 0.0 {
+            f32::INFINITY
+        } else {
+            (c as f32 + 0.5 * d.signum() - o) / d
+        }
+    };
+    let mut next = (boundary(ox, dx, cell.0), boundary(oz, dz, cell.1));
+    let delta = (1.0 / dx.abs(), 1.0 / dz.abs());
+
+    let mut t_enter = 0.0;
+    while t_enter < MAX_DISTANCE {
+        let t_exit = next.0.min(next.1);
+        let top = world.world_map.height(cell.0, cell.1) as f32 - 0.5;
+
+        if ray.at(t_enter).y <= top {
+            return Some((t_enter, cell.0, cell.1));
+        }
+        if ray.at(t_exit).y <= top {
+            let t = (top - ray.origin.y) / ray.dir.y;
+            return Some((t, cell.0, cell.1));
+        }
+
+        t_enter = t_exit;
+        if next.0 < next.1 {
+            cell.0 += step.0;
+            next.0 += delta.0;
+        } else {
+            cell.1 += step.1;
+            next.1 += delta.1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::WorldEntity;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn ray(origin: (f32, f32, f32), dir: (f32, f32, f32)) -> Ray {
+        Ray {
+            origin: Point3::new(origin.0, origin.1, origin.2),
+            dir: Vector3::new(dir.0, dir.1, dir.2).normalize(),
+        }
+    }
+
+    fn assert_near(a: Option<f32>, b: f32) {
+        match a {
+            Some(a) => assert!((a - b).abs() < EPSILON, "{} != {}", a, b),
+            None => panic!("missed, expected {}", b),
+        }
+    }
+
+    // Flat ground at height 1 with a wall at height 10 along x = 32, the
+    // first column of the next chunk, and another along x = -1.
+    fn walled_world() -> World {
+        let mut world = World::new(1);
+        for y in -8..8 {
+            for x in -8..40 {
+                let height = if x == 32 || x == -1 { 10 } else { 1 };
+                world.world_map.set_height(x, y, height);
+            }
+        }
+        world
+    }
+
+    #[test]
+    fn boxes_are_hit_where_the_ray_enters_them() {
+        let (min, max) = (Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+
+        assert_near(
+            intersect_box(&ray((-5.0, 0.0, 0.0), (1.0, 0.0, 0.0)), min, max),
+            4.0,
+        );
+        assert_near(
+            intersect_box(&ray((0.5, 3.0, 0.5), (0.0, -1.0, 0.0)), min, max),
+            2.0,
+        );
+        let diagonal = ray((-3.0, -3.0, 0.0), (1.0, 1.0, 0.0));
+        assert_near(intersect_box(&diagonal, min, max), 2.0 * 2f32.sqrt());
+
+        // Rays starting inside hit straight away
+        assert_near(
+            intersect_box(&ray((0.0, 0.0, 0.0), (0.0, 0.0, 1.0)), min, max),
+            0.0,
+        );
+    }
+
+    #[test]
+    fn boxes_behind_beside_or_too_far_are_missed() {
+        let (min, max) = (Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+
+        assert!(intersect_box(&ray((5.0, 0.0, 0.0), (1.0, 0.0, 0.0)), min, max).is_none());
+        assert!(intersect_box(&ray((-5.0, 1.5, 0.0), (1.0, 0.0, 0.0)), min, max).is_none());
+        assert!(intersect_box(&ray((-3.0, -5.5, 0.0), (1.0, 1.0, 0.0)), min, max).is_none());
+
+        let far = ray((-MAX_DISTANCE - 5.0, 0.0, 0.0), (1.0, 0.0, 0.0));
+        assert!(intersect_box(&far, min, max).is_none());
+    }
+
+    #[test]
+    fn the_terrain_is_hit_on_top_of_the_column_below() {
+        let world = walled_world();
+
+        // Voxel tops sit half a unit below the tile height
+        let hit = cast_terrain(&world, &ray((31.4, 20.0, 5.0), (0.0, -1.0, 0.0)));
+        assert_eq!(hit.map(|(_, x, y)| (x, y)), Some((31, 5)));
+        assert_near(hit.map(|(t, _, _)| t), 19.5);
+
+        let hit = cast_terrain(&world, &ray((31.6, 20.0, 5.0), (0.0, -1.0, 0.0)));
+        assert_eq!(hit.map(|(_, x, y)| (x, y)), Some((32, 5)));
+        assert_near(hit.map(|(t, _, _)| t), 10.5);
+
+        // Looking up at the sky
+        assert!(cast_terrain(&world, &ray((0.0, 20.0, 0.0), (0.2, 1.0, 0.1))).is_none());
+    }
+
+    #[test]
+    fn the_terrain_is_hit_across_chunk_edges() {
+        let world = walled_world();
+
+        // Along the ground into the side of the wall in the next chunk
+        let hit = cast_terrain(&world, &ray((28.0, 5.0, 3.0), (1.0, 0.0, 0.0)));
+        assert_eq!(hit.map(|(_, x, y)| (x, y)), Some((32, 3)));
+        assert_near(hit.map(|(t, _, _)| t), 3.5);
+
+        // And the other way, across the chunk edge at the origin
+        let hit = cast_terrain(&world, &ray((2.0, 5.0, -3.0), (-1.0, 0.0, 0.0)));
+        assert_eq!(hit.map(|(_, x, y)| (x, y)), Some((-1, -3)));
+        assert_near(hit.map(|(t, _, _)| t), 2.5);
+
+        // Downwards across the edge, landing on the ground just past it
+        let hit = cast_terrain(&world, &ray((30.0, 4.5, 0.0), (1.0, -1.0, 0.0)));
+        assert_eq!(hit.map(|(_, x, y)| (x, y)), Some((32, 0)));
+        assert_near(hit.map(|(t, _, _)| t), 1.5 * 2f32.sqrt());
+    }
+
+    #[test]
+    fn the_closest_thing_is_picked() {
+        let mut world = walled_world();
+        let along = ray((20.0, 2.0, 0.0), (1.0, 0.0, 0.0));
+        assert_eq!(pick(&world, &along), Some(Pick::Tile(32, 0)));
+
+        let mut actor = Actor::new(4);
+        actor.state.set_position(28, 0);
+        world.actors.push(actor);
+        let id = world
+            .entities
+            .add(WorldEntity::at(25, 0, 1).with_size(1, 1, 2));
+        assert_eq!(pick(&world, &along), Some(Pick::Entity(id)));
+
+        assert!(world.entities.remove(id).is_some());
+        assert_eq!(pick(&world, &along), Some(Pick::Actor(4)));
+    }
+}
@@ -0,0 +1,316 @@
+use crate::dialogue::{Conversation, Dialogues};
+use crate::input::Action;
+use crate::occupation;
+use crate::perception;
+use crate::verb::{Order, Verb};
+use crate::world::{Clock, World};
+
+//
+// Simulation
+//
+// Everything a recording has to reproduce: the World, the conversation with
+// another Actor, and the verb and facing of the player. It is advanced one
+// frame at a time by the Actions for that frame, so the main loop and a test
+// replaying a recording step it the same way. What is only drawn (the camera,
+// what the mouse is over, notifications) stays in the main loop.
+//
+pub struct Simulation {
+    pub world: World,
+    pub conversation: Option<Conversation>,
+
+    // The verb the player has selected and the direction they last moved;
+    // verbs act on the tile in front of the player.
+    pub verb: Verb,
+    pub facing: (i64, i64),
+
+    pub game_time: u64,
+    pub frame_number: u64,
+    last_move_frame: u64,
+}
+
+impl Simulation {
+    pub fn new(world: World) -> Self {
+        Self {
+            world,
+            conversation: None,
+            verb: Verb::Dig,
+            facing: (0, 1),
+            game_time: 0,
+            frame_number: 0,
+            last_move_frame: 0,
+        }
+    }
+
+    pub fn clock(&self) -> Clock {
+        Clock::new(self.game_time)
+    }
+
+    /// Runs one frame: updates the Actors, then handles the player's Actions.
+    ///
+    /// Returns the messages for the player produced during the frame. Actions
+    /// that only affect the view, such as Action::Select, are ignored.
+    pub fn step(&mut self, dialogues: &Dialogues, actions: &[Action]) -> Vec<String> {
+        let mut messages = Vec::new();
+
+        self.update_actors();
+
+        // Let the player know when one of their orders could not be carried out
+        let player = &mut self.world.actors[self.world.player_index];
+        while let Some(report) = player.state.pop_report() {
+            messages.push(report);
+        }
+
+        for action in actions {
+            self.process(dialogues, action, &mut messages);
+        }
+
+        // Conversations end if the other Actor has wandered off
+        if let Some(current) = &self.conversation {
+            let world = &self.world;
+            let (px, py) = world.actors[world.player_index].state.position();
+            let near = match world.actor_index(current.actor_id) {
+                Some(index) => {
+                    let (ax, ay) = world.actors[index].state.position();
+                    (ax - px).abs() + (ay - py).abs() <= 2
+                }
+                None => false,
+            };
+            if !near {
+                self.conversation = None;
+            }
+        }
+
+        self.game_time += 10;
+        self.frame_number += 1;
+        messages
+    }
+
+    fn update_actors(&mut self) {
+        let game_time = self.game_time;
+        let clock = self.clock();
+        let world = &mut self.world;
+
+        for actor in &mut world.actors {
+            let mut keys = Vec::new();
+            for marker in &mut actor.shortterm_memory {
+                if *marker.1 <= game_time {
+                    keys.push(marker.0.clone());
+                }
+            }
+            for k in keys {
+                actor.shortterm_memory.remove(&k);
+            }
+        }
+
+        if (self.frame_number + 1).is_multiple_of(1000) {
+            world.world_map.update_tile_ages();
+        }
+
+        //
+        // Update strategies
+        //
+        // NOTE: what's with this odd looking loop? The loop is refactored to allow us
+        // to have a mutable reference to the current actor and immutable references to
+        // all others. This is difficult to do "directly" since the borrow checker does
+        // not (really?) understand borrows from part of an array/vector.
+        //
+        // TODO: there has to be a better way to do this.
+        if let Some((actor, other_actors)) = world.actors.split_first_mut() {
+            let mut i = 0;
+            while i < other_actors.len() {
+                actor.occupation.update(game_time);
+                if actor.strategy.is_none() {
+                    let strategy = actor.occupation.generate_strategy();
+                    actor.strategy = Some(strategy);
+                }
+
+                perception::update(
+                    &mut actor.state,
+                    &world.world_map,
+                    &world.entities,
+                    other_actors,
+                    game_time,
+                );
+
+                let context = occupation::Context {
+                    game_time,
+                    clock,
+                    rng: &mut world.rng,
+                    map: &mut world.world_map,
+                    entities: &mut world.entities,
+                    items: &mut world.items,
+                    actor_state: &mut actor.state,
+                    other_actors,
+                };
+                actor.strategy.as_mut().unwrap().update(context);
+
+                std::mem::swap(actor, &mut other_actors[i]);
+                i += 1;
+            }
+        }
+        world.player_index = (world.player_index + 1) % world.actors.len();
+    }
+
+    fn process(&mut self, dialogues: &Dialogues, action: &Action, messages: &mut Vec<String>) {
+        let world = &mut self.world;
+        match *action {
+            Action::Move { x, y } => {
+                // Throttle Move commands, discard when there are too many.
+                // This counts frames rather than wall time so replays
+                // see the same moves.
+                if self.frame_number < self.last_move_frame + 6 {
+                    return;
+                }
+
+                self.facing = (x as i64, y as i64);
+
+                let (px, py) = world.actors[world.player_index].state.position();
+                let nx = px + x as i64;
+                let ny = py + y as i64;
+                if world.is_tile_empty(nx, ny) {
+                    let player = &mut world.actors[world.player_index];
+                    player.state.set_position(nx, ny);
+                    self.last_move_frame = self.frame_number;
+                }
+
+                if let Some(index) = world.actor_at_tile(nx, ny) {
+                    let (actor, player) = world.actor_pair_mut(index, world.player_index);
+
+                    let key = "Hello".to_string();
+                    if !actor.shortterm_memory.contains_key(&key) {
+                        self.conversation = Conversation::start(dialogues, actor, player);
+
+                        // Fallback for Actors without anything more interesting to say
+                        if self.conversation.is_none() {
+                            messages.push(format!(
+                                "{player_name} says to the {occupation}, \"Hello, {name}.\"",
+                                player_name = player.name,
+                                name = actor.name,
+                                occupation = actor.occupation.name(),
+                            ));
+                        }
+                        actor.shortterm_memory.insert(key, self.game_time + 1_800);
+                    }
+                }
+            }
+            Action::Choose(choice) => self.choose(dialogues, choice),
+            Action::EndConversation => self.conversation = None,
+            Action::SelectVerb(delta) => self.verb = self.verb.cycle(delta),
+            Action::Perform => {
+                let player = &mut world.actors[world.player_index];
+                let (px, py) = player.state.position();
+                player.state.push_order(Order {
+                    verb: self.verb,
+                    target: (px + self.facing.0, py + self.facing.1),
+                });
+            }
+            Action::Select | Action::ExportRegion => {}
+        }
+    }
+
+    // Picks one of the choices of the current conversation, which ends when the
+    // choice has no follow-up. A choice with no conversation open is ignored: the
+    // conversation may have ended earlier in the frame (i.e. on a closing choice
+    // followed by another number key), or a replayed recording may have been
+    // trimmed by hand.
+    fn choose(&mut self, dialogues: &Dialogues, choice: usize) {
+        let current = match self.conversation.as_mut() {
+            Some(current) => current,
+            None => return,
+        };
+        let world = &mut self.world;
+        if let Some(index) = world.actor_index(current.actor_id) {
+            let (actor, player) = world.actor_pair_mut(index, world.player_index);
+            if !current.choose(dialogues, choice, actor, player) {
+                self.conversation = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Actor;
+    use crate::input::Replay;
+
+    // A world with the player and one other actor, who only says goodbye
+    fn farewell() -> (Simulation, Dialogues) {
+        let mut world = World::new(7);
+        world.actors.push(Actor::new(1));
+        world.actors.push(Actor::new(2));
+        world.player_index = 0;
+
+        let mut dialogues = Dialogues::new();
+        dialogues.add(
+            ron::de::from_str(
+                r#"(
+                    name: "farewell",
+                    start: "start",
+                    nodes: {
+                        "start": (text: "Goodbye.", choices: [(text: "Bye.")]),
+                    },
+                )"#,
+            )
+            .unwrap(),
+        );
+        (Simulation::new(world), dialogues)
+    }
+
+    fn replay(name: &str, recording: &str, sim: &mut Simulation, dialogues: &Dialogues) {
+        let path =
+            std::env::temp_dir().join(format!("voxel-main-{}-{}.txt", name, std::process::id()));
+        std::fs::write(&path, recording).unwrap();
+        let mut replay = Replay::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(replay.seed, 7);
+
+        while !replay.is_finished() {
+            let mut action_queue = Vec::new();
+            replay.actions_for(sim.frame_number, &mut action_queue);
+            sim.step(dialogues, &action_queue);
+        }
+    }
+
+    #[test]
+    fn replayed_choices_with_no_conversation_open_are_ignored() {
+        let (mut sim, dialogues) = farewell();
+        replay(
+            "no-conversation",
+            "seed 7\n3 Choose(0)\n4 EndConversation\n4 Choose(2)\n",
+            &mut sim,
+            &dialogues,
+        );
+        assert!(sim.conversation.is_none());
+    }
+
+    #[test]
+    fn choices_after_the_conversation_ends_in_the_same_frame_are_ignored() {
+        let (mut sim, dialogues) = farewell();
+        let (player, actor) = sim.world.actors.split_at(1);
+        sim.conversation = Conversation::start(&dialogues, &actor[0], &player[0]);
+        assert!(sim.conversation.is_some());
+
+        replay(
+            "closing-choice",
+            "seed 7\n2 Choose(0)\n2 Choose(1)\n",
+            &mut sim,
+            &dialogues,
+        );
+        assert!(sim.conversation.is_none());
+    }
+
+    #[test]
+    fn verbs_are_performed_on_the_tile_the_player_faces() {
+        let (mut sim, dialogues) = farewell();
+        sim.step(&dialogues, &[Action::SelectVerb(-1), Action::Perform]);
+        assert_eq!(sim.verb, Verb::PickUp);
+
+        let player = &mut sim.world.actors[sim.world.player_index];
+        let order = player.state.pop_order().unwrap();
+        assert_eq!(order.verb, Verb::PickUp);
+        assert_eq!(order.target, (0, 1));
+        assert_eq!(sim.frame_number, 1);
+        assert_eq!(sim.game_time, 10);
+    }
+}
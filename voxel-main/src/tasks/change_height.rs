@@ -0,0 +1,58 @@
+use crate::item::ItemKind;
+use crate::occupation::{Context, Status2};
+use crate::tasks::within_reach;
+use Status2::*;
+
+// Digging yields soil and raising the ground uses it up
+#[derive(PartialEq, Copy, Clone)]
+pub struct ChangeHeightTask {
+    target: (i64, i64),
+    delta: i64,
+    done: bool,
+}
+
+impl ChangeHeightTask {
+    pub fn new(target: (i64, i64), delta: i64) -> Self {
+        Self {
+            target,
+            delta,
+            done: false,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) -> Status2 {
+        if self.done {
+            return Success;
+        }
+
+        let (x, y) = self.target;
+        if !within_reach(ctx, (x, y)) {
+            return Failure;
+        }
+
+        // Nothing can be dug out from under a building
+        if !ctx.map.tile(x, y).is_walkable() {
+            return Failure;
+        }
+
+        let height = ctx.map.height(x, y) as i64;
+        let new_height = height + self.delta;
+        if new_height < 1 || new_height > i16::MAX as i64 {
+            return Failure;
+        }
+
+        let soil = self.delta.unsigned_abs() as u32;
+        if self.delta > 0 {
+            if !ctx.actor_state.inventory_mut().remove(ItemKind::Soil, soil) {
+                return Failure;
+            }
+        } else {
+            ctx.actor_state.inventory_mut().add(ItemKind::Soil, soil);
+        }
+
+        ctx.map.set_height(x, y, new_height);
+        ctx.actor_state.inc_sync_id();
+        self.done = true;
+        Wait(20)
+    }
+}
@@ -1,4 +1,6 @@
+use crate::item::ItemKind;
 use crate::occupation::{Context, Status2};
+use crate::tasks::within_reach;
 use crate::world::TileKind;
 use Status2::*;
 
@@ -22,12 +24,21 @@ impl Builder {
 
     builder_field!(with_src, src_tile_kind, Option<TileKind>);
     builder_field!(with_dst, dst_tile_kind, TileKind);
+
+    // The tile to change; defaults to the tile the actor is standing on
+    builder_field!(with_target, target, Option<(i64, i64)>);
+
+    // An item taken from the actor's inventory to make the change
+    builder_field!(with_consumes, consumes, Option<ItemKind>);
 }
 
 #[derive(PartialEq, Copy, Clone)]
 pub struct ChangeTileTask {
     src_tile_kind: Option<TileKind>,
     dst_tile_kind: TileKind,
+    target: Option<(i64, i64)>,
+    consumes: Option<ItemKind>,
+    done: bool,
 }
 
 pub fn change_tile(dst: TileKind) -> Builder {
@@ -35,13 +46,23 @@ pub fn change_tile(dst: TileKind) -> Builder {
         task: ChangeTileTask {
             src_tile_kind: None,
             dst_tile_kind: dst,
+            target: None,
+            consumes: None,
+            done: false,
         },
     }
 }
 
 impl ChangeTileTask {
     pub fn update(&mut self, ctx: &mut Context) -> Status2 {
-        let (px, py) = ctx.actor_state.position();
+        if self.done {
+            return Success;
+        }
+
+        let (px, py) = self.target.unwrap_or(ctx.actor_state.position());
+        if !within_reach(ctx, (px, py)) {
+            return Failure;
+        }
 
         if let Some(src_kind) = self.src_tile_kind {
             let current_tile = ctx.map.tile(px, py);
@@ -50,7 +71,14 @@ impl ChangeTileTask {
             }
         }
 
+        if let Some(item) = self.consumes {
+            if !ctx.actor_state.inventory_mut().remove(item, 1) {
+                return Failure;
+            }
+        }
+
         ctx.map.set_kind(px, py, self.dst_tile_kind, true);
+        self.done = true;
         Wait(10)
     }
 }
@@ -1,5 +1,3 @@
-use rand::seq::SliceRandom;
-
 use crate::occupation::{Context, Status2};
 use crate::world::TileKind;
 use Status2::*;
@@ -51,22 +49,16 @@ impl LocateTileTask {
             return Failure;
         }
 
-        // TODO: start with a small search range on the first attempt
-        // and build towards the full range: i.e. simulate the actor
-        // biasing toward looking nearby first.
-        let p = ctx
-            .actor_state
-            .beacon_point_with_random(ctx.rng, self.range as i64);
-
-        for dy in -3..3 {
-            for dx in -3..3 {
-                let q = (p.0 + dx, p.1 + dy);
-                let tile = ctx.map.tile(q.0, q.1);
-                if tile.kind == self.tile_kind {
-                    self.destination = q;
-                    return Success;
-                }
-            }
+        // Only tiles the actor has actually seen are candidates, searched for
+        // around the beacon so workers stay near where they were told to work.
+        // Nearest-first biases the search toward the beacon before going
+        // further afield. A failed attempt waits a bit so perception has a
+        // chance to pick up something new.
+        let p = ctx.actor_state.beacon_point();
+        let memory = ctx.actor_state.memory();
+        if let Some(q) = memory.nearest_tile(self.tile_kind, p, self.range as i64) {
+            self.destination = q;
+            return Success;
         }
         self.attempts -= 1;
         Wait(10)
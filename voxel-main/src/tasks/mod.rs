@@ -1,4 +1,4 @@
-use crate::occupation::Status2;
+use crate::occupation::{Context, Status2};
 
 pub fn task2_wrapper(mut wait: u64, status: Status2) -> u64 {
     if wait > 0 {
@@ -14,9 +14,18 @@ pub fn task2_wrapper(mut wait: u64, status: Status2) -> u64 {
     wait
 }
 
+// Tasks that act on a particular tile require the actor to be on or next to it
+pub fn within_reach(ctx: &Context, target: (i64, i64)) -> bool {
+    let (px, py) = ctx.actor_state.position();
+    (target.0 - px).abs() <= 1 && (target.1 - py).abs() <= 1
+}
+
 mod wait;
 pub use wait::Wait;
 
+mod sleep;
+pub use sleep::Sleep;
+
 pub mod random_move;
 pub use random_move::RandomMove;
 
@@ -31,3 +40,189 @@ pub use locate_tile::LocateTileTask;
 
 mod change_tile;
 pub use change_tile::{change_tile, ChangeTileTask};
+
+mod change_height;
+pub use change_height::ChangeHeightTask;
+
+mod place_entity;
+pub use place_entity::PlaceEntityTask;
+
+mod remove_entity;
+pub use remove_entity::RemoveEntityTask;
+
+mod pick_up;
+pub use pick_up::PickUpTask;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::Actor;
+    use crate::item::ItemKind;
+    use crate::world::{
+        Clock, TileKind, WorldEntity, WorldEntityList, WorldItemList, WorldMap, WorldRng,
+    };
+    use rand::SeedableRng;
+
+    // An actor at the origin of a flat patch of grass at height 5
+    struct Fixture {
+        rng: WorldRng,
+        map: WorldMap,
+        entities: WorldEntityList,
+        items: WorldItemList,
+        actor: Actor,
+        others: Vec<Actor>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut rng = WorldRng::seed_from_u64(1);
+            let mut map = WorldMap::new(&mut rng);
+            for y in -5..=5 {
+                for x in -5..=5 {
+                    map.set_height(x, y, 5);
+                    map.set_kind(x, y, TileKind::Grass, false);
+                }
+            }
+            Self {
+                rng,
+                map,
+                entities: WorldEntityList::new(),
+                items: WorldItemList::new(),
+                actor: Actor::new(1),
+                others: Vec::new(),
+            }
+        }
+
+        fn update(&mut self, task: impl FnOnce(&mut Context) -> Status2) -> Status2 {
+            let mut ctx = Context {
+                game_time: 0,
+                clock: Clock::new(0),
+                rng: &mut self.rng,
+                map: &mut self.map,
+                entities: &mut self.entities,
+                items: &mut self.items,
+                actor_state: &mut self.actor.state,
+                other_actors: &self.others,
+            };
+            task(&mut ctx)
+        }
+
+        fn count(&self, kind: ItemKind) -> u32 {
+            self.actor.state.inventory().count(kind)
+        }
+    }
+
+    fn failed(status: Status2) -> bool {
+        matches!(status, Status2::Failure)
+    }
+
+    #[test]
+    fn changing_the_height_needs_reach_soil_and_open_ground() {
+        let mut f = Fixture::new();
+        assert!(failed(
+            f.update(|ctx| ChangeHeightTask::new((2, 0), -1).update(ctx))
+        ));
+
+        // Raising the ground uses up soil the actor does not have
+        assert!(failed(
+            f.update(|ctx| ChangeHeightTask::new((1, 0), 1).update(ctx))
+        ));
+        assert_eq!(f.map.height(1, 0), 5);
+
+        // The ground can not go below 1
+        assert!(failed(
+            f.update(|ctx| ChangeHeightTask::new((1, 0), -5).update(ctx))
+        ));
+
+        f.map.tile_mut(0, 1).set_walkable(false);
+        assert!(failed(
+            f.update(|ctx| ChangeHeightTask::new((0, 1), -1).update(ctx))
+        ));
+        assert_eq!(f.map.height(0, 1), 5);
+
+        let mut dig = ChangeHeightTask::new((1, 0), -2);
+        assert!(!failed(f.update(|ctx| dig.update(ctx))));
+        assert_eq!(f.map.height(1, 0), 3);
+        assert_eq!(f.count(ItemKind::Soil), 2);
+        assert!(matches!(f.update(|ctx| dig.update(ctx)), Status2::Success));
+
+        f.update(|ctx| ChangeHeightTask::new((-1, 0), 2).update(ctx));
+        assert_eq!(f.map.height(-1, 0), 7);
+        assert_eq!(f.count(ItemKind::Soil), 0);
+    }
+
+    #[test]
+    fn blocks_are_placed_next_to_the_actor_on_free_tiles_only() {
+        let mut f = Fixture::new();
+        assert!(failed(
+            f.update(|ctx| PlaceEntityTask::new((1, 0)).update(ctx))
+        ));
+        assert!(f.entities.entities.is_empty());
+
+        f.actor.state.inventory_mut().add(ItemKind::Stone, 1);
+        for &target in &[(2, 0), (0, 0)] {
+            assert!(failed(
+                f.update(|ctx| PlaceEntityTask::new(target).update(ctx))
+            ));
+        }
+
+        let mut other = Actor::new(2);
+        other.state.set_position(1, 1);
+        f.others.push(other);
+        f.entities.add(WorldEntity::at(-1, 0, 5));
+        for &target in &[(1, 1), (-1, 0)] {
+            assert!(failed(
+                f.update(|ctx| PlaceEntityTask::new(target).update(ctx))
+            ));
+        }
+        assert_eq!(f.count(ItemKind::Stone), 1);
+
+        assert!(!failed(
+            f.update(|ctx| PlaceEntityTask::new((1, 0)).update(ctx))
+        ));
+        assert_eq!(f.count(ItemKind::Stone), 0);
+        assert!(f.entities.at_tile(1, 0).is_some());
+        assert!(!f.map.tile(1, 0).is_walkable());
+    }
+
+    #[test]
+    fn removing_an_entity_needs_one_within_reach() {
+        let mut f = Fixture::new();
+        f.entities.add(WorldEntity::at(2, 0, 5));
+        assert!(failed(
+            f.update(|ctx| RemoveEntityTask::new((2, 0)).update(ctx))
+        ));
+        assert!(failed(
+            f.update(|ctx| RemoveEntityTask::new((1, 0)).update(ctx))
+        ));
+        assert_eq!(f.entities.entities.len(), 1);
+
+        f.entities.add(WorldEntity::at(0, 1, 5).with_size(2, 2, 2));
+        f.map.tile_mut(1, 2).set_walkable(false);
+        assert!(!failed(
+            f.update(|ctx| RemoveEntityTask::new((1, 1)).update(ctx))
+        ));
+        assert!(f.entities.at_tile(1, 2).is_none());
+        assert!(f.map.tile(1, 2).is_walkable());
+        assert_eq!(f.items.take_at(1, 1), [(ItemKind::Stone, 1)]);
+    }
+
+    #[test]
+    fn picking_up_needs_items_within_reach() {
+        let mut f = Fixture::new();
+        f.items.drop_at(2, 0, ItemKind::Seeds, 3);
+        assert!(failed(f.update(|ctx| PickUpTask::new((2, 0)).update(ctx))));
+        assert!(failed(f.update(|ctx| PickUpTask::new((1, 0)).update(ctx))));
+        assert_eq!(f.count(ItemKind::Seeds), 0);
+
+        f.items.drop_at(1, 0, ItemKind::Seeds, 3);
+        f.items.drop_at(1, 0, ItemKind::Soil, 1);
+        assert!(matches!(
+            f.update(|ctx| PickUpTask::new((1, 0)).update(ctx)),
+            Status2::Success
+        ));
+        assert_eq!(f.count(ItemKind::Seeds), 3);
+        assert_eq!(f.count(ItemKind::Soil), 1);
+        assert!(f.items.take_at(1, 0).is_empty());
+    }
+}
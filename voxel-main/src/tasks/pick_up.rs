@@ -0,0 +1,30 @@
+use crate::occupation::{Context, Status2};
+use crate::tasks::within_reach;
+use Status2::*;
+
+#[derive(PartialEq, Copy, Clone)]
+pub struct PickUpTask {
+    target: (i64, i64),
+}
+
+impl PickUpTask {
+    pub fn new(target: (i64, i64)) -> Self {
+        Self { target }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) -> Status2 {
+        let (x, y) = self.target;
+        if !within_reach(ctx, (x, y)) {
+            return Failure;
+        }
+
+        let taken = ctx.items.take_at(x, y);
+        if taken.is_empty() {
+            return Failure;
+        }
+        for (kind, count) in taken {
+            ctx.actor_state.inventory_mut().add(kind, count);
+        }
+        Success
+    }
+}
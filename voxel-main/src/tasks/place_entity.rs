@@ -0,0 +1,58 @@
+use crate::item::ItemKind;
+use crate::occupation::{Context, Status2};
+use crate::tasks::within_reach;
+use crate::world::WorldEntity;
+use Status2::*;
+
+// Places a single stone block on an empty tile
+#[derive(PartialEq, Copy, Clone)]
+pub struct PlaceEntityTask {
+    target: (i64, i64),
+    done: bool,
+}
+
+impl PlaceEntityTask {
+    pub fn new(target: (i64, i64)) -> Self {
+        Self {
+            target,
+            done: false,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) -> Status2 {
+        if self.done {
+            return Success;
+        }
+
+        let (x, y) = self.target;
+        if !within_reach(ctx, (x, y)) || ctx.actor_state.position() == (x, y) {
+            return Failure;
+        }
+
+        let occupied = !ctx.map.tile(x, y).is_walkable()
+            || ctx.entities.at_tile(x, y).is_some()
+            || ctx
+                .other_actors
+                .iter()
+                .any(|other| !other.state.ethereal() && other.state.position() == (x, y));
+        if occupied {
+            return Failure;
+        }
+
+        if !ctx.actor_state.inventory_mut().remove(ItemKind::Stone, 1) {
+            return Failure;
+        }
+
+        let z = ctx.map.height(x, y) as i64;
+        let id = ctx
+            .entities
+            .add(WorldEntity::at(x, y, z).with_color((0.6, 0.6, 0.6)));
+        if let Some(block) = ctx.entities.get_mut(id) {
+            block.state.set_model(Some("stone"));
+        }
+        ctx.map.tile_mut(x, y).set_walkable(false);
+
+        self.done = true;
+        Wait(20)
+    }
+}
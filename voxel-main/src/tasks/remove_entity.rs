@@ -0,0 +1,64 @@
+use crate::item::ItemKind;
+use crate::occupation::{Context, Status2};
+use crate::tasks::within_reach;
+use crate::world::TileKind;
+use Status2::*;
+
+// Tears down whatever entity covers the target tile, leaving a pile of stone
+#[derive(PartialEq, Copy, Clone)]
+pub struct RemoveEntityTask {
+    target: (i64, i64),
+    done: bool,
+}
+
+impl RemoveEntityTask {
+    pub fn new(target: (i64, i64)) -> Self {
+        Self {
+            target,
+            done: false,
+        }
+    }
+
+    pub fn update(&mut self, ctx: &mut Context) -> Status2 {
+        if self.done {
+            return Success;
+        }
+
+        let (x, y) = self.target;
+        if !within_reach(ctx, (x, y)) {
+            return Failure;
+        }
+
+        let id = match ctx.entities.at_tile(x, y) {
+            Some(id) => id,
+            None => return Failure,
+        };
+        let (x0, y0, width, length, volume) = match ctx.entities.remove(id) {
+            Some(s) => (
+                s.x,
+                s.y,
+                s.width as i64,
+                s.length as i64,
+                s.width as u32 * s.length as u32 * s.height as u32,
+            ),
+            None => return Failure,
+        };
+
+        // Houses mark their floor with the debug tile; return it to the
+        // concrete foundation it was built on.
+        for ty in y0..y0 + length {
+            for tx in x0..x0 + width {
+                if ctx.map.tile(tx, ty).kind == TileKind::_DebugTile {
+                    ctx.map.set_kind(tx, ty, TileKind::Concrete, true);
+                }
+                ctx.map.tile_mut(tx, ty).set_walkable(true);
+            }
+        }
+
+        ctx.items
+            .drop_at(x, y, ItemKind::Stone, (volume / 8).max(1));
+
+        self.done = true;
+        Wait(40)
+    }
+}
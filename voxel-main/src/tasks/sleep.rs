@@ -0,0 +1,30 @@
+use crate::occupation::{Context, Task, TaskStatus};
+
+// Sleeps until morning. The actor is drawn dimmed while asleep.
+#[derive(PartialEq, Clone, Copy)]
+pub struct Sleep {
+    prior_color: Option<(f32, f32, f32)>,
+}
+
+impl Sleep {
+    pub fn new() -> Self {
+        Sleep { prior_color: None }
+    }
+}
+
+impl Task for Sleep {
+    fn update(&mut self, ctx: &mut Context) -> TaskStatus {
+        if self.prior_color.is_none() {
+            let (r, g, b) = ctx.actor_state.color();
+            self.prior_color = Some((r, g, b));
+            ctx.actor_state.set_color(r * 0.3, g * 0.3, b * 0.3);
+        }
+        if ctx.clock.is_night() {
+            return TaskStatus::Active;
+        }
+
+        let (r, g, b) = self.prior_color.take().unwrap();
+        ctx.actor_state.set_color(r, g, b);
+        TaskStatus::Success
+    }
+}
@@ -9,8 +9,7 @@ pub struct Wait {
 }
 
 impl Wait {
-    pub fn new(game_time: u64, min: u64, max: u64) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn new<R: Rng>(rng: &mut R, game_time: u64, min: u64, max: u64) -> Self {
         let value = rng.gen_range(min, max);
         Wait {
             expiration: game_time + value,
@@ -0,0 +1,73 @@
+//
+// Verbs are the ways the player can act on the world. Each is carried out
+// by the Avatar's strategy using the same Tasks that NPC strategies use.
+//
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum Verb {
+    Dig,
+    Raise,
+    Till,
+    Plant,
+    Pave,
+    PlaceBlock,
+    RemoveEntity,
+    PickUp,
+}
+
+impl Verb {
+    pub const ALL: [Verb; 8] = [
+        Verb::Dig,
+        Verb::Raise,
+        Verb::Till,
+        Verb::Plant,
+        Verb::Pave,
+        Verb::PlaceBlock,
+        Verb::RemoveEntity,
+        Verb::PickUp,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Verb::Dig => "Dig",
+            Verb::Raise => "Raise",
+            Verb::Till => "Till",
+            Verb::Plant => "Plant",
+            Verb::Pave => "Pave",
+            Verb::PlaceBlock => "Place block",
+            Verb::RemoveEntity => "Remove",
+            Verb::PickUp => "Pick up",
+        }
+    }
+
+    /// The verb `delta` steps away in Verb::ALL, wrapping around
+    pub fn cycle(&self, delta: i32) -> Verb {
+        let n = Verb::ALL.len() as i32;
+        let i = Verb::ALL.iter().position(|v| v == self).unwrap() as i32;
+        Verb::ALL[(i + delta).rem_euclid(n) as usize]
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Order {
+    pub verb: Verb,
+    pub target: (i64, i64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycling_wraps_around_in_both_directions() {
+        assert_eq!(Verb::Dig.cycle(1), Verb::Raise);
+        assert_eq!(Verb::Dig.cycle(0), Verb::Dig);
+        assert_eq!(Verb::Dig.cycle(-1), Verb::PickUp);
+        assert_eq!(Verb::PickUp.cycle(1), Verb::Dig);
+        assert_eq!(Verb::Till.cycle(-3), Verb::PickUp);
+        assert_eq!(Verb::Till.cycle(10), Verb::Pave);
+        for verb in &Verb::ALL {
+            assert_eq!(verb.cycle(Verb::ALL.len() as i32), *verb);
+            assert_eq!(verb.cycle(3).cycle(-3), *verb);
+        }
+    }
+}
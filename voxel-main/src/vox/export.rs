@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+
+use crate::vox::{Models, PaletteBuilder, VoxFile, VoxModel, MAX_MODEL_SIZE};
+use crate::world::{MapRegion, TileKind, WorldEntity, WorldMap};
+
+// Tiles without a colour stand out rather than blend in
+const MISSING_COLOR: [u8; 3] = [255, 0, 255];
+
+/// Exports a region of the map as a single model: the terrain, with one
+/// voxel per tile and unit of height, and the entities standing on it.
+/// Columns are filled down to the lowest tile of the region.
+///
+/// Tiles are coloured with `tile_colors`. Entities are sampled from their
+/// model, stretched to their size, or filled with their colour.
+pub fn export_region(
+    world_map: &WorldMap,
+    entities: &[WorldEntity],
+    models: &Models,
+    tile_colors: &HashMap<TileKind, [u8; 3]>,
+    region: MapRegion,
+) -> Result<VoxFile, String> {
+    let MapRegion {
+        x: x0,
+        y: y0,
+        width,
+        length,
+    } = region;
+    let max = MAX_MODEL_SIZE as i64;
+    if width <= 0 || length <= 0 || width > max || length > max {
+        return Err(format!(
+            "a {}x{} region cannot be exported, the largest is {}x{}",
+            width, length, max, max
+        ));
+    }
+
+    let overlaps = |entity: &&WorldEntity| {
+        let s = &entity.state;
+        s.x < x0 + width
+            && s.x + s.width as i64 > x0
+            && s.y < y0 + length
+            && s.y + s.length as i64 > y0
+    };
+    let entities: Vec<&WorldEntity> = entities.iter().filter(overlaps).collect();
+
+    let mut bottom = i64::MAX;
+    let mut top = i64::MIN;
+    for y in y0..y0 + length {
+        for x in x0..x0 + width {
+            let height = world_map.height(x, y) as i64;
+            bottom = bottom.min(height);
+            top = top.max(height);
+        }
+    }
+    for entity in &entities {
+        top = top.max(entity.state.z + entity.state.height as i64);
+    }
+    let height = top - bottom + 1;
+    if height > max {
+        return Err(format!(
+            "the region is {} voxels tall, the most is {}",
+            height, max
+        ));
+    }
+
+    let mut model = VoxModel::new((width as usize, length as usize, height as usize));
+    let mut palette = PaletteBuilder::new();
+
+    for y in y0..y0 + length {
+        for x in x0..x0 + width {
+            let tile = world_map.tile(x, y);
+            if tile.kind == TileKind::Empty {
+                continue;
+            }
+            let color = tile_colors.get(&tile.kind).copied();
+            let index = palette.index(color.unwrap_or(MISSING_COLOR));
+            for z in bottom..=tile.height as i64 {
+                model.set(x - x0, y - y0, z - bottom, index);
+            }
+        }
+    }
+
+    // Entities stand on the tile at their z
+    for entity in &entities {
+        let s = &entity.state;
+        let (w, l, h) = (s.width as i64, s.length as i64, s.height as i64);
+        let source = s.model().and_then(|name| models.get(name));
+        let (r, g, b) = s.color();
+        let color = [to_byte(r), to_byte(g), to_byte(b)];
+
+        for k in 0..h {
+            for j in 0..l {
+                for i in 0..w {
+                    let index = match source {
+                        Some(source) => {
+                            let (sx, sy, sz) = source.voxels.size;
+                            let (sx, sy, sz) = (sx as i64, sy as i64, sz as i64);
+                            match source.voxels.get(i * sx / w, j * sy / l, k * sz / h) {
+                                0 => continue,
+                                index => {
+                                    let [r, g, b, _] = source.palette.colors[index as usize];
+                                    palette.index([r, g, b])
+                                }
+                            }
+                        }
+                        None => palette.index(color),
+                    };
+                    model.set(s.x + i - x0, s.y + j - y0, s.z + 1 + k - bottom, index);
+                }
+            }
+        }
+    }
+
+    Ok(VoxFile {
+        models: vec![model],
+        palette: palette.build(),
+    })
+}
+
+fn to_byte(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
@@ -0,0 +1,335 @@
+//
+// MagicaVoxel models
+//
+// Reads and writes the .vox format of MagicaVoxel, which most voxel editors
+// also use, so props and house templates can be authored outside the game
+// and settlements can be exported to be looked at and edited in those tools.
+//
+// A file is a tree of RIFF style chunks: the "VOX " magic and a version,
+// then a MAIN chunk whose children are a SIZE and an XYZI chunk per model
+// and an optional RGBA palette. Other chunks, such as the scene graph and
+// materials of newer versions, are skipped.
+//
+// See https://github.com/ephtracy/voxel-model/blob/master/MagicaVoxel-file-format-vox.txt
+//
+// Models are z-up like the world map, but MagicaVoxel is right handed while
+// the game draws the world with y and z swapped. Y is mirrored when reading
+// and writing so models look the same in the editor and in the game.
+//
+use std::collections::HashMap;
+
+mod export;
+pub use export::*;
+
+mod models;
+pub use models::*;
+
+// The version MagicaVoxel writes
+const VERSION: u32 = 150;
+
+/// The largest model MagicaVoxel can edit, along each side
+pub const MAX_MODEL_SIZE: usize = 256;
+
+/// The colours of a file, as RGBA. Voxels refer to them by index, with 0
+/// meaning empty, so only entries 1 to 255 are used.
+#[derive(Clone)]
+pub struct Palette {
+    pub colors: Vec<[u8; 4]>,
+}
+
+impl Palette {
+    /// MagicaVoxel's default palette, used by files without an RGBA chunk:
+    /// a 6x6x6 colour cube without black, then ramps of red, green, blue
+    /// and grey.
+    pub fn magica_default() -> Self {
+        const CUBE: [u8; 6] = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+        const RAMP: [u8; 10] = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+
+        let mut colors = vec![[0, 0, 0, 0]];
+        for &r in &CUBE {
+            for &g in &CUBE {
+                for &b in &CUBE {
+                    colors.push([r, g, b, 0xff]);
+                }
+            }
+        }
+        // Black is left out of the cube
+        colors.pop();
+        for channel in 0..4 {
+            for &value in &RAMP {
+                let mut color = [0, 0, 0, 0xff];
+                match channel {
+                    3 => color[..3].copy_from_slice(&[value; 3]),
+                    _ => color[channel] = value,
+                }
+                colors.push(color);
+            }
+        }
+        Self { colors }
+    }
+
+    /// The colour of a palette index, from 0 to 1
+    pub fn color(&self, index: u8) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.colors[index as usize];
+        (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0)
+    }
+}
+
+/// One model of a file, in world orientation
+#[derive(Clone)]
+pub struct VoxModel {
+    /// Voxels along x, y and z
+    pub size: (usize, usize, usize),
+    // Palette index of each voxel, x varying fastest and then y
+    indices: Vec<u8>,
+}
+
+impl VoxModel {
+    pub fn new(size: (usize, usize, usize)) -> Self {
+        Self {
+            size,
+            indices: vec![0; size.0 * size.1 * size.2],
+        }
+    }
+
+    fn offset(&self, x: i64, y: i64, z: i64) -> Option<usize> {
+        let (sx, sy, sz) = (self.size.0 as i64, self.size.1 as i64, self.size.2 as i64);
+        if x < 0 || y < 0 || z < 0 || x >= sx || y >= sy || z >= sz {
+            return None;
+        }
+        Some(((z * sy + y) * sx + x) as usize)
+    }
+
+    /// The palette index of a voxel, 0 if it is empty or outside the model
+    pub fn get(&self, x: i64, y: i64, z: i64) -> u8 {
+        self.offset(x, y, z).map_or(0, |i| self.indices[i])
+    }
+
+    /// Positions outside the model are ignored
+    pub fn set(&mut self, x: i64, y: i64, z: i64, index: u8) {
+        if let Some(i) = self.offset(x, y, z) {
+            self.indices[i] = index;
+        }
+    }
+
+    /// The number of solid voxels
+    pub fn voxel_count(&self) -> usize {
+        self.indices.iter().filter(|&&i| i != 0).count()
+    }
+}
+
+pub struct VoxFile {
+    pub models: Vec<VoxModel>,
+    pub palette: Palette,
+}
+
+impl VoxFile {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        Self::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, pos: 0 };
+        if reader.take(4)? != b"VOX " {
+            return Err("not a .vox file".to_string());
+        }
+        let _version = reader.u32()?;
+
+        let (id, _, mut children) = reader.chunk()?;
+        if id != b"MAIN" {
+            return Err(format!("expected a MAIN chunk, found {:?}", name(id)));
+        }
+
+        let mut models = Vec::new();
+        let mut palette = Palette::magica_default();
+        let mut size = None;
+        while children.pos < children.bytes.len() {
+            let (id, mut content, _) = children.chunk()?;
+            match id {
+                b"SIZE" => {
+                    let (x, y, z) = (content.u32()?, content.u32()?, content.u32()?);
+                    if [x, y, z].iter().any(|&n| n as usize > MAX_MODEL_SIZE) {
+                        return Err(format!("model size {}x{}x{} is too large", x, y, z));
+                    }
+                    size = Some((x as usize, y as usize, z as usize));
+                }
+                b"XYZI" => {
+                    let size = size
+                        .take()
+                        .ok_or_else(|| "XYZI chunk without a SIZE chunk".to_string())?;
+                    let mut model = VoxModel::new(size);
+                    let count = content.u32()?;
+                    for _ in 0..count {
+                        let v = content.take(4)?;
+                        let (x, y, z) = (v[0] as i64, v[1] as i64, v[2] as i64);
+                        if model.offset(x, y, z).is_none() {
+                            return Err(format!(
+                                "voxel ({}, {}, {}) is outside the model",
+                                x, y, z
+                            ));
+                        }
+                        model.set(x, size.1 as i64 - 1 - y, z, v[3]);
+                    }
+                    models.push(model);
+                }
+                b"RGBA" => {
+                    // Entry i of the chunk is the colour of index i + 1
+                    for i in 1..256 {
+                        let c = content.take(4)?;
+                        palette.colors[i] = [c[0], c[1], c[2], c[3]];
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if models.is_empty() {
+            return Err("the file has no models".to_string());
+        }
+        Ok(Self { models, palette })
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, self.to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut children = Vec::new();
+        for model in &self.models {
+            let (sx, sy, sz) = model.size;
+            let mut size = Vec::new();
+            for n in &[sx, sy, sz] {
+                size.extend_from_slice(&(*n as u32).to_le_bytes());
+            }
+            write_chunk(&mut children, b"SIZE", &size, &[]);
+
+            let mut voxels = Vec::new();
+            voxels.extend_from_slice(&(model.voxel_count() as u32).to_le_bytes());
+            for z in 0..sz as i64 {
+                for y in 0..sy as i64 {
+                    for x in 0..sx as i64 {
+                        let index = model.get(x, y, z);
+                        if index != 0 {
+                            let y = sy as i64 - 1 - y;
+                            voxels.extend_from_slice(&[x as u8, y as u8, z as u8, index]);
+                        }
+                    }
+                }
+            }
+            write_chunk(&mut children, b"XYZI", &voxels, &[]);
+        }
+
+        let mut rgba = Vec::new();
+        for i in 1..256 {
+            rgba.extend_from_slice(&self.palette.colors[i]);
+        }
+        rgba.extend_from_slice(&[0; 4]);
+        write_chunk(&mut children, b"RGBA", &rgba, &[]);
+
+        let mut bytes = b"VOX ".to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        write_chunk(&mut bytes, b"MAIN", &[], &children);
+        bytes
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend_from_slice(id);
+    out.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    out.extend_from_slice(content);
+    out.extend_from_slice(children);
+}
+
+fn name(id: &[u8]) -> String {
+    String::from_utf8_lossy(id).to_string()
+}
+
+// Reads little endian values, failing on truncated data instead of panicking
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| "unexpected end of file".to_string())?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    // A chunk's id and readers for its content and its children
+    fn chunk(&mut self) -> Result<(&'a [u8], Reader<'a>, Reader<'a>), String> {
+        let id = self.take(4)?;
+        let content_size = self.u32()? as usize;
+        let children_size = self.u32()? as usize;
+        let content = self.take(content_size)?;
+        let children = self.take(children_size)?;
+        Ok((
+            id,
+            Reader {
+                bytes: content,
+                pos: 0,
+            },
+            Reader {
+                bytes: children,
+                pos: 0,
+            },
+        ))
+    }
+}
+
+/// Maps colours to palette indices for writing, adding each new colour to
+/// the palette until it is full and then reusing the nearest one
+pub struct PaletteBuilder {
+    palette: Palette,
+    used: usize,
+    indices: HashMap<[u8; 3], u8>,
+}
+
+impl PaletteBuilder {
+    pub fn new() -> Self {
+        Self {
+            palette: Palette {
+                colors: vec![[0, 0, 0, 0]; 256],
+            },
+            used: 0,
+            indices: HashMap::new(),
+        }
+    }
+
+    pub fn index(&mut self, color: [u8; 3]) -> u8 {
+        if let Some(&index) = self.indices.get(&color) {
+            return index;
+        }
+        let index = if self.used < 255 {
+            self.used += 1;
+            self.palette.colors[self.used] = [color[0], color[1], color[2], 0xff];
+            self.used as u8
+        } else {
+            let distance = |c: &[u8; 4]| -> i32 {
+                (0..3).map(|i| (c[i] as i32 - color[i] as i32).pow(2)).sum()
+            };
+            (1..256)
+                .min_by_key(|&i| distance(&self.palette.colors[i]))
+                .unwrap() as u8
+        };
+        self.indices.insert(color, index);
+        index
+    }
+
+    pub fn build(self) -> Palette {
+        self.palette
+    }
+}
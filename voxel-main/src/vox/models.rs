@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::meshing::{BlockAppearances, BlockFace, Voxels};
+use crate::vox::{Palette, VoxFile, VoxModel};
+use crate::world::TileKind;
+
+/// A model entities can be drawn with: the first model of a .vox file and
+/// the file's palette
+pub struct Model {
+    pub voxels: VoxModel,
+    pub palette: Palette,
+}
+
+// Models are meshed like the terrain, with each face in the colour of its
+// voxel instead of a tile image
+impl Voxels for Model {
+    fn bounds(&self) -> ([i64; 3], [i64; 3]) {
+        let (x, y, z) = self.voxels.size;
+        ([0, 0, 0], [x as i64, y as i64, z as i64])
+    }
+
+    fn is_solid(&self, x: i64, y: i64, z: i64) -> bool {
+        self.voxels.get(x, y, z) != 0
+    }
+
+    fn face(
+        &self,
+        _appearances: &BlockAppearances,
+        x: i64,
+        y: i64,
+        z: i64,
+        _face: BlockFace,
+    ) -> (TileKind, (f32, f32, f32)) {
+        let color = self.palette.color(self.voxels.get(x, y, z));
+        (TileKind::Empty, color)
+    }
+}
+
+/// The models in src/assets/models, by file name without the extension
+pub struct Models {
+    models: HashMap<String, Model>,
+}
+
+impl Models {
+    pub fn load(pattern: &str) -> Self {
+        use glob::glob;
+
+        let mut models = HashMap::new();
+        for entry in glob(pattern).expect("Failed to read glob pattern") {
+            let path = match entry {
+                Ok(path) => path,
+                Err(e) => {
+                    println!("{:?}", e);
+                    continue;
+                }
+            };
+            let name = match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => continue,
+            };
+            match VoxFile::load(&path.to_string_lossy()) {
+                Ok(mut file) => {
+                    println!("Loaded model {}", path.display());
+                    let model = Model {
+                        voxels: file.models.swap_remove(0),
+                        palette: file.palette,
+                    };
+                    models.insert(name, model);
+                }
+                Err(e) => println!("Failed to load model {}: {}", path.display(), e),
+            }
+        }
+        Self { models }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Model> {
+        self.models.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Model)> {
+        self.models.iter()
+    }
+}
@@ -0,0 +1,182 @@
+//
+// World clock
+//
+// Hours, days and seasons derived from game_time. The clock has no state of
+// its own: it is rebuilt from game_time each frame, so a replay of the same
+// seed sees the same time of day on the same frame.
+//
+use std::fmt;
+
+// At 10 ticks per frame and 60 frames per second an hour passes every 30
+// seconds, so a full day takes 12 minutes.
+pub const TICKS_PER_HOUR: u64 = 18_000;
+pub const HOURS_PER_DAY: u64 = 24;
+pub const DAYS_PER_SEASON: u64 = 7;
+
+// The world starts in the morning of the first day of spring
+const START_HOUR: u64 = 7;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+
+    /// The hours of sunrise and sunset. Days are longest in summer.
+    pub fn daylight_hours(&self) -> (f32, f32) {
+        match self {
+            Season::Spring => (6.0, 19.0),
+            Season::Summer => (5.0, 21.0),
+            Season::Autumn => (6.5, 18.5),
+            Season::Winter => (8.0, 16.5),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Clock {
+    game_time: u64,
+}
+
+impl Clock {
+    pub fn new(game_time: u64) -> Self {
+        Self { game_time }
+    }
+
+    // Ticks since midnight of the first day
+    fn ticks(&self) -> u64 {
+        self.game_time + START_HOUR * TICKS_PER_HOUR
+    }
+
+    /// Days since the world began, starting at 0
+    pub fn day(&self) -> u64 {
+        self.ticks() / (TICKS_PER_HOUR * HOURS_PER_DAY)
+    }
+
+    /// Hours since midnight, from 0 up to (but not including) 24
+    pub fn hour(&self) -> f32 {
+        let ticks = self.ticks() % (TICKS_PER_HOUR * HOURS_PER_DAY);
+        ticks as f32 / TICKS_PER_HOUR as f32
+    }
+
+    pub fn season(&self) -> Season {
+        match (self.day() / DAYS_PER_SEASON) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// How far the sun is through its arc across the sky: 0 at sunrise,
+    /// 0.5 at noon and 1 at sunset. None while the sun is down.
+    pub fn sun_progress(&self) -> Option<f32> {
+        let (sunrise, sunset) = self.season().daylight_hours();
+        let hour = self.hour();
+        if hour < sunrise || hour >= sunset {
+            None
+        } else {
+            Some((hour - sunrise) / (sunset - sunrise))
+        }
+    }
+
+    pub fn is_night(&self) -> bool {
+        self.sun_progress().is_none()
+    }
+}
+
+// e.g. "Day 3, Spring 07:30"
+impl fmt::Display for Clock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hour = self.hour();
+        write!(
+            f,
+            "Day {}, {} {:02}:{:02}",
+            self.day() + 1,
+            self.season().name(),
+            hour as u32,
+            (hour.fract() * 60.0) as u32
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The game_time at the given hour of the given day
+    fn at(day: u64, hour: u64, minute: u64) -> Clock {
+        let ticks = (day * HOURS_PER_DAY + hour) * TICKS_PER_HOUR + minute * TICKS_PER_HOUR / 60;
+        Clock::new(ticks - START_HOUR * TICKS_PER_HOUR)
+    }
+
+    #[test]
+    fn the_world_starts_on_a_spring_morning() {
+        let clock = Clock::new(0);
+        assert_eq!(clock.day(), 0);
+        assert_eq!(clock.hour(), 7.0);
+        assert_eq!(clock.season(), Season::Spring);
+        assert!(!clock.is_night());
+        assert_eq!(clock.to_string(), "Day 1, Spring 07:00");
+    }
+
+    #[test]
+    fn hours_wrap_into_the_next_day() {
+        assert_eq!(at(0, 23, 30).hour(), 23.5);
+        assert_eq!(at(0, 23, 30).day(), 0);
+        assert_eq!(at(1, 0, 0).hour(), 0.0);
+        assert_eq!(at(1, 0, 0).day(), 1);
+        assert_eq!(at(2, 13, 45).to_string(), "Day 3, Spring 13:45");
+    }
+
+    #[test]
+    fn seasons_follow_each_other_and_repeat() {
+        let seasons: Vec<Season> = (0..5)
+            .map(|n| at(n * DAYS_PER_SEASON, 12, 0).season())
+            .collect();
+        assert_eq!(
+            seasons,
+            [
+                Season::Spring,
+                Season::Summer,
+                Season::Autumn,
+                Season::Winter,
+                Season::Spring
+            ]
+        );
+        assert_eq!(at(DAYS_PER_SEASON - 1, 23, 59).season(), Season::Spring);
+        assert_eq!(
+            at(2 * DAYS_PER_SEASON, 8, 15).to_string(),
+            "Day 15, Autumn 08:15"
+        );
+    }
+
+    #[test]
+    fn nights_last_from_sunset_to_sunrise() {
+        // Spring: sunrise at 6:00, sunset at 19:00
+        assert!(at(1, 5, 59).is_night());
+        assert!(!at(1, 6, 0).is_night());
+        assert!(!at(1, 18, 59).is_night());
+        assert!(at(1, 19, 0).is_night());
+        assert!(at(1, 0, 0).is_night());
+        assert_eq!(at(1, 12, 30).sun_progress(), Some(0.5));
+
+        // Winter days are shorter
+        let winter = 3 * DAYS_PER_SEASON;
+        assert!(at(winter, 7, 0).is_night());
+        assert!(!at(winter, 8, 0).is_night());
+        assert!(at(winter, 17, 0).is_night());
+    }
+}
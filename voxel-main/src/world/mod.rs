@@ -1,3 +1,6 @@
+pub mod clock;
+pub use clock::*;
+
 pub mod tile;
 pub use tile::*;
 
@@ -10,5 +13,8 @@ pub use world_map::*;
 pub mod world_entity;
 pub use world_entity::*;
 
+pub mod world_item;
+pub use world_item::*;
+
 pub mod graphics;
 pub use graphics::*;
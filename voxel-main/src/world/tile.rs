@@ -1,4 +1,6 @@
-#[derive(PartialEq, Eq, Copy, Clone, Hash)]
+use serde::Deserialize;
+
+#[derive(PartialEq, Eq, Copy, Clone, Hash, Debug, Deserialize)]
 #[repr(u8)]
 pub enum TileKind {
     Empty = 0,
@@ -1,8 +1,9 @@
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 
 use crate::actor::Actor;
 use crate::occupation::Occupation;
+use crate::world::world_item::WorldItemList;
 use crate::world::world_map::WorldMap;
 use crate::world_entity::*;
 
@@ -61,7 +62,7 @@ impl ActorBuilder {
             "Lince",
         ];
         for _ in 0..1 {
-            let mut actor = Actor::new();
+            let mut actor = Actor::new(world.next_actor_id);
 
             actor.name = match &self.name {
                 None => {
@@ -107,27 +108,38 @@ impl ActorBuilder {
             if self.is_player {
                 world.player_index = world.actors.len();
             }
+            world.next_actor_id += 1;
             world.actors.push(actor);
         }
     }
 }
 
+// All randomness in the simulation comes from one seeded generator so that a
+// seed plus the player's Actions reproduce a session exactly.
+pub type WorldRng = rand::rngs::StdRng;
+
 pub struct World {
-    pub rng: rand::rngs::ThreadRng,
+    pub rng: WorldRng,
     pub player_index: usize,
     pub actors: Vec<Actor>,
+    pub next_actor_id: u64,
     pub entities: WorldEntityList,
+    pub items: WorldItemList,
     pub world_map: WorldMap,
 }
 
 impl World {
-    pub fn new() -> Self {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = WorldRng::seed_from_u64(seed);
+        let world_map = WorldMap::new(&mut rng);
         Self {
-            rng: rand::thread_rng(),
+            rng,
             player_index: 0,
             actors: vec![],
+            next_actor_id: 1,
             entities: WorldEntityList::new(),
-            world_map: WorldMap::new(),
+            items: WorldItemList::new(),
+            world_map,
         }
     }
 
@@ -135,6 +147,22 @@ impl World {
         ActorBuilder::new()
     }
 
+    pub fn actor_index(&self, id: u64) -> Option<usize> {
+        self.actors.iter().position(|actor| actor.state.id() == id)
+    }
+
+    // Mutable references to two different actors at once
+    pub fn actor_pair_mut(&mut self, a: usize, b: usize) -> (&mut Actor, &mut Actor) {
+        assert!(a != b);
+        if a < b {
+            let (head, tail) = self.actors.split_at_mut(b);
+            (&mut head[a], &mut tail[0])
+        } else {
+            let (head, tail) = self.actors.split_at_mut(a);
+            (&mut tail[0], &mut head[b])
+        }
+    }
+
     pub fn actor_at_tile(&self, x: i64, y: i64) -> Option<usize> {
         if !self.world_map.is_tile_valid(x, y) {
             return None;
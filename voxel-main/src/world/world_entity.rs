@@ -1,7 +1,5 @@
-use crate::world::Graphics;
-use nalgebra::Translation3;
-
 pub struct WorldEntityState {
+    id: u64,
     sync_id: u64,
 
     pub x: i64,
@@ -12,9 +10,23 @@ pub struct WorldEntityState {
     pub height: u8,
 
     color: (f32, f32, f32),
+    // The voxel model the entity is drawn with, stretched to its size.
+    // Entities without one, or whose model is not loaded, are drawn as boxes.
+    model: Option<String>,
 }
 
 impl WorldEntityState {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn contains(&self, x: i64, y: i64) -> bool {
+        x >= self.x
+            && x < self.x + self.width as i64
+            && y >= self.y
+            && y < self.y + self.length as i64
+    }
+
     pub fn sync_id(&self) -> u64 {
         self.sync_id
     }
@@ -23,84 +35,107 @@ impl WorldEntityState {
         self.color
     }
 
-    pub fn set_color(&mut self, color: (f32, f32, f32)) {
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    pub fn set_model(&mut self, model: Option<&str>) {
         self.sync_id += 1;
-        self.color = color;
+        self.model = model.map(|name| name.to_string());
     }
 }
 
 pub struct WorldEntity {
     pub state: WorldEntityState,
-    pub graphics: Option<Graphics>,
 }
 
-impl WorldEntity {
-    fn new(x: i64, y: i64, z: i64, w: i64, l: i64, h: i64) -> Self {
+pub struct WorldEntityBuilder {
+    x: i64,
+    y: i64,
+    z: i64,
+    size: (i64, i64, i64),
+    color: (f32, f32, f32),
+}
+
+impl WorldEntityBuilder {
+    fn build(self, id: u64) -> WorldEntity {
+        let (w, l, h) = self.size;
         let state = WorldEntityState {
+            id,
             sync_id: 0,
-            x,
-            y,
-            z,
+            x: self.x,
+            y: self.y,
+            z: self.z,
             width: w as u8,
             length: l as u8,
             height: h as u8,
-            color: (1.0, 1.0, 1.0),
+            color: self.color,
+            model: None,
         };
-        Self {
-            state,
-            graphics: None,
+        WorldEntity { state }
+    }
+
+    // The footprint (width along x, length along y) and height in tiles
+    pub fn with_size(mut self, w: i64, l: i64, h: i64) -> WorldEntityBuilder {
+        self.size = (w, l, h);
+        self
+    }
+
+    pub fn with_color(mut self, color: (f32, f32, f32)) -> WorldEntityBuilder {
+        self.color = color;
+        self
+    }
+}
+
+impl WorldEntity {
+    // A white 1x1x1 entity standing at the given position, added to the
+    // world with WorldEntityList::add
+    pub fn at(x: i64, y: i64, z: i64) -> WorldEntityBuilder {
+        WorldEntityBuilder {
+            x,
+            y,
+            z,
+            size: (1, 1, 1),
+            color: (1.0, 1.0, 1.0),
         }
     }
 }
 
 pub struct WorldEntityList {
     pub entities: Vec<WorldEntity>,
+    next_id: u64,
 }
 
 impl WorldEntityList {
     pub fn new() -> Self {
         Self {
             entities: Vec::new(),
+            next_id: 1,
         }
     }
-    pub fn add(&mut self, x: i64, y: i64, z: i64, w: i64, l: i64, h: i64, color: (f32, f32, f32)) {
-        let mut entity = WorldEntity::new(x, y, z, w, l, h);
-        entity.state.set_color(color);
-        self.entities.push(entity);
-    }
-}
 
-pub fn sync_entity(window: &mut kiss3d::window::Window, entity: &mut WorldEntity) {
-    //
-    // Early return if the graphics are already up to date
-    //
-    if let Some(graphics) = entity.graphics.as_mut() {
-        if graphics.sync_id == entity.state.sync_id() {
-            return;
-        }
-    } else {
-        let node = window.add_cube(
-            entity.state.width as f32,
-            entity.state.height as f32,
-            entity.state.length as f32,
-        );
-        entity.graphics = Some(Graphics {
-            sync_id: entity.state.sync_id(),
-            node,
-        });
-    }
+    pub fn add(&mut self, entity: WorldEntityBuilder) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
 
-    let graphics = entity.graphics.as_mut().unwrap();
+        self.entities.push(entity.build(id));
+        id
+    }
 
-    let t = Translation3::new(
-        entity.state.x as f32 + (entity.state.width as f32) / 2.0 - 0.5,
-        entity.state.z as f32 + (entity.state.height as f32) / 2.0 - 0.5,
-        entity.state.y as f32 + (entity.state.length as f32) / 2.0 - 0.5,
-    );
-    graphics.node.set_local_translation(t);
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut WorldEntity> {
+        self.entities.iter_mut().find(|e| e.state.id() == id)
+    }
 
-    let (r, g, b) = entity.state.color();
-    graphics.node.set_color(r, g, b);
+    /// The id of the entity whose footprint covers the tile, if any
+    pub fn at_tile(&self, x: i64, y: i64) -> Option<u64> {
+        self.entities
+            .iter()
+            .find(|e| e.state.contains(x, y))
+            .map(|e| e.state.id())
+    }
 
-    // graphics.sync_id = entity.state.sync_id();
+    pub fn remove(&mut self, id: u64) -> Option<WorldEntityState> {
+        let index = self.entities.iter().position(|e| e.state.id() == id)?;
+        Some(self.entities.remove(index).state)
+    }
 }
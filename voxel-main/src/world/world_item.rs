@@ -0,0 +1,88 @@
+use crate::item::ItemKind;
+use crate::world::Graphics;
+use nalgebra::Translation3;
+
+// A pile of items lying on the ground
+pub struct WorldItem {
+    pub x: i64,
+    pub y: i64,
+    pub kind: ItemKind,
+    pub count: u32,
+    pub graphics: Option<Graphics>,
+}
+
+pub struct WorldItemList {
+    pub items: Vec<WorldItem>,
+
+    // Items that have been removed but whose graphics have not yet been
+    // cleaned up. The simulation has no access to the Window so this is
+    // drained by the render loop.
+    pub removed: Vec<WorldItem>,
+}
+
+impl WorldItemList {
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    /// Drops items on a tile, adding to any existing pile of the same kind
+    pub fn drop_at(&mut self, x: i64, y: i64, kind: ItemKind, count: u32) {
+        if count == 0 {
+            return;
+        }
+        match self
+            .items
+            .iter_mut()
+            .find(|item| item.x == x && item.y == y && item.kind == kind)
+        {
+            Some(item) => item.count += count,
+            None => self.items.push(WorldItem {
+                x,
+                y,
+                kind,
+                count,
+                graphics: None,
+            }),
+        }
+    }
+
+    /// Removes and returns everything lying on a tile
+    pub fn take_at(&mut self, x: i64, y: i64) -> Vec<(ItemKind, u32)> {
+        let mut taken = Vec::new();
+        let mut i = 0;
+        while i < self.items.len() {
+            if self.items[i].x == x && self.items[i].y == y {
+                let item = self.items.remove(i);
+                taken.push((item.kind, item.count));
+                self.removed.push(item);
+            } else {
+                i += 1;
+            }
+        }
+        taken
+    }
+}
+
+pub fn sync_item(window: &mut kiss3d::window::Window, height: i32, item: &mut WorldItem) {
+    if item.graphics.is_some() {
+        return;
+    }
+
+    let mut node = window.add_cube(0.3, 0.3, 0.3);
+    let (r, g, b) = match item.kind {
+        ItemKind::Seeds => (0.9, 0.85, 0.5),
+        ItemKind::Produce => (0.3, 0.8, 0.2),
+        ItemKind::Soil => (0.45, 0.3, 0.15),
+        ItemKind::Stone => (0.6, 0.6, 0.6),
+    };
+    node.set_color(r, g, b);
+    node.set_local_translation(Translation3::new(
+        item.x as f32,
+        height as f32 - 0.35,
+        item.y as f32,
+    ));
+    item.graphics = Some(Graphics { sync_id: 0, node });
+}
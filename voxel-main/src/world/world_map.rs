@@ -3,10 +3,14 @@ use std::cell::*;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
+use crate::meshing::MAX_STEP;
 use crate::world::tile::*;
 
 const REGION_SIZE: usize = 64;
 
+// Tiles along each side of the chunks the map is meshed in
+const CHUNK_SIZE: i64 = 32;
+
 #[derive(Copy, Clone)]
 pub struct MapRegion {
     pub x: i64,
@@ -42,8 +46,7 @@ struct WorldProps {
 }
 
 impl WorldProps {
-    fn new() -> Self {
-        let mut rng = rand::thread_rng();
+    fn new<R: Rng>(rng: &mut R) -> Self {
         Self {
             offset_x: rng.gen_range(0.0, 100.0),
             offset_y: rng.gen_range(0.0, 100.0),
@@ -235,11 +238,27 @@ impl WorldRegion {
         // TODO: move call to update_tile_ages here
     }
 
-    pub fn update_tile_ages(&mut self) {
+    // Returns the tiles that reached one of the ages in `thresholds` for
+    // their kind
+    pub fn update_tile_ages(
+        &mut self,
+        thresholds: &HashMap<TileKind, Vec<u64>>,
+    ) -> Vec<(i64, i64)> {
+        let mut reached = Vec::new();
         for i in 0..self.tiles.len() {
             let tile = &mut self.tiles[i];
+            let before = tile.age();
             tile.inc_age();
+            let after = tile.age();
+
+            if let Some(ages) = thresholds.get(&tile.kind) {
+                if ages.iter().any(|&age| before < age && age <= after) {
+                    let i = i as i64;
+                    reached.push((i % self.width(), i / self.width()));
+                }
+            }
         }
+        reached
     }
 }
 
@@ -253,6 +272,9 @@ pub struct WorldMap {
     lock_id_counter: u64,
     locked_regions: HashMap<u64, MapRegion>,
     locked_paths: HashMap<u64, Vec<(i64, i64)>>,
+
+    // The ages at which the looks of each tile kind change
+    age_thresholds: HashMap<TileKind, Vec<u64>>,
 }
 
 fn coords(x: i64, y: i64) -> (i64, i64, i64, i64) {
@@ -278,14 +300,37 @@ fn coords(x: i64, y: i64) -> (i64, i64, i64, i64) {
     (rx, ry, tx, ty)
 }
 
+// The position of tile (tx, ty) of region (rx, ry), the inverse of coords()
+fn world_coords(rx: i64, ry: i64, tx: i64, ty: i64) -> (i64, i64) {
+    let x = rx * REGION_SIZE as i64 + tx + if rx < 0 { 1 } else { 0 };
+    let y = ry * REGION_SIZE as i64 + ty + if ry < 0 { 1 } else { 0 };
+    (x, y)
+}
+
+// The origins of the chunks whose meshes take in the tile at (x, y). Besides
+// their own tiles, chunks sample a border from their neighbours reaching
+// MAX_STEP / 2 tiles before them and MAX_STEP / 2 + 1 after them (see
+// meshing::ChunkVoxels::border_tiles).
+fn sampling_chunks(x: i64, y: i64) -> Vec<(i64, i64)> {
+    let origins = |x: i64| {
+        let first = (x - MAX_STEP / 2 - 1).div_euclid(CHUNK_SIZE);
+        let last = (x + MAX_STEP / 2).div_euclid(CHUNK_SIZE);
+        (first..=last).map(|c| c * CHUNK_SIZE)
+    };
+    origins(y)
+        .flat_map(|oy| origins(x).map(move |ox| (ox, oy)))
+        .collect()
+}
+
 impl WorldMap {
-    pub fn new() -> Self {
+    pub fn new<R: Rng>(rng: &mut R) -> Self {
         Self {
-            props: WorldProps::new(),
+            props: WorldProps::new(rng),
             regions: RefCell::new(HashMap::new()),
             lock_id_counter: 0,
             locked_regions: HashMap::new(),
             locked_paths: HashMap::new(),
+            age_thresholds: HashMap::new(),
         }
     }
 
@@ -342,7 +387,14 @@ impl WorldMap {
 
     pub fn set_height(&mut self, x: i64, y: i64, z: i64) {
         let (_, _, tx, ty) = coords(x, y);
-        self.region_mut(x, y).set_height(tx, ty, z)
+        self.region_mut(x, y).set_height(tx, ty, z);
+
+        // The faces of the neighbouring columns depend on this height, and
+        // neighbouring chunks sample it for their borders
+        for (ox, oy) in sampling_chunks(x, y) {
+            let (_, _, tx, ty) = coords(ox, oy);
+            self.region_mut(ox, oy).update_chunk_sync_id(tx, ty, 0);
+        }
     }
 
     pub fn set_kind(&mut self, x: i64, y: i64, kind: TileKind, reset_age: bool) {
@@ -361,9 +413,29 @@ impl WorldMap {
         }
     }
 
+    /// Sets the ages at which the looks of each tile kind change, see
+    /// meshing::BlockAppearances::age_thresholds
+    pub fn set_age_thresholds(&mut self, thresholds: HashMap<TileKind, Vec<u64>>) {
+        self.age_thresholds = thresholds;
+    }
+
+    /// Ages every tile. The chunks of tiles reaching one of the age
+    /// thresholds of their kind are remeshed.
     pub fn update_tile_ages(&mut self) {
-        for (_key, region) in self.regions.borrow_mut().iter_mut() {
-            region.update_tile_ages()
+        let mut origins = HashSet::new();
+        for (&(rx, ry), region) in self.regions.borrow_mut().iter_mut() {
+            for (tx, ty) in region.update_tile_ages(&self.age_thresholds) {
+                let (x, y) = world_coords(rx, ry, tx, ty);
+                origins.insert((
+                    x.div_euclid(CHUNK_SIZE) * CHUNK_SIZE,
+                    y.div_euclid(CHUNK_SIZE) * CHUNK_SIZE,
+                ));
+            }
+        }
+
+        for (ox, oy) in origins {
+            let (_, _, tx, ty) = coords(ox, oy);
+            self.region_mut(ox, oy).update_chunk_sync_id(tx, ty, 0);
         }
     }
 